@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use std::collections::HashSet;
 use wasm_bindgen::prelude::*;
 
@@ -20,6 +21,16 @@ pub struct Widget {
     pub position: Position,
     pub locked: bool,
 
+    // Optional size bounds; unset means unconstrained
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_w: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_h: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_w: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_h: Option<i32>,
+
     // Runtime-only state, not serialized in DB
     #[serde(skip)]
     pub is_dragged: bool,
@@ -27,6 +38,22 @@ pub struct Widget {
     pub original_position: Option<Position>,
 }
 
+impl Widget {
+    /// Clamps a requested size to this widget's min/max bounds and to the
+    /// grid width. Every widget is at least one cell in each direction.
+    fn clamp_size(&self, w: i32, h: i32, columns: i32) -> (i32, i32) {
+        let clamp = |value: i32, min: Option<i32>, max: Option<i32>| {
+            let value = max.map_or(value, |max| value.min(max));
+            min.map_or(value, |min| value.max(min)).max(1)
+        };
+        let mut w = clamp(w, self.min_w, self.max_w);
+        if columns > 0 {
+            w = w.min(columns);
+        }
+        (w, clamp(h, self.min_h, self.max_h))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct GridConfig {
     #[serde(default)]
@@ -37,22 +64,172 @@ pub struct GridConfig {
     pub float: bool,
     #[serde(default)]
     pub static_grid: bool,
+    #[serde(default)]
+    pub gap_mode: GapMode,
+}
+
+impl GridConfig {
+    /// Gap the layout must keep between widgets, in cells.
+    fn cell_gap(&self) -> i32 {
+        match self.gap_mode {
+            GapMode::Cells => self.gap.max(0),
+            GapMode::Visual => 0,
+        }
+    }
+
+    /// Gap rendered between cells, in pixels. Cell-mode gaps are empty
+    /// cells, so they add no extra pixel spacing.
+    fn pixel_gap(&self) -> f64 {
+        match self.gap_mode {
+            GapMode::Visual => self.gap.max(0) as f64,
+            GapMode::Cells => 0.0,
+        }
+    }
+}
+
+/// How `GridConfig.gap` is interpreted by the layout engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GapMode {
+    /// The gap is applied only when rendering (e.g. CSS pixels); widgets may touch.
+    #[default]
+    Visual,
+    /// The gap is a number of empty cells the layout keeps between widgets.
+    Cells,
+}
+
+/// How `optimizeLayout` compacts unlocked widgets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CompactMode {
+    /// Pull each widget straight up, keeping its column and relative order.
+    #[default]
+    Top,
+    /// Repack by area (first-fit decreasing) to minimize total rows.
+    Dense,
+    /// Pack in the input order, so widget N always comes before widget N+1
+    /// in reading order (top-to-bottom, left-to-right). For list layouts.
+    Ordered,
+}
+
+/// Which free slot `findBestPosition` picks for a new widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PlacementMode {
+    /// The first free slot in reading order (top-most, then left-most).
+    #[default]
+    First,
+    /// The free slot in the top-most free row closest to the widget's
+    /// current `x`, so it doesn't jump across a dense grid.
+    Nearest,
+}
+
+/// Rows occupied by a layout, overall and per column.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GridHeight {
+    pub height: i32,
+    pub column_heights: Vec<i32>,
+}
+
+/// A grid cell coordinate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct CellPoint {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// A pixel coordinate relative to the grid's top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub struct PixelPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A run of adjacent cells in one row covered by the same widgets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OccupancyRun {
+    pub x: i32,
+    pub len: i32,
+    /// Widgets covering these cells: none when free, several when they overlap
+    pub widgets: Vec<String>,
+}
+
+/// Which widgets occupy each cell, row by row, for debugging placement.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OccupancyMap {
+    pub columns: i32,
+    /// Rows the layout occupies, which may exceed the rows returned
+    pub height: i32,
+    /// Run-length encoded rows from the top, at most `max_rows` of them
+    pub rows: Vec<Vec<OccupancyRun>>,
+    pub truncated: bool,
+}
+
+/// A resize request that could not be honoured as-is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeClamp {
+    pub requested_w: i32,
+    pub requested_h: i32,
+    pub applied_w: i32,
+    pub applied_h: i32,
+}
+
+/// Layout after a resize, plus the clamp applied to the resized widget (if any).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResizeResult {
+    pub widgets: Vec<Widget>,
+    pub clamped: Option<SizeClamp>,
 }
 
 // ---
 // 2. LAYOUT ENGINE (Pure Rust)
 // ---
 
+/// Cells one layout call may examine while searching for free space. The
+/// WASM calls run synchronously on the UI thread, so a pathological layout
+/// must not search forever: once the budget is spent, widgets still being
+/// placed go below everything placed so far.
+const MAX_CELL_PROBES: usize = 5_000_000;
+
 struct OccupiedGrid {
     positions: HashSet<(i32, i32)>,
     columns: i32,
+    gap: i32,
+    /// One past the lowest occupied row
+    bottom: i32,
+    probes: Cell<usize>,
+    budget: usize,
 }
 
 impl OccupiedGrid {
-    fn new(columns: i32) -> Self {
+    fn new(columns: i32, gap: i32) -> Self {
         OccupiedGrid {
             positions: HashSet::new(),
             columns,
+            gap,
+            bottom: 0,
+            probes: Cell::new(0),
+            budget: MAX_CELL_PROBES,
+        }
+    }
+
+    #[cfg(test)]
+    fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    fn over_budget(&self) -> bool {
+        self.probes.get() >= self.budget
+    }
+
+    /// A free spot for `pos` that needs no search: its column (kept inside
+    /// the grid), below every occupied cell and its gap.
+    fn below_all(&self, pos: &Position) -> Position {
+        Position {
+            x: pos.x.min(self.columns - pos.w).max(0),
+            y: self.bottom + self.gap,
+            ..*pos
         }
     }
 
@@ -60,8 +237,11 @@ impl OccupiedGrid {
         if pos.x < 0 || pos.y < 0 || (pos.x + pos.w > self.columns) {
             return false;
         }
-        for y in pos.y..(pos.y + pos.h) {
-            for x in pos.x..(pos.x + pos.w) {
+        let span = |size: i32| (size + 2 * self.gap).max(0) as usize;
+        self.probes.set(self.probes.get() + span(pos.w) * span(pos.h));
+        // Occupied cells within `gap` of the widget count as collisions
+        for y in (pos.y - self.gap)..(pos.y + pos.h + self.gap) {
+            for x in (pos.x - self.gap)..(pos.x + pos.w + self.gap) {
                 if self.positions.contains(&(x, y)) {
                     return false;
                 }
@@ -71,6 +251,7 @@ impl OccupiedGrid {
     }
 
     fn register_occupied(&mut self, pos: &Position) {
+        self.bottom = self.bottom.max(pos.y + pos.h);
         for y in pos.y..(pos.y + pos.h) {
             for x in pos.x..(pos.x + pos.w) {
                 self.positions.insert((x, y));
@@ -79,7 +260,20 @@ impl OccupiedGrid {
     }
 
     fn find_highest_position(&self, mut pos: Position) -> Position {
-        while pos.y > 0 {
+        // A block that overlaps (or sits inside the gap of) a placed block
+        // is first pushed down to the nearest free row.
+        if !self.can_place_at(&pos) {
+            match (pos.y + 1..pos.y + 1000)
+                .take_while(|_| !self.over_budget())
+                .find(|&y| self.can_place_at(&Position { y, ..pos.clone() }))
+            {
+                Some(free_y) => pos.y = free_y,
+                None if self.over_budget() => return self.below_all(&pos),
+                None => {}
+            }
+        }
+        // Out of budget, the widget stays at the free row it reached
+        while pos.y > 0 && !self.over_budget() {
             let test_pos = Position {
                 y: pos.y - 1,
                 ..pos
@@ -93,15 +287,23 @@ impl OccupiedGrid {
         pos
     }
 
-    fn find_best_position(&self, widget: &Widget) -> Position {
+    fn find_best_position(&self, widget: &Widget, mode: PlacementMode) -> Position {
         let pos = &widget.position;
         for y in 0..1000 {
             // Limit search
-            for x in 0..(self.columns - pos.w + 1) {
-                let test_pos = Position { x, y, ..*pos };
-                if self.can_place_at(&test_pos) {
-                    return test_pos;
-                }
+            if self.over_budget() {
+                return self.below_all(pos);
+            }
+            let mut free = (0..(self.columns - pos.w + 1))
+                .map(|x| Position { x, y, ..*pos })
+                .filter(|test_pos| self.can_place_at(test_pos));
+            // Nearest: ties go to the left, so the choice is deterministic
+            let found = match mode {
+                PlacementMode::First => free.next(),
+                PlacementMode::Nearest => free.min_by_key(|test_pos| ((test_pos.x - pos.x).abs(), test_pos.x)),
+            };
+            if let Some(found) = found {
+                return found;
             }
         }
         Position {
@@ -112,8 +314,233 @@ impl OccupiedGrid {
     }
 }
 
-fn blocks_collide(a: &Position, b: &Position) -> bool {
-    !(a.x >= (b.x + b.w) || (a.x + a.w) <= b.x || a.y >= (b.y + b.h) || (a.y + a.h) <= b.y)
+/// Computes the lowest occupied row for the layout and for each column.
+/// Locked widgets count like any other, since they still take up space.
+fn measure_grid_height(widgets: &[Widget], columns: i32) -> GridHeight {
+    let mut column_heights = vec![0; columns.max(0) as usize];
+    let mut height = 0;
+    for block in widgets {
+        let pos = &block.position;
+        let bottom = pos.y + pos.h;
+        height = height.max(bottom);
+        let start = pos.x.max(0);
+        let end = (pos.x + pos.w).min(columns);
+        for x in start..end {
+            let column = &mut column_heights[x as usize];
+            *column = (*column).max(bottom);
+        }
+    }
+    GridHeight {
+        height,
+        column_heights,
+    }
+}
+
+/// Builds the occupancy map of a layout, cut off after `max_rows` rows.
+/// Cells outside the grid's columns are left out.
+fn build_occupancy_map(widgets: &[Widget], columns: i32, max_rows: usize) -> OccupancyMap {
+    let mut occupied = OccupiedGrid::new(columns, 0);
+    for block in widgets {
+        occupied.register_occupied(&block.position);
+    }
+    let height = occupied.bottom.max(0);
+
+    let rows = (0..height)
+        .take(max_rows)
+        .map(|y| {
+            let in_row: Vec<&Widget> = widgets
+                .iter()
+                .filter(|b| b.position.y <= y && y < b.position.y + b.position.h)
+                .collect();
+            let mut runs: Vec<OccupancyRun> = Vec::new();
+            for x in 0..columns {
+                let owners: Vec<String> = if occupied.positions.contains(&(x, y)) {
+                    in_row
+                        .iter()
+                        .filter(|b| b.position.x <= x && x < b.position.x + b.position.w)
+                        .map(|b| b.id.clone())
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                match runs.last_mut() {
+                    Some(run) if run.widgets == owners => run.len += 1,
+                    _ => runs.push(OccupancyRun {
+                        x,
+                        len: 1,
+                        widgets: owners,
+                    }),
+                }
+            }
+            runs
+        })
+        .collect::<Vec<_>>();
+
+    OccupancyMap {
+        columns,
+        height,
+        truncated: rows.len() < height as usize,
+        rows,
+    }
+}
+
+/// Cell containing a pixel. Each cell spans its width plus the trailing gap,
+/// so a point inside a gap belongs to the cell before it. Columns are clamped
+/// to the grid and rows to zero.
+fn pixel_to_cell_point(px: f64, py: f64, config: &GridConfig, cell_width: f64, cell_height: f64) -> CellPoint {
+    let gap = config.pixel_gap();
+    let to_cell = |pixel: f64, size: f64| {
+        let pitch = size + gap;
+        if pitch > 0.0 {
+            (pixel / pitch).floor() as i32
+        } else {
+            0
+        }
+    };
+    let mut x = to_cell(px, cell_width).max(0);
+    if config.columns > 0 {
+        x = x.min(config.columns - 1);
+    }
+    CellPoint {
+        x,
+        y: to_cell(py, cell_height).max(0),
+    }
+}
+
+/// Top-left pixel of a cell; the inverse of `pixel_to_cell_point`.
+fn cell_to_pixel_point(x: i32, y: i32, config: &GridConfig, cell_width: f64, cell_height: f64) -> PixelPoint {
+    let gap = config.pixel_gap();
+    PixelPoint {
+        x: x as f64 * (cell_width + gap),
+        y: y as f64 * (cell_height + gap),
+    }
+}
+
+/// Pushes widgets that collide with the anchor down, then compacts the rest
+/// around it (GridStack.js "reflow" logic). The anchor keeps its position.
+fn reflow_around(widgets: &mut [Widget], config: &GridConfig, anchor_index: usize) {
+    let gap = config.cell_gap();
+    // Mark the anchor widget
+    widgets[anchor_index].is_dragged = true;
+    let anchor_pos = widgets[anchor_index].position.clone();
+    // Sort all non-locked, non-anchor blocks by Y position
+    let mut sorted_indices: Vec<usize> = (0..widgets.len())
+        .filter(|&i| i != anchor_index && !widgets[i].locked)
+        .collect();
+    sorted_indices.sort_by_key(|&i| widgets[i].position.y);
+
+    for index in sorted_indices {
+        let block = &mut widgets[index];
+        if blocks_collide(&block.position, &anchor_pos, gap) {
+            // (Simplified logic: push down)
+            let new_y = anchor_pos.y + anchor_pos.h + gap;
+            if new_y > block.position.y {
+                block.position.y = new_y;
+            }
+        }
+    }
+
+    // Compact everything except the anchor
+    let mut occupied = OccupiedGrid::new(config.columns, gap);
+    occupied.register_occupied(&anchor_pos);
+    for block in widgets.iter().filter(|b| b.locked) {
+        occupied.register_occupied(&block.position);
+    }
+
+    let mut compact_indices: Vec<usize> = (0..widgets.len())
+        .filter(|&i| i != anchor_index && !widgets[i].locked)
+        .collect();
+    compact_indices.sort_by_key(|&i| widgets[i].position.y);
+
+    for index in compact_indices {
+        let block = &mut widgets[index];
+        let new_pos = occupied.find_highest_position(block.position.clone());
+        block.position = new_pos;
+        occupied.register_occupied(&block.position);
+    }
+}
+
+/// Top-gravity compaction: sort blocks and move each one up as far as it goes.
+fn compact_top(widgets: &mut [Widget], config: &GridConfig) {
+    widgets.sort_by(|a, b| {
+        a.position
+            .y
+            .cmp(&b.position.y)
+            .then(a.position.x.cmp(&b.position.x))
+    });
+    let mut occupied = OccupiedGrid::new(config.columns, config.cell_gap());
+    for block in widgets.iter().filter(|b| b.locked) {
+        occupied.register_occupied(&block.position);
+    }
+    for block in widgets.iter_mut().filter(|b| !b.locked) {
+        let new_pos = occupied.find_highest_position(block.position.clone());
+        block.position = new_pos;
+        occupied.register_occupied(&block.position);
+    }
+}
+
+/// First-fit-decreasing pass: larger widgets are placed first, each in the
+/// first free slot. Locked widgets stay where they are as fixed obstacles.
+fn pack_dense(widgets: &mut [Widget], config: &GridConfig) {
+    let mut occupied = OccupiedGrid::new(config.columns, config.cell_gap());
+    for block in widgets.iter().filter(|b| b.locked) {
+        occupied.register_occupied(&block.position);
+    }
+
+    let mut pack_indices: Vec<usize> = (0..widgets.len()).filter(|&i| !widgets[i].locked).collect();
+    pack_indices.sort_by(|&a, &b| {
+        let (pa, pb) = (&widgets[a].position, &widgets[b].position);
+        (pb.w * pb.h)
+            .cmp(&(pa.w * pa.h))
+            .then(pa.y.cmp(&pb.y))
+            .then(pa.x.cmp(&pb.x))
+    });
+
+    for index in pack_indices {
+        let block = &mut widgets[index];
+        block.position = occupied.find_best_position(block, PlacementMode::First);
+        occupied.register_occupied(&block.position);
+    }
+}
+
+/// Reading-order packing: each unlocked widget, in its original index order,
+/// takes the first free slot at or after the previous widget's slot. The
+/// widget array itself is not reordered.
+fn compact_ordered(widgets: &mut [Widget], config: &GridConfig) {
+    let mut occupied = OccupiedGrid::new(config.columns, config.cell_gap());
+    for block in widgets.iter().filter(|b| b.locked) {
+        occupied.register_occupied(&block.position);
+    }
+
+    let (mut cursor_x, mut cursor_y) = (0, 0);
+    for block in widgets.iter_mut().filter(|b| !b.locked) {
+        let pos = block.position.clone();
+        // Rows past the occupied bottom (and its gap) are all free, so the
+        // search never needs to look further down than that
+        let last_row = (occupied.bottom + occupied.gap).max(cursor_y);
+        let slot = (cursor_y..=last_row)
+            .flat_map(|y| {
+                let first_x = if y == cursor_y { cursor_x } else { 0 };
+                (first_x..=(config.columns - pos.w)).map(move |x| (x, y))
+            })
+            .map(|(x, y)| Position { x, y, ..pos })
+            .take_while(|_| !occupied.over_budget())
+            .find(|candidate| occupied.can_place_at(candidate));
+
+        // Too wide for the grid, or out of budget
+        block.position = slot.unwrap_or_else(|| occupied.below_all(&pos));
+        occupied.register_occupied(&block.position);
+        cursor_x = block.position.x;
+        cursor_y = block.position.y;
+    }
+}
+
+/// Two widgets collide when they overlap or sit closer than `gap` cells apart.
+fn blocks_collide(a: &Position, b: &Position, gap: i32) -> bool {
+    !(a.x >= (b.x + b.w + gap)
+        || (a.x + a.w + gap) <= b.x
+        || a.y >= (b.y + b.h + gap)
+        || (a.y + a.h + gap) <= b.y)
 }
 
 // ---
@@ -135,10 +562,30 @@ fn serialize_to_js<T: Serialize>(rust_val: &T) -> Result<JsValue, JsValue> {
 }
 
 /// Optimizes the layout (GridStack.js "compact" logic).
+/// `js_mode` is an optional `CompactMode` ("top", "dense" or "ordered");
+/// defaults to "top". Searching is bounded by `MAX_CELL_PROBES`; past it the
+/// remaining widgets are stacked at the bottom rather than packed.
 #[wasm_bindgen(js_name = "optimizeLayout")]
-pub fn optimize_layout(js_widgets: JsValue, js_config: JsValue) -> Result<JsValue, JsValue> {
+pub fn optimize_layout(
+    js_widgets: JsValue,
+    js_config: JsValue,
+    js_mode: JsValue,
+) -> Result<JsValue, JsValue> {
     let mut widgets: Vec<Widget> = parse_from_js(&js_widgets)?;
     let config: GridConfig = parse_from_js(&js_config)?;
+    let mode: Option<CompactMode> = parse_from_js(&js_mode)?;
+
+    match mode.unwrap_or_default() {
+        CompactMode::Dense => {
+            pack_dense(&mut widgets, &config);
+            return serialize_to_js(&widgets);
+        }
+        CompactMode::Ordered => {
+            compact_ordered(&mut widgets, &config);
+            return serialize_to_js(&widgets);
+        }
+        CompactMode::Top => {}
+    }
 
     if config.float {
         // In float mode, just validate bounds
@@ -153,23 +600,7 @@ pub fn optimize_layout(js_widgets: JsValue, js_config: JsValue) -> Result<JsValu
         return serialize_to_js(&widgets);
     }
 
-    // Compact mode: sort blocks and move them up
-    widgets.sort_by(|a, b| {
-        a.position
-            .y
-            .cmp(&b.position.y)
-            .then(a.position.x.cmp(&b.position.x))
-    });
-    let mut occupied = OccupiedGrid::new(config.columns);
-    for block in widgets.iter().filter(|b| b.locked) {
-        occupied.register_occupied(&block.position);
-    }
-    for block in widgets.iter_mut().filter(|b| !b.locked) {
-        let new_pos = occupied.find_highest_position(block.position.clone());
-        block.position = new_pos;
-        occupied.register_occupied(&block.position);
-    }
-
+    compact_top(&mut widgets, &config);
     serialize_to_js(&widgets)
 }
 
@@ -184,67 +615,426 @@ pub fn resolve_conflicts(
     let config: GridConfig = parse_from_js(&js_config)?;
 
     let Some(dragged_index) = widgets.iter().position(|b| b.id == dragged_widget_id) else {
-        return optimize_layout(serialize_to_js(&widgets)?, js_config);
+        return optimize_layout(serialize_to_js(&widgets)?, js_config, JsValue::UNDEFINED);
     };
 
-    // Mark the dragged widget
-    widgets[dragged_index].is_dragged = true;
-    let dragged_pos = widgets[dragged_index].position.clone();
-    // Sort all non-locked, non-dragged blocks by Y position
-    let mut sorted_indices: Vec<usize> = (0..widgets.len())
-        .filter(|&i| i != dragged_index && !widgets[i].locked)
-        .collect();
-    sorted_indices.sort_by_key(|&i| widgets[i].position.y);
-
-    for index in sorted_indices {
-        let block = &mut widgets[index];
-        if blocks_collide(&block.position, &dragged_pos) {
-            // (Simplified logic: push down)
-            let new_y = dragged_pos.y + dragged_pos.h;
-            if new_y > block.position.y {
-                block.position.y = new_y;
-            }
-        }
-    }
-
-    // Compact except dragged
-    let mut occupied = OccupiedGrid::new(config.columns);
-    occupied.register_occupied(&dragged_pos);
-    for block in widgets.iter().filter(|b| b.locked) {
-        occupied.register_occupied(&block.position);
-    }
-
-    let mut compact_indices: Vec<usize> = (0..widgets.len())
-        .filter(|&i| i != dragged_index && !widgets[i].locked)
-        .collect();
-    compact_indices.sort_by_key(|&i| widgets[i].position.y);
-
-    for index in compact_indices {
-        let block = &mut widgets[index];
-        let new_pos = occupied.find_highest_position(block.position.clone());
-        block.position = new_pos;
-        occupied.register_occupied(&block.position);
-    }
-
+    reflow_around(&mut widgets, &config, dragged_index);
     serialize_to_js(&widgets)
 }
 
 /// Finds the best available position for a new widget.
+/// `js_mode` is an optional `PlacementMode` ("first" or "nearest");
+/// defaults to "first".
 #[wasm_bindgen(js_name = "findBestPosition")]
 pub fn find_best_position(
     js_widgets: JsValue,
     js_new_widget: JsValue,
     js_config: JsValue,
+    js_mode: JsValue,
 ) -> Result<JsValue, JsValue> {
     let widgets: Vec<Widget> = parse_from_js(&js_widgets)?;
     let new_widget: Widget = parse_from_js(&js_new_widget)?;
     let config: GridConfig = parse_from_js(&js_config)?;
+    let mode: Option<PlacementMode> = parse_from_js(&js_mode)?;
 
-    let mut occupied = OccupiedGrid::new(config.columns);
+    let mut occupied = OccupiedGrid::new(config.columns, config.cell_gap());
     for block in widgets {
         occupied.register_occupied(&block.position);
     }
 
-    let final_pos = occupied.find_best_position(&new_widget);
+    let final_pos = occupied.find_best_position(&new_widget, mode.unwrap_or_default());
     serialize_to_js(&final_pos)
 }
+
+/// Computes how many rows the layout occupies, overall and per column.
+#[wasm_bindgen(js_name = "computeGridHeight")]
+pub fn compute_grid_height(js_widgets: JsValue, js_config: JsValue) -> Result<JsValue, JsValue> {
+    let widgets: Vec<Widget> = parse_from_js(&js_widgets)?;
+    let config: GridConfig = parse_from_js(&js_config)?;
+
+    serialize_to_js(&measure_grid_height(&widgets, config.columns))
+}
+
+/// Maps a pixel position (relative to the grid) to the grid cell under it,
+/// accounting for the visual gap between cells.
+#[wasm_bindgen(js_name = "pixelToCell")]
+pub fn pixel_to_cell(
+    px: f64,
+    py: f64,
+    js_config: JsValue,
+    cell_width: f64,
+    cell_height: f64,
+) -> Result<JsValue, JsValue> {
+    let config: GridConfig = parse_from_js(&js_config)?;
+    serialize_to_js(&pixel_to_cell_point(px, py, &config, cell_width, cell_height))
+}
+
+/// Maps a grid cell to the pixel position of its top-left corner.
+#[wasm_bindgen(js_name = "cellToPixel")]
+pub fn cell_to_pixel(
+    x: i32,
+    y: i32,
+    js_config: JsValue,
+    cell_width: f64,
+    cell_height: f64,
+) -> Result<JsValue, JsValue> {
+    let config: GridConfig = parse_from_js(&js_config)?;
+    serialize_to_js(&cell_to_pixel_point(x, y, &config, cell_width, cell_height))
+}
+
+/// Returns which widgets occupy each grid cell (see `OccupancyMap`), so the
+/// frontend can render a heatmap when a widget won't place. Only the first
+/// `max_rows` rows are returned, to bound the payload.
+#[wasm_bindgen(js_name = "occupancyMap")]
+pub fn occupancy_map(js_widgets: JsValue, js_config: JsValue, max_rows: u32) -> Result<JsValue, JsValue> {
+    let widgets: Vec<Widget> = parse_from_js(&js_widgets)?;
+    let config: GridConfig = parse_from_js(&js_config)?;
+
+    serialize_to_js(&build_occupancy_map(&widgets, config.columns, max_rows as usize))
+}
+
+/// Resizes a widget, clamping the new size to its min/max bounds, then
+/// reflows the layout around it.
+#[wasm_bindgen(js_name = "resolveResize")]
+pub fn resolve_resize(
+    js_widgets: JsValue,
+    js_config: JsValue,
+    widget_id: String,
+    w: i32,
+    h: i32,
+) -> Result<JsValue, JsValue> {
+    let mut widgets: Vec<Widget> = parse_from_js(&js_widgets)?;
+    let config: GridConfig = parse_from_js(&js_config)?;
+
+    let Some(index) = widgets.iter().position(|b| b.id == widget_id) else {
+        return Err(JsValue::from_str(&format!(
+            "Widget not found: {}",
+            widget_id
+        )));
+    };
+
+    let clamped = apply_resize(&mut widgets, &config, index, w, h);
+    serialize_to_js(&ResizeResult { widgets, clamped })
+}
+
+/// Resizes the widget at `index` within its size limits and reflows the
+/// rest of the layout around it. Returns the clamp if the request was cut.
+fn apply_resize(widgets: &mut [Widget], config: &GridConfig, index: usize, w: i32, h: i32) -> Option<SizeClamp> {
+    let (applied_w, applied_h) = widgets[index].clamp_size(w, h, config.columns);
+    let clamped = if applied_w != w || applied_h != h {
+        Some(SizeClamp {
+            requested_w: w,
+            requested_h: h,
+            applied_w,
+            applied_h,
+        })
+    } else {
+        None
+    };
+
+    let block = &mut widgets[index];
+    block.original_position = Some(block.position.clone());
+    block.position.w = applied_w;
+    block.position.h = applied_h;
+    if config.columns > 0 {
+        block.position.x = block.position.x.min(config.columns - applied_w).max(0);
+    }
+
+    reflow_around(widgets, config, index);
+    clamped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widget(id: &str, x: i32, y: i32, w: i32, h: i32) -> Widget {
+        Widget {
+            id: id.to_string(),
+            position: Position { x, y, w, h },
+            locked: false,
+            min_w: None,
+            min_h: None,
+            max_w: None,
+            max_h: None,
+            is_dragged: false,
+            original_position: None,
+        }
+    }
+
+    fn config(gap: i32, gap_mode: GapMode) -> GridConfig {
+        GridConfig {
+            columns: 12,
+            gap,
+            gap_mode,
+            ..GridConfig::default()
+        }
+    }
+
+    fn position_of<'a>(widgets: &'a [Widget], id: &str) -> &'a Position {
+        &widgets.iter().find(|w| w.id == id).unwrap().position
+    }
+
+    #[test]
+    fn test_blocks_collide_with_gap() {
+        let a = Position {
+            x: 0,
+            y: 0,
+            w: 2,
+            h: 2,
+        };
+        let adjacent = Position {
+            x: 2,
+            y: 0,
+            w: 2,
+            h: 2,
+        };
+        let spaced = Position {
+            x: 3,
+            y: 0,
+            w: 2,
+            h: 2,
+        };
+
+        assert!(!blocks_collide(&a, &adjacent, 0));
+        assert!(blocks_collide(&a, &adjacent, 1));
+        assert!(!blocks_collide(&a, &spaced, 1));
+    }
+
+    #[test]
+    fn test_compact_gap_zero_butts_widgets() {
+        let mut widgets = vec![widget("a", 0, 0, 4, 2), widget("b", 0, 5, 4, 2)];
+        compact_top(&mut widgets, &config(0, GapMode::Cells));
+
+        assert_eq!(position_of(&widgets, "b").y, 2);
+    }
+
+    #[test]
+    fn test_compact_gap_one_keeps_spacing() {
+        let mut widgets = vec![widget("a", 0, 0, 4, 2), widget("b", 0, 5, 4, 2)];
+        compact_top(&mut widgets, &config(1, GapMode::Cells));
+        assert_eq!(position_of(&widgets, "b").y, 3);
+
+        // Widgets already touching are pushed apart
+        let mut widgets = vec![widget("a", 0, 0, 4, 2), widget("b", 0, 2, 4, 2)];
+        compact_top(&mut widgets, &config(1, GapMode::Cells));
+        assert_eq!(position_of(&widgets, "b").y, 3);
+    }
+
+    #[test]
+    fn test_visual_gap_is_ignored_by_layout() {
+        let mut widgets = vec![widget("a", 0, 0, 4, 2), widget("b", 0, 5, 4, 2)];
+        compact_top(&mut widgets, &config(8, GapMode::Visual));
+
+        assert_eq!(position_of(&widgets, "b").y, 2);
+    }
+
+    #[test]
+    fn test_reflow_respects_gap() {
+        let mut widgets = vec![widget("a", 0, 0, 4, 2), widget("b", 0, 1, 4, 2)];
+        reflow_around(&mut widgets, &config(1, GapMode::Cells), 0);
+
+        assert_eq!(position_of(&widgets, "b").y, 3);
+    }
+
+    #[test]
+    fn test_pixel_cell_mapping_accounts_for_gap() {
+        let visual = config(10, GapMode::Visual);
+        // 50px cells + 10px gap: column 2 starts at 120px
+        assert_eq!(cell_to_pixel_point(2, 1, &visual, 50.0, 30.0), PixelPoint { x: 120.0, y: 40.0 });
+        assert_eq!(pixel_to_cell_point(120.0, 40.0, &visual, 50.0, 30.0), CellPoint { x: 2, y: 1 });
+        // Inside the gap after column 1
+        assert_eq!(pixel_to_cell_point(115.0, 0.0, &visual, 50.0, 30.0), CellPoint { x: 1, y: 0 });
+        // Round trip for every column
+        for x in 0..12 {
+            let pixel = cell_to_pixel_point(x, 3, &visual, 50.0, 30.0);
+            assert_eq!(pixel_to_cell_point(pixel.x, pixel.y, &visual, 50.0, 30.0), CellPoint { x, y: 3 });
+        }
+        // Clamped to the grid
+        assert_eq!(pixel_to_cell_point(-5.0, -5.0, &visual, 50.0, 30.0), CellPoint { x: 0, y: 0 });
+        assert_eq!(pixel_to_cell_point(5000.0, 0.0, &visual, 50.0, 30.0).x, 11);
+
+        // Cell-mode gaps are layout cells, not pixels
+        let cells = config(1, GapMode::Cells);
+        assert_eq!(cell_to_pixel_point(2, 0, &cells, 50.0, 30.0).x, 100.0);
+    }
+
+    #[test]
+    fn test_ordered_compaction_preserves_original_order() {
+        // Input order a, b, c, d; visually d sits above b and c
+        let mut widgets = vec![
+            widget("a", 0, 0, 6, 2),
+            widget("b", 6, 8, 6, 2),
+            widget("c", 0, 9, 12, 1),
+            widget("d", 0, 4, 6, 2),
+        ];
+        compact_ordered(&mut widgets, &config(0, GapMode::Cells));
+
+        let ids: Vec<&str> = widgets.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c", "d"]);
+
+        let reading_order: Vec<(i32, i32)> = widgets.iter().map(|w| (w.position.y, w.position.x)).collect();
+        assert!(reading_order.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", reading_order);
+
+        // Gaps removed: a and b share row 0, c fills row 2, d follows
+        assert_eq!((position_of(&widgets, "b").x, position_of(&widgets, "b").y), (6, 0));
+        assert_eq!(position_of(&widgets, "c").y, 2);
+        assert_eq!(position_of(&widgets, "d").y, 3);
+    }
+
+    #[test]
+    fn test_ordered_compaction_falls_back_below_locked_widgets() {
+        // Too wide for any slot: it goes below the locked widget, not onto it
+        let locked = Widget {
+            locked: true,
+            ..widget("locked", 0, 1000, 12, 1)
+        };
+        let mut widgets = vec![locked, widget("wide", 0, 0, 14, 1), widget("after", 0, 5, 2, 1)];
+        compact_ordered(&mut widgets, &config(0, GapMode::Cells));
+
+        assert_eq!(position_of(&widgets, "wide").y, 1001);
+        // Reading order carries on after the fallback slot
+        let after = position_of(&widgets, "after");
+        assert_eq!((after.x, after.y), (0, 1002));
+    }
+
+    #[test]
+    fn test_search_budget_falls_back_below_placed_widgets() {
+        let mut occupied = OccupiedGrid::new(4, 0).with_budget(20);
+        // Every column blocked for the first 10 rows
+        occupied.register_occupied(&Position { x: 0, y: 0, w: 4, h: 10 });
+
+        let new_widget = widget("new", 0, 0, 2, 2);
+        let pos = occupied.find_best_position(&new_widget, PlacementMode::First);
+        assert!(occupied.over_budget());
+        assert_eq!((pos.x, pos.y), (0, 10));
+        assert!(occupied.can_place_at(&pos));
+
+        // Compaction keeps the free row it reached instead of searching on
+        let mut occupied = OccupiedGrid::new(4, 0).with_budget(12);
+        occupied.register_occupied(&Position { x: 0, y: 0, w: 4, h: 1 });
+        let pos = occupied.find_highest_position(Position { x: 0, y: 6, w: 2, h: 2 });
+        assert_eq!(pos.y, 4);
+    }
+
+    #[test]
+    fn test_occupancy_map_encodes_runs_and_overlaps() {
+        let widgets = vec![
+            widget("a", 0, 0, 4, 2),
+            widget("b", 6, 0, 2, 1),
+            widget("c", 2, 1, 4, 2),
+        ];
+        let map = build_occupancy_map(&widgets, 8, 100);
+        assert_eq!((map.columns, map.height, map.truncated), (8, 3, false));
+
+        let run = |x: i32, len: i32, ids: &[&str]| OccupancyRun {
+            x,
+            len,
+            widgets: ids.iter().map(|id| id.to_string()).collect(),
+        };
+        assert_eq!(map.rows[0], [run(0, 4, &["a"]), run(4, 2, &[]), run(6, 2, &["b"])]);
+        // a and c overlap on row 1
+        assert_eq!(map.rows[1], [run(0, 2, &["a"]), run(2, 2, &["a", "c"]), run(4, 2, &["c"]), run(6, 2, &[])]);
+        assert_eq!(map.rows[2], [run(0, 2, &[]), run(2, 4, &["c"]), run(6, 2, &[])]);
+
+        let capped = build_occupancy_map(&widgets, 8, 1);
+        assert_eq!((capped.rows.len(), capped.height, capped.truncated), (1, 3, true));
+    }
+
+    #[test]
+    fn test_nearest_placement_stays_close_to_current_x() {
+        let mut occupied = OccupiedGrid::new(12, 0);
+        // Row 0 is full; row 1 has free slots at x 0..2 and 8..12
+        occupied.register_occupied(&Position { x: 0, y: 0, w: 12, h: 1 });
+        occupied.register_occupied(&Position { x: 2, y: 1, w: 6, h: 1 });
+
+        let new_widget = widget("new", 9, 0, 2, 1);
+        let first = occupied.find_best_position(&new_widget, PlacementMode::First);
+        assert_eq!((first.x, first.y), (0, 1));
+        let nearest = occupied.find_best_position(&new_widget, PlacementMode::Nearest);
+        assert_eq!((nearest.x, nearest.y), (9, 1));
+
+        // Equally distant slots resolve to the left one
+        let mut occupied = OccupiedGrid::new(6, 0);
+        occupied.register_occupied(&Position { x: 2, y: 0, w: 2, h: 1 });
+        let between = occupied.find_best_position(&widget("new", 2, 0, 1, 1), PlacementMode::Nearest);
+        assert_eq!((between.x, between.y), (1, 0));
+    }
+
+    #[test]
+    fn test_resize_is_clamped_to_widget_limits() {
+        let limited = Widget {
+            min_w: Some(2),
+            min_h: Some(2),
+            max_w: Some(6),
+            max_h: Some(4),
+            ..widget("a", 8, 0, 3, 3)
+        };
+        assert_eq!(limited.clamp_size(1, 1, 12), (2, 2));
+        assert_eq!(limited.clamp_size(9, 7, 12), (6, 4));
+        assert_eq!(limited.clamp_size(4, 3, 12), (4, 3));
+        // The grid width caps the size even when the widget allows more
+        assert_eq!(widget("b", 0, 0, 1, 1).clamp_size(20, 1, 12), (12, 1));
+
+        let cfg = config(0, GapMode::Visual);
+        let mut widgets = vec![limited.clone(), widget("b", 0, 4, 2, 2)];
+        let clamp = apply_resize(&mut widgets, &cfg, 0, 1, 1).expect("below min is clamped");
+        assert_eq!((clamp.requested_w, clamp.requested_h), (1, 1));
+        assert_eq!((clamp.applied_w, clamp.applied_h), (2, 2));
+        let a = position_of(&widgets, "a");
+        assert_eq!((a.w, a.h), (2, 2));
+
+        let mut widgets = vec![limited.clone(), widget("b", 0, 4, 2, 2)];
+        let clamp = apply_resize(&mut widgets, &cfg, 0, 9, 7).expect("above max is clamped");
+        assert_eq!((clamp.applied_w, clamp.applied_h), (6, 4));
+        // Growing past the right edge pulls the widget back inside the grid
+        let a = position_of(&widgets, "a");
+        assert_eq!((a.x, a.y, a.w, a.h), (6, 0, 6, 4));
+
+        let mut widgets = vec![limited, widget("b", 0, 4, 2, 2)];
+        assert!(apply_resize(&mut widgets, &cfg, 0, 4, 3).is_none());
+        let a = position_of(&widgets, "a");
+        assert_eq!((a.x, a.y, a.w, a.h), (8, 0, 4, 3));
+    }
+
+    #[test]
+    fn test_dense_packing_fills_gap_left_by_top_compaction() {
+        // c sits under b, while a 4x2 hole remains right of a
+        let layout = vec![
+            widget("a", 0, 0, 8, 2),
+            widget("b", 0, 2, 6, 2),
+            widget("c", 0, 4, 4, 2),
+        ];
+        let cfg = config(0, GapMode::Visual);
+
+        let mut top = layout.clone();
+        compact_top(&mut top, &cfg);
+        let c = position_of(&top, "c");
+        assert_eq!((c.x, c.y), (0, 4));
+        assert_eq!(measure_grid_height(&top, cfg.columns).height, 6);
+
+        let mut dense = layout;
+        pack_dense(&mut dense, &cfg);
+        let c = position_of(&dense, "c");
+        assert_eq!((c.x, c.y), (8, 0));
+        assert_eq!(measure_grid_height(&dense, cfg.columns).height, 4);
+    }
+
+    #[test]
+    fn test_measure_grid_height() {
+        let empty = measure_grid_height(&[], 4);
+        assert_eq!(empty.height, 0);
+        assert_eq!(empty.column_heights, [0, 0, 0, 0]);
+
+        let widgets = vec![
+            widget("short", 0, 0, 2, 1),
+            widget("tall", 2, 1, 1, 9),
+            // Hangs past the last column; only the columns inside count
+            widget("edge", 3, 0, 3, 2),
+        ];
+        let measured = measure_grid_height(&widgets, 4);
+        assert_eq!(measured.height, 10);
+        assert_eq!(measured.column_heights, [1, 1, 10, 2]);
+    }
+}
+
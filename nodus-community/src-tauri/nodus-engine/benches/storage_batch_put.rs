@@ -0,0 +1,97 @@
+// benches/storage_batch_put.rs
+// Inserting 10k entities into SQLite: one `put` per entity versus a single
+// transactional `batch_put`. Run with `cargo bench --bench storage_batch_put`.
+
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use nodus::storage::{SqliteAdapter, StorageAdapter, StorageContext, StoredEntity, SyncStatus};
+
+const ENTITIES: usize = 10_000;
+
+fn entities() -> Vec<(String, StoredEntity)> {
+    let now = Utc::now();
+    (0..ENTITIES)
+        .map(|i| {
+            let id = format!("bench:{}", i);
+            let entity = StoredEntity {
+                id: id.clone(),
+                entity_type: "bench".to_string(),
+                data: serde_json::json!({ "n": i, "title": format!("Entity {}", i) }),
+                created_at: now,
+                updated_at: now,
+                created_by: "bench".to_string(),
+                updated_by: "bench".to_string(),
+                version: 1,
+                deleted_at: None,
+                sync_status: SyncStatus::Local,
+            };
+            (id, entity)
+        })
+        .collect()
+}
+
+/// Fresh database file in the working directory
+async fn open_adapter() -> (SqliteAdapter, String) {
+    let path = format!("nodus_bench_{}.sqlite", Uuid::new_v4());
+    // sqlx only opens existing files with a plain `sqlite://` URL
+    std::fs::File::create(&path).expect("create db file failed");
+    let mut adapter = SqliteAdapter::new(path.clone());
+    adapter.initialize().await.expect("initialize failed");
+    (adapter, path)
+}
+
+/// Close the pool and delete the database with its WAL files
+async fn remove_db(adapter: SqliteAdapter, path: String) {
+    if let Some(pool) = adapter.pool {
+        pool.close().await;
+    }
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+    }
+}
+
+async fn time_serial(ctx: &StorageContext) -> Duration {
+    let (adapter, path) = open_adapter().await;
+    let start = Instant::now();
+    for (key, entity) in entities() {
+        adapter.put(&key, entity, ctx).await.unwrap();
+    }
+    let elapsed = start.elapsed();
+    assert_eq!(adapter.get_stats().await.unwrap().total_entities, ENTITIES as u64);
+    remove_db(adapter, path).await;
+    elapsed
+}
+
+async fn time_batch(ctx: &StorageContext) -> Duration {
+    let (adapter, path) = open_adapter().await;
+    let start = Instant::now();
+    adapter.batch_put(entities(), ctx).await.unwrap();
+    let elapsed = start.elapsed();
+    assert_eq!(adapter.get_stats().await.unwrap().total_entities, ENTITIES as u64);
+    remove_db(adapter, path).await;
+    elapsed
+}
+
+#[tokio::main]
+async fn main() {
+    let ctx = StorageContext {
+        user_id: "bench".to_string(),
+        session_id: Uuid::new_v4(),
+        operation_id: Uuid::new_v4(),
+        skip_validation: true,
+    };
+
+    let serial_time = time_serial(&ctx).await;
+    let batch_time = time_batch(&ctx).await;
+
+    println!("{} entities into SQLite", ENTITIES);
+    println!("serial put loop: {:?}", serial_time);
+    println!("batch_put:       {:?}", batch_time);
+    println!(
+        "speedup:         {:.2}x",
+        serial_time.as_secs_f64() / batch_time.as_secs_f64().max(f64::EPSILON)
+    );
+}
@@ -0,0 +1,63 @@
+// benches/storage_metrics.rs
+// Contention comparison: one shared atomic vs a ShardedCounter, incremented
+// from every core at once. Run with `cargo bench --bench storage_metrics`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nodus::storage::ShardedCounter;
+
+const INCREMENTS_PER_THREAD: u64 = 2_000_000;
+
+fn run_threads<F>(threads: usize, increment: F) -> Duration
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let increment = Arc::new(increment);
+    let start = Instant::now();
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let increment = increment.clone();
+            std::thread::spawn(move || {
+                for _ in 0..INCREMENTS_PER_THREAD {
+                    increment();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let threads = std::thread::available_parallelism().map_or(4, |n| n.get()).max(4);
+
+    let single = Arc::new(AtomicU64::new(0));
+    let single_time = run_threads(threads, {
+        let single = single.clone();
+        move || {
+            single.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    let sharded = Arc::new(ShardedCounter::new());
+    let sharded_time = run_threads(threads, {
+        let sharded = sharded.clone();
+        move || sharded.increment()
+    });
+
+    let expected = threads as u64 * INCREMENTS_PER_THREAD;
+    assert_eq!(single.load(Ordering::Relaxed), expected);
+    assert_eq!(sharded.get(), expected);
+
+    println!("{} threads x {} increments", threads, INCREMENTS_PER_THREAD);
+    println!("single atomic:   {:?}", single_time);
+    println!("sharded counter: {:?}", sharded_time);
+    println!(
+        "speedup:         {:.2}x",
+        single_time.as_secs_f64() / sharded_time.as_secs_f64().max(f64::EPSILON)
+    );
+}
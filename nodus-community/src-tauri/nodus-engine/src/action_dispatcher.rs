@@ -140,6 +140,9 @@ pub struct ObservabilityMetadata {
     pub metrics_recorded: bool,
     pub performance_budget_status: String,
     pub middleware_executed: Vec<String>,
+    /// Plugin whose result answered the action; `None` when core handled it
+    #[serde(default)]
+    pub handled_by_plugin: Option<String>,
 }
 
 /// Action performance statistics
@@ -281,14 +284,7 @@ impl ActionDispatcher {
             Some(h)
         } else {
             // Support simple wildcard handlers registered as `prefix.*`, e.g. `grid.*`
-            handlers.values().find(|h| {
-                let pattern = h.action_type();
-                if let Some(prefix) = pattern.strip_suffix(".*") {
-                    action.action_type.starts_with(prefix)
-                } else {
-                    false
-                }
-            })
+            handlers.values().find(|h| wildcard_matches(h.action_type(), &action.action_type))
         };
 
         let handler = handler_opt.ok_or_else(|| ActionError::HandlerNotFound {
@@ -313,6 +309,7 @@ impl ActionDispatcher {
                     metrics_recorded: false,
                     performance_budget_status: "OK".to_string(),
                     middleware_executed: Vec::new(),
+                    handled_by_plugin: None,
                 },
             },
             Err(ref error) => ActionResult {
@@ -328,6 +325,7 @@ impl ActionDispatcher {
                     metrics_recorded: false,
                     performance_budget_status: "ERROR".to_string(),
                     middleware_executed: Vec::new(),
+                    handled_by_plugin: None,
                 },
             },
         };
@@ -379,6 +377,13 @@ impl ActionDispatcher {
         self.action_performance.read().await.clone()
     }
     
+    /// Whether a handler (exact or `prefix.*` wildcard) exists for an action type
+    pub async fn has_handler(&self, action_type: &str) -> bool {
+        let handlers = self.action_handlers.read().await;
+        handlers.contains_key(action_type)
+            || handlers.values().any(|h| wildcard_matches(h.action_type(), action_type))
+    }
+    
     /// Get list of registered action types
     pub async fn get_registered_actions(&self) -> Vec<String> {
         let handlers = self.action_handlers.read().await;
@@ -428,6 +433,13 @@ impl ActionDispatcher {
     }
 }
 
+/// `prefix.*` handler patterns match every action type under that prefix
+fn wildcard_matches(pattern: &str, action_type: &str) -> bool {
+    pattern
+        .strip_suffix(".*")
+        .map_or(false, |prefix| action_type.starts_with(prefix))
+}
+
 impl Default for ActionDispatcher {
     fn default() -> Self {
         Self {
@@ -515,7 +527,7 @@ impl ActionHandler for GridActionHandler {
                     app_state.clone(),
                 ).await {
                     Ok(v) => Ok(v),
-                    Err(e) => Err(ActionError::ExecutionError { message: e }),
+                    Err(e) => Err(ActionError::ExecutionError { message: e.to_string() }),
                 }
             }
         }
@@ -8,6 +8,7 @@ use tokio::sync::{RwLock, Semaphore};
 use std::collections::HashMap;
 use uuid::Uuid;
 use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
 
 /// Basic operation context used by AppState and plugin system for simple async operations
 #[derive(Debug, Clone)]
@@ -37,6 +38,12 @@ pub struct AsyncOrchestrator {
     
     // Resource monitoring (simplified)
     resource_monitor: ResourceMonitor,
+    
+    // Operation id source
+    ids: crate::id_gen::SharedIdGenerator,
+    
+    // While idle, Low/Background operations wait before starting
+    idle: crate::idle::SharedIdleState,
 }
 
 /// Operation runner for executing async operations (simplified)
@@ -49,6 +56,8 @@ pub struct OperationRunner {
     performance_budget: PerformanceBudget,
     retry_policy: Option<RetryPolicy>,
     timeout: Option<Duration>,
+    cancel_token: CancellationToken,
+    priority: OperationPriority,
 }
 
 /// Active operation tracking (simplified)
@@ -62,6 +71,25 @@ struct ActiveOperation {
     status: OperationStatus,
 }
 
+/// Scheduling priority; `Low` and `Background` work is deferred while idle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationPriority {
+    Critical,
+    High,
+    #[default]
+    Normal,
+    Low,
+    Background,
+}
+
+impl OperationPriority {
+    /// Whether work at this priority waits while the machine is idle
+    pub fn is_deferrable(&self) -> bool {
+        matches!(self, OperationPriority::Low | OperationPriority::Background)
+    }
+}
+
 /// Operation status
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum OperationStatus {
@@ -172,6 +200,9 @@ pub enum OrchestrationError {
     
     #[error("System error: {message}")]
     SystemError { message: String },
+    
+    #[error("Operation cancelled: {operation}")]
+    Cancelled { operation: String },
 }
 
 impl AsyncOrchestrator {
@@ -187,9 +218,23 @@ impl AsyncOrchestrator {
                 max_concurrent_operations: 100,
                 max_memory_usage_mb: 1024,
             },
+            ids: crate::id_gen::default_generator(),
+            idle: Arc::new(crate::idle::IdleState::new()),
         })
     }
     
+    /// Use a custom id generator for operation ids (e.g. deterministic ids in tests)
+    pub fn with_id_generator(mut self, ids: crate::id_gen::SharedIdGenerator) -> Self {
+        self.ids = ids;
+        self
+    }
+    
+    /// Share the app-wide idle flag so deferrable operations wait while idle
+    pub fn with_idle_state(mut self, idle: crate::idle::SharedIdleState) -> Self {
+        self.idle = idle;
+        self
+    }
+    
     /// Create operation runner (replaces JavaScript AsyncOrchestrator.createRunner)
     pub async fn create_runner(
         &self,
@@ -198,7 +243,7 @@ impl AsyncOrchestrator {
         session_id: Uuid,
         _classification: ClassificationLevel,
     ) -> OperationRunner {
-        let operation_id = Uuid::new_v4();
+        let operation_id = self.ids.next_id();
         
         let context = ObservabilityContext {
             component: "async_orchestrator".to_string(),
@@ -221,6 +266,8 @@ impl AsyncOrchestrator {
             performance_budget,
             retry_policy: None,
             timeout: None,
+            cancel_token: CancellationToken::new(),
+            priority: OperationPriority::default(),
         }
     }
 
@@ -362,6 +409,8 @@ impl Clone for AsyncOrchestrator {
             retry_policies: self.retry_policies.clone(),
             operation_metrics: self.operation_metrics.clone(),
             resource_monitor: self.resource_monitor.clone(),
+            ids: self.ids.clone(),
+            idle: self.idle.clone(),
         }
     }
 }
@@ -373,6 +422,8 @@ impl OperationRunner {
         F: FnOnce() -> T + Send + 'static,
         T: Send + 'static,
     {
+        self.defer_while_idle().await;
+        
         let start_time = Instant::now();
         
         println!("[AsyncOrchestrator] Starting operation: {}", self.operation_name);
@@ -431,6 +482,109 @@ impl OperationRunner {
         result
     }
     
+    /// Execute an async operation that stops as soon as the runner is
+    /// cancelled: the future is dropped and `Cancelled` is returned
+    pub async fn run_async<Fut, T>(&self, operation: Fut) -> Result<T, OrchestrationError>
+    where
+        Fut: std::future::Future<Output = Result<T, String>> + Send,
+    {
+        // Cancelling while deferred gives up without ever starting
+        {
+            let cancelled = self.cancel_token.cancelled();
+            let deferred = self.defer_while_idle();
+            futures::pin_mut!(cancelled, deferred);
+            if let futures::future::Either::Left(_) = futures::future::select(cancelled, deferred).await {
+                return Err(OrchestrationError::Cancelled { operation: self.operation_name.clone() });
+            }
+        }
+        
+        let start_time = Instant::now();
+        
+        if self.orchestrator.is_circuit_breaker_open(&self.operation_name).await {
+            return Err(OrchestrationError::CircuitBreakerOpen { operation: self.operation_name.clone() });
+        }
+        
+        let _permit = self.orchestrator.concurrency_limiter.acquire().await
+            .map_err(|_| OrchestrationError::ConcurrencyLimitExceeded)?;
+        
+        {
+            let mut active_ops = self.orchestrator.active_operations.write().await;
+            active_ops.insert(self.operation_id, ActiveOperation {
+                operation_id: self.operation_id,
+                operation_name: self.operation_name.clone(),
+                start_time,
+                user_id: self.context.user_id.clone(),
+                status: OperationStatus::Running,
+            });
+        }
+        
+        let cancelled = self.cancel_token.cancelled();
+        futures::pin_mut!(cancelled, operation);
+        let result = match futures::future::select(cancelled, operation).await {
+            futures::future::Either::Left(_) => Err(OrchestrationError::Cancelled {
+                operation: self.operation_name.clone(),
+            }),
+            futures::future::Either::Right((outcome, _)) => {
+                outcome.map_err(|message| OrchestrationError::OperationFailed { message })
+            }
+        };
+        
+        let duration = start_time.elapsed();
+        self.orchestrator.active_operations.write().await.remove(&self.operation_id);
+        
+        match &result {
+            Ok(_) => self.orchestrator.record_success(&self.operation_name, duration).await,
+            // Cancellation is a user decision, not a failure of the operation
+            Err(OrchestrationError::Cancelled { .. }) => {
+                println!("[AsyncOrchestrator] Operation cancelled: {} ({}ms)",
+                    self.operation_name, duration.as_millis());
+            }
+            Err(_) => self.orchestrator.record_failure(&self.operation_name, duration).await,
+        }
+        
+        result
+    }
+    
+    /// Signal cancellation; a running `run_async` stops and reports `Cancelled`
+    pub async fn cancel(&self) {
+        self.cancel_token.cancel();
+        if let Some(op) = self.orchestrator.active_operations.write().await.get_mut(&self.operation_id) {
+            op.status = OperationStatus::Cancelled;
+        }
+    }
+    
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_token.is_cancelled()
+    }
+    
+    pub fn operation_id(&self) -> Uuid {
+        self.operation_id
+    }
+    
+    /// Token for work running outside the runner that should also stop on cancel
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel_token.clone()
+    }
+    
+    pub fn priority(&self) -> OperationPriority {
+        self.priority
+    }
+    
+    /// Wait for the user to return before starting deferrable work
+    async fn defer_while_idle(&self) {
+        if self.priority.is_deferrable() && self.orchestrator.idle.is_idle() {
+            println!("[AsyncOrchestrator] Deferring {:?} operation while idle: {}",
+                self.priority, self.operation_name);
+            self.orchestrator.idle.wait_until_active().await;
+        }
+    }
+    
+    /// Set scheduling priority
+    pub fn with_priority(mut self, priority: OperationPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+    
     /// Set timeout for operation
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = Some(timeout);
@@ -523,4 +677,71 @@ mod tests {
         
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_cancel_running_operation() {
+        let orchestrator = AsyncOrchestrator::new().await.unwrap();
+        let runner = Arc::new(orchestrator.create_runner(
+            "stuck_import",
+            "test_user",
+            Uuid::new_v4(),
+            ClassificationLevel::Public,
+        ).await);
+        
+        let task_runner = runner.clone();
+        let task = tokio::spawn(async move {
+            task_runner.run_async(async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok::<_, String>("finished")
+            }).await
+        });
+        
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        runner.cancel().await;
+        
+        let result = tokio::time::timeout(Duration::from_secs(1), task).await
+            .expect("operation did not observe cancellation")
+            .unwrap();
+        assert!(matches!(result, Err(OrchestrationError::Cancelled { .. })));
+        assert!(runner.is_cancelled());
+        assert_eq!(orchestrator.get_active_operation_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_injected_id_generator() {
+        let orchestrator = AsyncOrchestrator::new().await.unwrap()
+            .with_id_generator(Arc::new(crate::id_gen::SequentialIds::new()));
+        
+        let first = orchestrator.create_runner("a", "test_user", Uuid::nil(), ClassificationLevel::Public).await;
+        let second = orchestrator.create_runner("b", "test_user", Uuid::nil(), ClassificationLevel::Public).await;
+        
+        assert_eq!(first.operation_id(), Uuid::from_u128(1));
+        assert_eq!(second.operation_id(), Uuid::from_u128(2));
+    }
+
+    #[tokio::test]
+    async fn test_background_work_waits_while_idle() {
+        let idle = Arc::new(crate::idle::IdleState::new());
+        let orchestrator = AsyncOrchestrator::new().await.unwrap().with_idle_state(idle.clone());
+        idle.set_idle(true);
+
+        // Normal priority is unaffected
+        let runner = orchestrator.create_runner("save", "test_user", Uuid::nil(), ClassificationLevel::Public).await;
+        let result = tokio::time::timeout(Duration::from_secs(1), runner.run_async(async { Ok::<_, String>(1) })).await;
+        assert_eq!(result.expect("normal work should not be deferred").unwrap(), 1);
+
+        let runner = orchestrator
+            .create_runner("reindex", "test_user", Uuid::nil(), ClassificationLevel::Public)
+            .await
+            .with_priority(OperationPriority::Background);
+        let task = tokio::spawn(async move { runner.run_async(async { Ok::<_, String>(2) }).await });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!task.is_finished());
+
+        idle.set_idle(false);
+        let result = tokio::time::timeout(Duration::from_secs(1), task).await
+            .expect("background work should resume on activity")
+            .unwrap();
+        assert_eq!(result.unwrap(), 2);
+    }
+}
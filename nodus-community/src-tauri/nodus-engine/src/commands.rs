@@ -3,7 +3,7 @@
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use serde_json::Value;
+use crate::error_code::{CommandError, ErrorCode};
 use crate::state_mod::AppState;
 
 // Engine-level command functions must not depend on Tauri so the engine crate
@@ -12,17 +12,21 @@ use crate::state_mod::AppState;
 
 pub type AppStateType = Arc<RwLock<AppState>>;
 
+/// A missing file is `NotFound`; any other I/O failure is `Internal`
+fn file_error(action: &str, path: &str, e: std::io::Error) -> CommandError {
+    let code = if e.kind() == std::io::ErrorKind::NotFound { ErrorCode::NotFound } else { ErrorCode::Internal };
+    CommandError::new(code, format!("Failed to {} {}: {}", action, path, e))
+}
+
 /// Get overall system status (engine-level). Accepts an Arc<RwLock<AppState>> so
 /// callers (including wrappers) can pass in the shared state.
-pub async fn get_system_status(state: AppStateType) -> Result<Value, String> {
+pub async fn get_system_status(state: AppStateType) -> Result<crate::state_mod::SystemStatus, CommandError> {
     let app_state = state.read().await;
-    // Use the existing get_app_stats() method on AppState for system stats
-    let status = app_state.get_app_stats().await;
-    serde_json::to_value(status).map_err(|e| e.to_string())
+    Ok(app_state.get_system_status().await)
 }
 
 /// List loaded plugins
-pub async fn list_plugins(state: AppStateType) -> Result<Vec<String>, String> {
+pub async fn list_plugins(state: AppStateType) -> Result<Vec<String>, CommandError> {
     let app_state = state.read().await;
     // Return plugin IDs from the AppState plugin info
     let plugins = app_state.get_plugin_info().await;
@@ -30,14 +34,314 @@ pub async fn list_plugins(state: AppStateType) -> Result<Vec<String>, String> {
 }
 
 /// Load a plugin (behavior depends on license tier)
-pub async fn load_plugin(state: AppStateType, plugin_path: String) -> Result<String, String> {
+pub async fn load_plugin(state: AppStateType, plugin_path: String) -> Result<String, CommandError> {
     // Delegate plugin loading to the plugin-specific module so plugin logic
     // remains colocated in `commands_plugin.rs`.
     crate::commands_plugin::load_plugin_from_path(state, plugin_path).await
 }
 
 /// Unload a plugin
-pub async fn unload_plugin(state: AppStateType, plugin_id: String) -> Result<(), String> {
+pub async fn unload_plugin(state: AppStateType, plugin_id: String) -> Result<(), CommandError> {
     // Delegate plugin unloading to the plugin-specific module implementation.
     crate::commands_plugin::unload_plugin_by_id(state, plugin_id).await
 }
+
+/// Reload the license from disk/environment without restarting.
+/// Returns the gained/lost feature delta; subscribers of the license manager
+/// receive the same event.
+pub async fn reload_license(state: AppStateType) -> Result<crate::license_mod::LicenseChangeEvent, CommandError> {
+    let mut app_state = state.write().await;
+    app_state
+        .reload_license()
+        .await
+        .map_err(|e| CommandError::from(e).context("Failed to reload license"))
+}
+
+/// Report everything support needs to know about the active license:
+/// tier, status, expiry, signature validity, matched key, features, limits
+/// and, for rejected licenses, the precise reason.
+pub async fn license_diagnostics(state: AppStateType) -> Result<crate::license_mod::LicenseDiagnostics, CommandError> {
+    let app_state = state.read().await;
+    let mut limit_usage = std::collections::HashMap::new();
+    limit_usage.insert(
+        "concurrent_sessions".to_string(),
+        app_state.sessions.read().await.len() as u32,
+    );
+    Ok(app_state.license_manager.diagnostics(limit_usage).await)
+}
+
+/// Check a license file before installing it. Returns the same report as
+/// `license_diagnostics`, for the file's license; the active license is
+/// left untouched.
+pub async fn validate_license_file(
+    state: AppStateType,
+    path: String,
+) -> Result<crate::license_mod::LicenseDiagnostics, CommandError> {
+    let app_state = state.read().await;
+    Ok(app_state.license_manager.validate_license_file(&path).await)
+}
+
+/// Get this machine's license fingerprint so customers can request a node-locked license
+pub async fn get_machine_id() -> Result<String, CommandError> {
+    Ok(crate::license_mod::LicenseManager::machine_fingerprint())
+}
+
+/// The `limit` most recent timed operations (see `spans::instrument`), newest first
+pub async fn get_recent_spans(limit: usize) -> Result<Vec<crate::spans::SpanRecord>, CommandError> {
+    Ok(crate::spans::span_log().recent(limit))
+}
+
+/// Current usage vs limit for the time-windowed license limits
+pub async fn get_license_usage(state: AppStateType) -> Result<Vec<crate::license_mod::LimitUsage>, CommandError> {
+    let app_state = state.read().await;
+    Ok(app_state.license_manager.get_usage().await)
+}
+
+/// Set the percentages of a license limit at which a warning is broadcast
+/// (default 80, 95, 100). Returns the thresholds in effect.
+pub async fn set_limit_warning_thresholds(state: AppStateType, thresholds: Vec<u8>) -> Result<Vec<u8>, CommandError> {
+    if let Some(bad) = thresholds.iter().find(|t| **t == 0 || **t > 100) {
+        return Err(CommandError::new(
+            ErrorCode::ValidationFailed,
+            format!("Warning thresholds must be between 1 and 100 percent, got {}", bad),
+        ));
+    }
+    let app_state = state.read().await;
+    Ok(app_state.license_manager.set_limit_warning_thresholds(thresholds))
+}
+
+/// Receive a `LimitWarningEvent` each time usage reaches a warning threshold
+pub async fn subscribe_limit_warnings(
+    state: AppStateType,
+) -> Result<tokio::sync::broadcast::Receiver<crate::license_mod::LimitWarningEvent>, CommandError> {
+    let app_state = state.read().await;
+    Ok(app_state.license_manager.subscribe_limit_warnings())
+}
+
+/// Health and declared capabilities of each registered storage backend
+pub async fn get_storage_health(
+    state: AppStateType,
+) -> Result<std::collections::HashMap<String, crate::storage::BackendHealth>, CommandError> {
+    let app_state = state.read().await;
+    Ok(app_state.storage.health_report().await)
+}
+
+/// Switch the storage primary and fallback backends at runtime (e.g. to
+/// migrate from memory to sqlite without restarting)
+pub async fn set_storage_backend(
+    state: AppStateType,
+    primary: String,
+    fallbacks: Vec<String>,
+) -> Result<(), CommandError> {
+    let app_state = state.read().await;
+    app_state
+        .storage
+        .set_backends(primary, fallbacks)
+        .map_err(|e| CommandError::from(e).context("Failed to set storage backend"))
+}
+
+/// One-shot copy of all stored entities from one backend to another,
+/// optionally switching the primary to the destination afterwards
+pub async fn migrate_storage_backend(
+    state: AppStateType,
+    source: String,
+    destination: String,
+    switch_primary: bool,
+) -> Result<crate::storage::MigrationReport, CommandError> {
+    let app_state = state.read().await;
+    app_state
+        .storage
+        .migrate_backend(&source, &destination, switch_primary)
+        .await
+        .map_err(|e| CommandError::from(e).context("Storage migration failed"))
+}
+
+/// Apply an RFC 6902 JSON Patch to a stored entity's data (field-level update)
+pub async fn patch_entity(
+    state: AppStateType,
+    key: String,
+    patch: Vec<crate::storage::PatchOp>,
+) -> Result<crate::storage::StoredEntity, CommandError> {
+    let app_state = state.read().await;
+    let ctx = crate::storage::StorageContext {
+        user_id: "system".to_string(),
+        session_id: uuid::Uuid::new_v4(),
+        operation_id: uuid::Uuid::new_v4(),
+        skip_validation: false,
+    };
+    app_state
+        .storage
+        .patch(&key, patch, &ctx)
+        .await
+        .map_err(|e| CommandError::from(e).context(&format!("Failed to patch entity {}", key)))
+}
+
+/// Register an entity validation schema (e.g. shipped by a plugin). The
+/// schema name is the entity type it validates.
+pub async fn register_validation_schema(
+    state: AppStateType,
+    schema: crate::storage::ValidationSchema,
+) -> Result<(), CommandError> {
+    let app_state = state.read().await;
+    app_state
+        .validation
+        .register_schema(schema)
+        .await
+        .map_err(|e| CommandError::from(e).context("Failed to register validation schema"))
+}
+
+/// Validate entity data on demand against the schema registered for its type
+pub async fn validate_entity(
+    state: AppStateType,
+    entity_type: String,
+    data: serde_json::Value,
+) -> Result<crate::storage::ValidationReport, CommandError> {
+    let app_state = state.read().await;
+    let ctx = crate::storage::validation_mod::ValidationContext {
+        user_id: "system".to_string(),
+        session_id: uuid::Uuid::new_v4(),
+        operation_id: uuid::Uuid::new_v4(),
+        entity_type: Some(entity_type.clone()),
+        validation_mode: crate::storage::validation_mod::ValidationMode::Strict,
+    };
+    app_state
+        .validation
+        .validate(&data, &entity_type, &ctx)
+        .await
+        .map(Into::into)
+        .map_err(|e| CommandError::from(e).context(&format!("Failed to validate {}", entity_type)))
+}
+
+/// Back up the whole store to `path` as JSON Lines, streaming it page by
+/// page so large stores never have to fit in memory
+pub async fn export_storage_to_file(
+    state: AppStateType,
+    path: String,
+) -> Result<crate::storage::ExportReport, CommandError> {
+    use std::io::Write;
+    
+    let file = std::fs::File::create(&path)
+        .map_err(|e| file_error("create export file", &path, e))?;
+    let mut writer = std::io::BufWriter::new(file);
+    
+    let app_state = state.read().await;
+    let report = app_state
+        .storage
+        .export_stream(crate::storage::DEFAULT_EXPORT_CHUNK_SIZE, |chunk| {
+            writer
+                .write_all(&chunk)
+                .map_err(|e| crate::storage::StorageError::Io { error: e.to_string() })
+        })
+        .await
+        .map_err(|e| CommandError::from(e).context("Storage export failed"))?;
+    
+    writer
+        .flush()
+        .map_err(|e| file_error("write export file", &path, e))?;
+    Ok(report)
+}
+
+/// Restore a backup written by `export_storage_to_file` (or a JSON array of
+/// entities). Lenient mode imports the valid records and reports the rest;
+/// strict mode imports nothing if any record is bad.
+pub async fn import_storage_from_file(
+    state: AppStateType,
+    path: String,
+    mode: crate::storage::ImportMode,
+) -> Result<crate::storage::ImportReport, CommandError> {
+    let data = std::fs::read(&path)
+        .map_err(|e| file_error("read import file", &path, e))?;
+    
+    let app_state = state.read().await;
+    let ctx = crate::storage::StorageContext {
+        user_id: "system".to_string(),
+        session_id: uuid::Uuid::new_v4(),
+        operation_id: uuid::Uuid::new_v4(),
+        skip_validation: false,
+    };
+    app_state
+        .storage
+        .import_data(&data, mode, &ctx)
+        .await
+        .map_err(|e| CommandError::from(e).context("Storage import failed"))
+}
+
+/// Manually purge audit events older than `older_than_days` (the background
+/// task applies the configured retention on its own schedule)
+pub async fn purge_old_audit_events(state: AppStateType, older_than_days: u32) -> Result<u64, CommandError> {
+    let app_state = state.read().await;
+    app_state
+        .storage
+        .purge_audit_events(older_than_days)
+        .await
+        .map_err(|e| CommandError::from(e).context("Failed to purge audit events"))
+}
+
+/// Entity count and stored bytes per entity type, largest first, for the
+/// storage-management screen
+pub async fn get_storage_usage(state: AppStateType) -> Result<Vec<crate::storage::EntityTypeUsage>, CommandError> {
+    let app_state = state.read().await;
+    app_state
+        .storage
+        .size_by_type()
+        .await
+        .map_err(|e| CommandError::from(e).context("Failed to read storage usage"))
+}
+
+/// Empty the storage cache so reads hit the backend again, e.g. after the
+/// SQLite file was changed outside the app. Returns the number of entries dropped.
+pub async fn clear_storage_cache(state: AppStateType) -> Result<usize, CommandError> {
+    let app_state = state.read().await;
+    let cleared = app_state.storage.clear_cache().await;
+    tracing::info!("Storage cache cleared ({} entries)", cleared);
+    Ok(cleared)
+}
+
+/// Evict one entity from the storage cache, returning whether it was cached
+pub async fn invalidate_storage_cache(state: AppStateType, key: String) -> Result<bool, CommandError> {
+    let app_state = state.read().await;
+    Ok(app_state.storage.invalidate_cache(&key).await)
+}
+
+/// Recent actions run by a session, oldest first (requires `activity_feeds`)
+pub async fn get_session_activity(
+    state: AppStateType,
+    session_id: uuid::Uuid,
+) -> Result<Vec<crate::state_mod::SessionActivity>, CommandError> {
+    let app_state = state.read().await;
+    app_state
+        .get_session_activity(session_id)
+        .await
+        .map_err(CommandError::from)
+}
+
+/// Report the OS idle state from the frontend. While idle, low-priority and
+/// background operations wait and background sync runs less often.
+/// Returns whether the state changed.
+pub async fn set_idle_state(state: AppStateType, idle: bool) -> Result<bool, CommandError> {
+    let app_state = state.read().await;
+    let changed = app_state.idle.set_idle(idle);
+    if changed {
+        tracing::info!("Idle state changed: idle={}", idle);
+    }
+    Ok(changed)
+}
+
+/// Write a redacted snapshot of the engine state to `path` as pretty JSON,
+/// to attach to bug reports
+pub async fn dump_state(state: AppStateType, path: String) -> Result<(), CommandError> {
+    let snapshot = state.read().await.snapshot().await;
+    let json = serde_json::to_vec_pretty(&snapshot)
+        .map_err(|e| CommandError::new(ErrorCode::Internal, format!("Failed to serialize state snapshot: {}", e)))?;
+    std::fs::write(&path, json).map_err(|e| file_error("write state dump", &path, e))
+}
+
+/// Whether `action_type` would be allowed, with the reason and required tier
+/// when it isn't, so the UI can disable controls without running the action
+pub async fn can_execute(
+    state: AppStateType,
+    action_type: String,
+) -> Result<crate::state_mod::ActionPermission, CommandError> {
+    let app_state = state.read().await;
+    Ok(app_state.check_action_permission(&action_type).await)
+}
@@ -1,13 +1,73 @@
 // Async orchestrator commands for JavaScript-to-Rust bridge
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use crate::error_code::{CommandError, ErrorCode};
 use crate::state_mod::AppState;
 
 pub type AppStateType = Arc<RwLock<AppState>>;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Entity type of persisted operation results, stored under
+/// `async_operation_result:{operation_id}`
+pub const ASYNC_OPERATION_RESULT_TYPE: &str = "async_operation_result";
+
+/// Days a persisted operation result is kept by default
+pub const DEFAULT_OPERATION_RESULT_RETENTION_DAYS: u32 = 7;
+
+/// Whether finished operations' results are stored, so their outcome
+/// survives a page reload, for how long, and how often expired ones are purged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OperationResultRetention {
+    pub enabled: bool,
+    pub retention_days: u32,
+    pub purge_interval_secs: u64,
+}
+
+impl Default for OperationResultRetention {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention_days: DEFAULT_OPERATION_RESULT_RETENTION_DAYS,
+            purge_interval_secs: 60 * 60,
+        }
+    }
+}
+
+/// Purge persisted operation results older than `retention_days`,
+/// returning how many were removed
+pub async fn purge_operation_results(
+    storage: &crate::storage::StorageManager,
+    retention_days: u32,
+) -> Result<u64, crate::storage::StorageError> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(retention_days as i64);
+    storage.purge_older_than(ASYNC_OPERATION_RESULT_TYPE, cutoff).await
+}
+
+/// Run `purge_operation_results` on the retention's interval until the
+/// returned task is aborted. Returns `None` when results aren't stored.
+pub fn spawn_operation_result_retention(
+    storage: Arc<crate::storage::StorageManager>,
+    retention: OperationResultRetention,
+) -> Option<tokio::task::JoinHandle<()>> {
+    if !retention.enabled {
+        return None;
+    }
+    let period = std::time::Duration::from_secs(retention.purge_interval_secs.max(1));
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = purge_operation_results(&storage, retention.retention_days).await {
+                tracing::warn!("Operation result retention purge failed: {}", e);
+            }
+        }
+    }))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AsyncOperationContext {
     pub operation_id: String,
     pub operation_name: String,
@@ -15,6 +75,293 @@ pub struct AsyncOperationContext {
     pub classification: String,
     pub timeout_ms: u64,
     pub metadata: Value,
+    /// Operation ids that must complete successfully before this one starts
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Low/background operations are deferred while the machine is idle
+    #[serde(default)]
+    pub priority: crate::async_orchestrator::OperationPriority,
+}
+
+/// Entry in the active-operations listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveOperationInfo {
+    pub operation_id: String,
+    pub operation_name: String,
+    /// "running" or "waiting"
+    pub status: String,
+    pub depends_on: Vec<String>,
+    /// Dependencies that have not completed yet
+    pub pending_dependencies: Vec<String>,
+}
+
+/// Sequencing state for operations started through `start_async_operation`
+#[derive(Debug, Default)]
+pub struct OperationDependencies {
+    waiting: HashMap<String, AsyncOperationContext>,
+    running: HashMap<String, AsyncOperationContext>,
+    /// Final outcome (true = success) of finished operations, kept only while
+    /// a waiting operation depends on them
+    outcomes: HashMap<String, bool>,
+}
+
+impl OperationDependencies {
+    /// Reject an operation whose id is already active, that depends on an
+    /// operation that isn't running, queued or awaited by a queued one, or
+    /// whose dependencies lead back to itself
+    fn check_dependencies(&self, context: &AsyncOperationContext) -> Result<(), CommandError> {
+        let id = &context.operation_id;
+        if self.running.contains_key(id) || self.waiting.contains_key(id) {
+            return Err(CommandError::new(
+                ErrorCode::Conflict,
+                format!("Operation {} is already active", id),
+            ));
+        }
+        let mut stack: Vec<&String> = context.depends_on.iter().collect();
+        let mut seen = HashSet::new();
+        while let Some(dep) = stack.pop() {
+            if dep == id {
+                return Err(CommandError::new(
+                    ErrorCode::ValidationFailed,
+                    format!("Operation {} has a dependency cycle", id),
+                ));
+            }
+            if !seen.insert(dep) {
+                continue;
+            }
+            if let Some(ctx) = self.waiting.get(dep).or_else(|| self.running.get(dep)) {
+                stack.extend(&ctx.depends_on);
+            }
+        }
+
+        match context.depends_on.iter().find(|dep| {
+            !self.running.contains_key(*dep) && !self.waiting.contains_key(*dep) && !self.outcomes.contains_key(*dep)
+        }) {
+            Some(unknown) => Err(CommandError::new(
+                ErrorCode::NotFound,
+                format!("Operation {} depends on unknown operation {}", id, unknown),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Forget outcomes no waiting operation is still waiting on
+    fn prune_outcomes(&mut self) {
+        let referenced: HashSet<&String> = self.waiting.values().flat_map(|ctx| &ctx.depends_on).collect();
+        self.outcomes.retain(|id, _| referenced.contains(id));
+    }
+
+    fn failed_dependency(&self, context: &AsyncOperationContext) -> Option<String> {
+        context
+            .depends_on
+            .iter()
+            .find(|dep| self.outcomes.get(*dep) == Some(&false))
+            .cloned()
+    }
+
+    fn pending_dependencies(&self, context: &AsyncOperationContext) -> Vec<String> {
+        context
+            .depends_on
+            .iter()
+            .filter(|dep| self.outcomes.get(*dep) != Some(&true))
+            .cloned()
+            .collect()
+    }
+
+    /// Running and waiting operations, sorted by id
+    pub fn active_operations(&self) -> Vec<ActiveOperationInfo> {
+        let running = self.running.values().map(|ctx| (ctx, "running"));
+        let waiting = self.waiting.values().map(|ctx| (ctx, "waiting"));
+        let mut operations: Vec<ActiveOperationInfo> = running
+            .chain(waiting)
+            .map(|(ctx, status)| ActiveOperationInfo {
+                operation_id: ctx.operation_id.clone(),
+                operation_name: ctx.operation_name.clone(),
+                status: status.to_string(),
+                depends_on: ctx.depends_on.clone(),
+                pending_dependencies: self.pending_dependencies(ctx),
+            })
+            .collect();
+        operations.sort_by(|a, b| a.operation_id.cmp(&b.operation_id));
+        operations
+    }
+
+    /// Remove waiting operations that can now start, failing (recursively) any
+    /// whose dependency failed. Returns the operations ready to start.
+    fn take_ready(&mut self) -> Vec<AsyncOperationContext> {
+        let mut ready = Vec::new();
+        loop {
+            let failed: Vec<String> = self
+                .waiting
+                .values()
+                .filter(|ctx| self.failed_dependency(ctx).is_some())
+                .map(|ctx| ctx.operation_id.clone())
+                .collect();
+            if failed.is_empty() {
+                break;
+            }
+            for id in failed {
+                println!("[AsyncOrchestrator] Operation {} failed: dependency failed", id);
+                self.waiting.remove(&id);
+                self.outcomes.insert(id, false);
+            }
+        }
+
+        let ready_ids: Vec<String> = self
+            .waiting
+            .values()
+            .filter(|ctx| self.pending_dependencies(ctx).is_empty())
+            .map(|ctx| ctx.operation_id.clone())
+            .collect();
+        for id in ready_ids {
+            if let Some(ctx) = self.waiting.remove(&id) {
+                ready.push(ctx);
+            }
+        }
+        ready
+    }
+}
+
+/// Hourly buckets kept by `OperationActivity` by default
+pub const DEFAULT_ACTIVITY_HOURS: usize = 24;
+
+/// Daily buckets kept by `OperationActivity` by default
+pub const DEFAULT_ACTIVITY_DAYS: usize = 30;
+
+/// Completions within one hour or day, keyed by the bucket's start
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityBucket {
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub count: u64,
+}
+
+/// Fixed-width buckets, oldest first, covering the most recent `capacity`
+/// bucket widths
+#[derive(Debug, Clone)]
+struct ActivityBuckets {
+    width: chrono::Duration,
+    capacity: usize,
+    buckets: VecDeque<ActivityBucket>,
+}
+
+impl ActivityBuckets {
+    fn new(width: chrono::Duration, capacity: usize) -> Self {
+        Self {
+            width,
+            capacity: capacity.max(1),
+            buckets: VecDeque::new(),
+        }
+    }
+
+    fn bucket_start(&self, at: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+        use chrono::DurationRound;
+        at.duration_trunc(self.width).unwrap_or(at)
+    }
+
+    /// Start of the oldest bucket still inside the window ending at `now`
+    fn window_start(&self, now: chrono::DateTime<chrono::Utc>) -> chrono::DateTime<chrono::Utc> {
+        self.bucket_start(now) - self.width * (self.capacity as i32 - 1)
+    }
+
+    fn record(&mut self, at: chrono::DateTime<chrono::Utc>) {
+        let start = self.bucket_start(at);
+        // Expire against the newest time seen, so a late report can't
+        // bring back buckets that have already rolled off
+        let newest = self.buckets.back().map_or(start, |b| b.start.max(start));
+        let window_start = self.window_start(newest);
+        while let Some(oldest) = self.buckets.front() {
+            if oldest.start >= window_start {
+                break;
+            }
+            self.buckets.pop_front();
+        }
+        if start < window_start {
+            return;
+        }
+
+        let pos = self.buckets.partition_point(|b| b.start < start);
+        match self.buckets.get_mut(pos) {
+            Some(bucket) if bucket.start == start => bucket.count += 1,
+            _ => self.buckets.insert(pos, ActivityBucket { start, count: 1 }),
+        }
+    }
+
+    /// Non-empty buckets inside the window ending at `now`, oldest first
+    fn series(&self, now: chrono::DateTime<chrono::Utc>) -> Vec<ActivityBucket> {
+        let window_start = self.window_start(now);
+        self.buckets
+            .iter()
+            .filter(|b| b.start >= window_start && b.start <= now)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Rolling record of completed operations, bucketed by hour and by day.
+/// Counts have bucket granularity: "this hour" means since the top of the
+/// hour, and the hourly total covers the current hour plus the previous
+/// `hours - 1`.
+#[derive(Debug, Clone)]
+pub struct OperationActivity {
+    hourly: ActivityBuckets,
+    daily: ActivityBuckets,
+}
+
+impl Default for OperationActivity {
+    fn default() -> Self {
+        Self::new(DEFAULT_ACTIVITY_HOURS, DEFAULT_ACTIVITY_DAYS)
+    }
+}
+
+impl OperationActivity {
+    pub fn new(hours: usize, days: usize) -> Self {
+        Self {
+            hourly: ActivityBuckets::new(chrono::Duration::hours(1), hours),
+            daily: ActivityBuckets::new(chrono::Duration::days(1), days),
+        }
+    }
+
+    /// Record one completion at `at`
+    pub fn record(&mut self, at: chrono::DateTime<chrono::Utc>) {
+        self.hourly.record(at);
+        self.daily.record(at);
+    }
+
+    /// Recent counts as of `now`, alongside the lifetime total
+    pub fn report(&self, lifetime_completed: u64, now: chrono::DateTime<chrono::Utc>) -> OperationActivityReport {
+        let hourly = self.hourly.series(now);
+        let daily = self.daily.series(now);
+        let current_hour = self.hourly.bucket_start(now);
+        OperationActivityReport {
+            lifetime_completed,
+            this_hour: hourly.iter().filter(|b| b.start == current_hour).map(|b| b.count).sum(),
+            hours: self.hourly.capacity,
+            hourly_total: hourly.iter().map(|b| b.count).sum(),
+            days: self.daily.capacity,
+            daily_total: daily.iter().map(|b| b.count).sum(),
+            hourly,
+            daily,
+        }
+    }
+}
+
+/// Lifetime and recent completion counts from `get_operation_activity`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationActivityReport {
+    /// Operations completed since startup
+    pub lifetime_completed: u64,
+    /// Completed since the top of the current hour
+    pub this_hour: u64,
+    /// Hours covered by `hourly_total` and `hourly`
+    pub hours: usize,
+    pub hourly_total: u64,
+    /// Days covered by `daily_total` and `daily`
+    pub days: usize,
+    pub daily_total: u64,
+    /// Non-empty hourly buckets, oldest first
+    pub hourly: Vec<ActivityBucket>,
+    /// Non-empty daily buckets, oldest first
+    pub daily: Vec<ActivityBucket>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,13 +373,55 @@ pub struct OperationResult {
     pub duration_ms: u64,
 }
 
-/// Start async operation tracking in Rust backend
+/// A finished operation's outcome as persisted for `get_operation_result`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredOperationResult {
+    pub operation_id: String,
+    pub operation_name: Option<String>,
+    pub success: bool,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub completed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Start async operation tracking in Rust backend. Operations with
+/// `depends_on` are queued until every dependency has completed successfully,
+/// and fail if any dependency fails. Each dependency must still be tracked:
+/// running, queued, or finished with a queued operation waiting on it.
+/// Outcomes nothing waits on are forgotten, so depending on one is rejected
+/// as unknown; a dependency cycle is rejected too.
 pub async fn start_async_operation(
     state: AppStateType, 
     context: AsyncOperationContext
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let app_state = state.read().await;
     
+    {
+        let mut deps = app_state.async_operation_dependencies.write().await;
+        deps.check_dependencies(&context)?;
+        if let Some(failed) = deps.failed_dependency(&context) {
+            return Err(CommandError::new(
+                ErrorCode::Conflict,
+                format!("Operation {} cannot start: dependency {} failed", context.operation_id, failed),
+            ));
+        }
+        if !deps.pending_dependencies(&context).is_empty() {
+            println!("[AsyncOrchestrator] Queued operation: {} ({}) waiting on {:?}",
+                context.operation_name, context.operation_id, context.depends_on);
+            deps.waiting.insert(context.operation_id.clone(), context);
+            return Ok(());
+        }
+        deps.running.insert(context.operation_id.clone(), context.clone());
+    }
+    
+    launch_operation(&app_state, context).await;
+    Ok(())
+}
+
+/// Create and track the runner for an operation whose dependencies are met
+async fn launch_operation(app_state: &AppState, context: AsyncOperationContext) {
     // Start operation through the async orchestrator
     let orchestrator = &app_state.async_orchestrator;
     
@@ -50,11 +439,11 @@ pub async fn start_async_operation(
         uuid::Uuid::parse_str(&context.operation_id)
             .unwrap_or_else(|_| uuid::Uuid::new_v4()),
         classification,
-    ).await;
+    ).await.with_priority(context.priority);
     
     // Store the runner for later completion
     let mut active_operations = app_state.active_async_operations.write().await;
-    active_operations.insert(context.operation_id.clone(), runner);
+    active_operations.insert(context.operation_id.clone(), Arc::new(runner));
     // Record start time for duration tracking
     let mut starts = app_state.active_async_operation_starts.write().await;
     starts.insert(context.operation_id.clone(), chrono::Utc::now());
@@ -62,8 +451,26 @@ pub async fn start_async_operation(
     // Log operation start
     println!("[AsyncOrchestrator] Started operation: {} ({})", 
         context.operation_name, context.operation_id);
+}
+
+/// Record an operation's outcome and start any queued operations it unblocked
+async fn finish_operation(app_state: &AppState, operation_id: &str, success: bool) {
+    let ready = {
+        let mut deps = app_state.async_operation_dependencies.write().await;
+        deps.running.remove(operation_id);
+        deps.waiting.remove(operation_id);
+        deps.outcomes.insert(operation_id.to_string(), success);
+        let ready = deps.take_ready();
+        for ctx in &ready {
+            deps.running.insert(ctx.operation_id.clone(), ctx.clone());
+        }
+        deps.prune_outcomes();
+        ready
+    };
     
-    Ok(())
+    for context in ready {
+        launch_operation(app_state, context).await;
+    }
 }
 
 /// Complete async operation (success or failure)
@@ -73,21 +480,18 @@ pub async fn complete_async_operation(
     success: bool,
     result: Option<String>,
     error: Option<String>,
-) -> Result<OperationResult, String> {
+) -> Result<OperationResult, CommandError> {
     let app_state = state.read().await;
     
     // Remove from active operations
-    let mut active_operations = app_state.active_async_operations.write().await;
-    let _runner = active_operations.remove(&operation_id);
+    let _runner = app_state.active_async_operations.write().await.remove(&operation_id);
     
     // Compute duration if we have a recorded start time
     let mut duration_ms: u64 = 0;
-    {
-        let mut starts = app_state.active_async_operation_starts.write().await;
-        if let Some(start_ts) = starts.remove(&operation_id) {
-            let dur = chrono::Utc::now().signed_duration_since(start_ts);
-            duration_ms = dur.num_milliseconds().max(0) as u64;
-        }
+    let started_at = app_state.active_async_operation_starts.write().await.remove(&operation_id);
+    if let Some(start_ts) = started_at {
+        let dur = chrono::Utc::now().signed_duration_since(start_ts);
+        duration_ms = dur.num_milliseconds().max(0) as u64;
     }
 
     // Create result record
@@ -112,19 +516,237 @@ pub async fn complete_async_operation(
         let mut completed = app_state.completed_operations_count.write().await;
         *completed = completed.saturating_add(1);
     }
+    app_state.operation_activity.write().await.record(chrono::Utc::now());
+    
+    let operation_name = operation_name(&app_state, &operation_id).await;
+    finish_operation(&app_state, &operation_id, success).await;
+    persist_operation_result(&app_state, &operation_result, operation_name, started_at).await;
     
     Ok(operation_result)
 }
 
+/// Run `work` as a tracked operation. The work runs through the operation's
+/// runner, so `cancel_async_operation` drops it at its next await point; it
+/// also gets the cancellation token for anything it hands off elsewhere.
+/// Operations whose dependencies haven't completed are rejected rather than
+/// queued, since there is no caller left to run them once they are ready.
+pub async fn run_async_operation<F, Fut>(
+    state: AppStateType,
+    context: AsyncOperationContext,
+    work: F,
+) -> Result<OperationResult, CommandError>
+where
+    F: FnOnce(tokio_util::sync::CancellationToken) -> Fut,
+    Fut: std::future::Future<Output = Result<String, String>> + Send,
+{
+    let operation_id = context.operation_id.clone();
+    {
+        let app_state = state.read().await;
+        let deps = app_state.async_operation_dependencies.read().await;
+        let pending = deps.pending_dependencies(&context);
+        if !pending.is_empty() {
+            return Err(CommandError::new(
+                ErrorCode::Conflict,
+                format!("Operation {} cannot run yet: waiting on {:?}", operation_id, pending),
+            ));
+        }
+    }
+    start_async_operation(state.clone(), context).await?;
+
+    // The state lock isn't held while the work runs, so a cancel can get in
+    let runner = state
+        .read()
+        .await
+        .active_async_operations
+        .read()
+        .await
+        .get(&operation_id)
+        .cloned()
+        .ok_or_else(|| CommandError::from(format!("Operation {} did not start", operation_id)))?;
+    let started = std::time::Instant::now();
+    let outcome = runner.run_async(work(runner.cancellation_token())).await;
+
+    match outcome {
+        Ok(result) => complete_async_operation(state, operation_id, true, Some(result), None).await,
+        // `cancel_async_operation` has already recorded the outcome
+        Err(crate::async_orchestrator::OrchestrationError::Cancelled { .. }) => Ok(OperationResult {
+            operation_id,
+            success: false,
+            result: None,
+            error: Some(format!("{:?}", crate::async_orchestrator::OperationStatus::Cancelled)),
+            duration_ms: started.elapsed().as_millis() as u64,
+        }),
+        Err(e) => complete_async_operation(state, operation_id, false, None, Some(e.to_string())).await,
+    }
+}
+
+/// Cancel an in-flight operation: signals its cancellation token, marks it
+/// `Cancelled` and stops tracking it. Work started with `run_async_operation`
+/// stops; an operation whose work runs in the frontend is only marked
+/// cancelled here, and the frontend has to abort that work itself.
+pub async fn cancel_async_operation(
+    state: AppStateType,
+    operation_id: String,
+) -> Result<OperationResult, CommandError> {
+    let app_state = state.read().await;
+    
+    let runner = app_state.active_async_operations.write().await.remove(&operation_id);
+    let was_waiting = app_state.async_operation_dependencies.read().await.waiting.contains_key(&operation_id);
+    match runner {
+        Some(runner) => runner.cancel().await,
+        None if was_waiting => {}
+        None => {
+            return Err(CommandError::new(
+                ErrorCode::NotFound,
+                format!("No active operation with id {}", operation_id),
+            ))
+        }
+    }
+    
+    // Dependents of a cancelled operation fail too
+    let operation_name = operation_name(&app_state, &operation_id).await;
+    finish_operation(&app_state, &operation_id, false).await;
+    
+    let started_at = app_state.active_async_operation_starts.write().await.remove(&operation_id);
+    let duration_ms = started_at
+        .map(|start_ts| chrono::Utc::now().signed_duration_since(start_ts).num_milliseconds().max(0) as u64)
+        .unwrap_or(0);
+    
+    println!("[AsyncOrchestrator] Cancelled operation: {}", operation_id);
+    
+    let operation_result = OperationResult {
+        operation_id,
+        success: false,
+        result: None,
+        error: Some(format!("{:?}", crate::async_orchestrator::OperationStatus::Cancelled)),
+        duration_ms,
+    };
+    persist_operation_result(&app_state, &operation_result, operation_name, started_at).await;
+    Ok(operation_result)
+}
+
+/// Name of a running or queued operation
+async fn operation_name(app_state: &AppState, operation_id: &str) -> Option<String> {
+    let deps = app_state.async_operation_dependencies.read().await;
+    deps.running
+        .get(operation_id)
+        .or_else(|| deps.waiting.get(operation_id))
+        .map(|ctx| ctx.operation_name.clone())
+}
+
+fn operation_result_key(operation_id: &str) -> String {
+    format!("{}:{}", ASYNC_OPERATION_RESULT_TYPE, operation_id)
+}
+
+fn system_context() -> crate::storage::StorageContext {
+    crate::storage::StorageContext {
+        user_id: "system".to_string(),
+        session_id: uuid::Uuid::new_v4(),
+        operation_id: uuid::Uuid::new_v4(),
+        skip_validation: false,
+    }
+}
+
+/// Store a finished operation's result when retention is enabled; expired
+/// results are purged by `spawn_operation_result_retention`. The operation
+/// has already finished, so a storage failure is logged rather than returned.
+async fn persist_operation_result(
+    app_state: &AppState,
+    operation_result: &OperationResult,
+    operation_name: Option<String>,
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+) {
+    if !app_state.config.operation_results.enabled {
+        return;
+    }
+    let completed_at = chrono::Utc::now();
+    let stored = StoredOperationResult {
+        operation_id: operation_result.operation_id.clone(),
+        operation_name,
+        success: operation_result.success,
+        result: operation_result.result.clone(),
+        error: operation_result.error.clone(),
+        duration_ms: operation_result.duration_ms,
+        started_at,
+        completed_at,
+    };
+    let data = match serde_json::to_value(&stored) {
+        Ok(data) => data,
+        Err(e) => {
+            tracing::warn!("Failed to serialize result of operation {}: {}", stored.operation_id, e);
+            return;
+        }
+    };
+    let key = operation_result_key(&stored.operation_id);
+    let entity = crate::storage::StoredEntity {
+        id: key.clone(),
+        entity_type: ASYNC_OPERATION_RESULT_TYPE.to_string(),
+        data,
+        created_at: completed_at,
+        updated_at: completed_at,
+        created_by: "system".to_string(),
+        updated_by: "system".to_string(),
+        version: 1,
+        deleted_at: None,
+        sync_status: crate::storage::SyncStatus::Local,
+    };
+    if let Err(e) = app_state.storage.put(&key, entity, &system_context()).await {
+        tracing::warn!("Failed to persist result of operation {}: {}", stored.operation_id, e);
+    }
+}
+
+/// The persisted result of a finished operation, or `None` if it is
+/// unknown, still running, or past the retention window
+pub async fn get_operation_result(
+    state: AppStateType,
+    operation_id: String,
+) -> Result<Option<StoredOperationResult>, CommandError> {
+    let app_state = state.read().await;
+    let entity = app_state
+        .storage
+        .get(&operation_result_key(&operation_id), &system_context())
+        .await
+        .map_err(|e| CommandError::from(e).context("Failed to read operation result"))?;
+    let Some(entity) = entity.filter(|e| e.deleted_at.is_none()) else {
+        return Ok(None);
+    };
+
+    let stored: StoredOperationResult = serde_json::from_value(entity.data)
+        .map_err(|e| {
+            CommandError::new(ErrorCode::Internal, format!("Failed to parse operation result: {}", e))
+        })?;
+    let retention_days = app_state.config.operation_results.retention_days as i64;
+    if stored.completed_at < chrono::Utc::now() - chrono::Duration::days(retention_days) {
+        return Ok(None);
+    }
+    Ok(Some(stored))
+}
+
 /// Get active operation count
-pub async fn get_active_operations_count(state: AppStateType) -> Result<usize, String> {
+pub async fn get_active_operations_count(state: AppStateType) -> Result<usize, CommandError> {
     let app_state = state.read().await;
     let active_operations = app_state.active_async_operations.read().await;
     Ok(active_operations.len())
 }
 
+/// Completed operations since startup and over the recent hourly and
+/// daily windows
+pub async fn get_operation_activity(state: AppStateType) -> Result<OperationActivityReport, CommandError> {
+    let app_state = state.read().await;
+    let completed = *app_state.completed_operations_count.read().await;
+    let activity = app_state.operation_activity.read().await;
+    Ok(activity.report(completed, chrono::Utc::now()))
+}
+
+/// List running and queued operations with their dependency state
+pub async fn list_active_operations(state: AppStateType) -> Result<Vec<ActiveOperationInfo>, CommandError> {
+    let app_state = state.read().await;
+    let deps = app_state.async_operation_dependencies.read().await;
+    Ok(deps.active_operations())
+}
+
 /// Get operation statistics  
-pub async fn get_operation_stats(state: AppStateType) -> Result<Value, String> {
+pub async fn get_operation_stats(state: AppStateType) -> Result<Value, CommandError> {
     let app_state = state.read().await;
     let _orchestrator = &app_state.async_orchestrator;
     
@@ -8,8 +8,11 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use uuid::Uuid;
 use chrono::Utc;
+use futures::StreamExt;
 
 use crate::action_dispatcher::Action;
+use crate::error_code::{CommandError, ErrorCode};
+use crate::payload_limits::PayloadKind;
 use crate::state_mod::AppState;
 
 pub type AppStateType = Arc<RwLock<AppState>>;
@@ -35,6 +38,9 @@ pub struct GridBlock {
     #[serde(default)]
     pub static_grid: bool,
     pub entity_id: Option<String>,
+    /// Last edit to this block; lets sync merge layouts block by block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<chrono::DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,13 +51,305 @@ pub struct GridStateUpdate {
     pub data: Value,
 }
 
-/// Simple ping to verify Rust backend connectivity
-pub async fn ping(_state: AppStateType) -> Result<String, String> {
-    Ok("pong".to_string())
+/// A starting layout `apply_template` can reset a grid to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub columns: Option<u32>,
+    pub blocks: Vec<GridBlock>,
+}
+
+/// Entity type of grid configs, stored (and synced) under
+/// `grid_config:{config_id}`
+pub const GRID_CONFIG_TYPE: &str = "grid_config";
+
+/// Entity type of templates registered at runtime, stored under
+/// `grid_template:{name}`
+pub const GRID_TEMPLATE_TYPE: &str = "grid_template";
+
+/// Templates shipped with the app. Adding one is a data change to this file;
+/// more can be registered at runtime with `register_template`.
+const BUILTIN_TEMPLATES_JSON: &str = include_str!("grid_templates.json");
+
+/// Default idle interval before a debounced grid update is persisted
+pub const DEFAULT_AUTOSAVE_IDLE_MS: u64 = 500;
+
+#[derive(Debug, Clone)]
+struct PendingGridSave {
+    config: GridConfig,
+    generation: u64,
+}
+
+/// Debounced persistence for `update_grid_state`. Rapid updates to the same
+/// `config_id` are coalesced in memory and written once the config has been
+/// idle for `idle_interval`, or when flushed explicitly (e.g. on shutdown).
+#[derive(Debug, Clone)]
+pub struct GridAutoSave {
+    storage: Arc<crate::storage::StorageManager>,
+    pending: Arc<tokio::sync::Mutex<HashMap<String, PendingGridSave>>>,
+    generation: Arc<std::sync::atomic::AtomicU64>,
+    idle_interval: std::time::Duration,
+}
+
+impl GridAutoSave {
+    /// Idle interval comes from `NODUS_GRID_AUTOSAVE_MS` (default 500ms)
+    pub fn new(storage: Arc<crate::storage::StorageManager>) -> Self {
+        let idle_ms = std::env::var("NODUS_GRID_AUTOSAVE_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_AUTOSAVE_IDLE_MS);
+
+        Self {
+            storage,
+            pending: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            generation: Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            idle_interval: std::time::Duration::from_millis(idle_ms),
+        }
+    }
+
+    /// Override the idle interval; zero disables debouncing (write-through)
+    pub fn with_idle_interval(mut self, idle_interval: std::time::Duration) -> Self {
+        self.idle_interval = idle_interval;
+        self
+    }
+
+    /// Unsaved config for `config_id`, if an update is waiting to be flushed
+    pub async fn pending_config(&self, config_id: &str) -> Option<GridConfig> {
+        self.pending.lock().await.get(config_id).map(|p| p.config.clone())
+    }
+
+    /// Number of configs waiting to be flushed
+    pub async fn pending_count(&self) -> usize {
+        self.pending.lock().await.len()
+    }
+
+    /// Record the latest config and persist it after the idle interval
+    pub async fn schedule(&self, config: GridConfig) -> Result<(), CommandError> {
+        if self.idle_interval.is_zero() {
+            return persist_grid_config(&self.storage, &config.config_id, &config).await;
+        }
+
+        let config_id = config.config_id.clone();
+        let generation = self.generation.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        self.pending.lock().await.insert(config_id.clone(), PendingGridSave { config, generation });
+
+        let autosave = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(autosave.idle_interval).await;
+            // Only the last update in a burst writes; newer ones reschedule
+            if let Err(e) = autosave.flush_generation(&config_id, Some(generation)).await {
+                tracing::warn!("Debounced grid save failed for {}: {}", config_id, e);
+            }
+        });
+        Ok(())
+    }
+
+    /// Persist the pending config for `config_id` now. Returns whether anything was written.
+    pub async fn flush(&self, config_id: &str) -> Result<bool, CommandError> {
+        self.flush_generation(config_id, None).await
+    }
+
+    /// Persist every pending config now, returning how many were written.
+    /// A config that fails to save stays pending; the first error is returned
+    /// once every other config has been tried.
+    pub async fn flush_all(&self) -> Result<usize, CommandError> {
+        let config_ids: Vec<String> = self.pending.lock().await.keys().cloned().collect();
+        let mut written = 0;
+        let mut first_error = None;
+        for config_id in config_ids {
+            match self.flush_generation(&config_id, None).await {
+                Ok(true) => written += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(written),
+        }
+    }
+
+    /// Drop any pending update for `config_id` (an explicit save supersedes it)
+    pub async fn discard(&self, config_id: &str) {
+        self.pending.lock().await.remove(config_id);
+    }
+
+    /// Write the pending config for `config_id`. The entry is only removed
+    /// once the write has succeeded, so a flush racing an in-flight debounced
+    /// write still sees (and writes) it instead of returning early.
+    async fn flush_generation(&self, config_id: &str, generation: Option<u64>) -> Result<bool, CommandError> {
+        let pending = match self.pending.lock().await.get(config_id) {
+            Some(p) if generation.map_or(true, |g| g == p.generation) => p.clone(),
+            _ => return Ok(false),
+        };
+
+        persist_grid_config(&self.storage, config_id, &pending.config).await?;
+
+        // An update that arrived during the write keeps its own entry
+        let mut entries = self.pending.lock().await;
+        if entries.get(config_id).map_or(false, |p| p.generation == pending.generation) {
+            entries.remove(config_id);
+        }
+        Ok(true)
+    }
+}
+
+/// Default number of duration samples kept per operation type for percentiles
+pub const DEFAULT_OPERATION_METRIC_SAMPLES: usize = 1000;
+
+/// Aggregated view of one operation type across `operation_completed` calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationStats {
+    pub success_count: u64,
+    pub failure_count: u64,
+    pub average_duration_ms: Option<f64>,
+    pub p50_duration_ms: Option<f64>,
+    pub p95_duration_ms: Option<f64>,
+    pub p99_duration_ms: Option<f64>,
+    pub last_error: Option<String>,
+    pub last_completed_at: chrono::DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+struct OperationAccumulator {
+    success_count: u64,
+    failure_count: u64,
+    total_duration_ms: f64,
+    timed_count: u64,
+    recent_durations: std::collections::VecDeque<f64>,
+    last_error: Option<String>,
+    last_completed_at: chrono::DateTime<Utc>,
+}
+
+/// Session-wide aggregation of `operation_completed` reports per operation type.
+/// Averages cover every call; percentiles use the most recent `max_samples`.
+#[derive(Debug, Clone)]
+pub struct OperationMetrics {
+    max_samples: usize,
+    operations: HashMap<String, OperationAccumulator>,
+}
+
+impl OperationMetrics {
+    pub fn new(max_samples: usize) -> Self {
+        Self {
+            max_samples: max_samples.max(1),
+            operations: HashMap::new(),
+        }
+    }
+
+    /// Record one completed operation
+    pub fn record(&mut self, operation_type: &str, success: bool, duration_ms: Option<f64>, error: Option<&str>) {
+        let max_samples = self.max_samples;
+        let entry = self.operations.entry(operation_type.to_string()).or_insert_with(|| OperationAccumulator {
+            success_count: 0,
+            failure_count: 0,
+            total_duration_ms: 0.0,
+            timed_count: 0,
+            recent_durations: std::collections::VecDeque::new(),
+            last_error: None,
+            last_completed_at: Utc::now(),
+        });
+
+        if success {
+            entry.success_count += 1;
+        } else {
+            entry.failure_count += 1;
+        }
+        if let Some(duration) = duration_ms {
+            entry.total_duration_ms += duration;
+            entry.timed_count += 1;
+            if entry.recent_durations.len() == max_samples {
+                entry.recent_durations.pop_front();
+            }
+            entry.recent_durations.push_back(duration);
+        }
+        if let Some(err) = error {
+            entry.last_error = Some(err.to_string());
+        }
+        entry.last_completed_at = Utc::now();
+    }
+
+    /// Aggregated stats per operation type
+    pub fn snapshot(&self) -> HashMap<String, OperationStats> {
+        self.operations
+            .iter()
+            .map(|(operation_type, acc)| {
+                let mut sorted: Vec<f64> = acc.recent_durations.iter().copied().collect();
+                sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+                let stats = OperationStats {
+                    success_count: acc.success_count,
+                    failure_count: acc.failure_count,
+                    average_duration_ms: if acc.timed_count > 0 {
+                        Some(acc.total_duration_ms / acc.timed_count as f64)
+                    } else {
+                        None
+                    },
+                    p50_duration_ms: percentile(&sorted, 50.0),
+                    p95_duration_ms: percentile(&sorted, 95.0),
+                    p99_duration_ms: percentile(&sorted, 99.0),
+                    last_error: acc.last_error.clone(),
+                    last_completed_at: acc.last_completed_at,
+                };
+                (operation_type.clone(), stats)
+            })
+            .collect()
+    }
+
+    pub fn reset(&mut self) {
+        self.operations.clear();
+    }
+}
+
+impl Default for OperationMetrics {
+    /// Sample window from `NODUS_OPERATION_METRIC_SAMPLES` (default 1000)
+    fn default() -> Self {
+        let max_samples = std::env::var("NODUS_OPERATION_METRIC_SAMPLES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_OPERATION_METRIC_SAMPLES);
+        Self::new(max_samples)
+    }
+}
+
+/// Nearest-rank percentile over already sorted samples
+fn percentile(sorted: &[f64], pct: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Reply to `ping`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingResponse {
+    pub pong: bool,
+    pub engine_version: String,
+    /// The engine's clock. Sync resolves conflicts by timestamp, so a
+    /// frontend whose clock is far from this one should warn.
+    pub server_time: chrono::DateTime<Utc>,
+    pub uptime_ms: u64,
+}
+
+/// Verify Rust backend connectivity, reporting the engine version and clock
+pub async fn ping(state: AppStateType) -> Result<PingResponse, CommandError> {
+    let app_state = state.read().await;
+    Ok(PingResponse {
+        pong: true,
+        engine_version: env!("CARGO_PKG_VERSION").to_string(),
+        server_time: Utc::now(),
+        uptime_ms: app_state.started_at.elapsed().as_millis() as u64,
+    })
 }
 
 /// Get grid configuration with actual storage lookup
-pub async fn get_grid_config(state: AppStateType, config_id: String) -> Result<GridConfig, String> {
+pub async fn get_grid_config(state: AppStateType, config_id: String) -> Result<GridConfig, CommandError> {
     // Read the app state for storage access. This value is used below
     // to access the storage subsystem.
     // Not used directly in this function (we call get_grid_config), keep the
@@ -59,11 +357,22 @@ pub async fn get_grid_config(state: AppStateType, config_id: String) -> Result<G
     // unused-variable warnings.
         let app_state = state.read().await;
     
+    // A layout being edited interactively shadows everything else
+    if let Some(config) = app_state.staged_layouts.read().await.get(&config_id) {
+        return Ok(config.clone());
+    }
+    
+    // Unflushed debounced updates are newer than storage
+    if let Some(config) = app_state.grid_autosave.pending_config(&config_id).await {
+        return Ok(config);
+    }
+    
     // Create storage context
     let ctx = crate::storage::StorageContext {
         user_id: "system".to_string(),
         session_id: Uuid::new_v4(),
         operation_id: Uuid::new_v4(),
+        skip_validation: false,
     };
     
     // Try to get from storage using the key format
@@ -92,16 +401,119 @@ pub async fn get_grid_config(state: AppStateType, config_id: String) -> Result<G
     }
 }
 
+/// How `load_grid_config` treats the layout it reads
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GridLoadOptions {
+    /// Fix out-of-bounds and overlapping blocks, then compact the layout
+    pub repair: bool,
+    /// Save a repaired layout. Without it, stored data is never changed.
+    pub persist: bool,
+}
+
+/// A loaded grid config and whether it needed repair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedGridConfig {
+    pub config: GridConfig,
+    /// The returned layout differs from the one read
+    pub repaired: bool,
+    /// The repaired layout was saved
+    pub persisted: bool,
+}
+
+/// `get_grid_config`, optionally normalizing layouts saved by old clients
+/// with overlapping or out-of-bounds blocks (see `GridLoadOptions`)
+pub async fn load_grid_config(
+    state: AppStateType,
+    config_id: String,
+    options: GridLoadOptions,
+) -> Result<LoadedGridConfig, CommandError> {
+    let mut config = get_grid_config(state.clone(), config_id.clone()).await?;
+    if !options.repair {
+        return Ok(LoadedGridConfig { config, repaired: false, persisted: false });
+    }
+
+    let before = layout_hash(&config.blocks);
+    normalize_layout(&mut config).await;
+    let repaired = layout_hash(&config.blocks) != before;
+    let persisted = repaired && options.persist;
+    if persisted {
+        save_grid_config(state, config_id, config.clone()).await?;
+    }
+    Ok(LoadedGridConfig { config, repaired, persisted })
+}
+
+/// Make a layout valid: every block at least 1x1 and within the grid's
+/// columns, overlaps pushed down in reading order (static blocks stay put),
+/// then compacted
+async fn normalize_layout(config: &mut GridConfig) {
+    let columns = config.columns.unwrap_or(24).max(1);
+    for block in &mut config.blocks {
+        block.w = block.w.clamp(1, columns);
+        block.h = block.h.max(1);
+        block.x = block.x.min(columns - block.w);
+    }
+    push_down_in_reading_order(&mut config.blocks);
+    compact_blocks(&mut config.blocks).await;
+}
+
 /// Save grid configuration to storage
 pub async fn save_grid_config(
     state: AppStateType, 
     config_id: String, 
-    config: GridConfig
-) -> Result<(), String> {
+    mut config: GridConfig
+) -> Result<(), CommandError> {
     // Read the app state (needed for storage access). This value is used
     // below when saving the grid config to the storage subsystem.
     let app_state = state.read().await;
     
+    let previous = match app_state.grid_autosave.pending_config(&config_id).await {
+        Some(previous) => Some(previous),
+        None => load_stored_config(&app_state.storage, &config_id).await,
+    };
+    stamp_changed_blocks(previous.map(|p| p.blocks).unwrap_or_default(), &mut config.blocks);
+    
+    // An explicit save supersedes any debounced update still waiting
+    app_state.grid_autosave.discard(&config_id).await;
+    persist_grid_config(&app_state.storage, &config_id, &config).await
+}
+
+/// The stored config, if there is one and it parses
+async fn load_stored_config(storage: &crate::storage::StorageManager, config_id: &str) -> Option<GridConfig> {
+    let ctx = crate::storage::StorageContext {
+        user_id: "system".to_string(),
+        session_id: Uuid::new_v4(),
+        operation_id: Uuid::new_v4(),
+        skip_validation: false,
+    };
+    let entity = storage.get(&format!("grid_config:{}", config_id), &ctx).await.ok()??;
+    serde_json::from_value(entity.data).ok()
+}
+
+/// Set `updated_at` on blocks that are new or differ from `previous`; the
+/// rest keep their previous stamp. Whole-config saves from the frontend
+/// don't carry per-block times, and sync merges rely on them.
+fn stamp_changed_blocks(previous: Vec<GridBlock>, blocks: &mut [GridBlock]) {
+    let previous: HashMap<String, GridBlock> = previous.into_iter().map(|b| (b.id.clone(), b)).collect();
+    let now = Utc::now();
+    for block in blocks {
+        block.updated_at = match previous.get(&block.id) {
+            Some(old) if canonical(&GridBlock { updated_at: None, ..old.clone() })
+                == canonical(&GridBlock { updated_at: None, ..block.clone() }) =>
+            {
+                old.updated_at.or(block.updated_at)
+            }
+            _ => Some(now),
+        };
+    }
+}
+
+/// Write a grid config to storage under `grid_config:{config_id}`
+async fn persist_grid_config(
+    storage: &crate::storage::StorageManager,
+    config_id: &str,
+    config: &GridConfig,
+) -> Result<(), CommandError> {
     println!("[GridCommands] Saving grid config: {} with {} blocks", config_id, config.blocks.len());
     
     // Create storage context
@@ -109,13 +521,14 @@ pub async fn save_grid_config(
         user_id: "system".to_string(),
         session_id: Uuid::new_v4(),
         operation_id: Uuid::new_v4(),
+        skip_validation: false,
     };
     
     // Create stored entity
     let entity = crate::storage::StoredEntity {
         id: format!("grid_config:{}", config_id),
-        entity_type: "grid_config".to_string(),
-        data: serde_json::to_value(&config).map_err(|e| format!("Serialization error: {}", e))?,
+        entity_type: GRID_CONFIG_TYPE.to_string(),
+        data: serde_json::to_value(config).map_err(|e| CommandError::new(ErrorCode::Internal, format!("Serialization error: {}", e)))?,
         created_at: Utc::now(),
         updated_at: Utc::now(),
         created_by: "system".to_string(),
@@ -127,15 +540,15 @@ pub async fn save_grid_config(
     
     // Save to storage
     let key = format!("grid_config:{}", config_id);
-    match app_state.storage.put(&key, entity, &ctx).await {
+    match storage.put(&key, entity, &ctx).await {
         Ok(()) => {
             println!("[GridCommands] Successfully saved grid config: {}", config_id);
             Ok(())
         },
         Err(e) => {
-            let error_msg = format!("Failed to save grid config: {}", e);
-            println!("[GridCommands] {}", error_msg);
-            Err(error_msg)
+            let error = CommandError::from(e).context("Failed to save grid config");
+            println!("[GridCommands] {}", error);
+            Err(error)
         }
     }
 }
@@ -144,15 +557,11 @@ pub async fn save_grid_config(
 pub async fn update_grid_state(
     state: AppStateType, 
     state_update: GridStateUpdate
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     println!("[GridCommands] Updating grid state: {} - {}", 
              state_update.config_id, state_update.update_type);
     
-    // Not used directly here (we call `get_grid_config`), keep the read for
-    // parity and future use; prefix with underscore to avoid warnings.
-    let _app_state = state.read().await;
-    
-    // Get current config
+    // Get current config (including any unflushed update)
     let mut config = get_grid_config(state.clone(), state_update.config_id.clone()).await?;
     
     // Apply the update based on type
@@ -175,6 +584,7 @@ pub async fn update_grid_state(
                 config: block_data.get("config").cloned().unwrap_or(Value::Object(serde_json::Map::new())),
                 static_grid: block_data.get("static_grid").and_then(|v| v.as_bool()).unwrap_or(false),
                 entity_id: block_data.get("entity_id").and_then(|v| v.as_str()).map(|s| s.to_string()),
+                updated_at: Some(Utc::now()),
             };
             
             // Add the block
@@ -216,10 +626,14 @@ pub async fn update_grid_state(
                 if let Some(title) = update_data.get("title").and_then(|v| v.as_str()) {
                     block.title = Some(title.to_string());
                 }
+                block.updated_at = Some(Utc::now());
                 
                 println!("[GridCommands] Updated block {} in grid {}", state_update.block_id, state_update.config_id);
             } else {
-                return Err(format!("Block {} not found in grid {}", state_update.block_id, state_update.config_id));
+                return Err(CommandError::new(
+                    ErrorCode::NotFound,
+                    format!("Block {} not found in grid {}", state_update.block_id, state_update.config_id),
+                ));
             }
         },
         
@@ -233,31 +647,106 @@ pub async fn update_grid_state(
                 ) {
                     block.x = x as u32;
                     block.y = y as u32;
+                    block.updated_at = Some(Utc::now());
                     println!("[GridCommands] Moved block {} to ({}, {}) in grid {}", 
                              state_update.block_id, x, y, state_update.config_id);
                 }
             } else {
-                return Err(format!("Block {} not found in grid {}", state_update.block_id, state_update.config_id));
+                return Err(CommandError::new(
+                    ErrorCode::NotFound,
+                    format!("Block {} not found in grid {}", state_update.block_id, state_update.config_id),
+                ));
             }
         },
         
         _ => {
-            return Err(format!("Unknown update type: {}", state_update.update_type));
+            return Err(invalid_payload(format!("Unknown update type: {}", state_update.update_type)));
         }
     }
     
-    // Save the updated config
-    save_grid_config(state.clone(), state_update.config_id, config).await?;
+    let app_state = state.read().await;
+    
+    // During a layout edit the update stays provisional until commit
+    let mut staged = app_state.staged_layouts.write().await;
+    if let Some(staged_config) = staged.get_mut(&config.config_id) {
+        *staged_config = config;
+        return Ok(());
+    }
+    drop(staged);
+    
+    // Coalesce with other rapid updates; persisted once the grid goes idle
+    app_state.grid_autosave.schedule(config).await?;
     
     Ok(())
 }
 
+/// Start an interactive layout edit: snapshot the current config into the
+/// staging area so reads see in-progress changes without touching storage
+pub async fn begin_layout_edit(state: AppStateType, config_id: String) -> Result<GridConfig, CommandError> {
+    let config = get_grid_config(state.clone(), config_id.clone()).await?;
+    let app_state = state.read().await;
+    app_state.staged_layouts.write().await.insert(config_id, config.clone());
+    Ok(config)
+}
+
+/// Replace the provisional blocks of a layout being edited
+pub async fn stage_layout(state: AppStateType, config_id: String, widgets: Vec<GridBlock>) -> Result<(), CommandError> {
+    let app_state = state.read().await;
+    let mut staged = app_state.staged_layouts.write().await;
+    let config = staged
+        .get_mut(&config_id)
+        .ok_or_else(|| no_layout_edit(&config_id))?;
+    config.blocks = widgets;
+    Ok(())
+}
+
+/// Persist the staged layout and end the edit
+pub async fn commit_layout(state: AppStateType, config_id: String) -> Result<(), CommandError> {
+    let config = {
+        let app_state = state.read().await;
+        let staged = app_state.staged_layouts.write().await.remove(&config_id);
+        staged.ok_or_else(|| no_layout_edit(&config_id))?
+    };
+    save_grid_config(state, config_id, config).await
+}
+
+fn no_layout_edit(config_id: &str) -> CommandError {
+    CommandError::new(ErrorCode::NotFound, format!("No layout edit in progress for {}", config_id))
+}
+
+/// Discard the staged layout, returning whether an edit was in progress
+pub async fn cancel_layout(state: AppStateType, config_id: String) -> Result<bool, CommandError> {
+    let app_state = state.read().await;
+    let removed = app_state.staged_layouts.write().await.remove(&config_id);
+    Ok(removed.is_some())
+}
+
+/// Persist debounced grid updates immediately: one config, or all when `None`.
+/// Returns the number of configs written.
+pub async fn flush_grid_state(state: AppStateType, config_id: Option<String>) -> Result<usize, CommandError> {
+    let app_state = state.read().await;
+    match config_id {
+        Some(id) => app_state.grid_autosave.flush(&id).await.map(|written| written as usize),
+        None => app_state.grid_autosave.flush_all().await,
+    }
+}
+
+/// Which payload limit a dispatched action's raw body is held to: a saved
+/// config gets the grid config limit, everything else the action limit
+pub fn dispatch_payload_kind(action_type: &str) -> PayloadKind {
+    if action_type == "grid.config.save" { PayloadKind::GridConfig } else { PayloadKind::Action }
+}
+
+fn invalid_payload(message: impl Into<String>) -> CommandError {
+    CommandError::new(ErrorCode::ValidationFailed, message)
+}
+
 /// Main dispatch entry point - ACTUALLY WORKING VERSION
 pub async fn dispatch_action(
     action_type: String,
     payload: Value,
     state: AppStateType,
-) -> Result<Value, String> {
+) -> Result<Value, CommandError> {
     println!("[GridCommands] Dispatching action: {}", action_type);
     
     match action_type.as_str() {
@@ -276,7 +765,7 @@ pub async fn dispatch_action(
         
         "grid.config.save" => {
             let config: GridConfig = serde_json::from_value(payload.clone())
-                .map_err(|e| format!("Invalid grid config: {}", e))?;
+                .map_err(|e| invalid_payload(format!("Invalid grid config: {}", e)))?;
             
             match save_grid_config(state.clone(), config.config_id.clone(), config).await {
                 Ok(()) => Ok(serde_json::json!({ "success": true })),
@@ -290,7 +779,7 @@ pub async fn dispatch_action(
             println!("[GridCommands] grid.block.add payload: {}", payload);
 
             let block_config = payload.get("blockConfig")
-                .ok_or("Missing blockConfig")?;
+                .ok_or_else(|| invalid_payload("Missing blockConfig"))?;
             let container_id = payload.get("containerId")
                 .and_then(|v| v.as_str())
                 .unwrap_or("default")
@@ -320,7 +809,7 @@ pub async fn dispatch_action(
         "grid.block.remove" => {
             let block_id = payload.get("blockId")
                 .and_then(|v| v.as_str())
-                .ok_or("Missing blockId")?
+                .ok_or_else(|| invalid_payload("Missing blockId"))?
                 .to_string();
             let container_id = payload.get("containerId")
                 .and_then(|v| v.as_str())
@@ -342,14 +831,14 @@ pub async fn dispatch_action(
 
         "grid.layout.update" => {
             let layout_config = payload.get("layoutConfig")
-                .ok_or("Missing layoutConfig")?;
+                .ok_or_else(|| invalid_payload("Missing layoutConfig"))?;
             let container_id = payload.get("containerId")
                 .and_then(|v| v.as_str())
                 .unwrap_or("default")
                 .to_string();
 
             let config: GridConfig = serde_json::from_value(layout_config.clone())
-                .map_err(|e| format!("Invalid layout config: {}", e))?;
+                .map_err(|e| invalid_payload(format!("Invalid layout config: {}", e)))?;
 
             match save_grid_config(state.clone(), container_id, config).await {
                 Ok(()) => Ok(serde_json::json!({ "success": true })),
@@ -373,14 +862,14 @@ pub async fn dispatch_action(
         "grid.block.update" => {
             let block_id = payload.get("blockId")
                 .and_then(|v| v.as_str())
-                .ok_or("Missing blockId")?
+                .ok_or_else(|| invalid_payload("Missing blockId"))?
                 .to_string();
             let container_id = payload.get("containerId")
                 .and_then(|v| v.as_str())
                 .unwrap_or("default")
                 .to_string();
             let updates = payload.get("updates")
-                .ok_or("Missing updates")?;
+                .ok_or_else(|| invalid_payload("Missing updates"))?;
 
             let state_update = GridStateUpdate {
                 config_id: container_id,
@@ -398,14 +887,14 @@ pub async fn dispatch_action(
         "grid.block.move" => {
             let block_id = payload.get("blockId")
                 .and_then(|v| v.as_str())
-                .ok_or("Missing blockId")?
+                .ok_or_else(|| invalid_payload("Missing blockId"))?
                 .to_string();
             let container_id = payload.get("containerId")
                 .and_then(|v| v.as_str())
                 .unwrap_or("default")
                 .to_string();
             let position = payload.get("position")
-                .ok_or("Missing position")?;
+                .ok_or_else(|| invalid_payload("Missing position"))?;
 
             let state_update = GridStateUpdate {
                 config_id: container_id,
@@ -466,7 +955,7 @@ fn create_default_config(config_id: &str) -> GridConfig {
 }
 
 /// Execute a grid action (alternative entry point)
-pub async fn execute_action(state: AppStateType, action: Action) -> Result<Value, String> {
+pub async fn execute_action(state: AppStateType, action: Action) -> Result<Value, CommandError> {
     let action_type = action.action_type;
     let payload = action.payload;
     
@@ -474,7 +963,7 @@ pub async fn execute_action(state: AppStateType, action: Action) -> Result<Value
 }
 
 /// Get grid statistics
-pub async fn get_grid_stats(config_id: String, state: AppStateType) -> Result<Value, String> {
+pub async fn get_grid_stats(config_id: String, state: AppStateType) -> Result<Value, CommandError> {
     match get_grid_config(state, config_id.clone()).await {
         Ok(config) => {
             let stats = serde_json::json!({
@@ -491,20 +980,23 @@ pub async fn get_grid_stats(config_id: String, state: AppStateType) -> Result<Va
 }
 
 /// Export grid configuration as JSON
-pub async fn export_grid_config(config_id: String, state: AppStateType) -> Result<String, String> {
+pub async fn export_grid_config(config_id: String, state: AppStateType) -> Result<String, CommandError> {
     match get_grid_config(state, config_id).await {
         Ok(config) => {
             serde_json::to_string_pretty(&config)
-                .map_err(|e| format!("Failed to serialize config: {}", e))
+                .map_err(|e| CommandError::new(ErrorCode::Internal, format!("Failed to serialize config: {}", e)))
         },
         Err(e) => Err(e)
     }
 }
 
 /// Import grid configuration from JSON
-pub async fn import_grid_config(config_json: String, state: AppStateType) -> Result<Value, String> {
+pub async fn import_grid_config(config_json: String, state: AppStateType) -> Result<Value, CommandError> {
+    // Checked on the raw string, before parsing
+    state.read().await.config.payload_limits
+        .check_len(PayloadKind::GridConfig, config_json.len())?;
     let config: GridConfig = serde_json::from_str(&config_json)
-        .map_err(|e| format!("Invalid JSON config: {}", e))?;
+        .map_err(|e| invalid_payload(format!("Invalid JSON config: {}", e)))?;
     
     match save_grid_config(state, config.config_id.clone(), config.clone()).await {
                 Ok(()) => Ok(serde_json::json!({ 
@@ -516,6 +1008,173 @@ pub async fn import_grid_config(config_json: String, state: AppStateType) -> Res
     }
 }
 
+/// Built-in templates plus any registered in storage, by name. A registered
+/// template replaces the built-in one with the same name.
+pub async fn list_templates(state: AppStateType) -> Result<Vec<GridTemplate>, CommandError> {
+    let mut templates: Vec<GridTemplate> = serde_json::from_str(BUILTIN_TEMPLATES_JSON)
+        .map_err(|e| CommandError::new(ErrorCode::Internal, format!("Invalid built-in grid templates: {}", e)))?;
+
+    let app_state = state.read().await;
+    let ctx = crate::storage::StorageContext {
+        user_id: "system".to_string(),
+        session_id: Uuid::new_v4(),
+        operation_id: Uuid::new_v4(),
+        skip_validation: false,
+    };
+    let query = crate::storage::StorageQuery::builder().entity_type(GRID_TEMPLATE_TYPE).build();
+    let stored = app_state.storage.query(&query, &ctx).await
+        .map_err(|e| CommandError::from(e).context("Failed to list grid templates"))?;
+    for entity in stored.into_iter().filter(|entity| entity.deleted_at.is_none()) {
+        match serde_json::from_value::<GridTemplate>(entity.data) {
+            Ok(template) => {
+                templates.retain(|t| t.name != template.name);
+                templates.push(template);
+            }
+            Err(e) => tracing::warn!("Skipping unreadable grid template {}: {}", entity.id, e),
+        }
+    }
+
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+/// Register (or replace) a template so `list_templates` and `apply_template`
+/// offer it
+pub async fn register_template(state: AppStateType, template: GridTemplate) -> Result<(), CommandError> {
+    if template.name.trim().is_empty() {
+        return Err(invalid_payload("Template name must not be empty"));
+    }
+    let app_state = state.read().await;
+    let ctx = crate::storage::StorageContext {
+        user_id: "system".to_string(),
+        session_id: Uuid::new_v4(),
+        operation_id: Uuid::new_v4(),
+        skip_validation: false,
+    };
+    let key = format!("{}:{}", GRID_TEMPLATE_TYPE, template.name);
+    let entity = crate::storage::StoredEntity {
+        id: key.clone(),
+        entity_type: GRID_TEMPLATE_TYPE.to_string(),
+        data: serde_json::to_value(&template).map_err(|e| CommandError::new(ErrorCode::Internal, format!("Serialization error: {}", e)))?,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        created_by: "system".to_string(),
+        updated_by: "system".to_string(),
+        version: 1,
+        deleted_at: None,
+        sync_status: crate::storage::SyncStatus::Local,
+    };
+    app_state.storage.put(&key, entity, &ctx).await
+        .map_err(|e| CommandError::from(e).context("Failed to register grid template"))
+}
+
+/// Replace a grid's layout with a template's. Template blocks get fresh ids.
+/// With `preserve_blocks`, the grid's existing blocks are kept: narrowed to
+/// fit the template's columns, moved below the template blocks and then
+/// compacted into any space left free.
+pub async fn apply_template(
+    state: AppStateType,
+    config_id: String,
+    template_name: String,
+    preserve_blocks: bool,
+) -> Result<GridConfig, CommandError> {
+    let template = list_templates(state.clone())
+        .await?
+        .into_iter()
+        .find(|t| t.name == template_name)
+        .ok_or_else(|| CommandError::new(ErrorCode::NotFound, format!("Unknown grid template: {}", template_name)))?;
+    let current = get_grid_config(state.clone(), config_id.clone()).await?;
+
+    let columns = template.columns.or(current.columns).unwrap_or(24).max(1);
+    let mut blocks: Vec<GridBlock> = {
+        let app_state = state.read().await;
+        template
+            .blocks
+            .into_iter()
+            .map(|block| GridBlock { id: app_state.ids.next_id().to_string(), ..block })
+            .collect()
+    };
+
+    if preserve_blocks {
+        let bottom = blocks.iter().map(|b| b.y + b.h).max().unwrap_or(0);
+        let top = current.blocks.iter().map(|b| b.y).min().unwrap_or(0);
+        blocks.extend(current.blocks.into_iter().map(|block| {
+            let w = block.w.clamp(1, columns);
+            GridBlock {
+                w,
+                x: block.x.min(columns - w),
+                y: block.y - top + bottom,
+                // Pinned positions belong to the old layout
+                static_grid: false,
+                ..block
+            }
+        }));
+        compact_blocks(&mut blocks).await;
+    }
+
+    let config = GridConfig {
+        blocks,
+        columns: Some(columns),
+        config_id: config_id.clone(),
+        metadata: Some(serde_json::json!({
+            "created_at": Utc::now().to_rfc3339(),
+            "template": template.name,
+        })),
+    };
+    save_grid_config(state, config_id, config.clone()).await?;
+    Ok(config)
+}
+
+/// Outcome of `reflow_to_columns`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReflowResult {
+    pub config: GridConfig,
+    /// Blocks whose position or width didn't scale to a whole column and
+    /// were rounded, or that had to be narrowed or moved left to fit
+    pub inexact_blocks: Vec<String>,
+}
+
+/// Fit a grid to a new column count, for switching layouts at responsive
+/// breakpoints. Each block's `x` and `w` are scaled by `new / old` (widths
+/// rounded, at least one column), blocks that overlap after rounding are
+/// pushed down in reading order, and the layout is compacted and saved.
+pub async fn reflow_to_columns(state: AppStateType, config_id: String, new_columns: u32) -> Result<ReflowResult, CommandError> {
+    if new_columns == 0 {
+        return Err(invalid_payload("A grid needs at least one column"));
+    }
+    let mut config = get_grid_config(state.clone(), config_id.clone()).await?;
+    let old_columns = config.columns.unwrap_or(24).max(1);
+    if old_columns == new_columns {
+        return Ok(ReflowResult { config, inexact_blocks: Vec::new() });
+    }
+
+    let inexact_blocks = scale_blocks(&mut config.blocks, old_columns, new_columns);
+    push_down_in_reading_order(&mut config.blocks);
+    compact_blocks(&mut config.blocks).await;
+    config.columns = Some(new_columns);
+
+    save_grid_config(state, config_id, config.clone()).await?;
+    Ok(ReflowResult { config, inexact_blocks })
+}
+
+/// Scale blocks' columns from `old` to `new`, returning the ids of blocks
+/// that didn't scale exactly
+fn scale_blocks(blocks: &mut [GridBlock], old: u32, new: u32) -> Vec<String> {
+    let (old, new) = (u64::from(old), u64::from(new));
+    let mut inexact = Vec::new();
+    for block in blocks {
+        let (x, w) = (u64::from(block.x) * new, u64::from(block.w) * new);
+        let scaled_w = ((w + old / 2) / old).clamp(1, new);
+        let scaled_x = (x / old).min(new - scaled_w);
+        if scaled_w * old != w || scaled_x * old != x {
+            inexact.push(block.id.clone());
+        }
+        block.x = scaled_x as u32;
+        block.w = scaled_w as u32;
+    }
+    inexact
+}
+
 /// Operation completed callback
 pub async fn operation_completed(
     operation_type: String,
@@ -523,8 +1182,16 @@ pub async fn operation_completed(
     duration: Option<f64>,
     error: Option<String>,
     metadata: Option<Value>,
-    _state: AppStateType,
-) -> Result<(), String> {
+    state: AppStateType,
+) -> Result<(), CommandError> {
+    state
+        .read()
+        .await
+        .operation_metrics
+        .write()
+        .await
+        .record(&operation_type, success, duration, error.as_deref());
+    
     let duration_str = duration.map(|d| format!("{:.2}ms", d)).unwrap_or_else(|| "unknown".to_string());
     let status = if success { "SUCCESS" } else { "FAILED" };
     
@@ -541,6 +1208,135 @@ pub async fn operation_completed(
     Ok(())
 }
 
+/// How many grid configs `compact_all_configs` compacts at once
+pub const COMPACT_ALL_CONCURRENCY: usize = 4;
+
+/// Overlap checks `compact_blocks` runs between yields to the runtime
+pub const COMPACT_YIELD_INTERVAL: usize = 10_000;
+
+/// Overlap checks after which `compact_blocks` stops, leaving the blocks it
+/// hasn't reached where they are
+pub const COMPACT_CHECK_BUDGET: usize = 50_000_000;
+
+/// Outcome of compacting one stored grid config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigCompactionResult {
+    pub config_id: String,
+    /// Blocks moved and the config was saved
+    pub changed: bool,
+    /// False when compaction ran out of budget; what it did settle is saved
+    pub complete: bool,
+    /// Left alone because a layout edit is in progress; its commit would
+    /// overwrite the compacted layout
+    #[serde(default)]
+    pub skipped: bool,
+    pub error: Option<String>,
+}
+
+/// How far a compaction pass got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionProgress {
+    /// Movable blocks settled in their final row
+    pub settled: usize,
+    /// Movable blocks in the layout
+    pub total: usize,
+}
+
+impl CompactionProgress {
+    pub fn is_complete(&self) -> bool {
+        self.settled == self.total
+    }
+}
+
+/// Compact every stored grid config and save the ones whose layout changed.
+/// Pending debounced saves are flushed first, so they can't later overwrite
+/// a compacted layout; configs with a layout edit in progress are skipped.
+/// Configs run through the orchestrator, a few at a time, so one huge grid
+/// doesn't hold up the rest; a failure is reported on that config only.
+pub async fn compact_all_configs(state: AppStateType) -> Result<Vec<ConfigCompactionResult>, CommandError> {
+    let (storage, orchestrator, staged_layouts) = {
+        let app_state = state.read().await;
+        app_state.grid_autosave
+            .flush_all()
+            .await
+            .map_err(|e| e.context("Failed to flush pending grid saves"))?;
+        (app_state.storage.clone(), app_state.async_orchestrator.clone(), app_state.staged_layouts.clone())
+    };
+
+    let ctx = crate::storage::StorageContext {
+        user_id: "system".to_string(),
+        session_id: Uuid::new_v4(),
+        operation_id: Uuid::new_v4(),
+        skip_validation: false,
+    };
+    let query = crate::storage::StorageQuery::builder().entity_type(GRID_CONFIG_TYPE).build();
+    let entities: Vec<_> = storage
+        .query(&query, &ctx)
+        .await
+        .map_err(|e| CommandError::from(e).context("Failed to list grid configs"))?
+        .into_iter()
+        .filter(|entity| entity.deleted_at.is_none())
+        .collect();
+
+    let results = futures::stream::iter(entities)
+        .map(|entity| {
+            let storage = storage.clone();
+            let orchestrator = orchestrator.clone();
+            let staged_layouts = staged_layouts.clone();
+            async move {
+                let config_id = entity.id.trim_start_matches("grid_config:").to_string();
+                if staged_layouts.read().await.contains_key(&config_id) {
+                    return ConfigCompactionResult { config_id, changed: false, complete: false, skipped: true, error: None };
+                }
+                let runner = orchestrator
+                    .create_runner(
+                        "compact_grid_config",
+                        "system",
+                        ctx.session_id,
+                        crate::async_orchestrator::ClassificationLevel::Internal,
+                    )
+                    .await;
+                let outcome = runner
+                    .run_async(async {
+                        let mut config: GridConfig = serde_json::from_value(entity.data)
+                            .map_err(|e| format!("Failed to parse stored config: {}", e))?;
+                        let before = layout_hash(&config.blocks);
+                        let progress = compact_blocks(&mut config.blocks).await;
+                        if !progress.is_complete() {
+                            tracing::warn!(
+                                "Compaction of grid config {} stopped at its budget ({} of {} blocks settled)",
+                                config_id, progress.settled, progress.total
+                            );
+                        }
+                        if layout_hash(&config.blocks) == before {
+                            return Ok((false, progress.is_complete()));
+                        }
+                        persist_grid_config(&storage, &config_id, &config)
+                            .await
+                            .map(|_| (true, progress.is_complete()))
+                            .map_err(String::from)
+                    })
+                    .await;
+                match outcome {
+                    Ok((changed, complete)) => ConfigCompactionResult { config_id, changed, complete, skipped: false, error: None },
+                    Err(e) => ConfigCompactionResult { config_id, changed: false, complete: false, skipped: false, error: Some(e.to_string()) },
+                }
+            }
+        })
+        .buffered(COMPACT_ALL_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    Ok(results)
+}
+
+/// Aggregated success/failure counts, durations and last error per operation type
+pub async fn get_operation_metrics(state: AppStateType) -> Result<HashMap<String, OperationStats>, CommandError> {
+    let app_state = state.read().await;
+    let metrics = app_state.operation_metrics.read().await;
+    Ok(metrics.snapshot())
+}
+
 /// Helper function to count block types for statistics
 fn count_block_types(blocks: &[GridBlock]) -> HashMap<String, u32> {
     let mut counts = HashMap::new();
@@ -550,10 +1346,410 @@ fn count_block_types(blocks: &[GridBlock]) -> HashMap<String, u32> {
     counts
 }
 
+/// Top-gravity compaction: every non-static block moves up as far as it can
+/// without overlapping. Static blocks stay put as obstacles. Blocks are
+/// settled top to bottom, left to right; their order in the slice is kept.
+///
+/// Huge grids yield to the runtime every `COMPACT_YIELD_INTERVAL` overlap
+/// checks and stop after `COMPACT_CHECK_BUDGET`; see `compact_blocks_with_budget`.
+pub async fn compact_blocks(blocks: &mut [GridBlock]) -> CompactionProgress {
+    compact_blocks_with_budget(blocks, COMPACT_CHECK_BUDGET).await
+}
+
+/// `compact_blocks` with an explicit overlap-check budget. When it runs out,
+/// the blocks not yet reached keep their positions: blocks only ever move
+/// into rows above them that are free, so a layout without overlaps stays
+/// without overlaps.
+pub async fn compact_blocks_with_budget(blocks: &mut [GridBlock], budget: usize) -> CompactionProgress {
+    let mut order: Vec<usize> = (0..blocks.len()).filter(|&i| !blocks[i].static_grid).collect();
+    order.sort_by_key(|&i| (blocks[i].y, blocks[i].x));
+    let total = order.len();
+
+    let mut placed: Vec<usize> = (0..blocks.len()).filter(|&i| blocks[i].static_grid).collect();
+    let mut checks = 0usize;
+    let mut since_yield = 0usize;
+    for (settled, index) in order.into_iter().enumerate() {
+        let mut target = None;
+        for y in 0..blocks[index].y {
+            if checks >= budget {
+                return CompactionProgress { settled, total };
+            }
+            let candidate = GridBlock { y, ..blocks[index].clone() };
+            let fits = !placed.iter().any(|&other| blocks_overlap(&candidate, &blocks[other]));
+            checks += placed.len().max(1);
+            since_yield += placed.len().max(1);
+            if since_yield >= COMPACT_YIELD_INTERVAL {
+                since_yield = 0;
+                tokio::task::yield_now().await;
+            }
+            if fits {
+                target = Some(y);
+                break;
+            }
+        }
+        if let Some(y) = target {
+            blocks[index].y = y;
+        }
+        placed.push(index);
+    }
+    CompactionProgress { settled: total, total }
+}
+
+fn blocks_overlap(a: &GridBlock, b: &GridBlock) -> bool {
+    a.x < b.x + b.w && b.x < a.x + a.w && a.y < b.y + b.h && b.y < a.y + a.h
+}
+
+/// Hash of a layout's geometry (ids and positions, not block contents), for
+/// cheaply telling whether a layout changed. Independent of block order.
+pub fn layout_hash(blocks: &[GridBlock]) -> String {
+    let mut cells: Vec<String> = blocks
+        .iter()
+        .map(|b| format!("{}:{},{},{},{}", b.id, b.x, b.y, b.w, b.h))
+        .collect();
+    cells.sort();
+    crate::marketplace::sha256_hex(cells.join(";").as_bytes())
+}
+
+/// Rows `find_free_regions` searches at most, so a block saved far down a
+/// corrupt layout can't make it allocate without bound
+pub const FREE_REGION_MAX_ROWS: u32 = 10_000;
+
+/// A rectangle of free grid cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreeRegion {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+/// Empty areas of a grid where a `min_w` x `min_h` widget fits, for
+/// highlighting drop zones. Each region is a maximal free rectangle (it can't
+/// grow in any direction), so regions may overlap; a widget fits somewhere
+/// exactly when it fits in one of them. Only rows down to the lowest block
+/// are searched, as everything below is free anyway.
+pub async fn find_free_regions(
+    state: AppStateType,
+    config_id: String,
+    min_w: u32,
+    min_h: u32,
+) -> Result<Vec<FreeRegion>, CommandError> {
+    let config = get_grid_config(state, config_id).await?;
+    let columns = config.columns.unwrap_or(24).max(1);
+    Ok(free_regions(&config.blocks, columns, min_w.max(1), min_h.max(1)))
+}
+
+/// Maximal free rectangles at least `min_w` x `min_h`, in reading order
+fn free_regions(blocks: &[GridBlock], columns: u32, min_w: u32, min_h: u32) -> Vec<FreeRegion> {
+    let rows = blocks
+        .iter()
+        .map(|b| b.y.saturating_add(b.h))
+        .max()
+        .unwrap_or(0)
+        .min(FREE_REGION_MAX_ROWS) as usize;
+    let cols = columns as usize;
+    let mut occupied = vec![vec![false; cols]; rows];
+    for block in blocks {
+        for row in occupied.iter_mut().take(block.y.saturating_add(block.h) as usize).skip(block.y as usize) {
+            for cell in row.iter_mut().take(block.x.saturating_add(block.w) as usize).skip(block.x as usize) {
+                *cell = true;
+            }
+        }
+    }
+
+    // For each row as a rectangle's bottom edge: `up[x]` free cells end at
+    // (x, y), and every column span gives the tallest rectangle over it
+    let mut up = vec![0u32; cols];
+    let mut regions = Vec::new();
+    for y in 0..rows {
+        for (x, free_above) in up.iter_mut().enumerate() {
+            *free_above = if occupied[y][x] { 0 } else { *free_above + 1 };
+        }
+        for left in 0..cols {
+            let mut h = u32::MAX;
+            for right in left..cols {
+                h = h.min(up[right]);
+                if h == 0 {
+                    break;
+                }
+                let grows_left = left > 0 && up[left - 1] >= h;
+                let grows_right = right + 1 < cols && up[right + 1] >= h;
+                let grows_down = y + 1 < rows && occupied[y + 1][left..=right].iter().all(|&cell| !cell);
+                let w = (right - left + 1) as u32;
+                if !grows_left && !grows_right && !grows_down && w >= min_w && h >= min_h {
+                    regions.push(FreeRegion { x: left as u32, y: y as u32 + 1 - h, w, h });
+                }
+            }
+        }
+    }
+    regions.sort_by_key(|r| (r.y, r.x, r.w, r.h));
+    regions
+}
+
+/// Sync conflict resolver for `grid_config`: merges the two layouts block by
+/// block instead of keeping one whole config (see `merge_grid_configs`)
+pub struct GridLayoutMerge;
+
+#[async_trait::async_trait]
+impl crate::storage::CustomConflictResolver for GridLayoutMerge {
+    async fn resolve(&self, conflict: &crate::storage::ConflictRecord) -> Result<Value, crate::storage::SyncError> {
+        let parse = |data: &Value| {
+            serde_json::from_value::<GridConfig>(data.clone())
+                .map_err(|e| crate::storage::SyncError::SerializationError { error: format!("Invalid grid config: {}", e) })
+        };
+        let merged = merge_grid_configs(
+            (parse(&conflict.local_data)?, conflict.local_updated_at),
+            (parse(&conflict.remote_data)?, conflict.remote_updated_at),
+        )
+        .await;
+        serde_json::to_value(merged).map_err(|e| crate::storage::SyncError::SerializationError { error: e.to_string() })
+    }
+}
+
+/// Merge two edits of the same grid config, each with the time of its last
+/// save. Deterministic: both replicas of a conflict get the same layout.
+///
+/// - A block on both sides takes the version with the later `updated_at`
+///   (the config's save time for blocks without one).
+/// - A block on one side only is kept. Without a common ancestor a removal
+///   looks the same as an addition, and losing a new block is worse than
+///   having to remove one again.
+/// - Columns and metadata come from the later save.
+///
+/// Blocks that now overlap are pushed down (the older edit moves), then the
+/// layout is compacted.
+pub async fn merge_grid_configs(
+    local: (GridConfig, chrono::DateTime<Utc>),
+    remote: (GridConfig, chrono::DateTime<Utc>),
+) -> GridConfig {
+    // Ties are broken by content so the result doesn't depend on which side is local
+    let newer_first = match local.1.cmp(&remote.1).then_with(|| canonical(&local.0).cmp(&canonical(&remote.0))) {
+        std::cmp::Ordering::Less => [remote, local],
+        _ => [local, remote],
+    };
+    let [(newer, newer_at), (older, older_at)] = newer_first;
+
+    let mut older_blocks: HashMap<String, GridBlock> = older.blocks.into_iter().map(|b| (b.id.clone(), b)).collect();
+    let mut blocks: Vec<GridBlock> = newer
+        .blocks
+        .into_iter()
+        .map(|block| match older_blocks.remove(&block.id) {
+            Some(other) => {
+                let ours = (block.updated_at.unwrap_or(newer_at), canonical(&block));
+                let theirs = (other.updated_at.unwrap_or(older_at), canonical(&other));
+                if theirs > ours { other } else { block }
+            }
+            None => block,
+        })
+        .collect();
+    let mut added: Vec<GridBlock> = older_blocks.into_values().collect();
+    added.sort_by(|a, b| a.id.cmp(&b.id));
+    blocks.extend(added);
+
+    resolve_collisions(&mut blocks);
+    compact_blocks(&mut blocks).await;
+    GridConfig { blocks, ..newer }
+}
+
+/// Push blocks down until none overlap. Static blocks hold their place,
+/// then the most recently edited blocks keep theirs; older ones move.
+fn resolve_collisions(blocks: &mut [GridBlock]) {
+    let mut order: Vec<usize> = (0..blocks.len()).filter(|&i| !blocks[i].static_grid).collect();
+    order.sort_by(|&a, &b| blocks[b].updated_at.cmp(&blocks[a].updated_at).then_with(|| blocks[a].id.cmp(&blocks[b].id)));
+    push_down_in_order(blocks, order);
+}
+
+/// Push overlapping blocks down; blocks earlier in reading order keep their place
+fn push_down_in_reading_order(blocks: &mut [GridBlock]) {
+    let mut order: Vec<usize> = (0..blocks.len()).filter(|&i| !blocks[i].static_grid).collect();
+    order.sort_by_key(|&i| (blocks[i].y, blocks[i].x));
+    push_down_in_order(blocks, order);
+}
+
+/// Place the non-static blocks at `order` one by one, each pushed below any
+/// already placed block it overlaps
+fn push_down_in_order(blocks: &mut [GridBlock], order: Vec<usize>) {
+    let mut placed: Vec<usize> = (0..blocks.len()).filter(|&i| blocks[i].static_grid).collect();
+    for index in order {
+        while let Some(below) = placed
+            .iter()
+            .filter(|&&other| blocks_overlap(&blocks[index], &blocks[other]))
+            .map(|&other| blocks[other].y + blocks[other].h)
+            .max()
+        {
+            blocks[index].y = below;
+        }
+        placed.push(index);
+    }
+}
+
+fn canonical<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
 /// Simple logging helper
 #[allow(dead_code)]
 async fn log_grid_interaction(action_type: &str, payload: &Value) {
     // Lightweight helper retained for future instrumentation. Keep the
     // implementation minimal to avoid extra deps.
     println!("[GridCommands] Grid interaction: {} - {}", action_type, payload);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_metrics_aggregation() {
+        let mut metrics = OperationMetrics::new(3);
+        metrics.record("import", true, Some(10.0), None);
+        metrics.record("import", true, Some(20.0), None);
+        metrics.record("import", false, Some(30.0), Some("timeout"));
+        metrics.record("import", true, Some(40.0), None);
+        metrics.record("export", true, None, None);
+
+        let snapshot = metrics.snapshot();
+        let import = &snapshot["import"];
+        assert_eq!(import.success_count, 3);
+        assert_eq!(import.failure_count, 1);
+        assert_eq!(import.average_duration_ms, Some(25.0));
+        // Percentiles only cover the last 3 samples (20, 30, 40)
+        assert_eq!(import.p50_duration_ms, Some(30.0));
+        assert_eq!(import.p99_duration_ms, Some(40.0));
+        assert_eq!(import.last_error.as_deref(), Some("timeout"));
+
+        assert_eq!(snapshot["export"].average_duration_ms, None);
+    }
+
+    fn block(id: &str, x: u32, y: u32, w: u32, h: u32) -> GridBlock {
+        GridBlock {
+            id: id.to_string(),
+            block_type: "html".to_string(),
+            title: None,
+            x,
+            y,
+            w,
+            h,
+            config: Value::Null,
+            static_grid: false,
+            entity_id: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_free_regions_are_maximal_and_large_enough() {
+        // 6 columns, 4 rows:
+        //   a a . . . .
+        //   a a . . b b
+        //   . . . . b b
+        //   c c c c c c
+        let blocks = vec![block("a", 0, 0, 2, 2), block("b", 4, 1, 2, 2), block("c", 0, 3, 6, 1)];
+        let region = |x, y, w, h| FreeRegion { x, y, w, h };
+
+        assert_eq!(
+            free_regions(&blocks, 6, 1, 1),
+            [region(2, 0, 2, 3), region(2, 0, 4, 1), region(0, 2, 4, 1)]
+        );
+        assert_eq!(free_regions(&blocks, 6, 3, 1), [region(2, 0, 4, 1), region(0, 2, 4, 1)]);
+        assert_eq!(free_regions(&blocks, 6, 2, 2), [region(2, 0, 2, 3)]);
+        assert!(free_regions(&blocks, 6, 3, 2).is_empty());
+
+        // An empty grid has no rows to search
+        assert!(free_regions(&[], 6, 1, 1).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_compact_blocks_floats_up_around_static_blocks() {
+        let mut pinned = block("pinned", 0, 2, 2, 1);
+        pinned.static_grid = true;
+        let mut blocks = vec![block("low", 0, 6, 2, 2), pinned, block("right", 3, 4, 1, 1), block("top", 0, 1, 1, 1)];
+        let before = layout_hash(&blocks);
+
+        assert!(compact_blocks(&mut blocks).await.is_complete());
+        let positions: Vec<(&str, u32)> = blocks.iter().map(|b| (b.id.as_str(), b.y)).collect();
+        // "low" stops under "pinned"; block order in the vec is unchanged
+        assert_eq!(positions, vec![("low", 3), ("pinned", 2), ("right", 0), ("top", 0)]);
+        assert_ne!(layout_hash(&blocks), before);
+
+        // Already compact: nothing moves, hash is stable and order-independent
+        let settled = layout_hash(&blocks);
+        compact_blocks(&mut blocks).await;
+        assert_eq!(layout_hash(&blocks), settled);
+        blocks.reverse();
+        assert_eq!(layout_hash(&blocks), settled);
+    }
+
+    #[tokio::test]
+    async fn test_compact_blocks_stops_at_budget_without_overlaps() {
+        let mut blocks: Vec<GridBlock> = (0..40).map(|i| block(&format!("b{}", i), 0, 10 + i * 3, 1, 1)).collect();
+
+        let progress = compact_blocks_with_budget(&mut blocks, 200).await;
+        assert!(!progress.is_complete());
+        assert!(progress.settled > 0 && progress.settled < 40, "{:?}", progress);
+        // Settled blocks are stacked from the top; the rest haven't moved
+        assert_eq!(blocks[0].y, 0);
+        assert_eq!(blocks[39].y, 10 + 39 * 3);
+        for (i, a) in blocks.iter().enumerate() {
+            assert!(blocks[i + 1..].iter().all(|b| !blocks_overlap(a, b)));
+        }
+
+        // Running again with the default budget finishes the job
+        assert!(compact_blocks(&mut blocks).await.is_complete());
+        assert!(blocks.iter().enumerate().all(|(i, b)| b.y == i as u32));
+    }
+
+    #[tokio::test]
+    async fn test_grid_merge_keeps_both_users_block_edits() {
+        use crate::storage::CustomConflictResolver;
+        let t0 = Utc::now() - chrono::Duration::minutes(10);
+        let edited = |block: GridBlock, minutes: i64| GridBlock {
+            updated_at: Some(t0 + chrono::Duration::minutes(minutes)),
+            ..block
+        };
+        let config = |blocks| GridConfig { blocks, columns: Some(12), config_id: "home".to_string(), metadata: None };
+
+        // Local moved "a" and added "c"; remote later moved "b" and added "d"
+        let local = config(vec![
+            edited(block("a", 6, 0, 2, 2), 2),
+            block("b", 0, 0, 2, 2),
+            edited(block("c", 0, 2, 2, 1), 3),
+        ]);
+        let remote = config(vec![
+            edited(block("a", 0, 0, 2, 2), 0),
+            edited(block("b", 6, 0, 2, 2), 5),
+            edited(block("d", 3, 0, 2, 1), 5),
+        ]);
+        let conflict = crate::storage::ConflictRecord {
+            entity_id: "grid_config:home".to_string(),
+            entity_type: GRID_CONFIG_TYPE.to_string(),
+            local_data: serde_json::to_value(&local).unwrap(),
+            remote_data: serde_json::to_value(&remote).unwrap(),
+            local_version: 3,
+            remote_version: 3,
+            local_updated_at: t0 + chrono::Duration::minutes(3),
+            remote_updated_at: t0 + chrono::Duration::minutes(5),
+            remote_user_id: "bob".to_string(),
+        };
+        let merged: GridConfig = serde_json::from_value(GridLayoutMerge.resolve(&conflict).await.unwrap()).unwrap();
+
+        let at = |id: &str| merged.blocks.iter().find(|b| b.id == id).map(|b| (b.x, b.y)).unwrap();
+        assert_eq!(merged.blocks.len(), 4);
+        // Both moved onto x = 6: the older move ("a") is pushed below the newer one
+        assert_eq!(at("b"), (6, 0));
+        assert_eq!(at("a"), (6, 2));
+        assert_eq!(at("c"), (0, 0));
+        assert_eq!(at("d"), (3, 0));
+        for (i, a) in merged.blocks.iter().enumerate() {
+            assert!(merged.blocks[i + 1..].iter().all(|b| !blocks_overlap(a, b)));
+        }
+
+        // Same result whichever side is local
+        let swapped = merge_grid_configs(
+            (remote, conflict.remote_updated_at),
+            (local, conflict.local_updated_at),
+        )
+        .await;
+        assert_eq!(canonical(&swapped), canonical(&merged));
+    }
+}
@@ -4,14 +4,35 @@
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use uuid::Uuid;
 
 use crate::state_mod::AppState;
-use crate::universal_plugin_system::{JSPlugin, PluginInfo, PluginMetadata, LicenseRequirement};
+use crate::universal_plugin_system::{
+    JSPlugin, LicenseRequirement, PluginError, PluginInfo, PluginMetadata, PluginSource, RegistrationStage,
+    UniversalPluginSystem,
+};
 use crate::license_mod::LicenseTier;
+use crate::marketplace::{MarketplaceClient, MarketplaceEntry};
+use crate::payload_limits::PayloadKind;
+use crate::error_code::{CommandError, ErrorCode};
 
 type AppStateType = Arc<RwLock<AppState>>;
 
+/// Timeout for an action invocation that doesn't set `timeout_ms`
+pub const DEFAULT_ACTION_TIMEOUT_MS: u64 = 30_000;
+
+/// Default largest plugin bundle accepted from an upload or a local file
+/// (see `PayloadLimits::plugin_bundle_bytes`)
+pub const MAX_PLUGIN_BUNDLE_SIZE: usize = 5 * 1024 * 1024;
+
+/// Audit log entries returned per page when the query sets no `limit`
+pub const DEFAULT_AUDIT_PAGE_SIZE: usize = 50;
+
+/// Largest audit log page a caller may request
+pub const MAX_AUDIT_PAGE_SIZE: usize = 500;
+
+/// Format version written by `export_plugin_manifest`
+pub const PLUGIN_MANIFEST_VERSION: u32 = 1;
+
 /// JavaScript Plugin Registration Request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JSPluginRequest {
@@ -24,6 +45,12 @@ pub struct JSPluginRequest {
     pub handled_actions: Vec<String>,
     pub metadata: PluginMetadata,
     pub license_requirements: Option<LicenseRequirement>,
+    /// Oldest engine version the plugin supports (semver)
+    #[serde(default)]
+    pub min_engine_version: Option<String>,
+    /// Where the plugin comes from; requests from the frontend are `Inline`
+    #[serde(default)]
+    pub source: PluginSource,
 }
 
 /// Plugin Registration Response
@@ -32,6 +59,87 @@ pub struct PluginRegistrationResponse {
     pub success: bool,
     pub plugin_id: String,
     pub message: String,
+    /// Set when `success` is false: which check failed and what to do about it
+    #[serde(default)]
+    pub failure: Option<RegistrationFailure>,
+}
+
+/// Why a plugin was not registered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrationFailure {
+    pub stage: RegistrationStage,
+    pub detail: String,
+    pub remediation: String,
+}
+
+impl PluginRegistrationResponse {
+    fn failed(plugin_id: String, stage: RegistrationStage, detail: String) -> Self {
+        Self {
+            success: false,
+            message: format!("Plugin {} failed {} check: {}", plugin_id, stage, detail),
+            plugin_id,
+            failure: Some(RegistrationFailure {
+                stage,
+                detail,
+                remediation: stage.remediation().to_string(),
+            }),
+        }
+    }
+}
+
+/// Outcome of one registration check in a dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageReport {
+    pub stage: RegistrationStage,
+    pub passed: bool,
+    /// Why the check failed
+    #[serde(default)]
+    pub detail: Option<String>,
+    #[serde(default)]
+    pub remediation: Option<String>,
+}
+
+/// What `register_js_plugin` would decide, stage by stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginValidationReport {
+    pub plugin_id: String,
+    /// True when every stage passed, i.e. registration would succeed
+    pub valid: bool,
+    pub stages: Vec<StageReport>,
+}
+
+impl StageReport {
+    fn new(stage: RegistrationStage, result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self { stage, passed: true, detail: None, remediation: None },
+            Err(detail) => Self {
+                stage,
+                passed: false,
+                detail: Some(detail),
+                remediation: Some(stage.remediation().to_string()),
+            },
+        }
+    }
+}
+
+/// What answered an action run through `execute_action_with_plugins`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionHandledBy {
+    Plugin,
+    Core,
+}
+
+/// An action's result with where it was routed and how long it took
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionExecution {
+    pub handled_by: ActionHandledBy,
+    /// The answering plugin, when `handled_by` is `plugin`
+    pub plugin_id: Option<String>,
+    /// `{ success, data, error, execution_time_ms, side_effects, plugin_executed }`
+    pub result: serde_json::Value,
+    /// Wall time of the whole dispatch, plugin routing included
+    pub duration_ms: u64,
 }
 
 /// Plugin System Status
@@ -46,6 +154,105 @@ pub struct PluginSystemStatus {
     pub can_load_enterprise: bool,
 }
 
+/// Filters and paging for `get_plugin_audit_log`. Every filter is optional;
+/// `since`/`until` bound the event time inclusively.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginAuditQuery {
+    pub plugin_id: Option<String>,
+    pub action_type: Option<String>,
+    /// Only events whose action succeeded
+    pub success_only: bool,
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: Option<usize>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+/// One plugin audit event, as stored under `AUDIT_EVENT_TYPE`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginAuditEntry {
+    pub id: String,
+    pub plugin_id: Option<String>,
+    pub action_type: Option<String>,
+    pub success: Option<bool>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub details: serde_json::Value,
+}
+
+/// A page of audit events, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginAuditPage {
+    pub entries: Vec<PluginAuditEntry>,
+    /// Pass back as `cursor` for the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// The installed JavaScript plugins, in a form another machine can install
+/// from with `import_plugin_manifest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub manifest_version: u32,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    /// In registration order
+    pub plugins: Vec<PluginManifestEntry>,
+}
+
+/// One installed plugin in a manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifestEntry {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub source: PluginSource,
+    /// Action-type patterns the plugin was granted
+    pub capabilities: Vec<String>,
+    pub priority: i32,
+    pub license_requirements: LicenseRequirement,
+    /// The bundle, for `Inline` plugins that have nowhere else to be
+    /// installed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+/// Outcome of installing one manifest entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginImportResult {
+    pub plugin_id: String,
+    pub success: bool,
+    pub message: String,
+    /// Set when a registration check rejected the plugin
+    #[serde(default)]
+    pub failure: Option<RegistrationFailure>,
+    /// Set when the install failed before registration, e.g. an unsigned
+    /// bundle or a missing file
+    #[serde(default)]
+    pub error_code: Option<ErrorCode>,
+}
+
+impl PluginImportResult {
+    fn installed(plugin_id: String, response: PluginRegistrationResponse) -> Self {
+        Self {
+            plugin_id,
+            success: response.success,
+            message: response.message,
+            failure: response.failure,
+            error_code: None,
+        }
+    }
+
+    fn errored(plugin_id: String, error: CommandError) -> Self {
+        Self {
+            plugin_id,
+            success: false,
+            message: error.message,
+            failure: None,
+            error_code: Some(error.code),
+        }
+    }
+}
+
 // ============================================================================
 // ENGINE-LEVEL PLUGIN FUNCTIONS (Framework Agnostic)
 // ============================================================================
@@ -54,24 +261,13 @@ pub struct PluginSystemStatus {
 pub async fn register_js_plugin(
     state: AppStateType,
     plugin_request: JSPluginRequest,
-) -> Result<PluginRegistrationResponse, String> {
+) -> Result<PluginRegistrationResponse, CommandError> {
     let app_state = state.read().await;
+    app_state.config.payload_limits
+        .check_len(PayloadKind::PluginBundle, plugin_request.code.len())?;
     
-    // Create JSPlugin from request
     let pid = plugin_request.id.clone();
-    let js_plugin = JSPlugin {
-        id: pid.clone(),
-        name: plugin_request.name,
-        version: plugin_request.version,
-        author: plugin_request.author,
-        description: plugin_request.description,
-        code: plugin_request.code,
-        handled_actions: plugin_request.handled_actions,
-        metadata: plugin_request.metadata,
-        license_requirements: plugin_request.license_requirements.unwrap_or_default(),
-        enabled: true,
-        loaded_at: chrono::Utc::now(),
-    };
+    let js_plugin = js_plugin_from_request(plugin_request);
 
     match app_state.plugin_system.register_js_plugin(js_plugin).await {
         Ok(()) => {
@@ -80,63 +276,213 @@ pub async fn register_js_plugin(
                 success: true,
                 plugin_id: pid,
                 message: "Plugin registered successfully".to_string(),
+                failure: None,
             })
         },
+        Err(PluginError::RegistrationFailed { stage, plugin_id, detail }) => {
+            tracing::warn!("JS plugin {} rejected at {} stage: {}", plugin_id, stage, detail);
+            Ok(PluginRegistrationResponse::failed(plugin_id, stage, detail))
+        },
         Err(e) => {
             tracing::error!("Failed to register JS plugin: {}", e);
-            Err(format!("Failed to register plugin: {}", e))
+            Err(CommandError::from(e).context("Failed to register plugin"))
         }
     }
 }
 
-/// Execute action (routes through plugin system)
+/// Dry run of `register_js_plugin`: runs the bundle size check and every
+/// registration stage, reporting each outcome, without loading the plugin
+pub async fn validate_plugin(
+    state: AppStateType,
+    plugin_request: JSPluginRequest,
+) -> Result<PluginValidationReport, CommandError> {
+    let app_state = state.read().await;
+    let mut stages = vec![StageReport::new(
+        RegistrationStage::Parse,
+        app_state.config.payload_limits
+            .check_len(PayloadKind::PluginBundle, plugin_request.code.len())
+            .map_err(|e| e.message),
+    )];
+
+    let js_plugin = js_plugin_from_request(plugin_request);
+    for (stage, result) in app_state.plugin_system.validate_js_plugin(&js_plugin).await {
+        stages.push(StageReport::new(stage, result.map_err(|e| match e {
+            PluginError::RegistrationFailed { detail, .. } => detail,
+            other => other.to_string(),
+        })));
+    }
+
+    Ok(PluginValidationReport {
+        plugin_id: js_plugin.id,
+        valid: stages.iter().all(|s| s.passed),
+        stages,
+    })
+}
+
+/// Execute action (routes through plugin system).
+///
+/// The invocation is bounded by `DEFAULT_ACTION_TIMEOUT_MS`, or by the
+/// payload's `timeout_ms` when set, up to the license tier's maximum.
 pub async fn execute_action_with_plugins(
     state: AppStateType,
     action_type: String,
     payload: serde_json::Value,
-) -> Result<serde_json::Value, String> {
-    // Dispatch using the shared AppStateType handle (avoids recreating state wrappers)
-    match crate::state_mod::execute_action(state.clone(), action_type, payload).await {
-        Ok(result) => Ok(serde_json::json!({
-            "success": result.success,
-            "data": result.data,
-            "error": result.error,
-            "execution_time_ms": result.execution_time_ms,
-            "side_effects": result.side_effects,
-            "plugin_executed": !result.observability_metadata.middleware_executed.is_empty(),
-        })),
+    session_id: Option<uuid::Uuid>,
+) -> Result<ActionExecution, CommandError> {
+    let timeout = {
+        let app_state = state.read().await;
+        let max_ms = crate::license_mod::LicenseFeatures::max_action_timeout_ms(&app_state.get_license_tier().await);
+        invocation_timeout(&payload, max_ms)?
+    };
+    
+    // Dispatch using the shared AppStateType handle (avoids recreating state wrappers);
+    // actions run with a session are recorded in its activity feed
+    let dispatch = async {
+        match session_id {
+            Some(session_id) => crate::state_mod::execute_session_action(state.clone(), session_id, action_type.clone(), payload).await,
+            None => crate::state_mod::execute_action(state.clone(), action_type.clone(), payload).await,
+        }
+    };
+    let started = std::time::Instant::now();
+    let result = tokio::time::timeout(timeout, dispatch)
+        .await
+        .map_err(|_| {
+            let message = format!("Action {} timed out after {}ms", action_type, timeout.as_millis());
+            CommandError::new(ErrorCode::Timeout, message)
+        })?;
+    match result {
+        Ok(result) => {
+            let plugin_id = result.observability_metadata.handled_by_plugin;
+            Ok(ActionExecution {
+                handled_by: if plugin_id.is_some() { ActionHandledBy::Plugin } else { ActionHandledBy::Core },
+                result: serde_json::json!({
+                    "success": result.success,
+                    "data": result.data,
+                    "error": result.error,
+                    "execution_time_ms": result.execution_time_ms,
+                    "side_effects": result.side_effects,
+                    "plugin_executed": plugin_id.is_some(),
+                }),
+                plugin_id,
+                duration_ms: started.elapsed().as_millis() as u64,
+            })
+        }
         Err(e) => {
             tracing::error!("Action execution failed: {}", e);
-            Err(format!("Action execution failed: {}", e))
+            Err(CommandError::from(e).context("Action execution failed"))
         }
     }
 }
 
+/// Split a frontend action request into its action type and payload.
+///
+/// The request is `{ actionType, payload }`, or the same wrapped as
+/// `{ args: { ... } }` by the low-level invoke shape. `action_type` is
+/// accepted for `actionType`. A missing or `null` payload is `{}`; any other
+/// payload must be an object.
+pub fn normalize_action_request(value: &serde_json::Value) -> Result<(String, serde_json::Value), CommandError> {
+    let args = action_request_args(value)?;
+    let action_type = match request_field(args, "actionType", "action_type")? {
+        Some(serde_json::Value::String(action_type)) if !action_type.trim().is_empty() => action_type.clone(),
+        Some(serde_json::Value::String(_)) => return Err(invalid_request("actionType must not be empty")),
+        Some(_) => return Err(invalid_request("actionType must be a string")),
+        None => return Err(invalid_request("Missing actionType")),
+    };
+    let payload = match args.get("payload") {
+        None | Some(serde_json::Value::Null) => serde_json::json!({}),
+        Some(payload @ serde_json::Value::Object(_)) => payload.clone(),
+        Some(_) => return Err(invalid_request("payload must be an object")),
+    };
+    Ok((action_type, payload))
+}
+
+/// The optional session (`sessionId` or `session_id`) of an action request,
+/// in either shape `normalize_action_request` accepts
+pub fn action_request_session(value: &serde_json::Value) -> Result<Option<uuid::Uuid>, CommandError> {
+    match request_field(action_request_args(value)?, "sessionId", "session_id")? {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::String(session_id)) => session_id
+            .parse()
+            .map(Some)
+            .map_err(|e| invalid_request(format!("Invalid sessionId: {}", e))),
+        Some(_) => Err(invalid_request("sessionId must be a string")),
+    }
+}
+
+/// The request object, unwrapped from `{ args: ... }` if needed
+fn action_request_args(value: &serde_json::Value) -> Result<&serde_json::Value, CommandError> {
+    let args = value.get("args").unwrap_or(value);
+    if !args.is_object() {
+        return Err(invalid_request("Action request must be an object"));
+    }
+    Ok(args)
+}
+
+/// A field given in camelCase or snake_case; both spellings must agree
+fn request_field<'a>(
+    args: &'a serde_json::Value,
+    camel: &str,
+    snake: &str,
+) -> Result<Option<&'a serde_json::Value>, CommandError> {
+    match (args.get(camel), args.get(snake)) {
+        (Some(a), Some(b)) if a != b => Err(invalid_request(format!("Conflicting {} and {}", camel, snake))),
+        (a, b) => Ok(a.or(b)),
+    }
+}
+
+fn invalid_request(message: impl Into<String>) -> CommandError {
+    CommandError::new(ErrorCode::ValidationFailed, message)
+}
+
 /// Get loaded plugins info (engine-level)
-pub async fn get_loaded_plugins(state: AppStateType) -> Result<Vec<PluginInfo>, String> {
+pub async fn get_loaded_plugins(state: AppStateType) -> Result<Vec<PluginInfo>, CommandError> {
     let app_state = state.read().await;
     Ok(app_state.plugin_system.get_all_plugins().await)
 }
 
+/// Report which plugin (if any) would handle an action type (engine-level)
+pub async fn which_plugin_handles(
+    state: AppStateType,
+    action_type: String,
+) -> Result<Option<String>, CommandError> {
+    let app_state = state.read().await;
+    Ok(app_state.plugin_system.find_handler(&action_type).await)
+}
+
+/// Change where a JavaScript plugin sits among plugins handling the same
+/// action (lower numbers are consulted first)
+pub async fn set_plugin_priority(
+    state: AppStateType,
+    plugin_id: String,
+    priority: i32,
+) -> Result<(), CommandError> {
+    let app_state = state.read().await;
+    app_state
+        .plugin_system
+        .set_plugin_priority(&plugin_id, priority)
+        .await
+        .map_err(|e| CommandError::from(e).context("Failed to set plugin priority"))
+}
+
 /// Remove JavaScript plugin (engine-level)
 pub async fn remove_js_plugin(
     state: AppStateType,
     plugin_id: String,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let app_state = state.read().await;
 
     app_state
         .plugin_system
         .remove_js_plugin(&plugin_id)
         .await
-        .map_err(|e| format!("Failed to remove plugin: {}", e))
+        .map_err(|e| CommandError::from(e).context("Failed to remove plugin"))
 }
 
 /// Load plugin from file path (engine-level helper for existing wrapper)
 pub async fn load_plugin_from_path(
     state: AppStateType,
     plugin_path: String,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     let app_state = state.read().await;
     
     // Check license first
@@ -145,14 +491,14 @@ pub async fn load_plugin_from_path(
     // For now, delegate to the existing load_plugin method
     // In future, this would parse the file and call register_js_plugin
     app_state.load_plugin(&plugin_path).await
-        .map_err(|e| format!("Failed to load plugin: {}", e))
+        .map_err(|e| CommandError::from(e).context("Failed to load plugin"))
 }
 
 /// Unload plugin by ID (engine-level helper for existing wrapper)
 pub async fn unload_plugin_by_id(
     state: AppStateType,
     plugin_id: String,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let app_state = state.read().await;
     
     // Try to remove from plugin system; if that fails, return an error
@@ -160,7 +506,7 @@ pub async fn unload_plugin_by_id(
         .plugin_system
         .remove_js_plugin(&plugin_id)
         .await
-        .map_err(|e| format!("Failed to unload plugin: {}", e))
+        .map_err(|e| CommandError::from(e).context("Failed to unload plugin"))
 }
 
 /// Upload plugin file and install (engine-level)
@@ -168,26 +514,33 @@ pub async fn upload_plugin_file(
     state: AppStateType,
     file_content: Vec<u8>,
     filename: String,
-) -> Result<PluginRegistrationResponse, String> {
+) -> Result<PluginRegistrationResponse, CommandError> {
     // Avoid holding a read lock while we pass `state` into register_js_plugin()
     // Create plugin_request below and then call register_js_plugin(state.clone(), ...)
     
     // Basic validation
     if filename.is_empty() {
-        return Err("Filename cannot be empty".to_string());
+        return Err(CommandError::new(ErrorCode::ValidationFailed, "Filename cannot be empty"));
     }
     
     if file_content.is_empty() {
-        return Err("File content cannot be empty".to_string());
+        return Err(CommandError::new(ErrorCode::ValidationFailed, "File content cannot be empty"));
     }
+    check_bundle_size(&state, file_content.len()).await?;
     
     // For JavaScript files, parse and register
     if filename.ends_with(".js") {
-        let code = String::from_utf8(file_content)
-            .map_err(|e| format!("Invalid UTF-8 in JavaScript file: {}", e))?;
+        let code = match String::from_utf8(file_content) {
+            Ok(code) => code,
+            Err(e) => {
+                let detail = format!("Invalid UTF-8 in JavaScript file: {}", e);
+                return Ok(PluginRegistrationResponse::failed(filename, RegistrationStage::Parse, detail));
+            }
+        };
 
         // Create basic plugin from file
-        let plugin_id = format!("uploaded_{}", Uuid::new_v4().simple());
+        let ids = state.read().await.ids.clone();
+        let plugin_id = format!("uploaded_{}", ids.next_id().simple());
         let plugin_request = JSPluginRequest {
             id: plugin_id.clone(),
             name: filename.replace(".js", ""),
@@ -197,34 +550,128 @@ pub async fn upload_plugin_file(
             code,
             handled_actions: vec!["*".to_string()], // Would parse from file
             metadata: PluginMetadata {
-                plugin_id: Uuid::new_v4(),
+                plugin_id: ids.next_id(),
                 name: filename.replace(".js", ""),
                 version: "1.0.0".to_string(),
                 author: "Uploaded".to_string(),
                 description: format!("Uploaded plugin from file: {}", filename),
                 tags: vec!["uploaded".to_string()],
                 priority: 100,
+                pass_through: false,
                 dependencies: Vec::new(),
                 conflicts: Vec::new(),
                 homepage: None,
                 documentation: None,
             },
             license_requirements: Some(LicenseRequirement::default()),
+            min_engine_version: None,
+            source: PluginSource::Inline,
         };
 
         // Pass a cloned Arc so we don't move the caller's Arc while holding any locks
         register_js_plugin(state.clone(), plugin_request).await
     } else {
-        Err(format!("Unsupported file type: {}", filename))
+        Err(CommandError::new(ErrorCode::ValidationFailed, format!("Unsupported file type: {}", filename)))
     }
 }
 
+/// Install a plugin bundle from a local file (engine-level).
+///
+/// For air-gapped machines and local plugin development. Applies the same
+/// checks as other installs: size limit, the detached signature in
+/// `<path>.sig` in SignedOnly mode, and the checksum in `<path>.sha256` when
+/// that sidecar file exists.
+pub async fn install_plugin_from_file(
+    state: AppStateType,
+    path: String,
+) -> Result<PluginRegistrationResponse, CommandError> {
+    let file_path = std::path::Path::new(&path);
+    if file_path.extension().and_then(|e| e.to_str()) != Some("js") {
+        return Err(CommandError::new(ErrorCode::ValidationFailed, format!("Unsupported file type: {}", path)));
+    }
+    let stem = file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| CommandError::new(ErrorCode::ValidationFailed, format!("Invalid plugin file name: {}", path)))?
+        .to_string();
+
+    let read_failed = |e: std::io::Error| {
+        let code = if e.kind() == std::io::ErrorKind::NotFound { ErrorCode::NotFound } else { ErrorCode::Internal };
+        CommandError::new(code, format!("Failed to read plugin file {}: {}", path, e))
+    };
+    let size = std::fs::metadata(&path).map_err(read_failed)?.len();
+    check_bundle_size(&state, size as usize).await?;
+    let bundle = std::fs::read(&path).map_err(read_failed)?;
+    if bundle.is_empty() {
+        return Err(CommandError::new(ErrorCode::ValidationFailed, "File content cannot be empty"));
+    }
+
+    // Optional sidecar checksum, `sha256sum` format ("<hex>  <file>") or bare hex
+    let sidecar = format!("{}.sha256", path);
+    if let Ok(contents) = std::fs::read_to_string(&sidecar) {
+        let expected = contents.split_whitespace().next().unwrap_or_default();
+        crate::marketplace::verify_sha256(&stem, &bundle, expected)?;
+    }
+
+    let app_state = state.read().await;
+    let signed_only = matches!(
+        app_state.get_plugin_access_mode().await,
+        crate::license_mod::PluginAccessMode::SignedOnly
+    );
+    // Check the bytes already read, so the file can't change after the check
+    if signed_only && !app_state.verify_plugin_bundle(&path, &bundle) {
+        return Err(CommandError::new(ErrorCode::PluginRejected, format!("Unsigned plugin rejected: {}", path)));
+    }
+    let ids = app_state.ids.clone();
+    drop(app_state);
+
+    let code = match String::from_utf8(bundle) {
+        Ok(code) => code,
+        Err(e) => {
+            let detail = format!("Invalid UTF-8 in JavaScript file: {}", e);
+            return Ok(PluginRegistrationResponse::failed(stem, RegistrationStage::Parse, detail));
+        }
+    };
+    let description = format!("Local plugin from file: {}", path);
+    let plugin_request = JSPluginRequest {
+        id: stem.clone(),
+        name: stem.clone(),
+        version: "1.0.0".to_string(),
+        author: "Local".to_string(),
+        description: description.clone(),
+        code,
+        handled_actions: vec!["*".to_string()],
+        metadata: PluginMetadata {
+            plugin_id: ids.next_id(),
+            name: stem,
+            version: "1.0.0".to_string(),
+            author: "Local".to_string(),
+            description,
+            tags: vec!["local".to_string()],
+            priority: 100,
+            pass_through: false,
+            dependencies: Vec::new(),
+            conflicts: Vec::new(),
+            homepage: None,
+            documentation: None,
+        },
+        license_requirements: Some(LicenseRequirement {
+            requires_signed: signed_only,
+            ..LicenseRequirement::default()
+        }),
+        min_engine_version: None,
+        source: PluginSource::File { path: path.clone() },
+    };
+    register_js_plugin(state, plugin_request).await
+}
+
 /// Install plugin from marketplace (engine-level)
 pub async fn install_marketplace_plugin(
     state: AppStateType,
     plugin_id: String,
-    _marketplace_url: Option<String>,
-) -> Result<PluginRegistrationResponse, String> {
+    marketplace_url: Option<String>,
+) -> Result<PluginRegistrationResponse, CommandError> {
     let app_state = state.read().await;
     
     // Check license requirements
@@ -232,26 +679,78 @@ pub async fn install_marketplace_plugin(
     
     // Validate marketplace access
     match license_tier {
-        LicenseTier::Community => {
-            if !app_state.license_manager.has_feature("community_plugin_marketplace").await {
-                return Err("Community plugin marketplace not available".to_string());
-            }
+        LicenseTier::Community
+            if !app_state.license_manager.has_feature("community_plugin_marketplace").await =>
+        {
+            return Err(CommandError::new(ErrorCode::AccessDenied, "Community plugin marketplace not available"));
         }
-        LicenseTier::Enterprise => {
-            if !app_state.license_manager.has_feature("enterprise_plugin_marketplace").await {
-                return Err("Enterprise plugin marketplace not available".to_string());
-            }
+        LicenseTier::Enterprise
+            if !app_state.license_manager.has_feature("enterprise_plugin_marketplace").await =>
+        {
+            return Err(CommandError::new(ErrorCode::AccessDenied, "Enterprise plugin marketplace not available"));
         }
         _ => {}
     }
+    let ids = app_state.ids.clone();
+    let access = app_state.config.http_access.clone();
+    let max_bundle_bytes = app_state.config.payload_limits.limit_for(PayloadKind::PluginBundle);
+    drop(app_state);
     
-    // Marketplace installation not implemented yet. Return a clear error so callers
-    // can distinguish between a real install and the unimplemented stub.
-    Err(format!("Marketplace plugin installation not yet implemented for plugin {}. Use upload or load_plugin_from_path instead.", plugin_id))
+    let client = MarketplaceClient::from_url_or_env(marketplace_url.clone())?
+        .with_access_policy(access)
+        .with_max_bundle_bytes(max_bundle_bytes);
+    let entry = client.find_plugin(&plugin_id).await?;
+    // Fail before downloading a bundle this engine can't run
+    if let Some(required) = &entry.min_engine_version {
+        UniversalPluginSystem::check_engine_version(&entry.id, required)?;
+    }
+    // Checksum is verified on download, in every license tier
+    let bundle = client.download_bundle(&entry).await?;
+    let code = match String::from_utf8(bundle) {
+        Ok(code) => code,
+        Err(e) => {
+            let detail = format!("Invalid UTF-8 in plugin bundle: {}", e);
+            return Ok(PluginRegistrationResponse::failed(plugin_id, RegistrationStage::Parse, detail));
+        }
+    };
+    
+    let plugin_request = JSPluginRequest {
+        id: entry.id.clone(),
+        name: entry.name.clone(),
+        version: entry.version.clone(),
+        author: entry.author.clone(),
+        description: entry.description.clone(),
+        code,
+        handled_actions: vec!["*".to_string()],
+        metadata: PluginMetadata {
+            plugin_id: ids.next_id(),
+            name: entry.name,
+            version: entry.version,
+            author: entry.author,
+            description: entry.description,
+            tags: vec!["marketplace".to_string()],
+            priority: 100,
+            pass_through: false,
+            dependencies: Vec::new(),
+            conflicts: Vec::new(),
+            homepage: None,
+            documentation: None,
+        },
+        license_requirements: Some(LicenseRequirement::default()),
+        min_engine_version: entry.min_engine_version,
+        source: PluginSource::Marketplace { url: marketplace_url },
+    };
+    register_js_plugin(state, plugin_request).await
+}
+
+/// List the plugins offered by the marketplace (engine-level)
+pub async fn get_plugin_marketplace(marketplace_url: Option<String>) -> Result<Vec<MarketplaceEntry>, CommandError> {
+    let client = MarketplaceClient::from_url_or_env(marketplace_url)?;
+    Ok(client.fetch_catalog().await?)
 }
 
 /// Get system plugin status (engine-level)
-pub async fn get_system_plugin_status(state: AppStateType) -> Result<PluginSystemStatus, String> {
+pub async fn get_system_plugin_status(state: AppStateType) -> Result<PluginSystemStatus, CommandError> {
     let app_state = state.read().await;
     
     let plugins = app_state.plugin_system.get_all_plugins().await;
@@ -276,30 +775,276 @@ pub async fn get_system_plugin_status(state: AppStateType) -> Result<PluginSyste
     })
 }
 
+/// Page through plugin audit events, newest first (ties broken by id,
+/// descending). The cursor names the last entry returned and each page
+/// resumes strictly after it, so events written while paging don't shift
+/// later pages. Pages come from `StorageManager::query_newest`, which walks
+/// a creation-time index where the backend has one.
+pub async fn get_plugin_audit_log(state: AppStateType, query: PluginAuditQuery) -> Result<PluginAuditPage, CommandError> {
+    use crate::storage::{ConditionOperator, StorageQuery, AUDIT_EVENT_TYPE};
+
+    let limit = query.limit.unwrap_or(DEFAULT_AUDIT_PAGE_SIZE);
+    if limit == 0 || limit > MAX_AUDIT_PAGE_SIZE {
+        let message = format!("limit must be between 1 and {}, got {}", MAX_AUDIT_PAGE_SIZE, limit);
+        return Err(CommandError::new(ErrorCode::ValidationFailed, message));
+    }
+    let cursor = query.cursor.as_deref().map(parse_audit_cursor).transpose()?;
+
+    let mut builder = StorageQuery::builder().entity_type(AUDIT_EVENT_TYPE);
+    if let Some(plugin_id) = &query.plugin_id {
+        builder = builder.filter("plugin_id", plugin_id.as_str());
+    }
+    if let Some(action_type) = &query.action_type {
+        builder = builder.filter("action_type", action_type.as_str());
+    }
+    if query.success_only {
+        builder = builder.filter("success", true);
+    }
+    if let Some(since) = &query.since {
+        builder = builder.condition("created_at", ConditionOperator::GreaterThanOrEqual, crate::storage::rfc3339(since));
+    }
+    if let Some(until) = &query.until {
+        builder = builder.condition("created_at", ConditionOperator::LessThanOrEqual, crate::storage::rfc3339(until));
+    }
+
+    let app_state = state.read().await;
+    let ctx = crate::storage::StorageContext {
+        user_id: "system".to_string(),
+        session_id: uuid::Uuid::new_v4(),
+        operation_id: uuid::Uuid::new_v4(),
+        skip_validation: false,
+    };
+
+    // One extra row tells whether another page follows
+    let before = cursor.as_ref().map(|(timestamp, id)| (*timestamp, id.as_str()));
+    let mut events = app_state.storage.query_newest(&builder.build(), before, limit + 1, &ctx).await
+        .map_err(|e| CommandError::from(e).context("Failed to read plugin audit log"))?;
+    let has_more = events.len() > limit;
+    events.truncate(limit);
+
+    let next_cursor = if has_more {
+        events.last().map(|last| format!("{}|{}", crate::storage::rfc3339(&last.created_at), last.id))
+    } else {
+        None
+    };
+    let entries = events
+        .into_iter()
+        .map(|event| PluginAuditEntry {
+            plugin_id: event.data.get("plugin_id").and_then(|v| v.as_str()).map(str::to_string),
+            action_type: event.data.get("action_type").and_then(|v| v.as_str()).map(str::to_string),
+            success: event.data.get("success").and_then(|v| v.as_bool()),
+            details: event.data.get("details").cloned().unwrap_or(serde_json::Value::Null),
+            timestamp: event.created_at,
+            id: event.id,
+        })
+        .collect();
+    Ok(PluginAuditPage { entries, next_cursor })
+}
+
+/// Describe every installed JavaScript plugin so the same set can be
+/// installed elsewhere. Rust plugins are compiled into the engine and are
+/// left out.
+pub async fn export_plugin_manifest(state: AppStateType) -> Result<PluginManifest, CommandError> {
+    let app_state = state.read().await;
+    let plugins = app_state
+        .plugin_system
+        .get_js_plugins()
+        .await
+        .into_iter()
+        .map(|plugin| PluginManifestEntry {
+            code: matches!(plugin.source, PluginSource::Inline).then(|| plugin.code),
+            id: plugin.id,
+            name: plugin.name,
+            version: plugin.version,
+            source: plugin.source,
+            capabilities: plugin.handled_actions,
+            priority: plugin.metadata.priority,
+            license_requirements: plugin.license_requirements,
+        })
+        .collect();
+    Ok(PluginManifest {
+        manifest_version: PLUGIN_MANIFEST_VERSION,
+        exported_at: chrono::Utc::now(),
+        plugins,
+    })
+}
+
+/// Install each plugin in a manifest, in order, through the same path a
+/// single install takes: marketplace and file entries get the license mode,
+/// signature and checksum checks of `install_marketplace_plugin` and
+/// `install_plugin_from_file`, and every plugin passes the registration
+/// checks. One failed entry doesn't stop the rest; each gets its own result.
+/// Plugins already installed at the manifest's version are left as they are.
+pub async fn import_plugin_manifest(
+    state: AppStateType,
+    manifest: PluginManifest,
+) -> Result<Vec<PluginImportResult>, CommandError> {
+    if manifest.manifest_version != PLUGIN_MANIFEST_VERSION {
+        let message = format!(
+            "Unsupported plugin manifest version {}, expected {}",
+            manifest.manifest_version, PLUGIN_MANIFEST_VERSION
+        );
+        return Err(CommandError::new(ErrorCode::ValidationFailed, message));
+    }
+
+    let mut results = Vec::with_capacity(manifest.plugins.len());
+    for entry in manifest.plugins {
+        let installed = state.read().await.plugin_system.get_all_plugins().await;
+        if installed.iter().any(|p| p.id == entry.id && p.version == entry.version) {
+            results.push(PluginImportResult {
+                plugin_id: entry.id,
+                success: true,
+                message: "Plugin already installed".to_string(),
+                failure: None,
+                error_code: None,
+            });
+            continue;
+        }
+
+        let plugin_id = entry.id.clone();
+        let priority = entry.priority;
+        let outcome = match entry.source.clone() {
+            PluginSource::Marketplace { url } => install_marketplace_plugin(state.clone(), entry.id, url).await,
+            PluginSource::File { path } => install_plugin_from_file(state.clone(), path).await,
+            PluginSource::Inline => match manifest_entry_request(&state, entry).await {
+                Ok(plugin_request) => register_js_plugin(state.clone(), plugin_request).await,
+                Err(e) => Err(e),
+            },
+        };
+        let result = match outcome {
+            Ok(response) => {
+                if response.success {
+                    // Marketplace and file installs register at the default priority
+                    let app_state = state.read().await;
+                    if let Err(e) = app_state.plugin_system.set_plugin_priority(&response.plugin_id, priority).await {
+                        tracing::warn!("Failed to restore priority of plugin {}: {}", response.plugin_id, e);
+                    }
+                }
+                PluginImportResult::installed(plugin_id, response)
+            }
+            Err(e) => PluginImportResult::errored(plugin_id, e),
+        };
+        results.push(result);
+    }
+    Ok(results)
+}
+
 // ============================================================================
 // HELPER FUNCTIONS
 // ============================================================================
 
+/// Registration request for an `Inline` manifest entry, which carries its
+/// own bundle
+async fn manifest_entry_request(state: &AppStateType, entry: PluginManifestEntry) -> Result<JSPluginRequest, CommandError> {
+    let code = entry.code.ok_or_else(|| {
+        CommandError::new(ErrorCode::ValidationFailed, format!("Inline plugin {} has no code in the manifest", entry.id))
+    })?;
+    let ids = state.read().await.ids.clone();
+    let description = format!("Imported plugin: {}", entry.name);
+    Ok(JSPluginRequest {
+        id: entry.id,
+        name: entry.name.clone(),
+        version: entry.version.clone(),
+        author: "Imported".to_string(),
+        description: description.clone(),
+        code,
+        handled_actions: entry.capabilities,
+        metadata: PluginMetadata {
+            plugin_id: ids.next_id(),
+            name: entry.name,
+            version: entry.version,
+            author: "Imported".to_string(),
+            description,
+            tags: vec!["imported".to_string()],
+            priority: entry.priority,
+            pass_through: false,
+            dependencies: Vec::new(),
+            conflicts: Vec::new(),
+            homepage: None,
+            documentation: None,
+        },
+        license_requirements: Some(entry.license_requirements),
+        min_engine_version: None,
+        source: PluginSource::Inline,
+    })
+}
+
+/// Timeout for one invocation: the payload's `timeout_ms` override if it's
+/// within `max_ms`, otherwise the default
+fn invocation_timeout(payload: &serde_json::Value, max_ms: u64) -> Result<std::time::Duration, CommandError> {
+    let timeout_ms = match payload.get("timeout_ms") {
+        None | Some(serde_json::Value::Null) => DEFAULT_ACTION_TIMEOUT_MS,
+        Some(value) => match value.as_u64() {
+            Some(0) | None => {
+                let message = format!("timeout_ms must be a positive integer, got {}", value);
+                return Err(CommandError::new(ErrorCode::ValidationFailed, message));
+            }
+            Some(ms) if ms > max_ms => {
+                let message = format!("timeout_ms {} exceeds this license's maximum of {}ms", ms, max_ms);
+                return Err(CommandError::new(ErrorCode::AccessDenied, message));
+            }
+            Some(ms) => ms,
+        },
+    };
+    Ok(std::time::Duration::from_millis(timeout_ms))
+}
+
+/// Build the plugin a registration request describes
+fn js_plugin_from_request(plugin_request: JSPluginRequest) -> JSPlugin {
+    JSPlugin {
+        id: plugin_request.id,
+        name: plugin_request.name,
+        version: plugin_request.version,
+        author: plugin_request.author,
+        description: plugin_request.description,
+        code: plugin_request.code,
+        handled_actions: plugin_request.handled_actions,
+        metadata: plugin_request.metadata,
+        license_requirements: plugin_request.license_requirements.unwrap_or_default(),
+        min_engine_version: plugin_request.min_engine_version,
+        source: plugin_request.source,
+        enabled: true,
+        loaded_at: chrono::Utc::now(),
+    }
+}
+
+/// Split an audit log cursor (`<rfc3339 timestamp>|<entry id>`)
+fn parse_audit_cursor(cursor: &str) -> Result<(chrono::DateTime<chrono::Utc>, String), CommandError> {
+    let invalid = || CommandError::new(ErrorCode::ValidationFailed, format!("Invalid audit log cursor: {}", cursor));
+    let (timestamp, id) = cursor.split_once('|').ok_or_else(invalid)?;
+    let timestamp = chrono::DateTime::parse_from_rfc3339(timestamp).map_err(|_| invalid())?;
+    Ok((timestamp.with_timezone(&chrono::Utc), id.to_string()))
+}
+
+/// Reject bundles over the configured plugin bundle limit, before reading
+/// or decoding them
+async fn check_bundle_size(state: &AppStateType, size: usize) -> Result<(), CommandError> {
+    state.read().await.config.payload_limits.check_len(PayloadKind::PluginBundle, size)
+}
+
 /// Validate plugin requirements against license
 pub async fn validate_plugin_requirements(
     license_manager: &crate::license_mod::LicenseManager,
     requirements: &LicenseRequirement,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     // Check minimum tier
     let current_tier = license_manager.get_tier().await;
     if current_tier < requirements.minimum_tier {
-        return Err(format!(
-            "Plugin requires {:?} license tier, current tier is {:?}",
-            requirements.minimum_tier, current_tier
+        return Err(CommandError::new(
+            ErrorCode::AccessDenied,
+            format!(
+                "Plugin requires {:?} license tier, current tier is {:?}",
+                requirements.minimum_tier, current_tier
+            ),
         ));
     }
     
     // Check enterprise features
     for feature in &requirements.enterprise_only_features {
         if !license_manager.has_feature(feature).await {
-            return Err(format!(
-                "Plugin requires enterprise feature '{}' not available in current license",
-                feature
+            return Err(CommandError::new(
+                ErrorCode::AccessDenied,
+                format!("Plugin requires enterprise feature '{}' not available in current license", feature),
             ));
         }
     }
@@ -308,7 +1053,7 @@ pub async fn validate_plugin_requirements(
     if requirements.requires_signed {
         let plugin_access_mode = license_manager.get_plugin_access_mode().await;
         if !matches!(plugin_access_mode, crate::license_mod::PluginAccessMode::SignedOnly) {
-            return Err("Plugin requires signed plugins mode".to_string());
+            return Err(CommandError::new(ErrorCode::AccessDenied, "Plugin requires signed plugins mode"));
         }
     }
     
@@ -333,4 +1078,61 @@ pub async fn get_plugin_capabilities(
         },
         "available_features": license_manager.get_available_features().await,
     })
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rejection(value: serde_json::Value) -> String {
+        let err = normalize_action_request(&value).unwrap_err();
+        assert_eq!(err.code, ErrorCode::ValidationFailed);
+        err.message
+    }
+
+    #[test]
+    fn test_normalize_action_request_accepts_both_shapes() {
+        let expected = ("grid.save".to_string(), json!({ "id": 1 }));
+        let shapes = [
+            json!({ "actionType": "grid.save", "payload": { "id": 1 } }),
+            json!({ "action_type": "grid.save", "payload": { "id": 1 } }),
+            json!({ "args": { "actionType": "grid.save", "payload": { "id": 1 } } }),
+            json!({ "args": { "action_type": "grid.save", "payload": { "id": 1 } } }),
+            json!({ "actionType": "grid.save", "action_type": "grid.save", "payload": { "id": 1 } }),
+        ];
+        for shape in shapes {
+            assert_eq!(normalize_action_request(&shape).unwrap(), expected, "{}", shape);
+        }
+    }
+
+    #[test]
+    fn test_normalize_action_request_defaults_empty_payload() {
+        for shape in [json!({ "actionType": "system.ping" }), json!({ "actionType": "system.ping", "payload": null })] {
+            assert_eq!(normalize_action_request(&shape).unwrap().1, json!({}));
+        }
+    }
+
+    #[test]
+    fn test_normalize_action_request_rejects_bad_input() {
+        assert_eq!(rejection(json!({ "payload": {} })), "Missing actionType");
+        assert_eq!(rejection(json!({ "args": { "payload": {} } })), "Missing actionType");
+        assert_eq!(rejection(json!({ "actionType": 7 })), "actionType must be a string");
+        assert_eq!(rejection(json!({ "actionType": "  " })), "actionType must not be empty");
+        assert_eq!(rejection(json!({ "actionType": "a", "action_type": "b" })), "Conflicting actionType and action_type");
+        assert_eq!(rejection(json!({ "actionType": "a", "payload": [1] })), "payload must be an object");
+        assert_eq!(rejection(json!({ "args": "grid.save" })), "Action request must be an object");
+        assert_eq!(rejection(json!("grid.save")), "Action request must be an object");
+    }
+
+    #[test]
+    fn test_action_request_session() {
+        let id = uuid::Uuid::new_v4();
+        assert_eq!(action_request_session(&json!({ "actionType": "a" })).unwrap(), None);
+        assert_eq!(action_request_session(&json!({ "sessionId": null })).unwrap(), None);
+        assert_eq!(action_request_session(&json!({ "sessionId": id.to_string() })).unwrap(), Some(id));
+        assert_eq!(action_request_session(&json!({ "args": { "session_id": id.to_string() } })).unwrap(), Some(id));
+        assert!(action_request_session(&json!({ "sessionId": "nope" })).is_err());
+        assert!(action_request_session(&json!({ "sessionId": 3 })).is_err());
+    }
+}
@@ -0,0 +1,305 @@
+// src/error_code.rs
+// Stable, machine-readable error codes for the command boundary.
+//
+// Engine modules keep their own thiserror enums. At the command layer each
+// one converts into a `CommandError`, whose `code` says what class of failure
+// happened. The frontend branches on the code and only shows the message, so
+// rewording a message can't break it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::action_dispatcher::ActionError;
+use crate::async_orchestrator::OrchestrationError;
+use crate::license_mod::LicenseError;
+use crate::marketplace::MarketplaceError;
+use crate::state_mod::AppStateError;
+use crate::storage::{StorageError, SyncError, ValidationError};
+use crate::universal_plugin_system::PluginError;
+
+/// Failure class of a command error. Serialized as `NOT_FOUND`,
+/// `ACCESS_DENIED`, ...; these strings are part of the frontend contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// The entity, plugin, session or handler doesn't exist
+    NotFound,
+    /// The license or caller isn't allowed to do this
+    AccessDenied,
+    /// The input was rejected (schema, format, size limits)
+    ValidationFailed,
+    /// The license has expired
+    LicenseExpired,
+    /// A plugin failed a registration or install check
+    PluginRejected,
+    /// The storage backend can't be reached or failed
+    StorageUnavailable,
+    /// The change collides with existing data
+    Conflict,
+    /// The operation ran out of time
+    Timeout,
+    /// Anything not covered above
+    Internal,
+}
+
+impl ErrorCode {
+    /// The serialized form of the code
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::AccessDenied => "ACCESS_DENIED",
+            ErrorCode::ValidationFailed => "VALIDATION_FAILED",
+            ErrorCode::LicenseExpired => "LICENSE_EXPIRED",
+            ErrorCode::PluginRejected => "PLUGIN_REJECTED",
+            ErrorCode::StorageUnavailable => "STORAGE_UNAVAILABLE",
+            ErrorCode::Conflict => "CONFLICT",
+            ErrorCode::Timeout => "TIMEOUT",
+            ErrorCode::Internal => "INTERNAL",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Command boundary errors: a stable code plus a human-readable message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[error("{message}")]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Structured context for the code, e.g. the limit a payload exceeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// Prefix the message with what was being done ("Failed to load plugin: ...")
+    pub fn context(mut self, context: &str) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+}
+
+/// Errors from code that still reports plain strings carry no class
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+/// For callers that still return `Result<_, String>`
+impl From<CommandError> for String {
+    fn from(error: CommandError) -> Self {
+        error.message
+    }
+}
+
+impl From<StorageError> for CommandError {
+    fn from(error: StorageError) -> Self {
+        Self::new(storage_code(&error), error.to_string())
+    }
+}
+
+impl From<ValidationError> for CommandError {
+    fn from(error: ValidationError) -> Self {
+        Self::new(validation_code(&error), error.to_string())
+    }
+}
+
+impl From<SyncError> for CommandError {
+    fn from(error: SyncError) -> Self {
+        Self::new(sync_code(&error), error.to_string())
+    }
+}
+
+impl From<LicenseError> for CommandError {
+    fn from(error: LicenseError) -> Self {
+        Self::new(license_code(&error), error.to_string())
+    }
+}
+
+impl From<PluginError> for CommandError {
+    fn from(error: PluginError) -> Self {
+        Self::new(plugin_code(&error), error.to_string())
+    }
+}
+
+impl From<MarketplaceError> for CommandError {
+    fn from(error: MarketplaceError) -> Self {
+        Self::new(marketplace_code(&error), error.to_string())
+    }
+}
+
+impl From<ActionError> for CommandError {
+    fn from(error: ActionError) -> Self {
+        Self::new(action_code(&error), error.to_string())
+    }
+}
+
+impl From<OrchestrationError> for CommandError {
+    fn from(error: OrchestrationError) -> Self {
+        Self::new(orchestration_code(&error), error.to_string())
+    }
+}
+
+impl From<AppStateError> for CommandError {
+    fn from(error: AppStateError) -> Self {
+        Self::new(app_state_code(&error), error.to_string())
+    }
+}
+
+// Failure class of each module's errors
+
+fn storage_code(error: &StorageError) -> ErrorCode {
+    match error {
+        StorageError::NotFound { .. } => ErrorCode::NotFound,
+        StorageError::AccessDenied { .. } => ErrorCode::AccessDenied,
+        StorageError::ValidationFailed { .. } => ErrorCode::ValidationFailed,
+        StorageError::SyncConflict { .. } | StorageError::KeyExists { .. } => ErrorCode::Conflict,
+        StorageError::BackendError { .. }
+        | StorageError::MigrationFailed { .. }
+        | StorageError::DatabaseUnavailable { .. }
+        | StorageError::Io { .. } => ErrorCode::StorageUnavailable,
+        StorageError::SerializationError { .. } | StorageError::Unsupported { .. } => ErrorCode::Internal,
+    }
+}
+
+fn validation_code(_error: &ValidationError) -> ErrorCode {
+    ErrorCode::ValidationFailed
+}
+
+fn sync_code(error: &SyncError) -> ErrorCode {
+    match error {
+        SyncError::SyncConflict { .. } => ErrorCode::Conflict,
+        SyncError::AuthenticationFailed { .. } => ErrorCode::AccessDenied,
+        SyncError::ValidationError { .. } => ErrorCode::ValidationFailed,
+        SyncError::Timeout { .. } => ErrorCode::Timeout,
+        SyncError::ConnectionFailed { .. }
+        | SyncError::NetworkError { .. }
+        | SyncError::StorageError { .. }
+        | SyncError::ServerError { .. }
+        | SyncError::NotConnected => ErrorCode::StorageUnavailable,
+        SyncError::SerializationError { .. } => ErrorCode::Internal,
+    }
+}
+
+fn license_code(error: &LicenseError) -> ErrorCode {
+    match error {
+        LicenseError::Expired => ErrorCode::LicenseExpired,
+        LicenseError::InvalidSignature
+        | LicenseError::Invalid
+        | LicenseError::DeploymentNotAllowed { .. }
+        | LicenseError::InconsistentFeatures(_)
+        | LicenseError::KeyRevoked(_)
+        | LicenseError::UnknownKey(_)
+        | LicenseError::MachineMismatch { .. }
+        | LicenseError::FeatureNotAvailable(_)
+        | LicenseError::LimitExceeded(_) => ErrorCode::AccessDenied,
+        LicenseError::Io(_) | LicenseError::Json(_) => ErrorCode::Internal,
+    }
+}
+
+fn plugin_code(error: &PluginError) -> ErrorCode {
+    match error {
+        PluginError::PluginNotFound { .. } => ErrorCode::NotFound,
+        PluginError::LicenseInsufficient { .. } => ErrorCode::AccessDenied,
+        PluginError::InvalidSignature { .. }
+        | PluginError::DependencyNotMet { .. }
+        | PluginError::PluginConflict { .. }
+        | PluginError::IncompatibleVersion { .. }
+        | PluginError::RegistrationFailed { .. } => ErrorCode::PluginRejected,
+        PluginError::ExecutionError { .. } | PluginError::InitializationError { .. } => ErrorCode::Internal,
+    }
+}
+
+fn marketplace_code(error: &MarketplaceError) -> ErrorCode {
+    match error {
+        MarketplaceError::PluginNotFound { .. } => ErrorCode::NotFound,
+        MarketplaceError::IntegrityError { .. } => ErrorCode::PluginRejected,
+        MarketplaceError::OriginNotAllowed { .. } => ErrorCode::AccessDenied,
+        MarketplaceError::BundleTooLarge { .. } => ErrorCode::ValidationFailed,
+        MarketplaceError::NotConfigured
+        | MarketplaceError::Request { .. }
+        | MarketplaceError::RetriesExhausted { .. } => ErrorCode::Internal,
+    }
+}
+
+fn action_code(error: &ActionError) -> ErrorCode {
+    match error {
+        ActionError::HandlerNotFound { .. } => ErrorCode::NotFound,
+        ActionError::ValidationError { .. } => ErrorCode::ValidationFailed,
+        ActionError::AuthorizationError { .. } => ErrorCode::AccessDenied,
+        ActionError::Timeout => ErrorCode::Timeout,
+        ActionError::ExecutionError { .. }
+        | ActionError::MiddlewareError { .. }
+        | ActionError::SerializationError { .. }
+        | ActionError::SystemError { .. } => ErrorCode::Internal,
+    }
+}
+
+fn orchestration_code(error: &OrchestrationError) -> ErrorCode {
+    match error {
+        OrchestrationError::Timeout { .. } => ErrorCode::Timeout,
+        OrchestrationError::CircuitBreakerOpen { .. }
+        | OrchestrationError::ConcurrencyLimitExceeded
+        | OrchestrationError::ResourceLimitExceeded { .. }
+        | OrchestrationError::OperationFailed { .. }
+        | OrchestrationError::RetryExhausted { .. }
+        | OrchestrationError::SystemError { .. }
+        | OrchestrationError::Cancelled { .. } => ErrorCode::Internal,
+    }
+}
+
+fn app_state_code(error: &AppStateError) -> ErrorCode {
+    match error {
+        AppStateError::License(inner) => license_code(inner),
+        AppStateError::Plugin(inner) => plugin_code(inner),
+        AppStateError::PluginNotFound { .. } | AppStateError::SessionNotFound { .. } => ErrorCode::NotFound,
+        AppStateError::UnsignedPluginRejected { .. } => ErrorCode::PluginRejected,
+        AppStateError::FeatureNotAvailable { .. } => ErrorCode::AccessDenied,
+        AppStateError::InitializationFailed { .. } | AppStateError::ShutdownFailed { .. } => ErrorCode::Internal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_errors_map_to_codes() {
+        let not_found = StorageError::NotFound { key: "grid-1".to_string() };
+        assert_eq!(CommandError::from(not_found).code, ErrorCode::NotFound);
+        assert_eq!(CommandError::from(LicenseError::Expired).code, ErrorCode::LicenseExpired);
+        let wrapped = AppStateError::License(LicenseError::Expired);
+        assert_eq!(CommandError::from(wrapped).code, ErrorCode::LicenseExpired);
+        assert_eq!(CommandError::from(ActionError::Timeout).code, ErrorCode::Timeout);
+        assert_eq!(CommandError::from("boom".to_string()).code, ErrorCode::Internal);
+    }
+
+    #[test]
+    fn test_command_error_serializes_code() {
+        let error = CommandError::new(ErrorCode::AccessDenied, "no access").context("Failed to install plugin");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "code": "ACCESS_DENIED", "message": "Failed to install plugin: no access" })
+        );
+        assert_eq!(String::from(error), "Failed to install plugin: no access");
+    }
+}
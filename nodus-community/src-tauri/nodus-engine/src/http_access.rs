@@ -0,0 +1,159 @@
+// src/http_access.rs
+// Origin and method controls shared by every HTTP surface the engine exposes.
+// The marketplace client checks each request against this policy before
+// sending it, instead of hardcoding its own allow-list.
+
+use serde::{Deserialize, Serialize};
+
+/// Environment variable with a comma-separated list of extra allowed origins
+pub const ALLOWED_ORIGINS_ENV: &str = "NODUS_ALLOWED_ORIGINS";
+
+/// Allowed origins and methods for HTTP endpoints.
+///
+/// An origin entry is either exact (`https://example.com`) or allows any port
+/// with `:*` (`http://localhost:*`). `*` alone allows every origin and should
+/// only be used for local development.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpAccessPolicy {
+    pub allowed_origins: Vec<String>,
+    pub allowed_methods: Vec<String>,
+}
+
+impl HttpAccessPolicy {
+    /// Localhost-only policy: the Tauri webview and local dev servers
+    pub fn localhost_only() -> Self {
+        Self {
+            allowed_origins: vec![
+                "http://localhost:*".to_string(),
+                "http://127.0.0.1:*".to_string(),
+                "tauri://localhost".to_string(),
+                "https://tauri.localhost".to_string(),
+            ],
+            allowed_methods: vec!["GET".to_string(), "POST".to_string(), "OPTIONS".to_string()],
+        }
+    }
+
+    /// Localhost-only plus any origins listed in `NODUS_ALLOWED_ORIGINS`
+    pub fn from_env() -> Self {
+        let mut policy = Self::localhost_only();
+        if let Ok(extra) = std::env::var(ALLOWED_ORIGINS_ENV) {
+            policy.allowed_origins.extend(
+                extra.split(',').map(str::trim).filter(|o| !o.is_empty()).map(str::to_string),
+            );
+        }
+        policy
+    }
+
+    pub fn with_origin(mut self, origin: impl Into<String>) -> Self {
+        self.allowed_origins.push(origin.into());
+        self
+    }
+
+    pub fn with_methods(mut self, methods: Vec<String>) -> Self {
+        self.allowed_methods = methods;
+        self
+    }
+
+    pub fn is_origin_allowed(&self, origin: &str) -> bool {
+        let origin = origin.trim_end_matches('/');
+        self.allowed_origins.iter().any(|allowed| origin_matches(allowed, origin))
+    }
+
+    pub fn is_method_allowed(&self, method: &str) -> bool {
+        self.allowed_methods.iter().any(|m| m.eq_ignore_ascii_case(method))
+    }
+
+    /// Whether a `method` request to `url` is allowed; URLs that don't parse
+    /// are not
+    pub fn allows_request(&self, method: &str, url: &str) -> bool {
+        self.is_method_allowed(method) && origin_of(url).map_or(false, |origin| self.is_origin_allowed(&origin))
+    }
+
+    /// `Access-Control-Allow-Origin` value for a request, if it is allowed
+    pub fn allow_origin_header(&self, origin: &str) -> Option<String> {
+        if self.is_origin_allowed(origin) {
+            Some(origin.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// `Access-Control-Allow-Methods` value
+    pub fn allow_methods_header(&self) -> String {
+        self.allowed_methods.join(", ")
+    }
+}
+
+impl Default for HttpAccessPolicy {
+    /// Localhost-only; use `from_env` to pick up `NODUS_ALLOWED_ORIGINS`
+    fn default() -> Self {
+        Self::localhost_only()
+    }
+}
+
+/// `scheme://host[:port]` of a URL, with default ports left out
+pub fn origin_of(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    Some(url.origin().ascii_serialization()).filter(|origin| origin != "null")
+}
+
+fn origin_matches(allowed: &str, origin: &str) -> bool {
+    if allowed == "*" {
+        return true;
+    }
+    match allowed.strip_suffix(":*") {
+        Some(base) => {
+            origin == base
+                || origin
+                    .strip_prefix(base)
+                    .and_then(|rest| rest.strip_prefix(':'))
+                    .map_or(false, |port| !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()))
+        }
+        None => allowed.eq_ignore_ascii_case(origin),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_localhost_only_by_default() {
+        let policy = HttpAccessPolicy::localhost_only();
+
+        assert!(policy.is_origin_allowed("http://localhost:1420"));
+        assert!(policy.is_origin_allowed("http://localhost"));
+        assert!(policy.is_origin_allowed("tauri://localhost"));
+        assert!(!policy.is_origin_allowed("http://localhost.evil.com"));
+        assert!(!policy.is_origin_allowed("http://localhost:80.evil.com"));
+        assert!(!policy.is_origin_allowed("https://example.com"));
+
+        assert!(policy.is_method_allowed("get"));
+        assert!(!policy.is_method_allowed("DELETE"));
+    }
+
+    #[test]
+    fn test_extra_origins() {
+        let policy = HttpAccessPolicy::localhost_only().with_origin("https://plugins.example.com");
+
+        assert!(policy.is_origin_allowed("https://plugins.example.com/"));
+        assert_eq!(
+            policy.allow_origin_header("https://plugins.example.com").as_deref(),
+            Some("https://plugins.example.com")
+        );
+        assert_eq!(policy.allow_origin_header("https://other.example.com"), None);
+    }
+
+    #[test]
+    fn test_allows_request_by_url_origin() {
+        let policy = HttpAccessPolicy::default().with_origin("https://cdn.example.com");
+
+        assert!(policy.allows_request("GET", "https://cdn.example.com/bundles/word-count.js"));
+        assert!(policy.allows_request("get", "http://127.0.0.1:8080/plugins"));
+        assert!(!policy.allows_request("GET", "https://cdn.example.com.evil.net/x.js"));
+        assert!(!policy.allows_request("DELETE", "https://cdn.example.com/x.js"));
+        assert!(!policy.allows_request("GET", "not a url"));
+        assert_eq!(origin_of("https://cdn.example.com:443/a?b").as_deref(), Some("https://cdn.example.com"));
+    }
+}
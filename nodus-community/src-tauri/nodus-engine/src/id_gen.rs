@@ -0,0 +1,75 @@
+// src/id_gen.rs
+// Id generation for operations, sessions and plugins
+//
+// Components take an `Arc<dyn IdGenerator>` so tests can swap the random
+// default for a deterministic sequence and assert on ids.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Source of new ids
+pub trait IdGenerator: Send + Sync + std::fmt::Debug {
+    fn next_id(&self) -> Uuid;
+}
+
+/// Shared handle passed to components that mint ids
+pub type SharedIdGenerator = Arc<dyn IdGenerator>;
+
+/// Random v4 UUIDs (the production default)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomIds;
+
+impl IdGenerator for RandomIds {
+    fn next_id(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+}
+
+/// Deterministic counter: 00000000-0000-0000-0000-000000000001, ...002, ...
+#[derive(Debug)]
+pub struct SequentialIds {
+    next: AtomicU64,
+}
+
+impl SequentialIds {
+    pub fn new() -> Self {
+        Self::starting_at(1)
+    }
+
+    pub fn starting_at(first: u64) -> Self {
+        Self { next: AtomicU64::new(first) }
+    }
+}
+
+impl Default for SequentialIds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdGenerator for SequentialIds {
+    fn next_id(&self) -> Uuid {
+        Uuid::from_u128(self.next.fetch_add(1, Ordering::Relaxed) as u128)
+    }
+}
+
+/// The generator used when none is injected
+pub fn default_generator() -> SharedIdGenerator {
+    Arc::new(RandomIds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequential_ids_are_deterministic() {
+        let ids = SequentialIds::new();
+        assert_eq!(ids.next_id().to_string(), "00000000-0000-0000-0000-000000000001");
+        assert_eq!(ids.next_id().to_string(), "00000000-0000-0000-0000-000000000002");
+
+        let ids = SequentialIds::starting_at(10);
+        assert_eq!(ids.next_id(), Uuid::from_u128(10));
+    }
+}
@@ -0,0 +1,81 @@
+// src/idle.rs
+// Process-wide idle flag, set from the frontend's OS idle detection.
+//
+// Background work (low-priority operations, periodic sync) checks this flag
+// to back off while the machine is idle or on battery, and resumes as soon as
+// the user is active again.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Shared idle state
+#[derive(Debug, Default)]
+pub struct IdleState {
+    idle: AtomicBool,
+    resumed: Notify,
+}
+
+/// Handle shared by `AppState`, the orchestrator and the sync manager
+pub type SharedIdleState = Arc<IdleState>;
+
+impl IdleState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle.load(Ordering::SeqCst)
+    }
+
+    /// Update the flag, returning whether it changed. Going active wakes
+    /// everything parked in `wait_until_active`.
+    pub fn set_idle(&self, idle: bool) -> bool {
+        let was_idle = self.idle.swap(idle, Ordering::SeqCst);
+        if was_idle && !idle {
+            self.resumed.notify_waiters();
+        }
+        was_idle != idle
+    }
+
+    /// Resolve immediately when active, otherwise once the user is back
+    pub async fn wait_until_active(&self) {
+        loop {
+            // Register before checking so a concurrent resume isn't missed
+            let resumed = self.resumed.notified();
+            if !self.is_idle() {
+                return;
+            }
+            resumed.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_wait_until_active_resumes_on_activity() {
+        let state = Arc::new(IdleState::new());
+        assert!(!state.set_idle(false));
+        tokio::time::timeout(Duration::from_millis(50), state.wait_until_active())
+            .await
+            .expect("active state should not block");
+
+        assert!(state.set_idle(true));
+        let waiter = tokio::spawn({
+            let state = state.clone();
+            async move { state.wait_until_active().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+
+        state.set_idle(false);
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("waiter should wake on activity")
+            .unwrap();
+    }
+}
@@ -19,6 +19,31 @@ pub mod commands_grid;
 // Storage modules for grid data persistence
 pub mod storage;
 
+// Tracing subscriber setup (NODUS_LOG filter)
+pub mod logging;
+
+// Swappable id generation (deterministic ids in tests)
+pub mod id_gen;
+
+// Allowed origins/methods for HTTP surfaces (marketplace, metrics)
+pub mod http_access;
+
+// Idle flag that pauses low-priority background work
+pub mod idle;
+
+// Per-kind size limits for payloads crossing the IPC boundary
+pub mod payload_limits;
+
+// Recent operation timings (ring buffer behind `spans::instrument`)
+pub mod spans;
+
+// Stable error codes the frontend branches on instead of message text
+pub mod error_code;
+
+// Backoff-with-jitter retries and the marketplace HTTP client built on them
+pub mod retry;
+pub mod marketplace;
+
 // NOTE: keeping the public module surface explicit and clean.
 // No backward-compatibility aliases — callers should use the canonical module names
 
@@ -8,9 +8,26 @@ use base64::{engine::general_purpose, Engine as _};
 use chrono::{DateTime, Utc};
 use ring::hmac;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
 use uuid::Uuid;
 
+use crate::storage::{StorageContext, StorageManager, StoredEntity, SyncStatus};
+
+/// Environment variable carrying the license itself (base64-encoded or raw JSON)
+pub const LICENSE_ENV: &str = "NODUS_LICENSE";
+
+/// Environment variable pointing at a license file
+pub const LICENSE_FILE_ENV: &str = "NODUS_LICENSE_FILE";
+
+/// License file checked when `NODUS_LICENSE_FILE` is not set
+pub const DEFAULT_LICENSE_FILE: &str = "license.json";
+
+/// Percentages of a limit at which a `LimitWarningEvent` is broadcast
+pub const DEFAULT_LIMIT_WARNING_THRESHOLDS: [u8; 3] = [80, 95, 100];
+
 /// Nodus 3-Tier License System - Apache Model
 /// Defense tier is a separate classified fork, not part of main distribution
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -74,6 +91,9 @@ pub struct LicenseInfo {
     pub limits: LicenseLimits,
     pub signature: String,
     pub verification_key: String,
+    /// Optional node-lock: the license is only valid on the machine with this fingerprint
+    #[serde(default)]
+    pub bound_machine_id: Option<String>,
 }
 
 /// License limits based on tier
@@ -87,6 +107,75 @@ pub struct LicenseLimits {
     pub max_tenants: Option<u32>,
 }
 
+/// Rollover window for usage counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UsageWindow {
+    Hourly,
+    Daily,
+}
+
+impl UsageWindow {
+    /// Window tracked for a limit type, if the limit is time-windowed
+    pub fn for_limit(limit_type: &str) -> Option<Self> {
+        match limit_type {
+            "operations_per_hour" => Some(Self::Hourly),
+            "api_calls_per_day" => Some(Self::Daily),
+            _ => None,
+        }
+    }
+
+    /// Start of the window containing `now`
+    pub fn window_start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let ts = now.timestamp();
+        let span = self.span_seconds();
+        DateTime::from_timestamp(ts - ts.rem_euclid(span), 0).unwrap_or(now)
+    }
+
+    fn span_seconds(&self) -> i64 {
+        match self {
+            Self::Hourly => 3600,
+            Self::Daily => 86_400,
+        }
+    }
+}
+
+/// Storage key holding every windowed usage counter, so saving them is a
+/// single write
+const USAGE_COUNTERS_KEY: &str = "license_usage:counters";
+
+/// How often `spawn_usage_persistence` saves changed usage counters
+pub const USAGE_PERSIST_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Persisted usage counter for a time-windowed limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageCounter {
+    pub limit_type: String,
+    pub window: UsageWindow,
+    pub window_start: DateTime<Utc>,
+    pub count: u32,
+}
+
+/// Current usage vs limit, as shown to users ("842 / 1000 operations used this hour")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitUsage {
+    pub limit_type: String,
+    pub window: Option<UsageWindow>,
+    pub used: u32,
+    pub limit: Option<u32>,
+    pub window_resets_at: Option<DateTime<Utc>>,
+}
+
+/// Event broadcast when usage of a limit reaches one of the warning thresholds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LimitWarningEvent {
+    pub limit_type: String,
+    /// The threshold reached, in percent of `limit`
+    pub threshold_percent: u8,
+    pub used: u32,
+    pub limit: u32,
+    pub reached_at: DateTime<Utc>,
+}
+
 /// Feature definitions for each tier - Apache Model Implementation
 pub struct LicenseFeatures;
 
@@ -262,6 +351,74 @@ impl LicenseFeatures {
         }
     }
 
+    /// License feature an action namespace requires, if any (`ai.search` -> `ai_search`)
+    pub fn feature_for_action(action_type: &str) -> Option<&'static str> {
+        let namespace = action_type.split('.').next().unwrap_or_default();
+        match namespace {
+            "ai" => Some("ai_search"),
+            "sync" => Some("offline_sync"),
+            "team" => Some("team_workspaces"),
+            "compliance" => Some("compliance_reporting"),
+            _ => None,
+        }
+    }
+
+    /// Prerequisites for features that build on other features
+    pub fn feature_dependencies() -> HashMap<&'static str, Vec<&'static str>> {
+        HashMap::from([
+            // Plugin ecosystem
+            ("unsigned_plugins_allowed", vec!["plugin_system"]),
+            ("community_plugin_marketplace", vec!["plugin_system"]),
+            ("plugin_signature_validation", vec!["plugin_system"]),
+            ("signed_plugins_only", vec!["plugin_signature_validation"]),
+            ("enterprise_plugin_marketplace", vec!["signed_plugins_only"]),
+            // AI
+            ("ai_search", vec!["ai_embedding"]),
+            ("ai_suggestions", vec!["ai_embedding"]),
+            ("smart_categorization", vec!["ai_embedding"]),
+            // Storage and sync
+            ("offline_sync", vec!["data_storage"]),
+            ("team_sync", vec!["offline_sync"]),
+            ("workflow_automation", vec!["workflows"]),
+            // Collaboration
+            ("shared_projects", vec!["team_workspaces"]),
+            ("team_permissions", vec!["team_workspaces", "user_authorization"]),
+            ("team_analytics", vec!["team_workspaces"]),
+            ("comment_system", vec!["collaboration_tools"]),
+            // Compliance and integrity
+            ("sox_reporting", vec!["compliance_reporting"]),
+            ("hipaa_reporting", vec!["compliance_reporting"]),
+            ("gdpr_reporting", vec!["compliance_reporting"]),
+            ("pci_reporting", vec!["compliance_reporting"]),
+            ("soc2_reporting", vec!["compliance_reporting"]),
+            ("tamper_detection", vec!["cryptographic_integrity"]),
+            ("forensic_chain_validation", vec!["cryptographic_integrity", "forensic_logging"]),
+            ("tenant_administration", vec!["multi_tenant_isolation"]),
+        ])
+    }
+
+    /// Report features present without their prerequisites
+    pub fn validate_feature_set(features: &HashSet<String>) -> Vec<MissingDependency> {
+        let dependencies = Self::feature_dependencies();
+        let mut missing: Vec<MissingDependency> = features
+            .iter()
+            .filter_map(|feature| {
+                let required = dependencies.get(feature.as_str())?;
+                let missing: Vec<String> = required
+                    .iter()
+                    .filter(|dep| !features.contains(**dep))
+                    .map(|dep| dep.to_string())
+                    .collect();
+                (!missing.is_empty()).then(|| MissingDependency {
+                    feature: feature.clone(),
+                    missing,
+                })
+            })
+            .collect();
+        missing.sort_by(|a, b| a.feature.cmp(&b.feature));
+        missing
+    }
+
     /// Get the plugin access mode for a tier (THE CRITICAL DIFFERENTIATOR)
     pub fn plugin_access_mode(tier: &LicenseTier) -> PluginAccessMode {
         match tier {
@@ -271,6 +428,23 @@ impl LicenseFeatures {
             LicenseTier::Enterprise => PluginAccessMode::SignedOnly,     // THE GATE
         }
     }
+
+    /// Longest timeout a single action invocation may ask for
+    pub fn max_action_timeout_ms(tier: &LicenseTier) -> u64 {
+        match tier {
+            LicenseTier::Community => 2 * 60 * 1000,
+            LicenseTier::Pro => 5 * 60 * 1000,
+            LicenseTier::Team => 15 * 60 * 1000,
+            LicenseTier::Enterprise => 60 * 60 * 1000,
+        }
+    }
+}
+
+/// A feature whose prerequisites are missing from a feature set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingDependency {
+    pub feature: String,
+    pub missing: Vec<String>,
 }
 
 /// Plugin access control mode (THE COMPETITIVE MOAT)
@@ -280,21 +454,143 @@ pub enum PluginAccessMode {
     SignedOnly,         // Enterprise: Only cryptographically signed plugins
 }
 
+/// Event broadcast when the active license changes at runtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseChangeEvent {
+    pub previous_tier: LicenseTier,
+    pub current_tier: LicenseTier,
+    pub gained_features: Vec<String>,
+    pub lost_features: Vec<String>,
+    pub plugin_access_mode: PluginAccessMode,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl LicenseChangeEvent {
+    /// True when the reload changed the tier or the feature set
+    pub fn has_changes(&self) -> bool {
+        self.previous_tier != self.current_tier
+            || !self.gained_features.is_empty()
+            || !self.lost_features.is_empty()
+    }
+}
+
+/// Lifecycle of a verification key version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyStatus {
+    /// Current key, used for new licenses
+    Active,
+    /// Still accepted, but licenses signed with it should be reissued
+    Deprecated,
+    /// No longer accepted
+    Revoked,
+}
+
+/// A versioned license verification key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationKey {
+    pub version: String,
+    pub secret: String,
+    pub status: KeyStatus,
+}
+
+/// On-disk key set, e.g. `license_keys.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerificationKeySet {
+    pub keys: Vec<VerificationKey>,
+    /// Key versions tried (in order) when a license's named key doesn't verify it
+    #[serde(default)]
+    pub fallback_versions: Vec<String>,
+}
+
+/// Why the most recent license candidate was rejected
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LicenseFailureReason {
+    Expired,
+    InvalidSignature,
+    Revoked,
+    InvalidStatus,
+    InconsistentFeatures,
+    DeploymentNotAllowed,
+    MachineMismatch,
+    Unreadable,
+}
+
+/// Record of the last rejected license, kept for diagnostics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseValidationFailure {
+    pub source: String,
+    pub reason: LicenseFailureReason,
+    pub message: String,
+    pub license_id: Option<Uuid>,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Structured self-test report for support
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseDiagnostics {
+    pub license_id: Option<Uuid>,
+    pub tier: LicenseTier,
+    pub status: Option<LicenseStatus>,
+    pub customer_name: Option<String>,
+    pub issued_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub days_remaining: Option<i64>,
+    pub signature_valid: bool,
+    pub matched_verification_key: Option<String>,
+    pub feature_count: usize,
+    pub plugin_access_mode: PluginAccessMode,
+    pub limits: LicenseLimits,
+    pub limit_usage: HashMap<String, u32>,
+    pub last_failure: Option<LicenseValidationFailure>,
+}
+
 /// License manager for validation and feature checking
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LicenseManager {
     current_license: Option<LicenseInfo>,
-    verification_keys: HashMap<String, String>,
-    feature_cache: HashMap<String, bool>,
+    verification_keys: HashMap<String, VerificationKey>,
+    fallback_key_versions: Vec<String>,
+    /// Features of the current license. Rebuilds swap in a complete map, so
+    /// concurrent `has_feature` calls never see a half-built cache.
+    feature_cache: Arc<std::sync::RwLock<Arc<HashMap<String, bool>>>>,
+    change_events: broadcast::Sender<LicenseChangeEvent>,
+    limit_warnings: broadcast::Sender<LimitWarningEvent>,
+    /// Ascending warning thresholds, in percent
+    warning_thresholds: Arc<std::sync::RwLock<Vec<u8>>>,
+    /// Highest threshold already reported per limit type, so each crossing
+    /// is broadcast once
+    warned_thresholds: Arc<std::sync::Mutex<HashMap<String, u8>>>,
+    last_failure: Option<LicenseValidationFailure>,
+    usage_counters: Arc<RwLock<HashMap<String, UsageCounter>>>,
+    usage_storage: Option<Arc<StorageManager>>,
+    /// Set when the counters changed since they were last saved
+    usage_dirty: Arc<std::sync::atomic::AtomicBool>,
+    /// Reject (rather than warn about) licenses with inconsistent feature sets
+    strict_feature_validation: bool,
 }
 
 impl LicenseManager {
     /// Create new license manager
     pub async fn new() -> Result<Self, LicenseError> {
+        let (change_events, _) = broadcast::channel(16);
+        let (limit_warnings, _) = broadcast::channel(16);
         let mut manager = Self {
             current_license: None,
             verification_keys: HashMap::new(),
-            feature_cache: HashMap::new(),
+            fallback_key_versions: Vec::new(),
+            feature_cache: Arc::default(),
+            change_events,
+            limit_warnings,
+            warning_thresholds: Arc::new(std::sync::RwLock::new(DEFAULT_LIMIT_WARNING_THRESHOLDS.to_vec())),
+            warned_thresholds: Arc::default(),
+            last_failure: None,
+            usage_counters: Arc::new(RwLock::new(HashMap::new())),
+            usage_storage: None,
+            usage_dirty: Arc::default(),
+            strict_feature_validation: std::env::var("NODUS_STRICT_LICENSE_FEATURES")
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
         };
 
         // Load verification keys (in production, these would be embedded or from secure storage)
@@ -306,26 +602,102 @@ impl LicenseManager {
         Ok(manager)
     }
 
+    /// Re-detect the license at runtime (e.g. after a new license.json is dropped in)
+    /// and broadcast the resulting feature delta to subscribers.
+    pub async fn reload(&mut self) -> Result<LicenseChangeEvent, LicenseError> {
+        let previous_tier = self.get_tier().await;
+        let previous_features: HashSet<String> = self.features().keys().cloned().collect();
+
+        if let Err(e) = self.detect_license().await {
+            // Keep the app usable: an unreadable/invalid license falls back to Community.
+            // The rejection reason stays available through `diagnostics`.
+            tracing::warn!("License reload failed ({}), falling back to Community tier", e);
+            self.set_community_license();
+        }
+        self.rebuild_feature_cache();
+
+        let current_features: HashSet<String> = self.features().keys().cloned().collect();
+        let mut gained_features: Vec<String> = current_features.difference(&previous_features).cloned().collect();
+        let mut lost_features: Vec<String> = previous_features.difference(&current_features).cloned().collect();
+        gained_features.sort();
+        lost_features.sort();
+
+        let current_tier = self.get_tier().await;
+        let event = LicenseChangeEvent {
+            previous_tier,
+            plugin_access_mode: LicenseFeatures::plugin_access_mode(&current_tier),
+            current_tier,
+            gained_features,
+            lost_features,
+            changed_at: Utc::now(),
+        };
+
+        if event.has_changes() {
+            tracing::info!(
+                "🔑 License reloaded: {:?} -> {:?} (+{} / -{} features)",
+                event.previous_tier, event.current_tier,
+                event.gained_features.len(), event.lost_features.len()
+            );
+            // A send error only means nobody is subscribed
+            let _ = self.change_events.send(event.clone());
+        }
+
+        Ok(event)
+    }
+
+    /// Subscribe to license change events emitted by `reload`
+    pub fn subscribe(&self) -> broadcast::Receiver<LicenseChangeEvent> {
+        self.change_events.subscribe()
+    }
+
+    /// Subscribe to warnings broadcast as usage approaches a limit
+    pub fn subscribe_limit_warnings(&self) -> broadcast::Receiver<LimitWarningEvent> {
+        self.limit_warnings.subscribe()
+    }
+
+    pub fn limit_warning_thresholds(&self) -> Vec<u8> {
+        self.warning_thresholds.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the warning thresholds (percentages of a limit). They are
+    /// sorted and deduplicated; an empty list turns warnings off.
+    pub fn set_limit_warning_thresholds(&self, mut thresholds: Vec<u8>) -> Vec<u8> {
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        *self.warning_thresholds.write().unwrap_or_else(|e| e.into_inner()) = thresholds.clone();
+        // Re-evaluate every limit against the new thresholds
+        self.warned_thresholds.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        thresholds
+    }
+
     /// Detect current license from environment/file/registry
     async fn detect_license(&mut self) -> Result<(), LicenseError> {
-        // Check for license file first
-        if let Ok(license_data) = std::fs::read_to_string("license.json") {
-            if let Ok(license) = serde_json::from_str::<LicenseInfo>(&license_data) {
-                self.validate_and_set_license(license).await?;
-                return Ok(());
+        self.last_failure = None;
+
+        // Check for license file first (NODUS_LICENSE_FILE, default license.json)
+        let license_path = std::env::var(LICENSE_FILE_ENV).unwrap_or_else(|_| DEFAULT_LICENSE_FILE.to_string());
+        if let Ok(license_data) = std::fs::read_to_string(&license_path) {
+            match serde_json::from_str::<LicenseInfo>(&license_data) {
+                Ok(license) => {
+                    self.validate_from_source(license, &license_path).await?;
+                    return Ok(());
+                }
+                Err(e) => self.record_failure(&license_path, LicenseFailureReason::Unreadable, e.to_string(), None),
             }
         }
 
         // Check environment variable
-        if let Ok(license_str) = std::env::var("NODUS_LICENSE") {
-            if let Ok(license_data) = general_purpose::STANDARD.decode(&license_str) {
-                if let Ok(license_str) = String::from_utf8(license_data) {
-                    if let Ok(license) = serde_json::from_str::<LicenseInfo>(&license_str) {
-                        self.validate_and_set_license(license).await?;
-                        return Ok(());
-                    }
-                }
+        if let Ok(license_str) = std::env::var(LICENSE_ENV) {
+            if let Some(license) = parse_env_license(&license_str) {
+                self.validate_from_source(license, LICENSE_ENV).await?;
+                return Ok(());
             }
+            self.record_failure(
+                LICENSE_ENV,
+                LicenseFailureReason::Unreadable,
+                "Could not decode license (expected base64-encoded or raw JSON)".to_string(),
+                None,
+            );
         }
 
         // No license found - default to Community (Apache Model)
@@ -334,6 +706,40 @@ impl LicenseManager {
         Ok(())
     }
 
+    /// Validate a license candidate, remembering the precise rejection reason
+    async fn validate_from_source(&mut self, license: LicenseInfo, source: &str) -> Result<(), LicenseError> {
+        let license_id = license.license_id;
+        let status = license.status.clone();
+        let result = self.validate_and_set_license(license).await;
+        if let Err(ref e) = result {
+            let reason = match e {
+                LicenseError::Expired => LicenseFailureReason::Expired,
+                LicenseError::InvalidSignature => LicenseFailureReason::InvalidSignature,
+                LicenseError::KeyRevoked(_) => LicenseFailureReason::Revoked,
+                LicenseError::InconsistentFeatures(_) => LicenseFailureReason::InconsistentFeatures,
+                LicenseError::DeploymentNotAllowed { .. } => LicenseFailureReason::DeploymentNotAllowed,
+                LicenseError::MachineMismatch { .. } => LicenseFailureReason::MachineMismatch,
+                _ if status == LicenseStatus::Revoked => LicenseFailureReason::Revoked,
+                _ => LicenseFailureReason::InvalidStatus,
+            };
+            self.record_failure(source, reason, e.to_string(), Some(license_id));
+        } else {
+            tracing::info!("🔑 Active license {} loaded from {}", license_id, source);
+        }
+        result
+    }
+
+    fn record_failure(&mut self, source: &str, reason: LicenseFailureReason, message: String, license_id: Option<Uuid>) {
+        tracing::warn!("License from {} rejected: {:?} ({})", source, reason, message);
+        self.last_failure = Some(LicenseValidationFailure {
+            source: source.to_string(),
+            reason,
+            message,
+            license_id,
+            detected_at: Utc::now(),
+        });
+    }
+
     /// Set default community license (Apache Model - full app)
     fn set_community_license(&mut self) {
         let community_license = LicenseInfo {
@@ -351,6 +757,7 @@ impl LicenseManager {
             limits: LicenseLimits::default(), // No limits for community (Apache Model)
             signature: "community-default".to_string(),
             verification_key: "community".to_string(),
+            bound_machine_id: None, // Community licenses are never node-locked
         };
 
         self.current_license = Some(community_license);
@@ -376,73 +783,250 @@ impl LicenseManager {
             return Err(LicenseError::Invalid);
         }
 
+        // Check feature set consistency
+        let missing = LicenseFeatures::validate_feature_set(&license.features);
+        if !missing.is_empty() {
+            let summary = missing
+                .iter()
+                .map(|m| format!("{} requires {}", m.feature, m.missing.join(", ")))
+                .collect::<Vec<_>>()
+                .join("; ");
+            if self.strict_feature_validation {
+                return Err(LicenseError::InconsistentFeatures(summary));
+            }
+            tracing::warn!("⚠️ License feature set is inconsistent: {}", summary);
+        }
+
+        // Check deployment restrictions
+        Self::check_deployment(&license.allowed_deployments, &Self::current_deployment())?;
+
+        // Check machine binding (node-locked licenses)
+        if let Some(ref bound_machine_id) = license.bound_machine_id {
+            let machine_id = Self::machine_fingerprint();
+            if !bound_machine_id.eq_ignore_ascii_case(&machine_id) {
+                return Err(LicenseError::MachineMismatch { machine_id });
+            }
+        }
+
         self.current_license = Some(license);
         self.rebuild_feature_cache();
 
         Ok(())
     }
 
-    /// Verify license signature using HMAC
-    fn verify_license_signature(&self, license: &LicenseInfo) -> Result<(), LicenseError> {
-        let verification_key = self
-            .verification_keys
-            .get(&license.verification_key)
-            .ok_or(LicenseError::InvalidSignature)?;
+    /// Current deployment type from `NODUS_DEPLOYMENT` (`desktop`, `server`, `cloud`).
+    /// Defaults to `desktop`, the community app's native deployment.
+    pub fn current_deployment() -> String {
+        std::env::var("NODUS_DEPLOYMENT")
+            .map(|d| d.trim().to_lowercase())
+            .ok()
+            .filter(|d| !d.is_empty())
+            .unwrap_or_else(|| "desktop".to_string())
+    }
+
+    /// Check a deployment type against a license's allowed deployments
+    pub fn check_deployment(allowed_deployments: &[String], deployment: &str) -> Result<(), LicenseError> {
+        let allowed = allowed_deployments
+            .iter()
+            .any(|d| d.eq_ignore_ascii_case("any") || d.eq_ignore_ascii_case(deployment));
+        if allowed {
+            Ok(())
+        } else {
+            Err(LicenseError::DeploymentNotAllowed {
+                deployment: deployment.to_string(),
+                allowed: allowed_deployments.to_vec(),
+            })
+        }
+    }
 
+    /// Toggle rejection of licenses whose feature set misses prerequisites
+    pub fn set_strict_feature_validation(&mut self, strict: bool) {
+        self.strict_feature_validation = strict;
+    }
+
+    /// Stable fingerprint for this machine, used for node-locked licenses.
+    /// Hashes the OS machine id (falling back to the hostname) together with
+    /// the OS and architecture so the raw identifiers never leave the machine.
+    pub fn machine_fingerprint() -> String {
+        let machine_id = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+            .iter()
+            .find_map(|path| std::fs::read_to_string(path).ok())
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty());
+
+        let hostname = std::env::var("COMPUTERNAME")
+            .or_else(|_| std::env::var("HOSTNAME"))
+            .ok()
+            .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+            .map(|h| h.trim().to_string())
+            .unwrap_or_default();
+
+        let mut hasher = Sha256::new();
+        hasher.update(machine_id.unwrap_or(hostname).as_bytes());
+        hasher.update(std::env::consts::OS.as_bytes());
+        hasher.update(std::env::consts::ARCH.as_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// Verify license signature using HMAC.
+    /// Tries the key version named by the license first, then the configured
+    /// fallback versions, so keys can be rolled without invalidating licenses.
+    /// Returns the key version that verified the signature.
+    fn verify_license_signature(&self, license: &LicenseInfo) -> Result<String, LicenseError> {
         // Create message to verify; clone the tier to avoid moving out of a borrowed `license`
-        let message = format!(
+        let mut message = format!(
             "{}:{}:{}:{}",
             license.license_id,
             license.tier.clone() as u8,
             license.customer_name,
             license.issued_at.timestamp()
         );
+        // Node-locked licenses sign the binding too, so it can't be stripped
+        if let Some(ref bound_machine_id) = license.bound_machine_id {
+            message.push(':');
+            message.push_str(&bound_machine_id.to_lowercase());
+        }
+
+        if let Some(named_key) = self.verification_keys.get(&license.verification_key) {
+            if named_key.status == KeyStatus::Revoked {
+                return Err(LicenseError::KeyRevoked(named_key.version.clone()));
+            }
+        }
+
+        let candidates = std::iter::once(&license.verification_key)
+            .chain(self.fallback_key_versions.iter().filter(|v| **v != license.verification_key));
 
-        // Verify HMAC signature
-        let key = hmac::Key::new(hmac::HMAC_SHA256, verification_key.as_bytes());
-        let expected_signature =
-            general_purpose::STANDARD.encode(hmac::sign(&key, message.as_bytes()).as_ref());
+        for version in candidates {
+            let Some(verification_key) = self.verification_keys.get(version) else {
+                continue;
+            };
+            if verification_key.status == KeyStatus::Revoked {
+                continue;
+            }
 
-        if expected_signature != license.signature {
-            return Err(LicenseError::InvalidSignature);
+            // Verify HMAC signature
+            let key = hmac::Key::new(hmac::HMAC_SHA256, verification_key.secret.as_bytes());
+            let expected_signature =
+                general_purpose::STANDARD.encode(hmac::sign(&key, message.as_bytes()).as_ref());
+
+            if expected_signature == license.signature {
+                if verification_key.status == KeyStatus::Deprecated {
+                    tracing::warn!(
+                        "⚠️ License {} verified with deprecated key {}; please request a reissued license",
+                        license.license_id, version
+                    );
+                }
+                return Ok(version.clone());
+            }
         }
 
-        Ok(())
+        Err(LicenseError::InvalidSignature)
+    }
+
+    /// Check a detached plugin bundle signature against the verification
+    /// keys. The signature reads `<key version>:<base64 HMAC-SHA256 of the
+    /// bundle>`. Returns the key version that verified it.
+    pub fn verify_bundle_signature(&self, bundle: &[u8], signature: &str) -> Result<String, LicenseError> {
+        let (version, encoded) = signature.trim().split_once(':').ok_or(LicenseError::InvalidSignature)?;
+        let verification_key = self
+            .verification_keys
+            .get(version)
+            .ok_or_else(|| LicenseError::UnknownKey(version.to_string()))?;
+        if verification_key.status == KeyStatus::Revoked {
+            return Err(LicenseError::KeyRevoked(version.to_string()));
+        }
+        let tag = general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| LicenseError::InvalidSignature)?;
+        let key = hmac::Key::new(hmac::HMAC_SHA256, verification_key.secret.as_bytes());
+        hmac::verify(&key, bundle, &tag).map_err(|_| LicenseError::InvalidSignature)?;
+        Ok(version.to_string())
     }
 
-    /// Load verification keys
+    /// Load verification keys: built-in versions first, then an optional key file
+    /// (`NODUS_LICENSE_KEYS_FILE`, default `license_keys.json`) that can add
+    /// new versions or change the status of existing ones.
     async fn load_verification_keys(&mut self) -> Result<(), LicenseError> {
         // In production, these would be embedded in the binary
-        self.verification_keys.insert(
-            "pro_key_v1".to_string(),
-            "pro_verification_key_2024".to_string(),
-        );
-        self.verification_keys.insert(
-            "team_key_v1".to_string(),
-            "team_verification_key_2024".to_string(),
-        );
-        self.verification_keys.insert(
-            "enterprise_key_v1".to_string(),
-            "enterprise_verification_key_2024".to_string(),
-        );
+        for (version, secret) in [
+            ("pro_key_v1", "pro_verification_key_2024"),
+            ("team_key_v1", "team_verification_key_2024"),
+            ("enterprise_key_v1", "enterprise_verification_key_2024"),
+        ] {
+            self.verification_keys.insert(
+                version.to_string(),
+                VerificationKey {
+                    version: version.to_string(),
+                    secret: secret.to_string(),
+                    status: KeyStatus::Active,
+                },
+            );
+        }
+
+        let key_file = std::env::var("NODUS_LICENSE_KEYS_FILE").unwrap_or_else(|_| "license_keys.json".to_string());
+        if std::path::Path::new(&key_file).exists() {
+            self.load_verification_keys_from_file(&key_file)?;
+        }
 
         Ok(())
     }
 
-    /// Rebuild feature cache for fast lookups
-    fn rebuild_feature_cache(&mut self) {
-        self.feature_cache.clear();
+    /// Merge a key set from disk into the loaded keys
+    pub fn load_verification_keys_from_file(&mut self, path: &str) -> Result<(), LicenseError> {
+        let data = std::fs::read_to_string(path)?;
+        let key_set: VerificationKeySet = serde_json::from_str(&data)?;
+        tracing::info!("🔑 Loaded {} verification key(s) from {}", key_set.keys.len(), path);
+        self.add_verification_keys(key_set);
+        Ok(())
+    }
 
-        if let Some(ref license) = self.current_license {
-            for feature in &license.features {
-                self.feature_cache.insert(feature.clone(), true);
+    /// Merge a key set into the loaded keys (same version replaces the existing key)
+    pub fn add_verification_keys(&mut self, key_set: VerificationKeySet) {
+        for key in key_set.keys {
+            self.verification_keys.insert(key.version.clone(), key);
+        }
+        for version in key_set.fallback_versions {
+            if !self.fallback_key_versions.contains(&version) {
+                self.fallback_key_versions.push(version);
             }
         }
     }
 
+    /// Mark a key version as active, deprecated (warn) or revoked (reject)
+    pub fn set_key_status(&mut self, version: &str, status: KeyStatus) -> Result<(), LicenseError> {
+        let key = self
+            .verification_keys
+            .get_mut(version)
+            .ok_or_else(|| LicenseError::UnknownKey(version.to_string()))?;
+        key.status = status;
+        Ok(())
+    }
+
+    /// Rebuild feature cache for fast lookups. The new map is built off to the
+    /// side and swapped in under the write lock in one step.
+    fn rebuild_feature_cache(&mut self) {
+        let features: HashMap<String, bool> = self
+            .current_license
+            .iter()
+            .flat_map(|license| license.features.iter())
+            .map(|feature| (feature.clone(), true))
+            .collect();
+
+        *self.feature_cache.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(features);
+    }
+
+    /// Current feature set; the lock is only held long enough to clone the `Arc`
+    fn features(&self) -> Arc<HashMap<String, bool>> {
+        self.feature_cache.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
     /// Check if a feature is available (replaces JS license.hasFeature)
     pub async fn has_feature(&self, feature: &str) -> bool {
-        self.feature_cache.get(feature).copied().unwrap_or(false)
+        self.features().get(feature).copied().unwrap_or(false)
     }
 
     /// Get current license tier
@@ -458,8 +1042,9 @@ impl LicenseManager {
         self.current_license.as_ref()
     }
 
-    /// Check if within usage limits
-    pub async fn check_limit(&self, limit_type: &str, current_usage: u32) -> bool {
+    /// Check if within usage limits.
+    /// Pass `None` for time-windowed limits to use the tracked usage counters.
+    pub async fn check_limit(&self, limit_type: &str, current_usage: Option<u32>) -> bool {
         // If there is no current license, default to allowing the operation (community default)
         let limits = if let Some(ref lic) = self.current_license {
             &lic.limits
@@ -467,22 +1052,259 @@ impl LicenseManager {
             return true;
         };
 
+        let current_usage = match current_usage {
+            Some(usage) => {
+                self.note_usage(limit_type, usage);
+                usage
+            }
+            None => self.current_usage(limit_type).await,
+        };
+
+        match Self::limit_value(limits, limit_type) {
+            Some(max) => current_usage <= max,
+            None => true, // Unlimited or unknown limits default to allowed
+        }
+    }
+
+    fn limit_value(limits: &LicenseLimits, limit_type: &str) -> Option<u32> {
         match limit_type {
-            "users" => limits.max_users.map_or(true, |max| current_usage <= max),
-            "storage_gb" => limits
-                .max_storage_gb
-                .map_or(true, |max| current_usage <= max),
-            "operations_per_hour" => limits
-                .max_operations_per_hour
-                .map_or(true, |max| current_usage <= max),
-            "api_calls_per_day" => limits
-                .max_api_calls_per_day
-                .map_or(true, |max| current_usage <= max),
-            "concurrent_sessions" => limits
-                .max_concurrent_sessions
-                .map_or(true, |max| current_usage <= max),
-            "tenants" => limits.max_tenants.map_or(true, |max| current_usage <= max),
-            _ => true, // Unknown limits default to allowed
+            "users" => limits.max_users,
+            "storage_gb" => limits.max_storage_gb,
+            "operations_per_hour" => limits.max_operations_per_hour,
+            "api_calls_per_day" => limits.max_api_calls_per_day,
+            "concurrent_sessions" => limits.max_concurrent_sessions,
+            "tenants" => limits.max_tenants,
+            _ => None,
+        }
+    }
+
+    /// Persist usage counters through the storage manager and load any saved state
+    pub async fn attach_usage_storage(&mut self, storage: Arc<StorageManager>) {
+        let ctx = Self::usage_context();
+        if let Ok(Some(entity)) = storage.get(USAGE_COUNTERS_KEY, &ctx).await {
+            if let Ok(saved) = serde_json::from_value::<HashMap<String, UsageCounter>>(entity.data) {
+                self.usage_counters.write().await.extend(saved);
+            }
+        }
+        self.usage_storage = Some(storage);
+    }
+
+    /// Record usage against a time-windowed limit, rolling the window over when it expires.
+    /// Returns the usage in the current window.
+    pub async fn record_usage(&self, limit_type: &str, amount: u32) -> u32 {
+        self.record_usages(&[(limit_type, amount)]).await[0]
+    }
+
+    /// Record usage against several windowed limits at once. Returns the
+    /// usage in each current window, in order (0 for limits that aren't
+    /// windowed). Counters are saved by `flush_usage`.
+    pub async fn record_usages(&self, usages: &[(&str, u32)]) -> Vec<u32> {
+        let counts = {
+            let mut counters = self.usage_counters.write().await;
+            Self::add_usages(&mut counters, usages, Utc::now())
+        };
+        self.usage_recorded(usages, &counts);
+        counts
+    }
+
+    /// Record usage against several limits only if all of it fits, checking
+    /// and counting under one lock so concurrent callers can't overshoot a
+    /// limit together. Nothing is recorded when any limit would be exceeded.
+    pub async fn try_record_usages(&self, usages: &[(&str, u32)]) -> Result<Vec<u32>, LicenseError> {
+        let counts = {
+            let mut counters = self.usage_counters.write().await;
+            let now = Utc::now();
+            for (limit_type, amount) in usages {
+                let Some(max) = self.current_license.as_ref().and_then(|l| Self::limit_value(&l.limits, limit_type)) else {
+                    continue;
+                };
+                if Self::usage_in_window(&counters, limit_type, now).saturating_add(*amount) > max {
+                    return Err(LicenseError::LimitExceeded(limit_type.to_string()));
+                }
+            }
+            Self::add_usages(&mut counters, usages, now)
+        };
+        self.usage_recorded(usages, &counts);
+        Ok(counts)
+    }
+
+    fn add_usages(counters: &mut HashMap<String, UsageCounter>, usages: &[(&str, u32)], now: DateTime<Utc>) -> Vec<u32> {
+        let mut counts = Vec::with_capacity(usages.len());
+        for (limit_type, amount) in usages {
+            let Some(window) = UsageWindow::for_limit(limit_type) else {
+                counts.push(0);
+                continue;
+            };
+            let window_start = window.window_start(now);
+            let counter = counters.entry(limit_type.to_string()).or_insert_with(|| UsageCounter {
+                limit_type: limit_type.to_string(),
+                window,
+                window_start,
+                count: 0,
+            });
+            if counter.window_start != window_start {
+                counter.window_start = window_start;
+                counter.count = 0;
+            }
+            counter.count = counter.count.saturating_add(*amount);
+            counts.push(counter.count);
+        }
+        counts
+    }
+
+    /// Mark the counters for saving and report crossed warning thresholds
+    fn usage_recorded(&self, usages: &[(&str, u32)], counts: &[u32]) {
+        if counts.iter().any(|count| *count > 0) {
+            self.usage_dirty.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+        for ((limit_type, _), count) in usages.iter().zip(counts) {
+            if *count > 0 {
+                self.note_usage(limit_type, *count);
+            }
+        }
+    }
+
+    /// Save the usage counters if they changed since the last save. Returns
+    /// whether anything was written; a failed save is retried next time.
+    pub async fn flush_usage(&self) -> Result<bool, crate::storage::StorageError> {
+        let Some(ref storage) = self.usage_storage else {
+            return Ok(false);
+        };
+        if !self.usage_dirty.swap(false, std::sync::atomic::Ordering::SeqCst) {
+            return Ok(false);
+        }
+        let snapshot = self.usage_counters.read().await.clone();
+        if let Err(e) = Self::persist_counters(storage, &snapshot).await {
+            self.usage_dirty.store(true, std::sync::atomic::Ordering::SeqCst);
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// Run `flush_usage` every `period` until the returned task is aborted.
+    /// Returns `None` when no usage storage is attached.
+    pub fn spawn_usage_persistence(&self, period: std::time::Duration) -> Option<tokio::task::JoinHandle<()>> {
+        self.usage_storage.as_ref()?;
+        let manager = self.clone();
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = manager.flush_usage().await {
+                    tracing::warn!("Failed to persist usage counters: {}", e);
+                }
+            }
+        }))
+    }
+
+    /// Whether `amount` more usage fits within a limit in its current window.
+    /// Only a hint under concurrency; use `try_record_usages` to check and
+    /// count in one step.
+    pub async fn has_capacity(&self, limit_type: &str, amount: u32) -> bool {
+        let Some(max) = self.current_license.as_ref().and_then(|l| Self::limit_value(&l.limits, limit_type)) else {
+            return true;
+        };
+        self.current_usage(limit_type).await.saturating_add(amount) <= max
+    }
+
+    /// Broadcast a `LimitWarningEvent` when `used` reaches a threshold above
+    /// the last one reported for this limit. Usage falling back (e.g. a new
+    /// window) re-arms the lower thresholds.
+    fn note_usage(&self, limit_type: &str, used: u32) {
+        let limit = match self.current_license.as_ref().and_then(|l| Self::limit_value(&l.limits, limit_type)) {
+            Some(limit) if limit > 0 => limit,
+            _ => return,
+        };
+        let reached = self
+            .limit_warning_thresholds()
+            .into_iter()
+            .filter(|threshold| u64::from(used) * 100 >= u64::from(*threshold) * u64::from(limit))
+            .max();
+
+        let mut warned = self.warned_thresholds.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = warned.get(limit_type).copied();
+        match reached {
+            None => {
+                warned.remove(limit_type);
+            }
+            Some(threshold) if previous.map_or(true, |previous| threshold > previous) => {
+                warned.insert(limit_type.to_string(), threshold);
+                tracing::info!("{} at {}% of its limit ({} / {})", limit_type, threshold, used, limit);
+                // A send error only means nobody is subscribed
+                let _ = self.limit_warnings.send(LimitWarningEvent {
+                    limit_type: limit_type.to_string(),
+                    threshold_percent: threshold,
+                    used,
+                    limit,
+                    reached_at: Utc::now(),
+                });
+            }
+            Some(threshold) => {
+                warned.insert(limit_type.to_string(), threshold);
+            }
+        }
+    }
+
+    /// Usage in the current window (0 once the window has rolled over)
+    pub async fn current_usage(&self, limit_type: &str) -> u32 {
+        Self::usage_in_window(&*self.usage_counters.read().await, limit_type, Utc::now())
+    }
+
+    fn usage_in_window(counters: &HashMap<String, UsageCounter>, limit_type: &str, now: DateTime<Utc>) -> u32 {
+        match counters.get(limit_type) {
+            Some(counter) if counter.window_start == counter.window.window_start(now) => counter.count,
+            _ => 0,
+        }
+    }
+
+    /// Current usage vs limit for the time-windowed limits
+    pub async fn get_usage(&self) -> Vec<LimitUsage> {
+        let limits = self
+            .current_license
+            .as_ref()
+            .map(|l| l.limits.clone())
+            .unwrap_or_default();
+        let now = Utc::now();
+
+        let mut usage = Vec::new();
+        for limit_type in ["operations_per_hour", "api_calls_per_day"] {
+            let window = UsageWindow::for_limit(limit_type);
+            usage.push(LimitUsage {
+                limit_type: limit_type.to_string(),
+                window,
+                used: self.current_usage(limit_type).await,
+                limit: Self::limit_value(&limits, limit_type),
+                window_resets_at: window
+                    .map(|w| w.window_start(now) + chrono::Duration::seconds(w.span_seconds())),
+            });
+        }
+        usage
+    }
+
+    async fn persist_counters(storage: &StorageManager, counters: &HashMap<String, UsageCounter>) -> Result<(), crate::storage::StorageError> {
+        let now = Utc::now();
+        let entity = StoredEntity {
+            id: USAGE_COUNTERS_KEY.to_string(),
+            entity_type: "license_usage".to_string(),
+            data: serde_json::to_value(counters)
+                .map_err(|e| crate::storage::StorageError::SerializationError { error: e.to_string() })?,
+            created_at: now,
+            updated_at: now,
+            created_by: "license_manager".to_string(),
+            updated_by: "license_manager".to_string(),
+            version: 0,
+            deleted_at: None,
+            sync_status: SyncStatus::Local,
+        };
+        storage.put(USAGE_COUNTERS_KEY, entity, &Self::usage_context()).await
+    }
+
+    fn usage_context() -> StorageContext {
+        StorageContext {
+            user_id: "license_manager".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            skip_validation: true,
         }
     }
 
@@ -503,7 +1325,69 @@ impl LicenseManager {
 
     /// Get all available features for current tier
     pub async fn get_available_features(&self) -> Vec<String> {
-        self.feature_cache.keys().cloned().collect()
+        self.features().keys().cloned().collect()
+    }
+
+    /// Build a self-test report for the current license.
+    /// `limit_usage` carries whatever usage figures the caller can measure.
+    pub async fn diagnostics(&self, limit_usage: HashMap<String, u32>) -> LicenseDiagnostics {
+        let license = self.current_license.as_ref();
+        let (signature_valid, matched_verification_key) = match license {
+            Some(l) if l.tier == LicenseTier::Community => (true, None),
+            Some(l) => match self.verify_license_signature(l) {
+                Ok(version) => (true, Some(version)),
+                Err(_) => (false, None),
+            },
+            None => (false, None),
+        };
+
+        LicenseDiagnostics {
+            license_id: license.map(|l| l.license_id),
+            tier: self.get_tier().await,
+            status: license.map(|l| l.status.clone()),
+            customer_name: license.map(|l| l.customer_name.clone()),
+            issued_at: license.map(|l| l.issued_at),
+            expires_at: license.and_then(|l| l.expires_at),
+            days_remaining: license
+                .and_then(|l| l.expires_at)
+                .map(|exp| (exp - Utc::now()).num_days()),
+            signature_valid,
+            matched_verification_key,
+            feature_count: self.features().len(),
+            plugin_access_mode: self.get_plugin_access_mode().await,
+            limits: license.map(|l| l.limits.clone()).unwrap_or_default(),
+            limit_usage,
+            last_failure: self.last_failure.clone(),
+        }
+    }
+
+    /// Check a license file the way activation would (parse, signature,
+    /// expiry, status/revocation, features, deployment, machine binding) and
+    /// report on it, without touching the active license. A rejected
+    /// license shows up with `last_failure` set.
+    pub async fn validate_license_file(&self, path: &str) -> LicenseDiagnostics {
+        // Validate on a scratch copy so the active license is never replaced
+        let mut candidate = self.clone();
+        candidate.current_license = None;
+        // Clones share the cache; give the candidate its own
+        candidate.feature_cache = Arc::default();
+        candidate.last_failure = None;
+
+        let parsed = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| serde_json::from_str::<LicenseInfo>(&data).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(license) => {
+                if candidate.validate_from_source(license.clone(), path).await.is_err() {
+                    // Report on the rejected license itself
+                    candidate.current_license = Some(license);
+                    candidate.rebuild_feature_cache();
+                }
+            }
+            Err(message) => candidate.record_failure(path, LicenseFailureReason::Unreadable, message, None),
+        }
+
+        candidate.diagnostics(HashMap::new()).await
     }
 
     /// Get plugin list for current tier
@@ -533,6 +1417,21 @@ pub enum LicenseError {
     #[error("License is invalid or revoked")]
     Invalid,
 
+    #[error("License not valid for '{deployment}' deployments (allowed: {allowed:?})")]
+    DeploymentNotAllowed { deployment: String, allowed: Vec<String> },
+
+    #[error("License feature set is inconsistent: {0}")]
+    InconsistentFeatures(String),
+
+    #[error("License verification key revoked: {0}")]
+    KeyRevoked(String),
+
+    #[error("Unknown verification key: {0}")]
+    UnknownKey(String),
+
+    #[error("License is bound to a different machine (this machine: {machine_id})")]
+    MachineMismatch { machine_id: String },
+
     #[error("Feature not available in current license: {0}")]
     FeatureNotAvailable(String),
 
@@ -548,6 +1447,18 @@ pub enum LicenseError {
 
 // Default implementation derived above
 
+/// Parse a license passed through `NODUS_LICENSE`: base64-encoded JSON first,
+/// then the value itself as raw JSON
+fn parse_env_license(value: &str) -> Option<LicenseInfo> {
+    let value = value.trim();
+    general_purpose::STANDARD
+        .decode(value)
+        .ok()
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .or_else(|| serde_json::from_str(value).ok())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -622,6 +1533,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_feature_dependency_validation() {
+        // Built-in tier sets are always consistent
+        for tier in [LicenseTier::Community, LicenseTier::Pro, LicenseTier::Team, LicenseTier::Enterprise] {
+            let features = LicenseFeatures::features_for_tier(&tier);
+            assert!(LicenseFeatures::validate_feature_set(&features).is_empty(), "{:?} is inconsistent", tier);
+        }
+
+        let custom: HashSet<String> = ["ai_search".to_string()].into_iter().collect();
+        let missing = LicenseFeatures::validate_feature_set(&custom);
+        assert_eq!(missing, vec![MissingDependency {
+            feature: "ai_search".to_string(),
+            missing: vec!["ai_embedding".to_string()],
+        }]);
+    }
+
     #[test]
     fn test_license_limits_apache_model() {
         let limits = LicenseLimits::default();
@@ -644,4 +1571,293 @@ mod tests {
         assert!(manager.has_feature("unsigned_plugins_allowed").await);
         assert!(!manager.has_feature("signed_plugins_only").await);
     }
+
+    #[tokio::test]
+    async fn test_license_reload_without_changes() {
+        let mut manager = LicenseManager::new().await.unwrap();
+        let mut events = manager.subscribe();
+
+        let event = manager.reload().await.unwrap();
+        assert_eq!(event.current_tier, LicenseTier::Community);
+        assert!(!event.has_changes());
+        // No delta means nothing is broadcast
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_machine_fingerprint_is_stable() {
+        let first = LicenseManager::machine_fingerprint();
+        assert_eq!(first, LicenseManager::machine_fingerprint());
+        assert_eq!(first.len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_usage_counters_feed_check_limit() {
+        let mut manager = LicenseManager::new().await.unwrap();
+        manager.attach_usage_storage(Arc::new(StorageManager::new())).await;
+        if let Some(ref mut license) = manager.current_license {
+            license.limits.max_operations_per_hour = Some(2);
+        }
+
+        assert_eq!(manager.record_usage("operations_per_hour", 2).await, 2);
+        assert!(manager.check_limit("operations_per_hour", None).await);
+        manager.record_usage("operations_per_hour", 1).await;
+        assert!(!manager.check_limit("operations_per_hour", None).await);
+
+        // Non-windowed limits are not tracked
+        assert_eq!(manager.record_usage("users", 5).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_capacity_check_does_not_consume_quota() {
+        let storage = Arc::new(StorageManager::new());
+        let mut manager = LicenseManager::new().await.unwrap();
+        manager.attach_usage_storage(storage.clone()).await;
+        if let Some(ref mut license) = manager.current_license {
+            license.limits.max_operations_per_hour = Some(1);
+        }
+
+        assert!(manager.has_capacity("operations_per_hour", 1).await);
+        assert!(manager.has_capacity("operations_per_hour", 1).await);
+        assert_eq!(manager.current_usage("operations_per_hour").await, 0);
+
+        let counts = manager.record_usages(&[("operations_per_hour", 1), ("api_calls_per_day", 1)]).await;
+        assert_eq!(counts, vec![1, 1]);
+        assert!(!manager.has_capacity("operations_per_hour", 1).await);
+        assert!(manager.has_capacity("api_calls_per_day", 1).await);
+
+        // Counters are saved on flush, not on every recorded use
+        assert!(storage.get(USAGE_COUNTERS_KEY, &LicenseManager::usage_context()).await.unwrap().is_none());
+        assert!(manager.flush_usage().await.unwrap());
+        assert!(!manager.flush_usage().await.unwrap());
+
+        // Both counters share one stored entity and reload together
+        let mut reloaded = LicenseManager::new().await.unwrap();
+        reloaded.attach_usage_storage(storage).await;
+        assert_eq!(reloaded.current_usage("operations_per_hour").await, 1);
+        assert_eq!(reloaded.current_usage("api_calls_per_day").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_try_record_usages_holds_the_limit_under_concurrency() {
+        let mut manager = LicenseManager::new().await.unwrap();
+        if let Some(ref mut license) = manager.current_license {
+            license.limits.max_operations_per_hour = Some(5);
+        }
+        let manager = Arc::new(manager);
+
+        let attempts: Vec<_> = (0..20)
+            .map(|_| {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    manager.try_record_usages(&[("api_calls_per_day", 1), ("operations_per_hour", 1)]).await
+                })
+            })
+            .collect();
+        let mut accepted = 0;
+        for attempt in attempts {
+            match attempt.await.unwrap() {
+                Ok(_) => accepted += 1,
+                Err(e) => assert!(matches!(e, LicenseError::LimitExceeded(ref limit) if limit == "operations_per_hour")),
+            }
+        }
+
+        assert_eq!(accepted, 5);
+        assert_eq!(manager.current_usage("operations_per_hour").await, 5);
+        // Rejected attempts count against none of their limits
+        assert_eq!(manager.current_usage("api_calls_per_day").await, 5);
+    }
+
+    #[tokio::test]
+    async fn test_limit_warnings_fire_once_per_threshold() {
+        let mut manager = LicenseManager::new().await.unwrap();
+        if let Some(ref mut license) = manager.current_license {
+            license.limits.max_operations_per_hour = Some(20);
+            license.limits.max_concurrent_sessions = Some(4);
+        }
+        let mut warnings = manager.subscribe_limit_warnings();
+
+        manager.record_usage("operations_per_hour", 15).await;
+        assert!(warnings.try_recv().is_err());
+        manager.record_usage("operations_per_hour", 1).await;
+        let warning = warnings.try_recv().unwrap();
+        assert_eq!((warning.threshold_percent, warning.used, warning.limit), (80, 16, 20));
+        manager.record_usage("operations_per_hour", 1).await;
+        assert!(warnings.try_recv().is_err());
+
+        // Jumping past several thresholds reports only the highest
+        manager.record_usage("operations_per_hour", 3).await;
+        assert_eq!(warnings.try_recv().unwrap().threshold_percent, 100);
+        assert!(warnings.try_recv().is_err());
+
+        // Explicit usage passed to check_limit is watched too
+        assert_eq!(manager.set_limit_warning_thresholds(vec![50, 25, 50]), vec![25, 50]);
+        manager.check_limit("concurrent_sessions", Some(2)).await;
+        let warning = warnings.try_recv().unwrap();
+        assert_eq!((warning.limit_type.as_str(), warning.threshold_percent), ("concurrent_sessions", 50));
+    }
+
+    fn signed_license(manager: &LicenseManager, key_version: &str, secret: &str) -> LicenseInfo {
+        let mut license = manager.current_license.clone().unwrap();
+        license.tier = LicenseTier::Pro;
+        license.verification_key = key_version.to_string();
+        let message = format!(
+            "{}:{}:{}:{}",
+            license.license_id, license.tier.clone() as u8, license.customer_name, license.issued_at.timestamp()
+        );
+        let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+        license.signature = general_purpose::STANDARD.encode(hmac::sign(&key, message.as_bytes()).as_ref());
+        license
+    }
+
+    #[tokio::test]
+    async fn test_key_rotation_fallback_and_revocation() {
+        let mut manager = LicenseManager::new().await.unwrap();
+        manager.add_verification_keys(VerificationKeySet {
+            keys: vec![VerificationKey {
+                version: "pro_key_v2".to_string(),
+                secret: "pro_verification_key_2025".to_string(),
+                status: KeyStatus::Active,
+            }],
+            fallback_versions: vec!["pro_key_v2".to_string()],
+        });
+
+        // License names the old version but was signed with the rolled key
+        let license = signed_license(&manager, "pro_key_v1", "pro_verification_key_2025");
+        assert_eq!(manager.verify_license_signature(&license).unwrap(), "pro_key_v2");
+
+        // Deprecated keys still verify
+        let license = signed_license(&manager, "pro_key_v1", "pro_verification_key_2024");
+        manager.set_key_status("pro_key_v1", KeyStatus::Deprecated).unwrap();
+        assert_eq!(manager.verify_license_signature(&license).unwrap(), "pro_key_v1");
+
+        // Revoked keys reject
+        manager.set_key_status("pro_key_v1", KeyStatus::Revoked).unwrap();
+        assert!(matches!(
+            manager.verify_license_signature(&license),
+            Err(LicenseError::KeyRevoked(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_bundle_signature() {
+        let mut manager = LicenseManager::new().await.unwrap();
+        let bundle = b"export default { name: 'signed' };";
+        let sign = |secret: &str, data: &[u8]| {
+            let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+            general_purpose::STANDARD.encode(hmac::sign(&key, data).as_ref())
+        };
+        let signature = format!("enterprise_key_v1:{}\n", sign("enterprise_verification_key_2024", bundle));
+
+        assert_eq!(manager.verify_bundle_signature(bundle, &signature).unwrap(), "enterprise_key_v1");
+        assert!(matches!(
+            manager.verify_bundle_signature(b"tampered", &signature),
+            Err(LicenseError::InvalidSignature)
+        ));
+        let wrong_key = format!("enterprise_key_v1:{}", sign("not_the_key", bundle));
+        assert!(matches!(manager.verify_bundle_signature(bundle, &wrong_key), Err(LicenseError::InvalidSignature)));
+        assert!(matches!(manager.verify_bundle_signature(bundle, "no separator"), Err(LicenseError::InvalidSignature)));
+        assert!(matches!(manager.verify_bundle_signature(bundle, "other_key:AAAA"), Err(LicenseError::UnknownKey(_))));
+
+        manager.set_key_status("enterprise_key_v1", KeyStatus::Revoked).unwrap();
+        assert!(matches!(manager.verify_bundle_signature(bundle, &signature), Err(LicenseError::KeyRevoked(_))));
+    }
+
+    #[tokio::test]
+    async fn test_validate_license_file_does_not_activate() {
+        let manager = LicenseManager::new().await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("license.json");
+        let path_str = path.to_string_lossy().to_string();
+
+        let license = signed_license(&manager, "pro_key_v1", "pro_verification_key_2024");
+        std::fs::write(&path, serde_json::to_string(&license).unwrap()).unwrap();
+        let report = manager.validate_license_file(&path_str).await;
+        assert!(report.signature_valid);
+        assert!(report.last_failure.is_none());
+        assert_eq!(report.tier, LicenseTier::Pro);
+        assert_eq!(report.matched_verification_key.as_deref(), Some("pro_key_v1"));
+        // Still running on the original license
+        assert_eq!(manager.get_tier().await, LicenseTier::Community);
+
+        let mut expired = license.clone();
+        expired.expires_at = Some(Utc::now() - chrono::Duration::days(1));
+        std::fs::write(&path, serde_json::to_string(&expired).unwrap()).unwrap();
+        let report = manager.validate_license_file(&path_str).await;
+        assert_eq!(report.last_failure.unwrap().reason, LicenseFailureReason::Expired);
+        assert!(report.days_remaining.unwrap() < 0);
+
+        let mut tampered = license;
+        tampered.customer_name = "Someone Else".to_string();
+        std::fs::write(&path, serde_json::to_string(&tampered).unwrap()).unwrap();
+        let report = manager.validate_license_file(&path_str).await;
+        assert!(!report.signature_valid);
+        assert_eq!(report.last_failure.unwrap().reason, LicenseFailureReason::InvalidSignature);
+
+        std::fs::write(&path, "not json").unwrap();
+        let report = manager.validate_license_file(&path_str).await;
+        assert_eq!(report.last_failure.unwrap().reason, LicenseFailureReason::Unreadable);
+        assert!(report.license_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_feature_cache_rebuild_is_atomic() {
+        let mut manager = LicenseManager::new().await.unwrap();
+        // Clones share the cache, so the reader observes every rebuild below
+        let reader = manager.clone();
+        let checks = tokio::spawn(async move {
+            for _ in 0..2000 {
+                assert!(reader.has_feature("plugin_system").await);
+                assert!(reader.has_feature("workflows").await);
+                tokio::task::yield_now().await;
+            }
+        });
+
+        for _ in 0..2000 {
+            manager.set_community_license();
+            tokio::task::yield_now().await;
+        }
+        checks.await.unwrap();
+        assert!(!manager.has_feature("no_such_feature").await);
+    }
+
+    #[tokio::test]
+    async fn test_parse_env_license_accepts_base64_and_raw_json() {
+        let manager = LicenseManager::new().await.unwrap();
+        let license = signed_license(&manager, "pro_key_v1", "pro_verification_key_2024");
+        let json = serde_json::to_string(&license).unwrap();
+
+        let from_base64 = parse_env_license(&general_purpose::STANDARD.encode(&json)).unwrap();
+        assert_eq!(from_base64.license_id, license.license_id);
+
+        let from_raw = parse_env_license(&format!("  {}\n", json)).unwrap();
+        assert_eq!(from_raw.license_id, license.license_id);
+        assert_eq!(from_raw.signature, license.signature);
+
+        assert!(parse_env_license("not a license").is_none());
+        assert!(parse_env_license(&general_purpose::STANDARD.encode("{}")).is_none());
+    }
+
+    #[test]
+    fn test_deployment_allowed() {
+        let community = vec!["any".to_string()];
+        assert!(LicenseManager::check_deployment(&community, "server").is_ok());
+
+        let desktop_and_cloud = vec!["desktop".to_string(), "Cloud".to_string()];
+        assert!(LicenseManager::check_deployment(&desktop_and_cloud, "desktop").is_ok());
+        assert!(LicenseManager::check_deployment(&desktop_and_cloud, "cloud").is_ok());
+    }
+
+    #[test]
+    fn test_deployment_disallowed() {
+        let desktop_only = vec!["desktop".to_string()];
+        match LicenseManager::check_deployment(&desktop_only, "server") {
+            Err(LicenseError::DeploymentNotAllowed { deployment, allowed }) => {
+                assert_eq!(deployment, "server");
+                assert_eq!(allowed, desktop_only);
+            }
+            other => panic!("expected DeploymentNotAllowed, got {:?}", other),
+        }
+        assert!(LicenseManager::check_deployment(&[], "desktop").is_err());
+    }
 }
@@ -0,0 +1,45 @@
+// src/logging.rs
+// Tracing subscriber setup driven by the NODUS_LOG filter
+
+use tracing::Level;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::prelude::*;
+
+/// Environment variable holding the log filter directives
+pub const LOG_FILTER_ENV: &str = "NODUS_LOG";
+
+/// Build the log filter from `NODUS_LOG` (e.g. `info,nodus::storage=debug`).
+/// Falls back to `info` when unset or unparsable, which keeps verbose storage
+/// events (emitted at debug/trace) out of the default output.
+pub fn log_filter() -> Targets {
+    std::env::var(LOG_FILTER_ENV)
+        .ok()
+        .and_then(|directives| parse_filter(&directives))
+        .unwrap_or_else(|| Targets::new().with_default(Level::INFO))
+}
+
+fn parse_filter(directives: &str) -> Option<Targets> {
+    directives.parse::<Targets>().ok()
+}
+
+/// Install the global fmt subscriber filtered by `NODUS_LOG`.
+/// Safe to call more than once; later calls are ignored.
+pub fn init() {
+    let _ = tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(log_filter())
+        .try_init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filter_directives() {
+        let filter = parse_filter("info,nodus::storage=debug").unwrap();
+        assert!(filter.would_enable("nodus::storage::storage_mod", &Level::DEBUG));
+        assert!(!filter.would_enable("nodus::state_mod", &Level::DEBUG));
+        assert!(filter.would_enable("nodus::state_mod", &Level::INFO));
+    }
+}
@@ -0,0 +1,353 @@
+// src/marketplace.rs
+// HTTP client for the plugin marketplace catalog and bundle downloads
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+use crate::http_access::{origin_of, HttpAccessPolicy};
+use crate::retry::{retry_with_backoff, RetryError};
+use crate::storage::RetryConfig;
+
+/// Environment variable with the default marketplace base URL
+pub const MARKETPLACE_URL_ENV: &str = "NODUS_MARKETPLACE_URL";
+
+/// Per-request timeout for marketplace calls
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Marketplace errors
+#[derive(Debug, thiserror::Error)]
+pub enum MarketplaceError {
+    #[error("No marketplace URL configured (set {MARKETPLACE_URL_ENV})")]
+    NotConfigured,
+
+    #[error("Plugin not found in marketplace: {plugin_id}")]
+    PluginNotFound { plugin_id: String },
+
+    #[error("Marketplace request to {url} failed: {error}")]
+    Request { url: String, error: String },
+
+    #[error("Marketplace request to {url} failed after {attempts} attempt(s): {error}")]
+    RetriesExhausted { url: String, attempts: u32, error: String },
+
+    #[error("Marketplace request to {url} is not allowed by the HTTP access policy")]
+    OriginNotAllowed { url: String },
+
+    #[error("Plugin bundle at {url} is larger than the {limit} byte limit")]
+    BundleTooLarge { url: String, limit: usize },
+
+    #[error("Checksum mismatch for plugin {plugin_id}: expected sha256 {expected}, got {actual}")]
+    IntegrityError {
+        plugin_id: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// One plugin listed in the marketplace catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceEntry {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    pub download_url: String,
+    /// Hex-encoded SHA-256 of the bundle at `download_url`
+    pub sha256: String,
+    /// Oldest engine version the plugin supports (semver)
+    #[serde(default)]
+    pub min_engine_version: Option<String>,
+}
+
+impl MarketplaceEntry {
+    /// Check a downloaded bundle against the catalog checksum
+    pub fn verify_bundle(&self, bundle: &[u8]) -> Result<(), MarketplaceError> {
+        verify_sha256(&self.id, bundle, &self.sha256)
+    }
+}
+
+/// Marketplace client; every request is retried with backoff on transient failures.
+/// Requests to the marketplace's own origin are always allowed; anything else
+/// the catalog points at (such as a bundle CDN) must pass the access policy.
+#[derive(Debug, Clone)]
+pub struct MarketplaceClient {
+    base_url: String,
+    http: reqwest::Client,
+    retry: RetryConfig,
+    access: HttpAccessPolicy,
+    max_bundle_bytes: usize,
+}
+
+impl MarketplaceClient {
+    /// Panics if the HTTP client cannot be built; a client without the
+    /// request timeout would never surface timeouts for retrying.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("marketplace HTTP client with request timeout"),
+            retry: RetryConfig::default(),
+            access: HttpAccessPolicy::default(),
+            max_bundle_bytes: crate::commands_plugin::MAX_PLUGIN_BUNDLE_SIZE,
+        }
+    }
+
+    /// Client for `url`, or for `NODUS_MARKETPLACE_URL` when `url` is None
+    pub fn from_url_or_env(url: Option<String>) -> Result<Self, MarketplaceError> {
+        url.or_else(|| std::env::var(MARKETPLACE_URL_ENV).ok())
+            .filter(|u| !u.is_empty())
+            .map(Self::new)
+            .ok_or(MarketplaceError::NotConfigured)
+    }
+
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_access_policy(mut self, access: HttpAccessPolicy) -> Self {
+        self.access = access;
+        self
+    }
+
+    /// Largest bundle `download_bundle` will read
+    pub fn with_max_bundle_bytes(mut self, max_bundle_bytes: usize) -> Self {
+        self.max_bundle_bytes = max_bundle_bytes;
+        self
+    }
+
+    fn check_access(&self, url: &str) -> Result<(), MarketplaceError> {
+        let same_origin = origin_of(url).map_or(false, |origin| Some(origin) == origin_of(&self.base_url));
+        if (same_origin && self.access.is_method_allowed("GET")) || self.access.allows_request("GET", url) {
+            Ok(())
+        } else {
+            Err(MarketplaceError::OriginNotAllowed { url: url.to_string() })
+        }
+    }
+
+    /// List the plugins the marketplace offers
+    pub async fn fetch_catalog(&self) -> Result<Vec<MarketplaceEntry>, MarketplaceError> {
+        let url = format!("{}/plugins", self.base_url);
+        let response = self.get(&url).await?;
+        response.json().await.map_err(|e| MarketplaceError::Request {
+            url,
+            error: e.to_string(),
+        })
+    }
+
+    /// Catalog entry for one plugin
+    pub async fn find_plugin(&self, plugin_id: &str) -> Result<MarketplaceEntry, MarketplaceError> {
+        self.fetch_catalog()
+            .await?
+            .into_iter()
+            .find(|entry| entry.id == plugin_id)
+            .ok_or_else(|| MarketplaceError::PluginNotFound { plugin_id: plugin_id.to_string() })
+    }
+
+    /// Download a plugin bundle and verify it against the catalog checksum.
+    /// A truncated or corrupted download fails with `IntegrityError`. Bundles
+    /// over the size limit fail with `BundleTooLarge`: up front when the
+    /// server declares a length, otherwise as soon as the body passes it.
+    pub async fn download_bundle(&self, entry: &MarketplaceEntry) -> Result<Vec<u8>, MarketplaceError> {
+        let url = &entry.download_url;
+        let too_large = || MarketplaceError::BundleTooLarge { url: url.clone(), limit: self.max_bundle_bytes };
+        let mut response = self.get(url).await?;
+        if response.content_length().map_or(false, |len| len > self.max_bundle_bytes as u64) {
+            return Err(too_large());
+        }
+
+        let mut bundle = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| MarketplaceError::Request {
+            url: url.clone(),
+            error: e.to_string(),
+        })? {
+            if bundle.len() + chunk.len() > self.max_bundle_bytes {
+                return Err(too_large());
+            }
+            bundle.extend_from_slice(&chunk);
+        }
+        entry.verify_bundle(&bundle)?;
+        Ok(bundle)
+    }
+
+    /// GET with retries. 5xx, timeouts and connection failures are retried;
+    /// 4xx and other errors fail immediately.
+    async fn get(&self, url: &str) -> Result<reqwest::Response, MarketplaceError> {
+        self.check_access(url)?;
+        retry_with_backoff(&self.retry, is_retriable, || async {
+            self.http.get(url).send().await?.error_for_status()
+        })
+        .await
+        .map_err(|e| match e {
+            RetryError::NonRetriable(error) => MarketplaceError::Request {
+                url: url.to_string(),
+                error: error.to_string(),
+            },
+            RetryError::Exhausted { attempts, last_error } => MarketplaceError::RetriesExhausted {
+                url: url.to_string(),
+                attempts,
+                error: last_error.to_string(),
+            },
+        })
+    }
+}
+
+/// Hex-encoded SHA-256 digest
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Compare `data` against an expected hex digest (case-insensitive)
+pub fn verify_sha256(plugin_id: &str, data: &[u8], expected: &str) -> Result<(), MarketplaceError> {
+    let actual = sha256_hex(data);
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(MarketplaceError::IntegrityError {
+            plugin_id: plugin_id.to_string(),
+            expected: expected.trim().to_string(),
+            actual,
+        })
+    }
+}
+
+/// Transient failures worth retrying: server errors, timeouts, refused or
+/// dropped connections
+pub fn is_retriable(error: &reqwest::Error) -> bool {
+    if let Some(status) = error.status() {
+        return status.is_server_error();
+    }
+    error.is_timeout() || error.is_connect() || error.is_request()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_for(bundle: &[u8]) -> MarketplaceEntry {
+        MarketplaceEntry {
+            id: "word-count".to_string(),
+            name: "Word Count".to_string(),
+            version: "1.0.0".to_string(),
+            author: String::new(),
+            description: String::new(),
+            download_url: "http://127.0.0.1:1/word-count.js".to_string(),
+            sha256: sha256_hex(bundle),
+            min_engine_version: None,
+        }
+    }
+
+    #[test]
+    fn test_bundle_checksum_verification() {
+        let bundle = b"export default { name: 'word-count' };";
+        let entry = entry_for(bundle);
+
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(entry.verify_bundle(bundle).is_ok());
+
+        // Truncated download
+        match entry.verify_bundle(&bundle[..10]) {
+            Err(MarketplaceError::IntegrityError { plugin_id, expected, .. }) => {
+                assert_eq!(plugin_id, "word-count");
+                assert_eq!(expected, entry.sha256);
+            }
+            other => panic!("expected IntegrityError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused_is_retried_then_reported() {
+        // Nothing listens on port 1; connecting is refused immediately
+        let client = MarketplaceClient::new("http://127.0.0.1:1").with_retry_config(RetryConfig {
+            max_retries: 2,
+            base_delay_ms: 1,
+            max_delay_ms: 5,
+            backoff_multiplier: 2.0,
+        });
+
+        match client.fetch_catalog().await {
+            Err(MarketplaceError::RetriesExhausted { attempts, url, .. }) => {
+                assert_eq!(attempts, 3);
+                assert_eq!(url, "http://127.0.0.1:1/plugins");
+            }
+            other => panic!("expected RetriesExhausted, got {:?}", other),
+        }
+    }
+
+    /// Serve one raw HTTP response on a local port, returning its URL
+    fn serve_once(response: Vec<u8>) -> String {
+        use std::io::{Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/word-count.js", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(&response);
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_oversized_bundles_are_refused() {
+        let bundle = vec![b'x'; 64];
+        let client = MarketplaceClient::new("http://127.0.0.1").with_max_bundle_bytes(32);
+        let mut entry = entry_for(&bundle);
+
+        // Declared length over the limit: refused before the body is read
+        let mut declared = b"HTTP/1.1 200 OK\r\nContent-Length: 64\r\nConnection: close\r\n\r\n".to_vec();
+        declared.extend_from_slice(&bundle);
+        entry.download_url = serve_once(declared);
+        assert!(matches!(
+            client.download_bundle(&entry).await,
+            Err(MarketplaceError::BundleTooLarge { limit: 32, .. })
+        ));
+
+        // No declared length: the body is cut off once it passes the limit
+        let mut undeclared = b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n".to_vec();
+        undeclared.extend_from_slice(&bundle);
+        entry.download_url = serve_once(undeclared);
+        assert!(matches!(
+            client.download_bundle(&entry).await,
+            Err(MarketplaceError::BundleTooLarge { limit: 32, .. })
+        ));
+
+        // Within the limit the bundle is read and verified
+        let client = client.with_max_bundle_bytes(64);
+        let mut exact = b"HTTP/1.1 200 OK\r\nContent-Length: 64\r\nConnection: close\r\n\r\n".to_vec();
+        exact.extend_from_slice(&bundle);
+        entry.download_url = serve_once(exact);
+        assert_eq!(client.download_bundle(&entry).await.unwrap(), bundle);
+    }
+
+    #[tokio::test]
+    async fn test_downloads_outside_the_access_policy_are_refused() {
+        let retry = RetryConfig { max_retries: 0, base_delay_ms: 1, max_delay_ms: 1, backoff_multiplier: 1.0 };
+        let client = MarketplaceClient::new("https://marketplace.example.com").with_retry_config(retry);
+        let mut entry = entry_for(b"bundle");
+
+        // Refused before any request is sent
+        entry.download_url = "https://cdn.example.com/word-count.js".to_string();
+        assert!(matches!(
+            client.download_bundle(&entry).await,
+            Err(MarketplaceError::OriginNotAllowed { url }) if url == entry.download_url
+        ));
+
+        // Localhost is in the default policy, so this one is attempted
+        entry.download_url = "http://127.0.0.1:1/word-count.js".to_string();
+        assert!(matches!(client.download_bundle(&entry).await, Err(MarketplaceError::RetriesExhausted { .. })));
+
+        let client = client.with_access_policy(HttpAccessPolicy::default().with_methods(vec!["POST".to_string()]));
+        entry.download_url = "https://marketplace.example.com/word-count.js".to_string();
+        assert!(matches!(client.download_bundle(&entry).await, Err(MarketplaceError::OriginNotAllowed { .. })));
+    }
+}
@@ -0,0 +1,140 @@
+// src/payload_limits.rs
+// Size limits for payloads arriving from the frontend over IPC. Grid configs,
+// plugin bundles and generic action payloads each get their own ceiling so a
+// malformed or hostile frontend can't push arbitrarily large data into the
+// engine. Raw strings and bytes are checked before they are parsed.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error_code::{CommandError, ErrorCode};
+
+/// Environment variables overriding the default limits (in bytes)
+pub const MAX_GRID_CONFIG_BYTES_ENV: &str = "NODUS_MAX_GRID_CONFIG_BYTES";
+pub const MAX_PLUGIN_BUNDLE_BYTES_ENV: &str = "NODUS_MAX_PLUGIN_BUNDLE_BYTES";
+pub const MAX_ACTION_PAYLOAD_BYTES_ENV: &str = "NODUS_MAX_ACTION_PAYLOAD_BYTES";
+
+/// Default grid config limit: far beyond any hand-built dashboard
+pub const DEFAULT_MAX_GRID_CONFIG_BYTES: usize = 1024 * 1024;
+
+/// Default generic action payload limit
+pub const DEFAULT_MAX_ACTION_PAYLOAD_BYTES: usize = 256 * 1024;
+
+/// What a payload carries; each kind has its own limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadKind {
+    GridConfig,
+    PluginBundle,
+    Action,
+}
+
+impl std::fmt::Display for PayloadKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PayloadKind::GridConfig => "Grid config",
+            PayloadKind::PluginBundle => "Plugin bundle",
+            PayloadKind::Action => "Action",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Maximum serialized size, in bytes, per payload kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PayloadLimits {
+    pub grid_config_bytes: usize,
+    pub plugin_bundle_bytes: usize,
+    pub action_bytes: usize,
+}
+
+impl PayloadLimits {
+    /// Built-in limits, ignoring the environment
+    pub fn defaults() -> Self {
+        Self {
+            grid_config_bytes: DEFAULT_MAX_GRID_CONFIG_BYTES,
+            plugin_bundle_bytes: crate::commands_plugin::MAX_PLUGIN_BUNDLE_SIZE,
+            action_bytes: DEFAULT_MAX_ACTION_PAYLOAD_BYTES,
+        }
+    }
+
+    /// Built-in limits with any `NODUS_MAX_*_BYTES` overrides applied
+    pub fn from_env() -> Self {
+        let read = |name: &str, default: usize| {
+            std::env::var(name).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        };
+        let defaults = Self::defaults();
+        Self {
+            grid_config_bytes: read(MAX_GRID_CONFIG_BYTES_ENV, defaults.grid_config_bytes),
+            plugin_bundle_bytes: read(MAX_PLUGIN_BUNDLE_BYTES_ENV, defaults.plugin_bundle_bytes),
+            action_bytes: read(MAX_ACTION_PAYLOAD_BYTES_ENV, defaults.action_bytes),
+        }
+    }
+
+    pub fn limit_for(&self, kind: PayloadKind) -> usize {
+        match kind {
+            PayloadKind::GridConfig => self.grid_config_bytes,
+            PayloadKind::PluginBundle => self.plugin_bundle_bytes,
+            PayloadKind::Action => self.action_bytes,
+        }
+    }
+
+    /// Check a raw payload (string, bytes, file) by its length before parsing
+    /// it. Too large is `ValidationFailed`, with the kind, size and limit as
+    /// details.
+    pub fn check_len(&self, kind: PayloadKind, size: usize) -> Result<(), CommandError> {
+        let limit = self.limit_for(kind);
+        if size > limit {
+            let message = format!("{} payload is {} bytes, larger than the {} byte limit", kind, size, limit);
+            return Err(CommandError::new(ErrorCode::ValidationFailed, message)
+                .with_details(serde_json::json!({ "kind": kind, "size": size, "limit": limit })));
+        }
+        Ok(())
+    }
+
+    /// Decode a raw JSON IPC body. Its length is checked first, so an
+    /// oversized body is rejected before any of it is parsed; malformed JSON
+    /// is `ValidationFailed` too.
+    pub fn decode<T: DeserializeOwned>(&self, kind: PayloadKind, body: &[u8]) -> Result<T, CommandError> {
+        self.check_len(kind, body.len())?;
+        serde_json::from_slice(body)
+            .map_err(|e| CommandError::new(ErrorCode::ValidationFailed, format!("Invalid {} payload: {}", kind, e)))
+    }
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_are_per_kind() {
+        let limits = PayloadLimits {
+            grid_config_bytes: 64,
+            plugin_bundle_bytes: 1024,
+            action_bytes: 16,
+        };
+        let body = serde_json::to_vec(&serde_json::json!({ "text": "x".repeat(30) })).unwrap();
+
+        let payload: serde_json::Value = limits.decode(PayloadKind::GridConfig, &body).unwrap();
+        assert_eq!(payload["text"].as_str().map(str::len), Some(30));
+        let err = limits.decode::<serde_json::Value>(PayloadKind::Action, &body).unwrap_err();
+        assert_eq!(err.code, ErrorCode::ValidationFailed);
+        assert_eq!(err.details, Some(serde_json::json!({ "kind": "action", "size": body.len(), "limit": 16 })));
+        // The size is checked on the raw bytes, before they are parsed
+        let err = limits.decode::<serde_json::Value>(PayloadKind::Action, &[b'{'; 17]).unwrap_err();
+        assert!(err.details.is_some());
+        let err = limits.decode::<serde_json::Value>(PayloadKind::GridConfig, b"{").unwrap_err();
+        assert!(err.message.starts_with("Invalid Grid config payload"), "{}", err);
+
+        assert!(limits.check_len(PayloadKind::PluginBundle, 1024).is_ok());
+        let err = limits.check_len(PayloadKind::PluginBundle, 1025).unwrap_err();
+        assert_eq!(err.to_string(), "Plugin bundle payload is 1025 bytes, larger than the 1024 byte limit");
+    }
+}
@@ -0,0 +1,140 @@
+// src/retry.rs
+// Exponential backoff with jitter, driven by the sync `RetryConfig`
+
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::storage::RetryConfig;
+
+/// Why a retried operation ultimately failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum RetryError<E> {
+    /// The error was not worth retrying (e.g. an HTTP 4xx)
+    NonRetriable(E),
+    /// Every attempt failed with a retriable error
+    Exhausted { attempts: u32, last_error: E },
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RetryError::NonRetriable(e) => write!(f, "{}", e),
+            RetryError::Exhausted { attempts, last_error } => {
+                write!(f, "giving up after {} attempt(s): {}", attempts, last_error)
+            }
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Upper bound of the delay before retry number `retry` (0-based):
+    /// `base * multiplier^retry`, capped at `max_delay_ms`
+    pub fn backoff_delay(&self, retry: u32) -> Duration {
+        let delay = self.base_delay_ms as f64 * self.backoff_multiplier.powi(retry as i32);
+        Duration::from_millis(delay.min(self.max_delay_ms as f64) as u64)
+    }
+
+    /// Backoff delay with "equal jitter": a random point in the upper half,
+    /// so concurrent clients don't retry in lockstep
+    pub fn jittered_delay(&self, retry: u32) -> Duration {
+        let max = self.backoff_delay(retry).as_millis() as u64;
+        let half = max / 2;
+        Duration::from_millis(half + rand::thread_rng().gen_range(0..=max - half))
+    }
+}
+
+/// Run `operation` until it succeeds, fails with an error `is_retriable`
+/// rejects, or `config.max_retries` retries have been spent
+pub async fn retry_with_backoff<T, E, F, Fut>(
+    config: &RetryConfig,
+    is_retriable: impl Fn(&E) -> bool,
+    mut operation: F,
+) -> Result<T, RetryError<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut retry = 0;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) if !is_retriable(&e) => return Err(RetryError::NonRetriable(e)),
+            Err(e) if retry >= config.max_retries => {
+                return Err(RetryError::Exhausted { attempts: retry + 1, last_error: e })
+            }
+            Err(_) => {
+                tokio::time::sleep(config.jittered_delay(retry)).await;
+                retry += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_config(max_retries: u32) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            base_delay_ms: 1,
+            max_delay_ms: 5,
+            backoff_multiplier: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_backoff_delay_is_capped() {
+        let config = RetryConfig::default();
+        assert_eq!(config.backoff_delay(0), Duration::from_millis(1000));
+        assert_eq!(config.backoff_delay(2), Duration::from_millis(4000));
+        assert_eq!(config.backoff_delay(10), Duration::from_millis(30000));
+
+        for retry in 0..5 {
+            let delay = config.jittered_delay(retry);
+            assert!(delay <= config.backoff_delay(retry));
+            assert!(delay >= config.backoff_delay(retry) / 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let calls = AtomicU32::new(0);
+        let result: Result<u32, RetryError<&str>> = retry_with_backoff(&fast_config(3), |_| true, || async {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            if n < 2 { Err("503") } else { Ok(n) }
+        })
+        .await;
+
+        assert_eq!(result, Ok(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = retry_with_backoff(&fast_config(2), |_| true, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err("timeout")
+        })
+        .await;
+
+        assert_eq!(result, Err(RetryError::Exhausted { attempts: 3, last_error: "timeout" }));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_non_retriable_fails_immediately() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = retry_with_backoff(&fast_config(5), |e: &&str| *e != "404", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err("404")
+        })
+        .await;
+
+        assert_eq!(result, Err(RetryError::NonRetriable("404")));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
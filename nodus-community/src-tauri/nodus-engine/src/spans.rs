@@ -0,0 +1,132 @@
+// src/spans.rs
+// In-process record of recent operation timings.
+//
+// The community build has no observability stack, so `instrument` times an
+// operation and appends `{ name, duration_ms, timestamp, success }` to a
+// process-wide ring buffer. Once the buffer is full the oldest span is
+// dropped, so memory stays bounded however long the app runs. The same
+// wrapper times native and wasm code; only the clock differs.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+
+/// Spans kept by the process-wide log
+pub const DEFAULT_SPAN_CAPACITY: usize = 1024;
+
+static SPAN_LOG: Lazy<SpanLog> = Lazy::new(|| SpanLog::new(DEFAULT_SPAN_CAPACITY));
+
+/// One timed operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpanRecord {
+    pub name: String,
+    pub duration_ms: f64,
+    /// When the operation finished
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub success: bool,
+}
+
+/// Bounded ring buffer of spans
+#[derive(Debug)]
+pub struct SpanLog {
+    capacity: usize,
+    spans: Mutex<VecDeque<SpanRecord>>,
+}
+
+impl SpanLog {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            spans: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn record(&self, span: SpanRecord) {
+        let mut spans = self.spans.lock().unwrap_or_else(|e| e.into_inner());
+        if spans.len() == self.capacity {
+            spans.pop_front();
+        }
+        spans.push_back(span);
+    }
+
+    /// Up to `limit` spans, newest first
+    pub fn recent(&self, limit: usize) -> Vec<SpanRecord> {
+        let spans = self.spans.lock().unwrap_or_else(|e| e.into_inner());
+        spans.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+/// The process-wide span log `instrument` writes to
+pub fn span_log() -> &'static SpanLog {
+    &SPAN_LOG
+}
+
+/// Run `operation`, recording its duration and whether it returned `Ok`
+pub async fn instrument<F, T, E>(name: &str, operation: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    let start = now_ms();
+    let result = operation.await;
+    span_log().record(SpanRecord {
+        name: name.to_string(),
+        duration_ms: now_ms() - start,
+        timestamp: chrono::Utc::now(),
+        success: result.is_ok(),
+    });
+    result
+}
+
+/// Milliseconds since an arbitrary origin, for measuring durations
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    static ORIGIN: Lazy<std::time::Instant> = Lazy::new(std::time::Instant::now);
+    ORIGIN.elapsed().as_secs_f64() * 1000.0
+}
+
+/// `Instant` panics in the browser, so wasm builds use the JS clock
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(name: &str) -> SpanRecord {
+        SpanRecord {
+            name: name.to_string(),
+            duration_ms: 1.0,
+            timestamp: chrono::Utc::now(),
+            success: true,
+        }
+    }
+
+    #[test]
+    fn test_span_log_drops_oldest_when_full() {
+        let log = SpanLog::new(3);
+        for name in ["a", "b", "c", "d"] {
+            log.record(span(name));
+        }
+
+        let names: Vec<_> = log.recent(10).into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["d", "c", "b"]);
+        assert_eq!(log.recent(1)[0].name, "d");
+    }
+
+    #[tokio::test]
+    async fn test_instrument_records_outcome() {
+        let ok: Result<u32, String> = instrument("spans_test.ok", async { Ok(1) }).await;
+        let err: Result<u32, String> = instrument("spans_test.err", async { Err("boom".to_string()) }).await;
+        assert!(ok.is_ok() && err.is_err());
+
+        let recent = span_log().recent(DEFAULT_SPAN_CAPACITY);
+        let find = |name: &str| recent.iter().find(|s| s.name == name).unwrap();
+        assert!(find("spans_test.ok").success);
+        assert!(!find("spans_test.err").success);
+    }
+}
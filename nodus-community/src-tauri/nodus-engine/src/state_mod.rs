@@ -5,11 +5,11 @@
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use uuid::Uuid;
 
 // Import from your license module (instead of duplicating types)
-use crate::license_mod::{LicenseManager, LicenseTier, PluginAccessMode, LicenseError};
+use crate::license_mod::{LicenseManager, LicenseTier, LicenseFeatures, PluginAccessMode, LicenseError};
 
 // Import your universal plugin system
 use crate::universal_plugin_system::{UniversalPluginSystem, PluginInfo, PluginError};
@@ -26,6 +26,9 @@ pub struct AppState {
     pub config: AppConfig,
     pub sessions: Arc<RwLock<HashMap<Uuid, SessionInfo>>>,
     
+    // Recent actions per session (newest last), for the activity feed
+    pub session_activity: Arc<RwLock<HashMap<Uuid, VecDeque<SessionActivity>>>>,
+    
     // UNIVERSAL PLUGIN SYSTEM INTEGRATION (not simple Vec<String>)
     pub plugin_system: Arc<UniversalPluginSystem>,
     
@@ -34,10 +37,52 @@ pub struct AppState {
     pub action_dispatcher: Arc<crate::action_dispatcher::ActionDispatcher>,
     pub async_orchestrator: Arc<crate::async_orchestrator::AsyncOrchestrator>,
     
+    // Shared schema registry; plugins register their own entity schemas here
+    pub validation: Arc<crate::storage::ValidationManager>,
+    
     // Tracking for active async operations
-    pub active_async_operations: Arc<RwLock<HashMap<String, crate::async_orchestrator::OperationRunner>>>,
+    pub active_async_operations: Arc<RwLock<HashMap<String, Arc<crate::async_orchestrator::OperationRunner>>>>,
     pub active_async_operation_starts: Arc<RwLock<HashMap<String, chrono::DateTime<chrono::Utc>>>>,
     pub completed_operations_count: Arc<RwLock<u64>>,
+    // Completions bucketed by hour and day, for recent activity counts
+    pub operation_activity: Arc<RwLock<crate::commands_async::OperationActivity>>,
+    pub async_operation_dependencies: Arc<RwLock<crate::commands_async::OperationDependencies>>,
+    
+    // Debounced persistence for interactive grid edits
+    pub grid_autosave: Arc<crate::commands_grid::GridAutoSave>,
+    
+    // Aggregated `operation_completed` reports for dashboards
+    pub operation_metrics: Arc<RwLock<crate::commands_grid::OperationMetrics>>,
+    
+    // Provisional layouts during an interactive edit, keyed by config_id;
+    // never persisted until committed
+    pub staged_layouts: Arc<RwLock<HashMap<String, crate::commands_grid::GridConfig>>>,
+    
+    // Optional remote sync; None for local-only setups
+    pub sync_manager: Option<Arc<crate::storage::SyncManager>>,
+    
+    // Process start, used for uptime reporting
+    pub started_at: std::time::Instant,
+    
+    // Id source for sessions and plugins; shared with the orchestrator and
+    // plugin system so tests can make every id deterministic
+    pub ids: crate::id_gen::SharedIdGenerator,
+    
+    // Background purge of expired audit events, started by `initialize`
+    pub audit_retention_task: Option<Arc<tokio::task::JoinHandle<()>>>,
+    
+    // Background pruning of old entity versions, started by `initialize`
+    pub version_pruning_task: Option<Arc<tokio::task::JoinHandle<()>>>,
+    
+    // Periodic save of license usage counters, started by `initialize`
+    pub usage_persistence_task: Option<Arc<tokio::task::JoinHandle<()>>>,
+    
+    // Background purge of expired operation results, started by `initialize`
+    pub operation_result_retention_task: Option<Arc<tokio::task::JoinHandle<()>>>,
+    
+    // OS idle state reported by the frontend; shared with the orchestrator
+    // and sync manager so background work backs off while idle
+    pub idle: crate::idle::SharedIdleState,
 }
 
 /// Shared AppState handle used across engine modules
@@ -50,6 +95,16 @@ pub struct AppConfig {
     pub version: String,
     pub license_tier: String,
     pub plugin_access_mode: String,
+    #[serde(default)]
+    pub audit_retention: crate::storage::AuditRetentionPolicy,
+    #[serde(default)]
+    pub version_retention: crate::storage::VersionRetentionPolicy,
+    #[serde(default)]
+    pub http_access: crate::http_access::HttpAccessPolicy,
+    #[serde(default)]
+    pub payload_limits: crate::payload_limits::PayloadLimits,
+    #[serde(default)]
+    pub operation_results: crate::commands_async::OperationResultRetention,
 }
 
 /// Basic session information
@@ -61,11 +116,39 @@ pub struct SessionInfo {
     pub last_access: chrono::DateTime<chrono::Utc>,
 }
 
+/// Most recent actions kept per session
+pub const MAX_SESSION_ACTIVITY: usize = 100;
+
+/// Longest `AppState::shutdown` waits for pending grid saves to be written
+pub const SHUTDOWN_FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// One action a session ran
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionActivity {
+    pub action_type: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub execution_time_ms: u64,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
 impl AppState {
     /// Create new app state with proper license integration
     pub async fn new() -> Result<Self, AppStateError> {
-        // Initialize your license manager first
-        let license_manager = Arc::new(LicenseManager::new().await?);
+        Self::with_id_generator(crate::id_gen::default_generator()).await
+    }
+
+    /// Create application state whose session, plugin and operation ids all
+    /// come from `ids`
+    pub async fn with_id_generator(ids: crate::id_gen::SharedIdGenerator) -> Result<Self, AppStateError> {
+        // Storage comes first so the license manager can persist usage counters
+        let validation = Arc::new(crate::storage::ValidationManager::new());
+        let storage = Arc::new(crate::storage::StorageManager::new().with_validation(validation.clone()));
+
+        // Initialize your license manager
+        let mut license_manager = LicenseManager::new().await?;
+        license_manager.attach_usage_storage(storage.clone()).await;
+        let license_manager = Arc::new(license_manager);
         
         // Get tier and plugin access mode from license manager
         let license_tier = license_manager.get_tier().await;
@@ -76,12 +159,22 @@ impl AppState {
             version: env!("CARGO_PKG_VERSION").to_string(),
             license_tier: license_tier.display_name().to_string(),
             plugin_access_mode: format!("{:?}", plugin_access_mode),
+            audit_retention: crate::storage::AuditRetentionPolicy::default(),
+            version_retention: crate::storage::VersionRetentionPolicy::default(),
+            http_access: crate::http_access::HttpAccessPolicy::from_env(),
+            payload_limits: crate::payload_limits::PayloadLimits::default(),
+            operation_results: crate::commands_async::OperationResultRetention::default(),
         };
 
         // Initialize core components
-        let storage = Arc::new(crate::storage::StorageManager::new());
         let action_dispatcher = Arc::new(crate::action_dispatcher::ActionDispatcher::new().await?);
-        let async_orchestrator = Arc::new(crate::async_orchestrator::AsyncOrchestrator::new().await?);
+        let idle = Arc::new(crate::idle::IdleState::new());
+        let async_orchestrator = Arc::new(
+            crate::async_orchestrator::AsyncOrchestrator::new()
+                .await?
+                .with_id_generator(ids.clone())
+                .with_idle_state(idle.clone())
+        );
 
         // Register default core handlers and middleware so frontend actions
         // like `grid.*`, `system.*`, and `ui.*` are handled out-of-the-box in
@@ -150,7 +243,7 @@ impl AppState {
 
         // Initialize universal plugin system with license constraints
         let plugin_system = Arc::new(
-            UniversalPluginSystem::new(license_tier, plugin_access_mode).await
+            UniversalPluginSystem::new(license_tier, plugin_access_mode).await.with_id_generator(ids.clone())
         );
 
         Ok(Self {
@@ -158,13 +251,28 @@ impl AppState {
             initialized: false,
             config,
             sessions: Arc::new(RwLock::new(HashMap::new())),
+            session_activity: Arc::new(RwLock::new(HashMap::new())),
             plugin_system,
-            storage,
+            storage: storage.clone(),
             action_dispatcher,
             async_orchestrator,
+            validation,
             active_async_operations: Arc::new(RwLock::new(HashMap::new())),
             active_async_operation_starts: Arc::new(RwLock::new(HashMap::new())),
             completed_operations_count: Arc::new(RwLock::new(0)),
+            operation_activity: Arc::new(RwLock::new(Default::default())),
+            async_operation_dependencies: Arc::new(RwLock::new(Default::default())),
+            grid_autosave: Arc::new(crate::commands_grid::GridAutoSave::new(storage.clone())),
+            operation_metrics: Arc::new(RwLock::new(crate::commands_grid::OperationMetrics::default())),
+            staged_layouts: Arc::new(RwLock::new(HashMap::new())),
+            sync_manager: None,
+            started_at: std::time::Instant::now(),
+            ids,
+            audit_retention_task: None,
+            version_pruning_task: None,
+            usage_persistence_task: None,
+            operation_result_retention_task: None,
+            idle,
         })
     }
 
@@ -212,6 +320,29 @@ impl AppState {
             }
         }
 
+        if self.audit_retention_task.is_none() {
+            self.audit_retention_task = self.storage.clone()
+                .spawn_audit_retention(self.config.audit_retention.clone())
+                .map(Arc::new);
+        }
+        if self.version_pruning_task.is_none() {
+            self.version_pruning_task = self.storage.clone()
+                .spawn_version_pruning(self.config.version_retention.clone())
+                .map(Arc::new);
+        }
+        if self.usage_persistence_task.is_none() {
+            self.usage_persistence_task = self.license_manager
+                .spawn_usage_persistence(crate::license_mod::USAGE_PERSIST_INTERVAL)
+                .map(Arc::new);
+        }
+        if self.operation_result_retention_task.is_none() {
+            self.operation_result_retention_task = crate::commands_async::spawn_operation_result_retention(
+                self.storage.clone(),
+                self.config.operation_results.clone(),
+            )
+            .map(Arc::new);
+        }
+
         self.initialized = true;
         Ok(())
     }
@@ -261,7 +392,7 @@ impl AppState {
                 tracing::info!("Loading unsigned plugin: {}", plugin_path);
                 // Use plugin system to load
                 // Implementation would call plugin_system.load_js_plugin_from_file()
-                Ok(format!("plugin_{}", Uuid::new_v4()))
+                Ok(format!("plugin_{}", self.ids.next_id()))
             }
             PluginAccessMode::SignedOnly => {
                 // Validate enterprise access first
@@ -270,7 +401,7 @@ impl AppState {
                 tracing::info!("Checking plugin signature: {}", plugin_path);
                 // Implementation would verify signature then load
                 if self.verify_plugin_signature(plugin_path).await? {
-                    Ok(format!("signed_plugin_{}", Uuid::new_v4()))
+                    Ok(format!("signed_plugin_{}", self.ids.next_id()))
                 } else {
                     Err(AppStateError::UnsignedPluginRejected {
                         plugin_path: plugin_path.to_string(),
@@ -280,11 +411,53 @@ impl AppState {
         }
     }
 
-    /// Verify plugin signature (enterprise feature)
-    async fn verify_plugin_signature(&self, plugin_path: &str) -> Result<bool, AppStateError> {
-        // Implementation would use your license system's crypto validation
-        // For now, just check if path contains "signed"
-        Ok(plugin_path.contains("signed"))
+    /// Verify a plugin file against its detached signature (enterprise feature)
+    pub(crate) async fn verify_plugin_signature(&self, plugin_path: &str) -> Result<bool, AppStateError> {
+        match std::fs::read(plugin_path) {
+            Ok(bundle) => Ok(self.verify_plugin_bundle(plugin_path, &bundle)),
+            Err(e) => {
+                tracing::warn!("Cannot read plugin {} to check its signature: {}", plugin_path, e);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Verify plugin bundle bytes against the signature stored next to the
+    /// plugin in `<path>.sig`, signed with a license verification key (see
+    /// `LicenseManager::verify_bundle_signature`). A missing signature fails.
+    pub(crate) fn verify_plugin_bundle(&self, plugin_path: &str, bundle: &[u8]) -> bool {
+        let signature_path = format!("{}.{}", plugin_path, PLUGIN_SIGNATURE_EXTENSION);
+        let Ok(signature) = std::fs::read_to_string(&signature_path) else {
+            tracing::warn!("No signature file for plugin {}", plugin_path);
+            return false;
+        };
+        match self.license_manager.verify_bundle_signature(bundle, &signature) {
+            Ok(key_version) => {
+                tracing::info!("Plugin {} signature verified with key {}", plugin_path, key_version);
+                true
+            }
+            Err(e) => {
+                tracing::warn!("Plugin {} signature rejected: {}", plugin_path, e);
+                false
+            }
+        }
+    }
+
+    /// Reload the license at runtime and propagate the new tier to dependent components.
+    /// Replaces the shared license manager so later readers see the new license.
+    pub async fn reload_license(&mut self) -> Result<crate::license_mod::LicenseChangeEvent, AppStateError> {
+        let mut license_manager = (*self.license_manager).clone();
+        let event = license_manager.reload().await?;
+        self.license_manager = Arc::new(license_manager);
+
+        // Re-evaluate plugin access constraints for the new tier
+        self.plugin_system
+            .update_license_constraints(event.current_tier.clone(), event.plugin_access_mode.clone())
+            .await;
+        self.config.license_tier = event.current_tier.display_name().to_string();
+        self.config.plugin_access_mode = format!("{:?}", event.plugin_access_mode);
+
+        Ok(event)
     }
 
     /// Get plugin info (delegates to universal plugin system)
@@ -294,7 +467,7 @@ impl AppState {
 
     /// Create a new session
     pub async fn create_session(&self, user_id: &str) -> Result<Uuid, AppStateError> {
-        let session_id = Uuid::new_v4();
+        let session_id = self.ids.next_id();
         let session = SessionInfo {
             session_id,
             user_id: user_id.to_string(),
@@ -308,6 +481,41 @@ impl AppState {
         Ok(session_id)
     }
 
+    /// Record an action against a session's activity feed and touch its
+    /// last access time. A no-op without the `activity_feeds` feature.
+    pub async fn record_session_activity(&self, session_id: Uuid, activity: SessionActivity) {
+        if let Some(session) = self.sessions.write().await.get_mut(&session_id) {
+            session.last_access = activity.timestamp;
+        }
+        if !self.license_manager.has_feature("activity_feeds").await {
+            return;
+        }
+
+        let mut feeds = self.session_activity.write().await;
+        let feed = feeds.entry(session_id).or_default();
+        if feed.len() >= MAX_SESSION_ACTIVITY {
+            feed.pop_front();
+        }
+        feed.push_back(activity);
+    }
+
+    /// Recent actions for a session, oldest first
+    pub async fn get_session_activity(&self, session_id: Uuid) -> Result<Vec<SessionActivity>, AppStateError> {
+        if !self.license_manager.has_feature("activity_feeds").await {
+            return Err(AppStateError::FeatureNotAvailable { feature: "activity_feeds".to_string() });
+        }
+        if !self.sessions.read().await.contains_key(&session_id) {
+            return Err(AppStateError::SessionNotFound { session_id });
+        }
+        Ok(self
+            .session_activity
+            .read()
+            .await
+            .get(&session_id)
+            .map(|feed| feed.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
     /// Get app stats (enhanced with license info)
     pub async fn get_app_stats(&self) -> AppStats {
         let license_tier = self.get_license_tier().await;
@@ -330,6 +538,188 @@ impl AppState {
     }
 }
 
+impl AppState {
+    /// Turn on remote sync. Grid configs get the block-level layout merge
+    /// (`commands_grid::GridLayoutMerge`) as their conflict resolver.
+    pub async fn attach_sync_manager(&mut self, sync_manager: Arc<crate::storage::SyncManager>) {
+        sync_manager
+            .register_conflict_resolver(
+                crate::commands_grid::GRID_CONFIG_TYPE,
+                Arc::new(crate::commands_grid::GridLayoutMerge),
+            )
+            .await;
+        self.sync_manager = Some(sync_manager);
+    }
+
+    /// Flush anything still buffered in memory; call (and await) before the
+    /// app exits. Pending debounced grid saves are written before this
+    /// returns, giving up after `SHUTDOWN_FLUSH_TIMEOUT` so a stuck backend
+    /// can't hang the quit.
+    pub async fn shutdown(&self) -> Result<(), AppStateError> {
+        for task in [
+            &self.audit_retention_task,
+            &self.version_pruning_task,
+            &self.usage_persistence_task,
+            &self.operation_result_retention_task,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            task.abort();
+        }
+        if let Err(e) = self.license_manager.flush_usage().await {
+            tracing::warn!("Failed to persist usage counters on shutdown: {}", e);
+        }
+        let flushed = tokio::time::timeout(SHUTDOWN_FLUSH_TIMEOUT, self.grid_autosave.flush_all())
+            .await
+            .map_err(|_| AppStateError::ShutdownFailed {
+                reason: format!("timed out after {:?} flushing pending grid configs", SHUTDOWN_FLUSH_TIMEOUT),
+            })?
+            .map_err(|reason| AppStateError::ShutdownFailed { reason: reason.to_string() })?;
+        tracing::info!("Flushed {} pending grid config(s) on shutdown", flushed);
+        self.storage.flush().await.map_err(|e| AppStateError::ShutdownFailed {
+            reason: format!("failed to flush buffered storage writes: {}", e),
+        })?;
+        Ok(())
+    }
+
+    /// Diagnostics bundle of the whole engine state, for bug reports. Not a
+    /// persistence format; license signatures and keys are redacted.
+    pub async fn snapshot(&self) -> StateSnapshot {
+        let recent_actions = self.session_activity.read().await;
+        let mut sessions: Vec<SessionSummary> = self
+            .sessions
+            .read()
+            .await
+            .values()
+            .map(|s| SessionSummary {
+                session_id: s.session_id,
+                user_id: s.user_id.clone(),
+                created_at: s.created_at,
+                last_access: s.last_access,
+                recent_actions: recent_actions.get(&s.session_id).map_or(0, |feed| feed.len()),
+            })
+            .collect();
+        drop(recent_actions);
+        sessions.sort_by_key(|s| s.created_at);
+
+        let license = self.license_manager.get_license_info().await.cloned().map(|mut license| {
+            license.signature = REDACTED.to_string();
+            license.verification_key = REDACTED.to_string();
+            license
+        });
+
+        StateSnapshot {
+            captured_at: chrono::Utc::now(),
+            status: self.get_system_status().await,
+            config: self.config.clone(),
+            license_tier: self.get_license_tier().await.display_name().to_string(),
+            license,
+            sessions,
+            plugins: self.get_plugin_info().await,
+            storage_stats: self.storage.get_stats().await.ok(),
+            active_operations: self.async_operation_dependencies.read().await.active_operations(),
+            idle: self.idle.is_idle(),
+        }
+    }
+
+    /// Typed status snapshot for the frontend status bar
+    pub async fn get_system_status(&self) -> SystemStatus {
+        let sync = match &self.sync_manager {
+            Some(sync_manager) => {
+                let stats = sync_manager.get_stats().await;
+                SyncStatusSummary {
+                    enabled: sync_manager.is_sync_enabled(),
+                    connected: sync_manager.is_connected().await,
+                    pending_changes: stats.pending_entities,
+                    last_sync: stats.last_sync,
+                }
+            }
+            None => SyncStatusSummary::default(),
+        };
+
+        SystemStatus {
+            app_version: self.config.version.clone(),
+            license_tier: self.get_license_tier().await.display_name().to_string(),
+            plugin_count: self.get_plugin_info().await.len() as u32,
+            active_sessions: self.sessions.read().await.len() as u32,
+            storage_backend: self.storage.primary_backend(),
+            storage_health: self.storage.health_check().await.unwrap_or_default(),
+            active_async_operations: self.active_async_operations.read().await.len() as u32,
+            uptime_seconds: self.started_at.elapsed().as_secs(),
+            sync,
+        }
+    }
+}
+
+/// Whether an action would be allowed right now, and why not
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionPermission {
+    pub allowed: bool,
+    pub reason: Option<String>,
+    /// Lowest license tier that unlocks the action, when the license is the blocker
+    pub required_tier: Option<String>,
+}
+
+impl ActionPermission {
+    fn allowed() -> Self {
+        Self { allowed: true, reason: None, required_tier: None }
+    }
+
+    fn denied(reason: String, required_tier: Option<LicenseTier>) -> Self {
+        Self {
+            allowed: false,
+            reason: Some(reason),
+            required_tier: required_tier.map(|t| t.display_name().to_string()),
+        }
+    }
+}
+
+impl AppState {
+    /// License gate for an action's namespace; shared by `execute_action`
+    /// and `check_action_permission`
+    async fn check_action_feature(&self, action_type: &str) -> Result<(), AppStateError> {
+        match LicenseFeatures::feature_for_action(action_type) {
+            Some(feature) if !self.license_manager.has_feature(feature).await => {
+                Err(AppStateError::FeatureNotAvailable { feature: feature.to_string() })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Evaluate the checks `execute_action` enforces (license feature, usage
+    /// limits, a plugin or handler to run it) without running the action
+    pub async fn check_action_permission(&self, action_type: &str) -> ActionPermission {
+        if let Err(AppStateError::FeatureNotAvailable { feature }) = self.check_action_feature(action_type).await {
+            let tier = LicenseFeatures::minimum_tier_for_feature(&feature);
+            return ActionPermission::denied(
+                format!("Requires the '{}' feature ({} license)", feature, tier.display_name()),
+                Some(tier),
+            );
+        }
+
+        for limit in ACTION_USAGE_LIMITS {
+            if !self.license_manager.has_capacity(limit, 1).await {
+                return ActionPermission::denied(format!("License limit reached: {}", limit), None);
+            }
+        }
+
+        if self.plugin_system.find_handler(action_type).await.is_none()
+            && !self.action_dispatcher.has_handler(action_type).await
+        {
+            return ActionPermission::denied(format!("No plugin or handler for action '{}'", action_type), None);
+        }
+
+        ActionPermission::allowed()
+    }
+}
+
+/// Windowed license limits every dispatched action counts against
+/// Extension of the detached signature file next to a plugin, `<path>.sig`
+pub const PLUGIN_SIGNATURE_EXTENSION: &str = "sig";
+
+const ACTION_USAGE_LIMITS: [&str; 2] = ["operations_per_hour", "api_calls_per_day"];
+
 /// Dispatch an action using the shared `AppStateType` handle.
 /// This is the canonical entrypoint when callers already have the
 /// Arc<RwLock<AppState>> handle (e.g. Tauri wrappers, tests).
@@ -338,12 +728,79 @@ pub async fn execute_action(
     action_type: String,
     payload: serde_json::Value,
 ) -> Result<ActionResult, AppStateError> {
-    // Create action and context
-    let action = crate::action_dispatcher::Action::new(&action_type, payload.clone()).with_metadata(None, None, None);
+    let action = crate::action_dispatcher::Action::new(&action_type, payload).with_metadata(None, None, None);
     let context = crate::action_dispatcher::ActionContext::new("", "");
+    let span = format!("action.{}", action_type);
+    crate::spans::instrument(&span, dispatch_with_context(state, action, context)).await
+}
 
+/// Dispatch an action on behalf of a session, recording it in the
+/// session's activity feed
+pub async fn execute_session_action(
+    state: AppStateType,
+    session_id: Uuid,
+    action_type: String,
+    payload: serde_json::Value,
+) -> Result<ActionResult, AppStateError> {
+    let user_id = state
+        .read()
+        .await
+        .sessions
+        .read()
+        .await
+        .get(&session_id)
+        .map(|s| s.user_id.clone())
+        .ok_or(AppStateError::SessionNotFound { session_id })?;
+
+    let action = crate::action_dispatcher::Action::new(&action_type, payload).with_metadata(
+        Some(user_id.clone()),
+        Some(session_id.to_string()),
+        None,
+    );
+    let context = crate::action_dispatcher::ActionContext::new(&user_id, &session_id.to_string());
+    let span = format!("action.{}", action_type);
+    let result = crate::spans::instrument(&span, dispatch_with_context(state.clone(), action, context)).await;
+
+    let activity = match &result {
+        Ok(r) => SessionActivity {
+            action_type,
+            success: r.success,
+            error: r.error.clone(),
+            execution_time_ms: r.execution_time_ms,
+            timestamp: chrono::Utc::now(),
+        },
+        Err(e) => SessionActivity {
+            action_type,
+            success: false,
+            error: Some(e.to_string()),
+            execution_time_ms: 0,
+            timestamp: chrono::Utc::now(),
+        },
+    };
+    state.read().await.record_session_activity(session_id, activity).await;
+    result
+}
+
+/// License checks, then the plugin system, then the dispatcher
+async fn dispatch_with_context(
+    state: AppStateType,
+    action: crate::action_dispatcher::Action,
+    context: crate::action_dispatcher::ActionContext,
+) -> Result<ActionResult, AppStateError> {
     // Try plugin system first using a read lock
     let guard = state.read().await;
+
+    // Check the windowed license limits first, and only count the
+    // operation once it has been accepted
+    for limit in ACTION_USAGE_LIMITS {
+        if !guard.license_manager.has_capacity(limit, 1).await {
+            return Err(AppStateError::License(LicenseError::LimitExceeded(limit.to_string())));
+        }
+    }
+    guard.check_action_feature(&action.action_type).await?;
+    // The check above is only a hint; this one holds under concurrency
+    let usages: Vec<(&str, u32)> = ACTION_USAGE_LIMITS.iter().map(|limit| (*limit, 1)).collect();
+    guard.license_manager.try_record_usages(&usages).await?;
     match guard.plugin_system.try_execute_action(&action, &context, &guard).await {
         Ok(Some(result)) => Ok(result),
         Ok(None) => {
@@ -374,6 +831,61 @@ pub struct AppStats {
     pub license_status: String,
 }
 
+/// Placeholder for secrets removed from diagnostics output
+pub const REDACTED: &str = "[redacted]";
+
+/// Engine state captured by `AppState::snapshot` for bug reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub captured_at: chrono::DateTime<chrono::Utc>,
+    pub status: SystemStatus,
+    pub config: AppConfig,
+    pub license_tier: String,
+    /// Active license with signature and verification key redacted
+    pub license: Option<crate::license_mod::LicenseInfo>,
+    pub sessions: Vec<SessionSummary>,
+    pub plugins: Vec<PluginInfo>,
+    /// None when the primary backend can't report stats
+    pub storage_stats: Option<crate::storage::StorageStats>,
+    pub active_operations: Vec<crate::commands_async::ActiveOperationInfo>,
+    pub idle: bool,
+}
+
+/// Per-session portion of `StateSnapshot`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub session_id: Uuid,
+    pub user_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_access: chrono::DateTime<chrono::Utc>,
+    /// Entries in the session's activity feed
+    pub recent_actions: usize,
+}
+
+/// Status bar contract returned by `get_system_status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemStatus {
+    pub app_version: String,
+    pub license_tier: String,
+    pub plugin_count: u32,
+    pub active_sessions: u32,
+    pub storage_backend: String,
+    /// Health per registered storage backend
+    pub storage_health: HashMap<String, bool>,
+    pub active_async_operations: u32,
+    pub uptime_seconds: u64,
+    pub sync: SyncStatusSummary,
+}
+
+/// Sync portion of `SystemStatus`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncStatusSummary {
+    pub enabled: bool,
+    pub connected: bool,
+    pub pending_changes: u64,
+    pub last_sync: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 /// Application state errors (enhanced with license errors)
 #[derive(Debug, thiserror::Error)]
 pub enum AppStateError {
@@ -397,6 +909,9 @@ pub enum AppStateError {
 
     #[error("Initialization failed: {reason}")]
     InitializationFailed { reason: String },
+
+    #[error("Shutdown failed: {reason}")]
+    ShutdownFailed { reason: String },
 }
 
 // Convert lower-level errors into AppStateError
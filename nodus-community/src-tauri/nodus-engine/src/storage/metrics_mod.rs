@@ -0,0 +1,104 @@
+// src/storage/metrics_mod.rs
+// Low-contention counters for storage hot paths.
+//
+// Every storage operation bumps a few counters. With a single atomic per
+// counter, concurrent operations on different cores keep stealing the same
+// cache line. A `ShardedCounter` spreads increments over cache-line-padded
+// shards, one per thread slot, and sums them only when read.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Upper bound on shards per counter
+const MAX_SHARDS: usize = 64;
+
+/// Round-robin source for per-thread shard slots
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static SHARD_SLOT: usize = NEXT_SHARD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// An atomic alone on its cache line
+#[derive(Debug, Default)]
+#[repr(align(64))]
+struct PaddedCounter(AtomicU64);
+
+/// Counter whose increments from different threads land on different cache lines
+#[derive(Debug)]
+pub struct ShardedCounter {
+    shards: Box<[PaddedCounter]>,
+}
+
+impl ShardedCounter {
+    /// One shard per available core (capped), so each worker thread
+    /// usually has a shard to itself
+    pub fn new() -> Self {
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_shards(cores.clamp(1, MAX_SHARDS))
+    }
+
+    pub fn with_shards(shards: usize) -> Self {
+        Self {
+            shards: (0..shards.max(1)).map(|_| PaddedCounter::default()).collect(),
+        }
+    }
+
+    pub fn add(&self, n: u64) {
+        let slot = SHARD_SLOT.with(|slot| *slot) % self.shards.len();
+        self.shards[slot].0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    /// Sum over all shards. Exact once concurrent writers have finished;
+    /// while they run it may miss increments still in flight.
+    pub fn get(&self) -> u64 {
+        self.shards.iter().map(|shard| shard.0.load(Ordering::Relaxed)).sum()
+    }
+}
+
+impl Default for ShardedCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Aggregated storage counters, as returned by `StorageManager::metrics_snapshot`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct StorageMetricsSnapshot {
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub operations_total: u64,
+    pub errors_total: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_sharded_counter_sums_across_threads() {
+        let counter = Arc::new(ShardedCounter::with_shards(4));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let counter = counter.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10_000 {
+                        counter.increment();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        counter.add(5);
+
+        assert_eq!(counter.get(), 80_005);
+        assert_eq!(std::mem::align_of::<PaddedCounter>(), 64);
+    }
+}
@@ -2,10 +2,12 @@
 // Storage module for Nodus Community Version
 // Simplified storage without enterprise dependencies
 
+pub mod metrics_mod;
 pub mod sqlite_adapter;
 pub mod storage_mod;
 pub mod sync_mod;
 pub mod validation_mod; // Register sqlite_adapter module
+pub mod write_buffer_mod;
 
 // IndexedDB adapter only available on wasm32
 #[cfg(target_arch = "wasm32")]
@@ -13,9 +15,35 @@ pub mod indexeddb_adapter;
 
 // Re-export main types and traits
 pub use storage_mod::{
+    AdapterCapabilities,
+    AuditRetentionPolicy,
+    AUDIT_EVENT_TYPE,
+    BackendHealth,
+    CascadeMode,
+    EntityTypeUsage,
+    DEFAULT_BATCH_WRITE_CONCURRENCY,
+    DEFAULT_EXPORT_CHUNK_SIZE,
+    DEFAULT_SCAN_BATCH_SIZE,
+    encode_backup,
+    ENTITY_FILTER_FIELDS,
+    ExportReport,
+    FilterCondition,
+    ImportMode,
+    ImportRecordError,
+    ImportReport,
+    KeyStrategy,
+    MAX_KEY_ATTEMPTS,
+    MigrationReport,
+    PatchOp,
+    put_concurrently,
+    ReadConsistency,
+    RELATIONSHIP_ENTITY_TYPE,
+    ScanPage,
+    SerializationFormat,
     SortCriteria,
     SortDirection,
     StorageAdapter,
+    StorageCapability,
     // expose sqlite adapter type
     // SqliteAdapter is provided as a separate module for clarity
     StorageConfig,
@@ -23,9 +51,21 @@ pub use storage_mod::{
     StorageError,
     StorageManager,
     StorageQuery,
+    StorageQueryBuilder,
     StorageStats,
     StoredEntity,
     SyncStatus,
+    TIMESTAMP_FILTER_FIELDS,
+    VersionRetentionPolicy,
+    rfc3339,
+};
+
+pub use metrics_mod::{ShardedCounter, StorageMetricsSnapshot};
+
+pub use write_buffer_mod::{
+    WriteBufferConfig,
+    DEFAULT_WRITE_BUFFER_FLUSH_INTERVAL_MS,
+    DEFAULT_WRITE_BUFFER_MAX_ENTRIES,
 };
 
 // Re-export sqlite adapter type so callers can construct/register it easily
@@ -33,14 +73,30 @@ pub use sqlite_adapter::SqliteAdapter;
 
 // Re-export sync types if needed
 pub use sync_mod::{
+    ChangeRecord,
+    ChangeSource,
+    ConflictRecord,
+    ConflictResolver,
+    ConflictStrategy,
+    CustomConflictResolver,
+    NullSyncClient,
+    RemoteSyncClient,
+    RetryConfig,
+    SyncClient,
+    SyncConfig,
     SyncError,
+    SyncPreview,
     // Add other sync exports as needed
     SyncManager,
 };
 
 // Re-export validation types if needed
 pub use validation_mod::{
+    ConditionOperator,
     ValidationError,
+    ValidationManager,
+    ValidationReport,
     // Add other validation exports as needed
     ValidationResult,
+    ValidationSchema,
 };
@@ -1,7 +1,9 @@
-use crate::storage::{StorageAdapter, StorageError, StoredEntity, StorageContext, StorageQuery, StorageStats};
+use crate::storage::{rfc3339, AdapterCapabilities, ConditionOperator, FilterCondition, ScanPage, SerializationFormat, SortDirection, ENTITY_FILTER_FIELDS, TIMESTAMP_FILTER_FIELDS, StorageAdapter, StorageCapability, StorageError, StoredEntity, StorageContext, StorageQuery, StorageStats};
+use chrono::{DateTime, Utc};
 use sqlx::{SqlitePool, Row};
+use sqlx::sqlite::SqliteRow;
 use async_trait::async_trait;
-use serde_json;
+use serde_json::{self, Value};
 use std::collections::HashMap;
 
 /// SQLite-backed adapter using `sqlx`. This adapter will initialize the
@@ -11,12 +13,300 @@ use std::collections::HashMap;
 pub struct SqliteAdapter {
     pub pool: Option<SqlitePool>,
     pub db_path: String,
+    /// Encoding for new `kv_store` values; existing rows are read in either format
+    pub format: SerializationFormat,
 }
 
 impl SqliteAdapter {
     pub fn new(db_path: impl Into<String>) -> Self {
-        Self { pool: None, db_path: db_path.into() }
+        Self { pool: None, db_path: db_path.into(), format: SerializationFormat::Json }
     }
+
+    pub fn with_serialization_format(mut self, format: SerializationFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Close the pool, waiting for its connections to finish, so another
+    /// adapter can open the same database file without contending for locks
+    pub async fn close(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            pool.close().await;
+        }
+    }
+
+    /// Whether any row is MessagePack, which SQL can't look inside
+    async fn has_blob_rows(&self) -> Result<bool, StorageError> {
+        let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
+        let row = sqlx::query("SELECT EXISTS(SELECT 1 FROM kv_store WHERE typeof(value) = 'blob')")
+            .fetch_one(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("query failed: {}", e) })?;
+        Ok(row.get::<i64, _>(0) != 0)
+    }
+
+    /// Bind an entity as TEXT (JSON, readable in any SQLite client) or BLOB
+    fn encode_value(&self, entity: &StoredEntity) -> Result<EncodedValue, StorageError> {
+        let bytes = self.format.encode(entity)?;
+        Ok(match self.format {
+            SerializationFormat::Json => EncodedValue::Text(String::from_utf8(bytes).map_err(|e| StorageError::SerializationError { error: e.to_string() })?),
+            SerializationFormat::MessagePack => EncodedValue::Blob(bytes),
+        })
+    }
+
+    /// Decode the `value` column of a row; soft-deleted (NULL) or undecodable
+    /// values yield `None`
+    fn decode_row(row: &SqliteRow) -> Option<StoredEntity> {
+        let bytes: Vec<u8> = row.try_get::<Option<Vec<u8>>, _>(0).ok().flatten()?;
+        SerializationFormat::decode(&bytes).ok()
+    }
+
+    /// Upsert an encoded entity under `key`, first copying the live value it
+    /// replaces into `kv_history`. The entity's type, id and `rfc3339`
+    /// creation time go in their own columns for the `kv_store_newest` index.
+    async fn write_versioned(conn: &mut sqlx::SqliteConnection, key: &str, entity: &StoredEntity, value: EncodedValue) -> Result<(), sqlx::Error> {
+        let current = sqlx::query("SELECT value FROM kv_store WHERE key = ? AND value IS NOT NULL")
+            .bind(key)
+            .fetch_optional(&mut *conn).await?;
+        if let Some(replaced) = current.as_ref().and_then(Self::decode_row) {
+            sqlx::query("INSERT OR REPLACE INTO kv_history(key, updated_at, value) SELECT key, ?, value FROM kv_store WHERE key = ?")
+                .bind(rfc3339(&replaced.updated_at))
+                .bind(key)
+                .execute(&mut *conn).await?;
+        }
+
+        let query = sqlx::query(
+            "INSERT INTO kv_store(key, value, metadata, updated_at, entity_type, entity_id, created_at) \
+             VALUES (?, ?, ?, datetime('now'), ?, ?, ?) \
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, metadata = excluded.metadata, updated_at = datetime('now'), \
+             entity_type = excluded.entity_type, entity_id = excluded.entity_id, created_at = excluded.created_at;",
+        )
+        .bind(key);
+        value.bind(query)
+            .bind(serde_json::json!({}).to_string())
+            .bind(&entity.entity_type)
+            .bind(&entity.id)
+            .bind(rfc3339(&entity.created_at))
+            .execute(&mut *conn).await?;
+        Ok(())
+    }
+
+    /// Add the columns behind the `kv_store_newest` index to databases
+    /// created before them, and fill them in for rows written back then
+    async fn migrate_newest_index(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        let columns: Vec<String> = sqlx::query("SELECT name FROM pragma_table_info('kv_store')")
+            .fetch_all(pool).await?
+            .iter()
+            .map(|row| row.get(0))
+            .collect();
+        for column in ["entity_type", "entity_id", "created_at"] {
+            if !columns.iter().any(|c| c == column) {
+                sqlx::query(&format!("ALTER TABLE kv_store ADD COLUMN {} TEXT", column)).execute(pool).await?;
+            }
+        }
+        sqlx::query("CREATE INDEX IF NOT EXISTS kv_store_newest ON kv_store(entity_type, created_at, entity_id)")
+            .execute(pool).await?;
+
+        let rows = sqlx::query("SELECT value, key FROM kv_store WHERE value IS NOT NULL AND created_at IS NULL")
+            .fetch_all(pool).await?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let mut tx = pool.begin().await?;
+        for row in &rows {
+            let Some(entity) = Self::decode_row(row) else {
+                continue;
+            };
+            sqlx::query("UPDATE kv_store SET entity_type = ?, entity_id = ?, created_at = ? WHERE key = ?")
+                .bind(&entity.entity_type)
+                .bind(&entity.id)
+                .bind(rfc3339(&entity.created_at))
+                .bind(row.get::<String, _>(1))
+                .execute(&mut *tx).await?;
+        }
+        tx.commit().await
+    }
+}
+
+/// SQL for a condition on `created_at`, compared against the `rfc3339`
+/// column rather than the stored JSON text; `None` for anything else
+fn created_at_sql(condition: &FilterCondition) -> Option<(String, Vec<SqlArg>)> {
+    if condition.field != "created_at" {
+        return None;
+    }
+    let op = match condition.operator {
+        ConditionOperator::Equals => "=",
+        ConditionOperator::GreaterThan => ">",
+        ConditionOperator::GreaterThanOrEqual => ">=",
+        ConditionOperator::LessThan => "<",
+        ConditionOperator::LessThanOrEqual => "<=",
+        _ => return None,
+    };
+    let timestamp = DateTime::parse_from_rfc3339(condition.value.as_str()?).ok()?;
+    Some((format!("created_at {} ?", op), vec![SqlArg::Text(rfc3339(&timestamp.with_timezone(&Utc)))]))
+}
+
+enum EncodedValue {
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl EncodedValue {
+    fn bind<'q>(
+        self,
+        query: sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    ) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+        match self {
+            EncodedValue::Text(text) => query.bind(text),
+            EncodedValue::Blob(bytes) => query.bind(bytes),
+        }
+    }
+}
+
+/// A bound parameter of a translated filter
+#[derive(Debug, Clone, PartialEq)]
+enum SqlArg {
+    Int(i64),
+    Real(f64),
+    Text(String),
+}
+
+impl SqlArg {
+    /// A scalar JSON value as `json_extract` returns it (booleans are 0/1).
+    /// Arrays, objects and null have no SQL equivalent.
+    fn from_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(SqlArg::Int(*b as i64)),
+            Value::Number(n) => n.as_i64().map(SqlArg::Int).or_else(|| n.as_f64().map(SqlArg::Real)),
+            Value::String(s) => Some(SqlArg::Text(s.clone())),
+            _ => None,
+        }
+    }
+
+    fn bind<'q>(
+        self,
+        query: sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    ) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+        match self {
+            SqlArg::Int(n) => query.bind(n),
+            SqlArg::Real(n) => query.bind(n),
+            SqlArg::Text(s) => query.bind(s),
+        }
+    }
+}
+
+/// `json_extract` path of a filter field: entity properties at the top
+/// level, anything else under `data`. Keys are quoted; `None` if a key
+/// can't be, or for timestamps, whose stored text doesn't compare in time
+/// order (those are filtered and sorted after decoding).
+fn json_path(field: &str) -> Option<String> {
+    if TIMESTAMP_FILTER_FIELDS.contains(&field) {
+        return None;
+    }
+    let mut path = if ENTITY_FILTER_FIELDS.contains(&field) { "$".to_string() } else { "$.data".to_string() };
+    for part in field.split('.') {
+        if part.contains('"') {
+            return None;
+        }
+        path.push_str(&format!(".\"{}\"", part));
+    }
+    Some(path)
+}
+
+/// SQL narrowing JSON `value` rows to those that may satisfy `condition`.
+/// It can let extra rows through (SQLite compares across types where the
+/// Rust predicate doesn't), never drop a match; `None` when the condition
+/// can't be expressed at all. Results are always re-checked in Rust.
+fn condition_sql(condition: &FilterCondition) -> Option<(String, Vec<SqlArg>)> {
+    let path = SqlArg::Text(json_path(&condition.field)?);
+    let field = "json_extract(value, ?)";
+    let scalar = || SqlArg::from_json(&condition.value);
+    let options = || -> Option<Vec<SqlArg>> { condition.value.as_array()?.iter().map(SqlArg::from_json).collect() };
+
+    Some(match condition.operator {
+        ConditionOperator::Exists => (format!("{} IS NOT NULL", field), vec![path]),
+        ConditionOperator::NotExists => (format!("{} IS NULL", field), vec![path]),
+        ConditionOperator::Equals => (format!("{} = ?", field), vec![path, scalar()?]),
+        ConditionOperator::NotEquals => (
+            format!("({f} IS NULL OR {f} != ?)", f = field),
+            vec![path.clone(), path, scalar()?],
+        ),
+        ConditionOperator::In => {
+            let options = options()?;
+            if options.is_empty() {
+                return Some(("0".to_string(), Vec::new()));
+            }
+            let placeholders = vec!["?"; options.len()].join(", ");
+            (format!("{} IN ({})", field, placeholders), std::iter::once(path).chain(options).collect())
+        }
+        ConditionOperator::NotIn => {
+            let options = options()?;
+            if options.is_empty() {
+                return None;
+            }
+            let placeholders = vec!["?"; options.len()].join(", ");
+            (
+                format!("({f} IS NULL OR {f} NOT IN ({p}))", f = field, p = placeholders),
+                [path.clone(), path].into_iter().chain(options).collect(),
+            )
+        }
+        ConditionOperator::GreaterThan
+        | ConditionOperator::GreaterThanOrEqual
+        | ConditionOperator::LessThan
+        | ConditionOperator::LessThanOrEqual => {
+            if condition.value.is_boolean() {
+                return None;
+            }
+            let op = match condition.operator {
+                ConditionOperator::GreaterThan => ">",
+                ConditionOperator::GreaterThanOrEqual => ">=",
+                ConditionOperator::LessThan => "<",
+                _ => "<=",
+            };
+            (format!("{} {} ?", field, op), vec![path, scalar()?])
+        }
+        // Substring of a string, or of an array/object's JSON text
+        ConditionOperator::Contains => {
+            let needle = condition.value.as_str()?.to_string();
+            (format!("instr({}, ?) > 0", field), vec![path, SqlArg::Text(needle)])
+        }
+    })
+}
+
+/// WHERE clause for a query's type and conditions over `kv_store`. MessagePack
+/// rows (and any text that isn't JSON, which `json_extract` would reject)
+/// always pass and are filtered after decoding.
+fn query_where(query: &StorageQuery) -> (String, Vec<SqlArg>) {
+    let mut clauses = Vec::new();
+    let mut args = Vec::new();
+    if let Some(entity_type) = &query.entity_type {
+        clauses.push("json_extract(value, '$.entity_type') = ?".to_string());
+        args.push(SqlArg::Text(entity_type.clone()));
+    }
+    for (sql, condition_args) in query.all_conditions().iter().filter_map(condition_sql) {
+        clauses.push(sql);
+        args.extend(condition_args);
+    }
+    if clauses.is_empty() {
+        return ("value IS NOT NULL".to_string(), args);
+    }
+    (format!("value IS NOT NULL AND (typeof(value) = 'blob' OR NOT json_valid(value) OR ({}))", clauses.join(" AND ")), args)
+}
+
+/// ORDER BY for a query's sort criteria, tied off by `id` (and the key) the
+/// way `StorageQuery::compare` does. `None` if a field can't be addressed.
+fn order_by(query: &StorageQuery) -> Option<(String, Vec<SqlArg>)> {
+    let mut terms = Vec::new();
+    let mut args = Vec::new();
+    for criteria in query.sort.iter().flatten() {
+        args.push(SqlArg::Text(json_path(&criteria.field)?));
+        let direction = match criteria.direction {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        };
+        terms.push(format!("json_extract(value, ?) {}", direction));
+    }
+    terms.push("json_extract(value, '$.id')".to_string());
+    terms.push("key".to_string());
+    Some((terms.join(", "), args))
 }
 
 #[async_trait]
@@ -47,16 +337,21 @@ impl StorageAdapter for SqliteAdapter {
         // Run migrations from embedded SQL file
         // Use include_str to embed the schema at compile time
         let sql = include_str!("../core-migrations/nodus.sqlite");
+        // The script opens its own transaction, so every statement must run on
+        // the same connection; spread across the pool, the open transaction
+        // locks out the statements that land elsewhere
+        let mut conn = pool.acquire().await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("Failed to connect: {}", e) })?;
         // Split on semicolon and execute each statement
         for stmt in sql.split(';') {
             let stmt = stmt.trim();
             if stmt.is_empty() { continue; }
             // Execute statement; ignore statements that are only PRAGMA or comments
-            if let Err(e) = sqlx::query(stmt).execute(&pool).await {
+            if let Err(e) = sqlx::query(stmt).execute(&mut *conn).await {
                 // Some statements may fail if they already exist; log and continue
                 tracing::warn!("SQLite migration statement failed (continuing): {}", e);
             }
         }
+        drop(conn);
 
         // Ensure a simple kv_store table exists for engine key/value usage
         let kv_sql = r#"
@@ -69,6 +364,19 @@ impl StorageAdapter for SqliteAdapter {
         "#;
         sqlx::query(kv_sql).execute(&pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("Failed to create kv_store: {}", e) })?;
 
+        // Superseded kv_store values, keyed (and so indexed) on the key and the
+        // entity's own `updated_at` in `rfc3339` form
+        let history_sql = r#"
+            CREATE TABLE IF NOT EXISTS kv_history (
+                key TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                value,
+                PRIMARY KEY (key, updated_at)
+            );
+        "#;
+        sqlx::query(history_sql).execute(&pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("Failed to create kv_history: {}", e) })?;
+        Self::migrate_newest_index(&pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("Failed to index kv_store by creation time: {}", e) })?;
+
         self.pool = Some(pool);
         Ok(())
     }
@@ -82,32 +390,30 @@ impl StorageAdapter for SqliteAdapter {
         }
     }
 
+    fn capabilities(&self) -> AdapterCapabilities {
+        AdapterCapabilities {
+            count: true,
+            history: true,
+            ..AdapterCapabilities::default()
+        }
+    }
+
     async fn get(&self, key: &str, _ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
         let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
         let row = sqlx::query("SELECT value FROM kv_store WHERE key = ?")
             .bind(key)
             .fetch_optional(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("query failed: {}", e) })?;
-        if let Some(r) = row {
-            let value: String = r.get(0);
-            // Deserialize into StoredEntity if possible; otherwise return NotFound
-            match serde_json::from_str::<StoredEntity>(&value) {
-                Ok(ent) => Ok(Some(ent)),
-                Err(_) => Ok(None),
-            }
-        } else {
-            Ok(None)
-        }
+        // Deserialize into StoredEntity if possible; otherwise return NotFound
+        Ok(row.as_ref().and_then(Self::decode_row))
     }
 
     async fn put(&self, key: &str, entity: StoredEntity, _ctx: &StorageContext) -> Result<(), StorageError> {
         let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
-        let value = serde_json::to_string(&entity).map_err(|e| StorageError::SerializationError { error: format!("serialize failed: {}", e) })?;
-        sqlx::query("INSERT INTO kv_store(key, value, metadata, updated_at) VALUES (?, ?, ?, datetime('now')) ON CONFLICT(key) DO UPDATE SET value = excluded.value, metadata = excluded.metadata, updated_at = datetime('now');")
-            .bind(key)
-            .bind(&value)
-            .bind(serde_json::json!({}).to_string())
-            .execute(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("put failed: {}", e) })?;
-        Ok(())
+        let failed = |e: sqlx::Error| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("put failed: {}", e) };
+        let value = self.encode_value(&entity)?;
+        let mut tx = pool.begin().await.map_err(failed)?;
+        Self::write_versioned(&mut tx, key, &entity, value).await.map_err(failed)?;
+        tx.commit().await.map_err(failed)
     }
 
     async fn delete(&self, key: &str, _ctx: &StorageContext) -> Result<(), StorageError> {
@@ -120,26 +426,73 @@ impl StorageAdapter for SqliteAdapter {
 
     async fn purge(&self, key: &str, _ctx: &StorageContext) -> Result<(), StorageError> {
         let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
-        sqlx::query("DELETE FROM kv_store WHERE key = ?")
-            .bind(key)
-            .execute(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("purge failed: {}", e) })?;
-        Ok(())
+        let failed = |e: sqlx::Error| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("purge failed: {}", e) };
+        let mut tx = pool.begin().await.map_err(failed)?;
+        for table in ["kv_store", "kv_history"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE key = ?", table))
+                .bind(key)
+                .execute(&mut *tx).await.map_err(failed)?;
+        }
+        tx.commit().await.map_err(failed)
     }
 
-    async fn query(&self, _query: &StorageQuery, _ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
+    async fn query(&self, query: &StorageQuery, _ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
         // For KV-based usage we return all values; complex queries should use
         // the full schema tables implemented above (objects/events etc.).
         let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
-        let rows = sqlx::query("SELECT value FROM kv_store")
-            .fetch_all(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("query failed: {}", e) })?;
-        let mut out = Vec::new();
-        for r in rows {
-            let v: String = r.get(0);
-            if let Ok(ent) = serde_json::from_str::<StoredEntity>(&v) {
-                out.push(ent);
+        let failed = |e: sqlx::Error| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("query failed: {}", e) };
+        if !query.is_filtered() && !query.is_sorted() {
+            // Stable key order so callers can page with limit/offset (-1 = no limit)
+            let limit = query.limit.map(|l| l as i64).unwrap_or(-1);
+            let offset = query.offset.unwrap_or(0) as i64;
+            let rows = sqlx::query("SELECT value FROM kv_store WHERE value IS NOT NULL ORDER BY key LIMIT ? OFFSET ?")
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(pool).await.map_err(failed)?;
+            return Ok(rows.iter().filter_map(Self::decode_row).collect());
+        }
+
+        // Sorting without conditions can run entirely in SQL, provided every
+        // row is JSON that `json_extract` can read
+        if query.is_sorted() && query.all_conditions().is_empty() && !self.has_blob_rows().await? {
+            if let Some((order, order_args)) = order_by(query) {
+                let mut sql = "SELECT value FROM kv_store WHERE value IS NOT NULL AND json_valid(value)".to_string();
+                if query.entity_type.is_some() {
+                    sql.push_str(" AND json_extract(value, '$.entity_type') = ?");
+                }
+                sql.push_str(&format!(" ORDER BY {} LIMIT ? OFFSET ?", order));
+                let mut statement = sqlx::query(&sql);
+                if let Some(entity_type) = &query.entity_type {
+                    statement = statement.bind(entity_type.clone());
+                }
+                for arg in order_args {
+                    statement = arg.bind(statement);
+                }
+                let rows = statement
+                    .bind(query.limit.map(|l| l as i64).unwrap_or(-1))
+                    .bind(query.offset.unwrap_or(0) as i64)
+                    .fetch_all(pool).await.map_err(failed)?;
+                return Ok(rows.iter().filter_map(Self::decode_row).collect());
             }
         }
-        Ok(out)
+
+        // SQL narrows the scan; the exact match, order and paging happen here
+        let (clause, args) = query_where(query);
+        let sql = format!("SELECT value FROM kv_store WHERE {} ORDER BY key", clause);
+        let mut statement = sqlx::query(&sql);
+        for arg in args {
+            statement = arg.bind(statement);
+        }
+        let rows = statement.fetch_all(pool).await.map_err(failed)?;
+        let matches = query.predicate();
+        let mut entities: Vec<StoredEntity> = rows.iter().filter_map(Self::decode_row).filter(|entity| matches(entity)).collect();
+        if query.is_sorted() {
+            entities.sort_by(|a, b| query.compare(a, b));
+        }
+        Ok(entities.into_iter()
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect())
     }
 
     async fn get_by_type(&self, entity_type: &str, _ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
@@ -166,41 +519,193 @@ impl StorageAdapter for SqliteAdapter {
         let rows = sqlx::query("SELECT value FROM kv_store WHERE key LIKE ?")
             .bind(format!("{}:%", entity_type))
             .fetch_all(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("kv query failed: {}", e) })?;
-        let mut out = Vec::new();
-        for r in rows {
-            let v: String = r.get(0);
-            if let Ok(ent) = serde_json::from_str::<StoredEntity>(&v) {
-                out.push(ent);
+        Ok(rows.iter().filter_map(Self::decode_row).collect())
+    }
+
+    /// Keyset scan over `kv_store`: `key > cursor ORDER BY key LIMIT n`, so
+    /// each page reads at most `limit` rows. Rows SQL can't see into (blobs,
+    /// invalid JSON) are fetched and checked after decoding, which can leave
+    /// a page short or empty; the cursor is the last key read either way.
+    async fn scan_type(
+        &self,
+        entity_type: &str,
+        after: Option<&str>,
+        limit: usize,
+        _ctx: &StorageContext,
+    ) -> Result<ScanPage, StorageError> {
+        let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
+        let rows = sqlx::query(
+            "SELECT value, key FROM kv_store WHERE value IS NOT NULL AND key > ? \
+             AND (typeof(value) = 'blob' OR NOT json_valid(value) OR json_extract(value, '$.entity_type') = ?) \
+             ORDER BY key LIMIT ?",
+        )
+        .bind(after.unwrap_or(""))
+        .bind(entity_type)
+        .bind(limit as i64)
+        .fetch_all(pool).await
+        .map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("scan failed: {}", e) })?;
+
+        let next_cursor = if rows.len() < limit { None } else { rows.last().map(|row| row.get::<String, _>(1)) };
+        let entities = rows
+            .iter()
+            .filter_map(Self::decode_row)
+            .filter(|entity| entity.entity_type == entity_type && entity.deleted_at.is_none())
+            .collect();
+        Ok(ScanPage { entities, next_cursor })
+    }
+
+    /// Keyset walk of the `kv_store_newest` index, `(created_at, entity_id)`
+    /// descending. Conditions SQL can express narrow each batch; every row is
+    /// re-checked after decoding, and the walk continues from the last row
+    /// read until the page is full or the index runs out.
+    async fn query_newest(
+        &self,
+        query: &StorageQuery,
+        before: Option<(DateTime<Utc>, &str)>,
+        limit: usize,
+        _ctx: &StorageContext,
+    ) -> Result<Vec<StoredEntity>, StorageError> {
+        let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
+        let failed = |e: sqlx::Error| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("query failed: {}", e) };
+
+        let mut clauses = vec!["value IS NOT NULL".to_string()];
+        let mut args = Vec::new();
+        if let Some(entity_type) = &query.entity_type {
+            clauses.push("entity_type = ?".to_string());
+            args.push(SqlArg::Text(entity_type.clone()));
+        }
+        let mut json_clauses = Vec::new();
+        for condition in query.all_conditions() {
+            if let Some((sql, condition_args)) = created_at_sql(&condition) {
+                clauses.push(sql);
+                args.extend(condition_args);
+            } else if let Some((sql, condition_args)) = condition_sql(&condition) {
+                json_clauses.push(sql);
+                args.extend(condition_args);
+            }
+        }
+        if !json_clauses.is_empty() {
+            clauses.push(format!("(typeof(value) = 'blob' OR NOT json_valid(value) OR ({}))", json_clauses.join(" AND ")));
+        }
+        let sql_with = |keyset: bool| {
+            let keyset = if keyset { " AND (created_at, entity_id) < (?, ?)" } else { "" };
+            format!(
+                "SELECT value, created_at, entity_id FROM kv_store WHERE {}{} ORDER BY created_at DESC, entity_id DESC LIMIT ?",
+                clauses.join(" AND "),
+                keyset
+            )
+        };
+
+        let matches = query.predicate();
+        let mut cursor = before.map(|(created_at, id)| (rfc3339(&created_at), id.to_string()));
+        let mut entities = Vec::new();
+        while entities.len() < limit {
+            let batch = limit - entities.len();
+            let sql = sql_with(cursor.is_some());
+            let mut statement = sqlx::query(&sql);
+            for arg in args.iter().cloned() {
+                statement = arg.bind(statement);
+            }
+            if let Some((created_at, id)) = &cursor {
+                statement = statement.bind(created_at.clone()).bind(id.clone());
+            }
+            let rows = statement.bind(batch as i64).fetch_all(pool).await.map_err(failed)?;
+            entities.extend(rows.iter().filter_map(Self::decode_row).filter(|entity| matches(entity)));
+            if rows.len() < batch {
+                break;
             }
+            cursor = rows.last().map(|row| (row.get::<String, _>(1), row.get::<String, _>(2)));
         }
-        Ok(out)
+        Ok(entities)
     }
 
+    /// Write the whole batch in one transaction: a single commit (and fsync)
+    /// instead of one per row, and all-or-nothing on failure
     async fn batch_put(&self, entities: Vec<(String, StoredEntity)>, _ctx: &StorageContext) -> Result<(), StorageError> {
         let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
+        let failed = |e: sqlx::Error| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("batch put failed: {}", e) };
+        let mut tx = pool.begin().await.map_err(failed)?;
         for (k, v) in entities {
-            let value = serde_json::to_string(&v).map_err(|e| StorageError::SerializationError { error: format!("serialize failed: {}", e) })?;
-            sqlx::query("INSERT INTO kv_store(key, value, metadata, updated_at) VALUES (?, ?, ?, datetime('now')) ON CONFLICT(key) DO UPDATE SET value = excluded.value, metadata = excluded.metadata, updated_at = datetime('now');")
-                .bind(k)
-                .bind(value)
-                .bind(serde_json::json!({}).to_string())
-                .execute(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("batch put failed: {}", e) })?;
+            let value = self.encode_value(&v)?;
+            Self::write_versioned(&mut tx, &k, &v, value).await.map_err(failed)?;
         }
-        Ok(())
+        tx.commit().await.map_err(failed)
+    }
+
+    async fn batch_delete(&self, keys: &[String], _ctx: &StorageContext) -> Result<(), StorageError> {
+        let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
+        // Stay well under SQLite's bound-parameter limit (999 on older builds)
+        let failed = |e: sqlx::Error| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("batch delete failed: {}", e) };
+        let mut tx = pool.begin().await.map_err(failed)?;
+        for chunk in keys.chunks(500) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            for table in ["kv_store", "kv_history"] {
+                let sql = format!("DELETE FROM {} WHERE key IN ({})", table, placeholders);
+                let mut query = sqlx::query(&sql);
+                for key in chunk {
+                    query = query.bind(key);
+                }
+                query.execute(&mut *tx).await.map_err(failed)?;
+            }
+        }
+        tx.commit().await.map_err(failed)
+    }
+
+    async fn get_version_as_of(&self, key: &str, timestamp: DateTime<Utc>, ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
+        if let Some(current) = self.get(key, ctx).await?.filter(|e| e.updated_at <= timestamp) {
+            return Ok(Some(current));
+        }
+        let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
+        let row = sqlx::query("SELECT value FROM kv_history WHERE key = ? AND updated_at <= ? ORDER BY updated_at DESC LIMIT 1")
+            .bind(key)
+            .bind(rfc3339(&timestamp))
+            .fetch_optional(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("history query failed: {}", e) })?;
+        Ok(row.as_ref().and_then(Self::decode_row))
+    }
+
+    async fn prune_versions(&self, keep: usize, _ctx: &StorageContext) -> Result<u64, StorageError> {
+        let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
+        let result = sqlx::query(
+            "DELETE FROM kv_history WHERE rowid IN ( \
+                 SELECT rowid FROM ( \
+                     SELECT rowid, ROW_NUMBER() OVER (PARTITION BY key ORDER BY updated_at DESC) AS newer \
+                     FROM kv_history \
+                 ) WHERE newer > ? \
+             )",
+        )
+        .bind(keep as i64)
+        .execute(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("history prune failed: {}", e) })?;
+        Ok(result.rows_affected())
     }
 
     async fn get_stats(&self) -> Result<StorageStats, StorageError> {
         let pool = self.pool.as_ref().ok_or(StorageError::DatabaseUnavailable { reason: "pool not initialized".to_string() })?;
         let row = sqlx::query("SELECT COUNT(*) as c FROM kv_store").fetch_one(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("stats query failed: {}", e) })?;
         let c: i64 = row.get::<i64, _>(0);
-        Ok(StorageStats { total_entities: c as u64, entities_by_type: HashMap::new(), storage_size_bytes: 0, last_sync: None, pending_changes: 0 })
-    }
 
-    async fn export_data(&self, _ctx: &StorageContext) -> Result<Vec<u8>, StorageError> {
-        Err(StorageError::BackendError { backend: "sqlite".to_string(), error: "export not implemented".to_string() })
+        // Type from the JSON value when readable, else the key prefix
+        // (`grid_config:main` -> `grid_config`), which also covers MessagePack
+        // rows. Sizes are bytes, not characters, hence the BLOB cast.
+        let rows = sqlx::query(
+            "SELECT COALESCE( \
+                 CASE WHEN typeof(value) = 'text' AND json_valid(value) THEN json_extract(value, '$.entity_type') END, \
+                 CASE WHEN instr(key, ':') > 0 THEN substr(key, 1, instr(key, ':') - 1) ELSE key END \
+             ) AS entity_type, COUNT(*), SUM(length(CAST(value AS BLOB))) \
+             FROM kv_store WHERE value IS NOT NULL GROUP BY 1",
+        )
+        .fetch_all(pool).await.map_err(|e| StorageError::BackendError { backend: "sqlite".to_string(), error: format!("size query failed: {}", e) })?;
+        let mut entities_by_type = HashMap::new();
+        let mut bytes_by_type = HashMap::new();
+        for row in rows {
+            let entity_type: String = row.get(0);
+            entities_by_type.insert(entity_type.clone(), row.get::<i64, _>(1) as u64);
+            bytes_by_type.insert(entity_type, row.get::<i64, _>(2) as u64);
+        }
+        let storage_size_bytes = bytes_by_type.values().sum();
+        Ok(StorageStats { total_entities: c as u64, entities_by_type, bytes_by_type, storage_size_bytes, last_sync: None, pending_changes: 0 })
     }
 
-    async fn import_data(&mut self, _data: &[u8], _ctx: &StorageContext) -> Result<(), StorageError> {
-        Err(StorageError::BackendError { backend: "sqlite".to_string(), error: "import not implemented".to_string() })
+    async fn export_data(&self, _ctx: &StorageContext) -> Result<Vec<u8>, StorageError> {
+        Err(StorageError::Unsupported { backend: "sqlite".to_string(), operation: StorageCapability::Export.as_str().to_string() })
     }
 }
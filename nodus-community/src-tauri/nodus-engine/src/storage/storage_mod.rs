@@ -2,7 +2,7 @@
 // Storage Manager - Community Version (Simplified)
 // Multi-backend storage without enterprise security integration
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -10,12 +10,17 @@ use serde_json::Value;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt, TryStreamExt};
+use tracing::instrument;
 
-// Sub-modules
-#[cfg(target_arch = "wasm32")]
-pub mod indexeddb_adapter;
+use crate::storage::metrics_mod::{ShardedCounter, StorageMetricsSnapshot};
+use crate::storage::validation_mod::ConditionOperator;
+use crate::storage::write_buffer_mod::{PendingWrites, WriteBufferConfig};
 
-// `sync_mod` and `validation_mod` are declared at `storage/mod.rs` to keep the
+/// A single RFC 6902 JSON Patch operation (`add`, `remove`, `replace`, `move`, `copy`, `test`)
+pub use json_patch::PatchOperation as PatchOp;
+
+// `indexeddb_adapter`, `sync_mod` and `validation_mod` are declared at `storage/mod.rs` to keep the
 // module tree flat (they are siblings of `storage_mod`). Declaring them here
 // would attempt to create nested modules (e.g. `storage_mod::sync_mod`) which
 // causes the compiler to look for files under a `storage_mod/` subdirectory.
@@ -47,19 +52,228 @@ pub enum StorageError {
     
     #[error("Database unavailable: {reason}")]
     DatabaseUnavailable { reason: String },
+    
+    #[error("Unsupported operation: {backend} does not support {operation}")]
+    Unsupported { backend: String, operation: String },
+    
+    #[error("I/O error: {error}")]
+    Io { error: String },
+    
+    #[error("Key already taken: {key}")]
+    KeyExists { key: String },
 }
 
 /// Storage query interface (replaces JS query objects)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StorageQuery {
     pub entity_type: Option<String>,
+    /// Equality filters, shorthand for `Equals` conditions
     pub filters: HashMap<String, Value>,
+    /// Filters with an explicit operator; all must hold
+    #[serde(default)]
+    pub conditions: Vec<FilterCondition>,
     pub sort: Option<Vec<SortCriteria>>,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
     pub include_deleted: bool,
 }
 
+impl StorageQuery {
+    /// Start building a query; matches everything until narrowed down
+    pub fn builder() -> StorageQueryBuilder {
+        StorageQueryBuilder::default()
+    }
+    
+    /// `filters` as `Equals` conditions followed by `conditions`
+    pub fn all_conditions(&self) -> Vec<FilterCondition> {
+        self.filters
+            .iter()
+            .map(|(field, value)| FilterCondition {
+                field: field.clone(),
+                operator: ConditionOperator::Equals,
+                value: value.clone(),
+            })
+            .chain(self.conditions.iter().cloned())
+            .collect()
+    }
+    
+    /// Predicate for adapters that filter in memory: entity type, deleted
+    /// state and every condition. Ignores sort, limit and offset.
+    pub fn predicate(&self) -> impl Fn(&StoredEntity) -> bool + '_ {
+        let conditions = self.all_conditions();
+        move |entity| {
+            self.entity_type.as_ref().map_or(true, |t| &entity.entity_type == t)
+                && (self.include_deleted || entity.deleted_at.is_none())
+                && conditions.iter().all(|c| c.matches(entity))
+        }
+    }
+    
+    /// Order by each `sort` criterion in turn, then by `id` so ties always
+    /// come out the same way
+    pub fn compare(&self, a: &StoredEntity, b: &StoredEntity) -> std::cmp::Ordering {
+        self.sort
+            .iter()
+            .flatten()
+            .map(|criteria| {
+                let ordering = compare_field_values(a.field(&criteria.field).as_ref(), b.field(&criteria.field).as_ref());
+                match criteria.direction {
+                    SortDirection::Asc => ordering,
+                    SortDirection::Desc => ordering.reverse(),
+                }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.id.cmp(&b.id))
+    }
+    
+    pub fn is_sorted(&self) -> bool {
+        self.sort.as_ref().map_or(false, |sort| !sort.is_empty())
+    }
+    
+    /// Whether anything beyond paging narrows the results
+    pub fn is_filtered(&self) -> bool {
+        self.entity_type.is_some() || !self.filters.is_empty() || !self.conditions.is_empty()
+    }
+}
+
+/// `field <operator> value` on a stored entity. `field` names an entity
+/// property (`id`, `entity_type`, `created_at`, `updated_at`, `created_by`,
+/// `updated_by`, `version`) or a path into `data`, dot-separated for nested
+/// objects (`owner.name`). Timestamps compare as RFC 3339 strings; values
+/// given for them are normalized first, so any RFC 3339 form works.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterCondition {
+    pub field: String,
+    pub operator: ConditionOperator,
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// Entity properties `FilterCondition` reads from the entity instead of `data`
+pub const ENTITY_FILTER_FIELDS: &[&str] = &["id", "entity_type", "created_at", "updated_at", "created_by", "updated_by", "version"];
+
+/// Entity timestamps. Stored JSON trims trailing zeros off the fraction, so
+/// the stored strings don't sort chronologically; backends must compare
+/// these in their normalized form rather than in the stored text.
+pub const TIMESTAMP_FILTER_FIELDS: &[&str] = &["created_at", "updated_at"];
+
+impl FilterCondition {
+    pub fn new(field: impl Into<String>, operator: ConditionOperator, value: impl Into<Value>) -> Self {
+        Self { field: field.into(), operator, value: value.into() }
+    }
+    
+    pub fn matches(&self, entity: &StoredEntity) -> bool {
+        let actual = entity.field(&self.field);
+        if TIMESTAMP_FILTER_FIELDS.contains(&self.field.as_str()) {
+            return self.operator.evaluate(actual.as_ref(), &normalize_timestamps(&self.value));
+        }
+        self.operator.evaluate(actual.as_ref(), &self.value)
+    }
+}
+
+/// Order two optional field values the way SQLite orders `json_extract`
+/// results: missing/null, then numbers (booleans as 0/1), then strings, with
+/// arrays and objects compared as their JSON text
+fn compare_field_values(a: Option<&Value>, b: Option<&Value>) -> std::cmp::Ordering {
+    enum Key {
+        Null,
+        Number(f64),
+        Text(String),
+    }
+    fn key(value: Option<&Value>) -> Key {
+        match value {
+            None | Some(Value::Null) => Key::Null,
+            Some(Value::Bool(b)) => Key::Number(*b as u8 as f64),
+            Some(Value::Number(n)) => Key::Number(n.as_f64().unwrap_or(0.0)),
+            Some(Value::String(s)) => Key::Text(s.clone()),
+            Some(other) => Key::Text(other.to_string()),
+        }
+    }
+    match (key(a), key(b)) {
+        (Key::Null, Key::Null) => std::cmp::Ordering::Equal,
+        (Key::Null, _) => std::cmp::Ordering::Less,
+        (_, Key::Null) => std::cmp::Ordering::Greater,
+        (Key::Number(x), Key::Number(y)) => x.partial_cmp(&y).unwrap_or(std::cmp::Ordering::Equal),
+        (Key::Number(_), Key::Text(_)) => std::cmp::Ordering::Less,
+        (Key::Text(_), Key::Number(_)) => std::cmp::Ordering::Greater,
+        (Key::Text(x), Key::Text(y)) => x.cmp(&y),
+    }
+}
+
+/// JSON pointer into `data` for a dotted field path (`owner.name` -> `/owner/name`)
+fn data_pointer(field: &str) -> String {
+    field.split('.').map(|part| format!("/{}", part.replace('~', "~0").replace('/', "~1"))).collect()
+}
+
+/// Timestamps with a fixed nine-digit fraction, so they order correctly as
+/// strings
+pub fn rfc3339(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
+}
+
+/// Rewrite RFC 3339 strings (alone or in an array) into the `rfc3339` form
+fn normalize_timestamps(value: &Value) -> Value {
+    match value {
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .map(|t| Value::from(rfc3339(&t.with_timezone(&Utc))))
+            .unwrap_or_else(|_| value.clone()),
+        Value::Array(items) => Value::Array(items.iter().map(normalize_timestamps).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Fluent builder for `StorageQuery`
+#[derive(Debug, Clone, Default)]
+pub struct StorageQueryBuilder {
+    query: StorageQuery,
+}
+
+impl StorageQueryBuilder {
+    pub fn entity_type(mut self, entity_type: impl Into<String>) -> Self {
+        self.query.entity_type = Some(entity_type.into());
+        self
+    }
+
+    /// Require `field` to equal `value`; repeated calls add more filters
+    pub fn filter(mut self, field: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.query.filters.insert(field.into(), value.into());
+        self
+    }
+
+    /// Require `field <operator> value`; repeated calls add more conditions
+    pub fn condition(mut self, field: impl Into<String>, operator: ConditionOperator, value: impl Into<Value>) -> Self {
+        self.query.conditions.push(FilterCondition::new(field, operator, value));
+        self
+    }
+
+    /// Sort by `field`; repeated calls add tie-breakers in order
+    pub fn sort(mut self, field: impl Into<String>, direction: SortDirection) -> Self {
+        self.query.sort.get_or_insert_with(Vec::new).push(SortCriteria {
+            field: field.into(),
+            direction,
+        });
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.query.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.query.offset = Some(offset);
+        self
+    }
+
+    pub fn include_deleted(mut self, include_deleted: bool) -> Self {
+        self.query.include_deleted = include_deleted;
+        self
+    }
+
+    pub fn build(self) -> StorageQuery {
+        self.query
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SortCriteria {
     pub field: String,
@@ -78,6 +292,8 @@ pub struct StorageContext {
     pub user_id: String,
     pub session_id: Uuid,
     pub operation_id: Uuid,
+    /// Internal system writes (sync, migration, usage counters) skip schema validation
+    pub skip_validation: bool,
     // Removed enterprise-specific fields:
     // - tenant_id, classification_level, compartments
 }
@@ -99,6 +315,41 @@ pub struct StoredEntity {
     // - classification, compartments, tenant_id
 }
 
+impl StoredEntity {
+    /// Canonical JSON: object keys sorted at every level, so identical
+    /// entities always give identical bytes. Used for sizes, checksums and
+    /// comparisons; reads and writes use the faster `SerializationFormat`.
+    pub fn canonical_json(&self) -> Result<Vec<u8>, StorageError> {
+        let value = serde_json::to_value(self)
+            .map_err(|e| StorageError::SerializationError { error: format!("serialize failed: {}", e) })?;
+        serde_json::to_vec(&sort_keys(value))
+            .map_err(|e| StorageError::SerializationError { error: format!("serialize failed: {}", e) })
+    }
+    
+    /// Value of a query field: an entity property (`ENTITY_FILTER_FIELDS`)
+    /// or a dotted path into `data`
+    pub fn field(&self, field: &str) -> Option<Value> {
+        match field {
+            "id" => Some(Value::from(self.id.as_str())),
+            "entity_type" => Some(Value::from(self.entity_type.as_str())),
+            "created_at" => Some(Value::from(rfc3339(&self.created_at))),
+            "updated_at" => Some(Value::from(rfc3339(&self.updated_at))),
+            "created_by" => Some(Value::from(self.created_by.as_str())),
+            "updated_by" => Some(Value::from(self.updated_by.as_str())),
+            "version" => Some(Value::from(self.version)),
+            path => self.data.pointer(&data_pointer(path)).cloned(),
+        }
+    }
+    
+    /// Hex SHA-256 over the canonical form of `entity_type` and `data`.
+    /// Bookkeeping (timestamps, version, sync status) is left out, so two
+    /// entities with the same content hash the same however they were written.
+    pub fn content_hash(&self) -> String {
+        let content = sort_keys(serde_json::json!({ "entity_type": self.entity_type, "data": self.data }));
+        crate::marketplace::sha256_hex(content.to_string().as_bytes())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SyncStatus {
     Local,
@@ -107,6 +358,231 @@ pub enum SyncStatus {
     Conflict,
 }
 
+/// Optional operations an adapter may support
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageCapability {
+    Export,
+    FullTextSearch,
+    Transactions,
+    Count,
+    History,
+}
+
+impl StorageCapability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageCapability::Export => "export",
+            StorageCapability::FullTextSearch => "full_text_search",
+            StorageCapability::Transactions => "transactions",
+            StorageCapability::Count => "count",
+            StorageCapability::History => "history",
+        }
+    }
+}
+
+/// Features declared by a storage adapter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdapterCapabilities {
+    pub export: bool,
+    pub full_text_search: bool,
+    pub transactions: bool,
+    pub count: bool,
+    /// Superseded versions are kept for `get_version_as_of`
+    #[serde(default)]
+    pub history: bool,
+}
+
+impl AdapterCapabilities {
+    pub fn supports(&self, capability: StorageCapability) -> bool {
+        match capability {
+            StorageCapability::Export => self.export,
+            StorageCapability::FullTextSearch => self.full_text_search,
+            StorageCapability::Transactions => self.transactions,
+            StorageCapability::Count => self.count,
+            StorageCapability::History => self.history,
+        }
+    }
+}
+
+/// Default number of entities copied per chunk during a backend migration
+pub const DEFAULT_MIGRATION_CHUNK_SIZE: usize = 500;
+
+/// Progress/result of a backend-to-backend data migration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub source: String,
+    pub destination: String,
+    pub entities_migrated: u64,
+    pub deleted_entities_migrated: u64,
+    pub chunks: u64,
+    pub switched_primary: bool,
+}
+
+/// Entity type of audit-trail records written by plugin audit logging
+pub const AUDIT_EVENT_TYPE: &str = "audit_event";
+
+/// Days audit events are kept when no retention is configured
+pub const DEFAULT_AUDIT_RETENTION_DAYS: u32 = 90;
+
+/// How long audit events are kept and how often old ones are purged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditRetentionPolicy {
+    pub enabled: bool,
+    pub retention_days: u32,
+    pub purge_interval_secs: u64,
+}
+
+impl Default for AuditRetentionPolicy {
+    /// Retention from `NODUS_AUDIT_RETENTION_DAYS` (default 90), purged daily
+    fn default() -> Self {
+        let retention_days = std::env::var("NODUS_AUDIT_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_AUDIT_RETENTION_DAYS);
+        Self {
+            enabled: true,
+            retention_days,
+            purge_interval_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// Superseded versions kept per entity when no retention is configured
+pub const DEFAULT_KEEP_VERSIONS: usize = 20;
+
+/// How many superseded versions of each entity the version history keeps
+/// and how often older ones are pruned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VersionRetentionPolicy {
+    pub enabled: bool,
+    pub keep_versions: usize,
+    pub prune_interval_secs: u64,
+}
+
+impl Default for VersionRetentionPolicy {
+    /// Keep the last 20 versions, pruned hourly
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keep_versions: DEFAULT_KEEP_VERSIONS,
+            prune_interval_secs: 60 * 60,
+        }
+    }
+}
+
+/// Default number of entities serialized per chunk by `export_stream`
+pub const DEFAULT_EXPORT_CHUNK_SIZE: usize = 500;
+
+/// Default in-flight writes for `put_concurrently`
+pub const DEFAULT_BATCH_WRITE_CONCURRENCY: usize = 8;
+
+/// Default entities per batch yielded by `iter_by_type`
+pub const DEFAULT_SCAN_BATCH_SIZE: usize = 500;
+
+/// One page of `StorageAdapter::scan_type`
+#[derive(Debug, Clone)]
+pub struct ScanPage {
+    pub entities: Vec<StoredEntity>,
+    /// Where the next page starts; `None` once the scan is complete. A page
+    /// can be empty and still have a next one.
+    pub next_cursor: Option<String>,
+}
+
+/// Result of a streaming export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportReport {
+    pub backend: String,
+    pub entities_exported: u64,
+    pub chunks: u64,
+    pub bytes: u64,
+}
+
+/// Which backends `get` may answer from when the cache misses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadConsistency {
+    /// Only the primary backend; its errors are returned as they are
+    PrimaryOnly,
+    /// On a primary error, serve the first fallback that has the entity,
+    /// even if it may be stale
+    #[default]
+    FallbackAllowed,
+    /// Read the primary and every fallback and serve the copy with the latest
+    /// `updated_at`, the primary winning ties. A primary that answers "not
+    /// found" is trusted; a primary error falls back as with `FallbackAllowed`.
+    PreferFresh,
+}
+
+/// How `import_data` treats malformed or invalid records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Import the valid records and report the rest
+    #[default]
+    Lenient,
+    /// All-or-nothing: any bad record aborts the import before anything is written
+    Strict,
+}
+
+/// Entity type of relationship (edge) entities. Their `data` names the two
+/// ends as `source_id` and `target_id`.
+pub const RELATIONSHIP_ENTITY_TYPE: &str = "relationship";
+
+/// What `delete` does with relationships that reference the deleted entity
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CascadeMode {
+    /// Delete only the entity; its edges are left in place
+    #[default]
+    None,
+    /// Also delete every relationship referencing the entity
+    DeleteEdges,
+    /// Delete the edges and soft-delete the entities on their other end,
+    /// when those are of one of the given types. Does not recurse further.
+    DeleteRelated { entity_types: Vec<String> },
+}
+
+/// How `StorageManager::insert` picks keys. Keys are `<entity_type>:<id>`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum KeyStrategy {
+    /// Random UUID v4 (`note:9b2c...`)
+    #[default]
+    Uuid,
+    /// Slug of a string field in `data` (`dashboard:q3-sales`); a taken slug
+    /// gets a numeric suffix (`q3-sales-2`)
+    Slug { field: String },
+    /// Per-type counter starting at 1 (`ticket:42`)
+    Sequential,
+}
+
+/// Candidate keys `insert` tries before failing with `KeyExists`
+pub const MAX_KEY_ATTEMPTS: u32 = 100;
+
+/// A record that could not be imported, by its position in the backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRecordError {
+    pub index: usize,
+    pub error: String,
+}
+
+/// Result of an import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub imported: u64,
+    pub failed: u64,
+    pub errors: Vec<ImportRecordError>,
+}
+
+/// Health and declared capabilities of a registered backend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendHealth {
+    pub healthy: bool,
+    pub capabilities: AdapterCapabilities,
+}
+
 /// Storage adapter trait (simplified)
 #[async_trait]
 pub trait StorageAdapter: Send + Sync {
@@ -116,6 +592,11 @@ pub trait StorageAdapter: Send + Sync {
     /// Check if the backend is healthy
     async fn health_check(&self) -> Result<(), StorageError>;
     
+    /// Optional features this adapter supports; nothing optional by default
+    fn capabilities(&self) -> AdapterCapabilities {
+        AdapterCapabilities::default()
+    }
+    
     /// Get a single entity by key
     async fn get(&self, key: &str, ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError>;
     
@@ -134,17 +615,90 @@ pub trait StorageAdapter: Send + Sync {
     /// Get entities by type
     async fn get_by_type(&self, entity_type: &str, ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError>;
     
+    /// Up to `limit` live entities of a type, in a stable order, starting
+    /// after `after` (the previous page's `next_cursor`). The default pages
+    /// `query` by id; adapters with an ordered key should override it with a
+    /// range scan so no page reads more than `limit` rows.
+    async fn scan_type(
+        &self,
+        entity_type: &str,
+        after: Option<&str>,
+        limit: usize,
+        ctx: &StorageContext,
+    ) -> Result<ScanPage, StorageError> {
+        let mut builder = StorageQuery::builder()
+            .entity_type(entity_type)
+            .sort("id", SortDirection::Asc)
+            .limit(limit);
+        if let Some(after) = after {
+            builder = builder.condition("id", ConditionOperator::GreaterThan, after);
+        }
+        let entities = self.query(&builder.build(), ctx).await?;
+        let next_cursor = if entities.len() < limit { None } else { entities.last().map(|e| e.id.clone()) };
+        Ok(ScanPage { entities, next_cursor })
+    }
+    
+    /// Up to `limit` matches of `query`, newest first by `(created_at, id)`
+    /// descending, starting strictly after `before` in that order (the last
+    /// entity of the previous page). `query`'s own sort and paging are
+    /// ignored. The default sorts every match; adapters with an index on
+    /// creation time should override it with a keyset walk of that index.
+    async fn query_newest(
+        &self,
+        query: &StorageQuery,
+        before: Option<(DateTime<Utc>, &str)>,
+        limit: usize,
+        ctx: &StorageContext,
+    ) -> Result<Vec<StoredEntity>, StorageError> {
+        let mut newest_first = query.clone();
+        newest_first.sort = Some(vec![
+            SortCriteria { field: "created_at".to_string(), direction: SortDirection::Desc },
+            SortCriteria { field: "id".to_string(), direction: SortDirection::Desc },
+        ]);
+        newest_first.limit = None;
+        newest_first.offset = None;
+        Ok(self
+            .query(&newest_first, ctx)
+            .await?
+            .into_iter()
+            .filter(|entity| before.map_or(true, |(created_at, id)| (entity.created_at, entity.id.as_str()) < (created_at, id)))
+            .take(limit)
+            .collect())
+    }
+    
     /// Batch operations
     async fn batch_put(&self, entities: Vec<(String, StoredEntity)>, ctx: &StorageContext) -> Result<(), StorageError>;
     
+    /// Hard delete many entities; adapters should override this with a single
+    /// bulk statement where the backend allows it
+    async fn batch_delete(&self, keys: &[String], ctx: &StorageContext) -> Result<(), StorageError> {
+        for key in keys {
+            self.purge(key, ctx).await?;
+        }
+        Ok(())
+    }
+    
+    /// The version of `key` that was current at `timestamp`: the newest one
+    /// written at or before it, live or superseded. Adapters that declare
+    /// `history` keep each version when a write replaces it, keyed on
+    /// `(key, updated_at)`, and drop it when the entity is purged. Without
+    /// history there is nothing to look in.
+    async fn get_version_as_of(&self, _key: &str, _timestamp: DateTime<Utc>, _ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
+        Ok(None)
+    }
+    
+    /// Drop all but the newest `keep` superseded versions of every key,
+    /// returning how many were removed. Live entities are never touched.
+    async fn prune_versions(&self, _keep: usize, _ctx: &StorageContext) -> Result<u64, StorageError> {
+        Ok(0)
+    }
+    
     /// Get storage statistics
     async fn get_stats(&self) -> Result<StorageStats, StorageError>;
     
-    /// Export data for backup
+    /// Export data for backup. Backups are restored by
+    /// `StorageManager::import_data`, which writes through `batch_put`.
     async fn export_data(&self, ctx: &StorageContext) -> Result<Vec<u8>, StorageError>;
-    
-    /// Import data from backup
-    async fn import_data(&mut self, data: &[u8], ctx: &StorageContext) -> Result<(), StorageError>;
 }
 
 /// Storage statistics
@@ -152,30 +706,97 @@ pub trait StorageAdapter: Send + Sync {
 pub struct StorageStats {
     pub total_entities: u64,
     pub entities_by_type: HashMap<String, u64>,
+    /// Stored (serialized) size per entity type; sums to `storage_size_bytes`
+    /// for backends that can measure it
+    #[serde(default)]
+    pub bytes_by_type: HashMap<String, u64>,
     pub storage_size_bytes: u64,
     pub last_sync: Option<DateTime<Utc>>,
     pub pending_changes: u64,
 }
 
+/// Storage used by one entity type, from `StorageManager::size_by_type`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityTypeUsage {
+    pub entity_type: String,
+    pub count: u64,
+    pub bytes: u64,
+}
+
 /// Main storage manager (simplified for community)
 pub struct StorageManager {
     adapters: HashMap<String, Box<dyn StorageAdapter>>,
-    primary_backend: String,
-    fallback_backends: Vec<String>,
+    // Behind a lock so the backends can be switched on a shared manager
+    selection: std::sync::RwLock<BackendSelection>,
     cache: Arc<RwLock<HashMap<String, CachedEntity>>>,
     metrics: StorageMetrics,
+    // Per-key locks taken by every write, so a read-modify-write such as
+    // `patch` can't be interleaved with a plain `put` to the same key
+    write_locks: KeyLocks,
+    // Schemas checked on `put` for entity types that have one registered
+    validation: Option<Arc<super::ValidationManager>>,
+    key_strategy: KeyStrategy,
+    // Next `Sequential` id per entity type, seeded from the stored count
+    sequences: tokio::sync::Mutex<HashMap<String, u64>>,
+    // Coalesces puts into batches when set (see `with_write_buffer`)
+    write_buffer: Option<WriteBufferConfig>,
+    pending_writes: tokio::sync::Mutex<PendingWrites>,
+    read_consistency: ReadConsistency,
 }
 
 impl std::fmt::Debug for StorageManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("StorageManager")
-            .field("primary_backend", &self.primary_backend)
-            .field("fallback_backends", &self.fallback_backends)
+            .field("primary_backend", &self.primary_backend())
+            .field("fallback_backends", &self.fallback_backends())
             .field("adapters_count", &self.adapters.len())
             .finish()
     }
 }
 
+/// Stripes of `KeyLocks` per manager
+const WRITE_LOCK_STRIPES: usize = 64;
+
+/// Write locks striped by key hash: a key always maps to the same lock, and
+/// writes to different keys rarely wait on each other
+struct KeyLocks {
+    stripes: Vec<tokio::sync::Mutex<()>>,
+}
+
+impl KeyLocks {
+    fn new(stripes: usize) -> Self {
+        Self { stripes: (0..stripes.max(1)).map(|_| tokio::sync::Mutex::new(())).collect() }
+    }
+    
+    fn stripe(&self, key: &str) -> usize {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.stripes.len() as u64) as usize
+    }
+    
+    async fn lock(&self, key: &str) -> tokio::sync::MutexGuard<'_, ()> {
+        self.stripes[self.stripe(key)].lock().await
+    }
+    
+    /// Lock every key, taking stripes in index order so overlapping batches
+    /// can't deadlock
+    async fn lock_all<'k>(&self, keys: impl IntoIterator<Item = &'k str>) -> Vec<tokio::sync::MutexGuard<'_, ()>> {
+        let stripes: std::collections::BTreeSet<usize> = keys.into_iter().map(|key| self.stripe(key)).collect();
+        let mut guards = Vec::with_capacity(stripes.len());
+        for stripe in stripes {
+            guards.push(self.stripes[stripe].lock().await);
+        }
+        guards
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BackendSelection {
+    primary: String,
+    fallbacks: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 struct CachedEntity {
     entity: StoredEntity,
@@ -183,12 +804,13 @@ struct CachedEntity {
     ttl_seconds: u64,
 }
 
-#[derive(Debug, Clone)]
+/// Hot-path counters; sharded so concurrent operations don't contend
+#[derive(Debug, Clone, Default)]
 struct StorageMetrics {
-    pub cache_hits: Arc<std::sync::atomic::AtomicU64>,
-    pub cache_misses: Arc<std::sync::atomic::AtomicU64>,
-    pub operations_total: Arc<std::sync::atomic::AtomicU64>,
-    pub errors_total: Arc<std::sync::atomic::AtomicU64>,
+    pub cache_hits: Arc<ShardedCounter>,
+    pub cache_misses: Arc<ShardedCounter>,
+    pub operations_total: Arc<ShardedCounter>,
+    pub errors_total: Arc<ShardedCounter>,
 }
 
 impl Default for StorageManager {
@@ -203,11 +825,28 @@ impl Default for StorageManager {
 /// runs and unit tests.
 pub struct MemoryAdapter {
     inner: Arc<RwLock<HashMap<String, StoredEntity>>>,
+    history: Arc<RwLock<HashMap<String, VersionHistory>>>,
 }
 
+/// Superseded versions of one key, ordered by `updated_at`
+type VersionHistory = BTreeMap<DateTime<Utc>, StoredEntity>;
+
 impl MemoryAdapter {
     pub fn new() -> Self {
-        Self { inner: Arc::new(RwLock::new(HashMap::new())) }
+        Self {
+            inner: Arc::new(RwLock::new(HashMap::new())),
+            history: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+    
+    /// Keep the live version a write replaced; soft-deleted ones are gone already
+    async fn archive(&self, replaced: Vec<(String, StoredEntity)>) {
+        let mut history = self.history.write().await;
+        for (key, entity) in replaced {
+            if entity.deleted_at.is_none() {
+                history.entry(key).or_default().insert(entity.updated_at, entity);
+            }
+        }
     }
 }
 
@@ -228,6 +867,14 @@ impl StorageAdapter for MemoryAdapter {
         Ok(())
     }
 
+    fn capabilities(&self) -> AdapterCapabilities {
+        AdapterCapabilities {
+            count: true,
+            history: true,
+            ..AdapterCapabilities::default()
+        }
+    }
+
     async fn get(&self, key: &str, _ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
         let map = self.inner.read().await;
         Ok(map.get(key).cloned())
@@ -235,7 +882,8 @@ impl StorageAdapter for MemoryAdapter {
 
     async fn put(&self, key: &str, entity: StoredEntity, _ctx: &StorageContext) -> Result<(), StorageError> {
         let mut map = self.inner.write().await;
-        map.insert(key.to_string(), entity);
+        let replaced = map.insert(key.to_string(), entity);
+        self.archive(replaced.map(|old| (key.to_string(), old)).into_iter().collect()).await;
         Ok(())
     }
 
@@ -251,19 +899,34 @@ impl StorageAdapter for MemoryAdapter {
     async fn purge(&self, key: &str, _ctx: &StorageContext) -> Result<(), StorageError> {
         let mut map = self.inner.write().await;
         map.remove(key);
+        self.history.write().await.remove(key);
+        Ok(())
+    }
+
+    async fn batch_delete(&self, keys: &[String], _ctx: &StorageContext) -> Result<(), StorageError> {
+        let mut map = self.inner.write().await;
+        let mut history = self.history.write().await;
+        for key in keys {
+            map.remove(key);
+            history.remove(key);
+        }
         Ok(())
     }
 
     async fn query(&self, query: &StorageQuery, _ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
         let map = self.inner.read().await;
-        let mut results = Vec::new();
-        for (_k, v) in map.iter() {
-            if let Some(ref et) = query.entity_type {
-                if &v.entity_type != et { continue; }
-            }
-            results.push(v.clone());
+        let mut keys: Vec<&String> = map.keys().collect();
+        keys.sort();
+
+        // Stable key order (or the query's sort) so callers can page with limit/offset
+        let matches = query.predicate();
+        let mut results: Vec<&StoredEntity> = keys.into_iter().map(|k| &map[k]).filter(|v| matches(v)).collect();
+        if query.is_sorted() {
+            results.sort_by(|a, b| query.compare(a, b));
         }
-        Ok(results)
+        let offset = query.offset.unwrap_or(0);
+        let limit = query.limit.unwrap_or(usize::MAX);
+        Ok(results.into_iter().skip(offset).take(limit).cloned().collect())
     }
 
     async fn get_by_type(&self, entity_type: &str, _ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
@@ -273,33 +936,63 @@ impl StorageAdapter for MemoryAdapter {
 
     async fn batch_put(&self, entities: Vec<(String, StoredEntity)>, _ctx: &StorageContext) -> Result<(), StorageError> {
         let mut map = self.inner.write().await;
+        let mut replaced = Vec::new();
         for (k, v) in entities {
-            map.insert(k, v);
+            if let Some(old) = map.insert(k.clone(), v) {
+                replaced.push((k, old));
+            }
         }
+        self.archive(replaced).await;
         Ok(())
     }
 
+    async fn get_version_as_of(&self, key: &str, timestamp: DateTime<Utc>, _ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
+        let map = self.inner.read().await;
+        if let Some(current) = map.get(key).filter(|e| e.updated_at <= timestamp) {
+            return Ok(Some(current.clone()));
+        }
+        let history = self.history.read().await;
+        Ok(history.get(key).and_then(|versions| versions.range(..=timestamp).next_back()).map(|(_, e)| e.clone()))
+    }
+
+    async fn prune_versions(&self, keep: usize, _ctx: &StorageContext) -> Result<u64, StorageError> {
+        let mut history = self.history.write().await;
+        let mut pruned = 0;
+        for versions in history.values_mut() {
+            let excess = versions.len().saturating_sub(keep);
+            let expired: Vec<DateTime<Utc>> = versions.keys().take(excess).copied().collect();
+            for updated_at in expired {
+                versions.remove(&updated_at);
+            }
+            pruned += excess as u64;
+        }
+        history.retain(|_, versions| !versions.is_empty());
+        Ok(pruned)
+    }
+
     async fn get_stats(&self) -> Result<StorageStats, StorageError> {
         let map = self.inner.read().await;
         let mut by_type: HashMap<String, u64> = HashMap::new();
+        let mut bytes_by_type: HashMap<String, u64> = HashMap::new();
         for v in map.values() {
             *by_type.entry(v.entity_type.clone()).or_insert(0) += 1;
+            // Nothing is serialized in memory; measure the canonical form so
+            // the numbers don't move between runs
+            let size = v.canonical_json().map_or(0, |bytes| bytes.len() as u64);
+            *bytes_by_type.entry(v.entity_type.clone()).or_insert(0) += size;
         }
         Ok(StorageStats {
             total_entities: map.len() as u64,
             entities_by_type: by_type,
-            storage_size_bytes: 0,
+            storage_size_bytes: bytes_by_type.values().sum(),
+            bytes_by_type,
             last_sync: None,
             pending_changes: 0,
         })
     }
 
     async fn export_data(&self, _ctx: &StorageContext) -> Result<Vec<u8>, StorageError> {
-        Err(StorageError::BackendError { backend: "memory".to_string(), error: "export not implemented".to_string() })
-    }
-
-    async fn import_data(&mut self, _data: &[u8], _ctx: &StorageContext) -> Result<(), StorageError> {
-        Err(StorageError::BackendError { backend: "memory".to_string(), error: "import not implemented".to_string() })
+        Err(StorageError::Unsupported { backend: "memory".to_string(), operation: StorageCapability::Export.as_str().to_string() })
     }
 }
 
@@ -322,39 +1015,128 @@ impl StorageManager {
 
                 m
             },
-            // Determine primary backend from env or default to memory
-            primary_backend: if let Ok(backend) = std::env::var("NODUS_STORAGE_BACKEND") {
-                backend
-            } else if std::env::var("NODUS_SQLITE_DB").is_ok() {
-                "sqlite".to_string()
-            } else {
-                "memory".to_string()
-            },
-            fallback_backends: vec!["memory".to_string()],
+            selection: std::sync::RwLock::new(BackendSelection {
+                // Determine primary backend from env or default to memory
+                primary: if let Ok(backend) = std::env::var("NODUS_STORAGE_BACKEND") {
+                    backend
+                } else if std::env::var("NODUS_SQLITE_DB").is_ok() {
+                    "sqlite".to_string()
+                } else {
+                    "memory".to_string()
+                },
+                fallbacks: vec!["memory".to_string()],
+            }),
             cache: Arc::new(RwLock::new(HashMap::new())),
-            metrics: StorageMetrics {
-                cache_hits: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-                cache_misses: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-                operations_total: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-                errors_total: Arc::new(std::sync::atomic::AtomicU64::new(0)),
-            },
+            metrics: StorageMetrics::default(),
+            write_locks: KeyLocks::new(WRITE_LOCK_STRIPES),
+            validation: None,
+            key_strategy: KeyStrategy::default(),
+            sequences: tokio::sync::Mutex::new(HashMap::new()),
+            write_buffer: None,
+            pending_writes: tokio::sync::Mutex::new(PendingWrites::default()),
+            read_consistency: ReadConsistency::default(),
         }
     }
     
+    /// Create a storage manager from explicit configuration instead of the
+    /// environment. The SQLite backend writes entities in
+    /// `config.serialization_format`.
+    pub fn with_config(config: &StorageConfig) -> Result<Self, StorageError> {
+        let mut manager = Self::new();
+        let db_path = std::env::var("NODUS_SQLITE_DB").unwrap_or_else(|_| "./nodus.sqlite".to_string());
+        manager.register_adapter(
+            "sqlite".to_string(),
+            Box::new(super::sqlite_adapter::SqliteAdapter::new(db_path).with_serialization_format(config.serialization_format)),
+        );
+        manager.set_backends(config.primary_backend.clone(), config.fallback_backends.clone())?;
+        manager.write_buffer = config.write_buffer.clone();
+        manager.read_consistency = config.read_consistency;
+        Ok(manager.with_key_strategy(config.key_strategy.clone()))
+    }
+    
+    /// Key generation for `insert`
+    pub fn with_key_strategy(mut self, key_strategy: KeyStrategy) -> Self {
+        self.key_strategy = key_strategy;
+        self
+    }
+    
+    /// Buffer puts and write them to the primary backend in batches (see
+    /// `WriteBufferConfig`). `get` reads through the buffer; queries, deletes
+    /// and exports flush it first. Call `flush` when a write must be durable
+    /// now, and run `spawn_write_buffer_flusher` so a quiet buffer still drains.
+    pub fn with_write_buffer(mut self, config: WriteBufferConfig) -> Self {
+        self.write_buffer = Some(config);
+        self
+    }
+    
+    /// Which backends `get` may serve from (see `ReadConsistency`)
+    pub fn with_read_consistency(mut self, read_consistency: ReadConsistency) -> Self {
+        self.read_consistency = read_consistency;
+        self
+    }
+    
+    pub fn read_consistency(&self) -> ReadConsistency {
+        self.read_consistency
+    }
+    
+    /// Validate entities on `put` against the schema registered for their type
+    pub fn with_validation(mut self, validation: Arc<super::ValidationManager>) -> Self {
+        self.validation = Some(validation);
+        self
+    }
+    
     /// Register a storage adapter
     pub fn register_adapter(&mut self, name: String, adapter: Box<dyn StorageAdapter>) {
         self.adapters.insert(name, adapter);
     }
     
-    /// Set primary backend
-    pub fn set_primary_backend(&mut self, backend: String) -> Result<(), StorageError> {
-        if !self.adapters.contains_key(&backend) {
+    /// Set primary backend. Cached entities are kept; they are still valid
+    /// until their TTL regardless of which backend serves misses.
+    pub fn set_primary_backend(&self, backend: String) -> Result<(), StorageError> {
+        self.ensure_registered(&backend)?;
+        self.selection.write().unwrap_or_else(|e| e.into_inner()).primary = backend;
+        Ok(())
+    }
+    
+    /// Replace the fallback order used when the primary backend fails
+    pub fn set_fallback_backends(&self, backends: Vec<String>) -> Result<(), StorageError> {
+        for backend in &backends {
+            self.ensure_registered(backend)?;
+        }
+        self.selection.write().unwrap_or_else(|e| e.into_inner()).fallbacks = backends;
+        Ok(())
+    }
+    
+    /// Switch primary and fallbacks together; nothing changes unless every
+    /// backend is registered
+    pub fn set_backends(&self, primary: String, fallbacks: Vec<String>) -> Result<(), StorageError> {
+        self.ensure_registered(&primary)?;
+        for backend in &fallbacks {
+            self.ensure_registered(backend)?;
+        }
+        let mut selection = self.selection.write().unwrap_or_else(|e| e.into_inner());
+        selection.primary = primary;
+        selection.fallbacks = fallbacks;
+        Ok(())
+    }
+    
+    /// Name of the current primary backend
+    pub fn primary_backend(&self) -> String {
+        self.selection.read().unwrap_or_else(|e| e.into_inner()).primary.clone()
+    }
+    
+    /// Current fallback order
+    pub fn fallback_backends(&self) -> Vec<String> {
+        self.selection.read().unwrap_or_else(|e| e.into_inner()).fallbacks.clone()
+    }
+    
+    fn ensure_registered(&self, backend: &str) -> Result<(), StorageError> {
+        if !self.adapters.contains_key(backend) {
             return Err(StorageError::BackendError {
-                backend: backend.clone(),
+                backend: backend.to_string(),
                 error: "Adapter not registered".to_string(),
             });
         }
-        self.primary_backend = backend;
         Ok(())
     }
     
@@ -369,56 +1151,128 @@ impl StorageManager {
         Ok(())
     }
     
-    /// Get an entity with caching and fallback
+    /// Operation, cache and error counts since the manager was created
+    pub fn metrics_snapshot(&self) -> StorageMetricsSnapshot {
+        StorageMetricsSnapshot {
+            cache_hits: self.metrics.cache_hits.get(),
+            cache_misses: self.metrics.cache_misses.get(),
+            operations_total: self.metrics.operations_total.get(),
+            errors_total: self.metrics.errors_total.get(),
+        }
+    }
+    
+    /// Drop every cached entity so the next reads go to the backend (e.g.
+    /// after another process edited the database). Returns how many were dropped.
+    pub async fn clear_cache(&self) -> usize {
+        let mut cache = self.cache.write().await;
+        let cleared = cache.len();
+        cache.clear();
+        cleared
+    }
+    
+    /// Drop one cached entity, returning whether it was cached
+    pub async fn invalidate_cache(&self, key: &str) -> bool {
+        self.cache.write().await.remove(key).is_some()
+    }
+    
+    /// Get an entity with caching, falling back to other backends as the
+    /// read consistency allows
+    #[instrument(name = "storage_get", level = "debug", skip_all, fields(key = %key, backend = %self.primary_backend()))]
     pub async fn get(&self, key: &str, ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
-        self.metrics.operations_total.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.metrics.operations_total.increment();
+        
+        // Writes not yet flushed are the newest version
+        if self.write_buffer.is_some() {
+            if let Some(entity) = self.pending_writes.lock().await.entries.get(key) {
+                self.metrics.cache_hits.increment();
+                return Ok(Some(entity.clone()));
+            }
+        }
         
         // Check cache first
         if let Some(entity) = self.get_from_cache(key).await {
-            self.metrics.cache_hits.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            self.metrics.cache_hits.increment();
+            tracing::trace!("cache hit");
             return Ok(Some(entity));
         }
         
-        self.metrics.cache_misses.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.metrics.cache_misses.increment();
+        
+        if self.read_consistency == ReadConsistency::PreferFresh {
+            return self.get_freshest(key, ctx).await;
+        }
         
         // Try primary backend first
-        match self.get_from_backend(&self.primary_backend, key, ctx).await {
+        match self.get_from_backend(&self.primary_backend(), key, ctx).await {
             Ok(Some(entity)) => {
                 self.cache_entity(key, &entity).await;
                 Ok(Some(entity))
             }
             Ok(None) => Ok(None),
+            Err(e) if self.read_consistency == ReadConsistency::PrimaryOnly => {
+                self.metrics.errors_total.increment();
+                Err(e)
+            }
             Err(e) => {
-                println!("[StorageManager] Primary backend failed for key {}: {}", key, e);
+                tracing::warn!(error = %e, "primary backend failed, trying fallbacks");
                 
                 // Try fallback backends
-                for backend in &self.fallback_backends {
+                for backend in &self.fallback_backends() {
                     if let Ok(Some(entity)) = self.get_from_backend(backend, key, ctx).await {
+                        tracing::debug!(fallback = %backend, "served from fallback backend");
                         self.cache_entity(key, &entity).await;
                         return Ok(Some(entity));
                     }
                 }
                 
-                self.metrics.errors_total.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                self.metrics.errors_total.increment();
                 Err(e)
             }
         }
     }
     
-    /// Put an entity with sync
-    pub async fn put(&self, key: &str, mut entity: StoredEntity, ctx: &StorageContext) -> Result<(), StorageError> {
-        self.metrics.operations_total.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    /// Put an entity with sync. Waits for any `patch`, `merge` or `insert`
+    /// in progress on the same key.
+    pub async fn put(&self, key: &str, entity: StoredEntity, ctx: &StorageContext) -> Result<(), StorageError> {
+        let _guard = self.write_locks.lock(key).await;
+        self.write(key, entity, ctx).await
+    }
+    
+    /// Validate, stamp and store an entity; the caller holds the key's write lock
+    #[instrument(name = "storage_put", level = "debug", skip_all, fields(key = %key, backend = %self.primary_backend(), entity_type = %entity.entity_type))]
+    async fn write(&self, key: &str, mut entity: StoredEntity, ctx: &StorageContext) -> Result<(), StorageError> {
+        self.metrics.operations_total.increment();
+        
+        if !ctx.skip_validation {
+            self.validate_entity(&entity, ctx).await?;
+        }
         
         // Update metadata
         entity.updated_at = Utc::now();
         entity.updated_by = ctx.user_id.clone();
-        entity.version += 1;
+        // Versions only move forward; at the ceiling they stay there rather than wrap
+        entity.version = entity.version.saturating_add(1);
         entity.sync_status = SyncStatus::Pending;
         
-        // Store in primary backend
-        let adapter = self.adapters.get(&self.primary_backend)
-            .ok_or_else(|| StorageError::BackendError {
-                backend: self.primary_backend.clone(),
+        if let Some(config) = &self.write_buffer {
+            let mut pending = self.pending_writes.lock().await;
+            pending.insert(key, entity.clone());
+            self.cache_entity(key, &entity).await;
+            // The entity is buffered either way; a failed flush is retried by
+            // the next one, and `flush` and the flusher task report it
+            if pending.is_due(config) {
+                if let Err(e) = self.flush_pending(&mut pending).await {
+                    tracing::warn!("Write buffer flush failed: {}", e);
+                }
+            }
+            return Ok(());
+        }
+        
+        // Store in primary backend
+        let backend = self.primary_backend();
+        let adapter = self.adapters.get(&backend)
+            .ok_or_else(|| StorageError::BackendError {
+                backend: backend.clone(),
                 error: "Adapter not found".to_string(),
             })?;
         
@@ -427,57 +1281,698 @@ impl StorageManager {
         // Update cache
         self.cache_entity(key, &entity).await;
         
-        println!("[StorageManager] Entity stored: {}", key);
+        tracing::debug!(version = entity.version, "entity stored");
+        
+        Ok(())
+    }
+    
+    /// Write every buffered put to the primary backend in one `batch_put`,
+    /// returning how many entities were written. A no-op without a write
+    /// buffer.
+    pub async fn flush(&self) -> Result<usize, StorageError> {
+        if self.write_buffer.is_none() {
+            return Ok(0);
+        }
+        let mut pending = self.pending_writes.lock().await;
+        self.flush_pending(&mut pending).await
+    }
+    
+    /// Flush the write buffer every `flush_interval_ms` until the returned
+    /// task is aborted. Returns `None` when there is no write buffer.
+    pub fn spawn_write_buffer_flusher(self: Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let period = self.write_buffer.as_ref()?.flush_interval();
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.flush().await {
+                    tracing::warn!("Write buffer flush failed: {}", e);
+                }
+            }
+        }))
+    }
+    
+    /// On failure the writes stay buffered, so the next flush retries them
+    async fn flush_pending(&self, pending: &mut PendingWrites) -> Result<usize, StorageError> {
+        if pending.entries.is_empty() {
+            return Ok(0);
+        }
+        let backend = self.primary_backend();
+        self.ensure_registered(&backend)?;
+        let ctx = StorageContext {
+            user_id: "system".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            skip_validation: true,
+        };
+        let entities: Vec<(String, StoredEntity)> = pending.entries
+            .iter()
+            .map(|(key, entity)| (key.clone(), entity.clone()))
+            .collect();
+        let flushed = entities.len();
+        if let Err(e) = self.adapters[&backend].batch_put(entities, &ctx).await {
+            self.metrics.errors_total.increment();
+            return Err(e);
+        }
+        pending.clear();
+        tracing::debug!(flushed, backend = %backend, "write buffer flushed");
+        Ok(flushed)
+    }
+    
+    /// Reject entities that fail their type's schema. Types without a
+    /// registered schema are stored unchecked.
+    async fn validate_entity(&self, entity: &StoredEntity, ctx: &StorageContext) -> Result<(), StorageError> {
+        let Some(validation) = &self.validation else {
+            return Ok(());
+        };
+        if !validation.has_schema(&entity.entity_type).await {
+            return Ok(());
+        }
+        
+        let validation_ctx = super::validation_mod::ValidationContext {
+            user_id: ctx.user_id.clone(),
+            session_id: ctx.session_id,
+            operation_id: ctx.operation_id,
+            entity_type: Some(entity.entity_type.clone()),
+            validation_mode: super::validation_mod::ValidationMode::Strict,
+        };
+        let result = validation
+            .validate(&entity.data, &entity.entity_type, &validation_ctx)
+            .await
+            .map_err(|e| StorageError::ValidationFailed { error: e.to_string() })?;
+        
+        if result.is_valid {
+            Ok(())
+        } else {
+            let errors: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+            Err(StorageError::ValidationFailed { error: errors.join("; ") })
+        }
+    }
+    
+    /// Hard delete many entities from the primary backend in one batch
+    pub async fn batch_delete(&self, keys: &[String], ctx: &StorageContext) -> Result<(), StorageError> {
+        self.flush().await?;
+        let backend = self.primary_backend();
+        self.ensure_registered(&backend)?;
+        self.adapters[&backend].batch_delete(keys, ctx).await?;
         
+        let mut cache = self.cache.write().await;
+        for key in keys {
+            cache.remove(key);
+        }
         Ok(())
     }
     
-    /// Delete an entity
-    pub async fn delete(&self, key: &str, ctx: &StorageContext) -> Result<(), StorageError> {
-        self.metrics.operations_total.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    /// Hard delete every `entity_type` entity created before `cutoff`.
+    /// Returns how many were removed.
+    pub async fn purge_older_than(&self, entity_type: &str, cutoff: DateTime<Utc>) -> Result<u64, StorageError> {
+        self.flush().await?;
+        let backend = self.primary_backend();
+        self.ensure_registered(&backend)?;
+        let adapter = &self.adapters[&backend];
+        let ctx = StorageContext {
+            user_id: "system".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            skip_validation: true,
+        };
+        
+        // Collect first so deleting doesn't shift the pages being read
+        let mut expired = Vec::new();
+        let mut offset = 0;
+        loop {
+            let query = StorageQuery::builder()
+                .entity_type(entity_type)
+                .limit(DEFAULT_EXPORT_CHUNK_SIZE)
+                .offset(offset)
+                .include_deleted(true)
+                .build();
+            let page = adapter.query(&query, &ctx).await?;
+            let fetched = page.len();
+            offset += fetched;
+            expired.extend(
+                page.into_iter()
+                    .filter(|e| e.entity_type == entity_type && e.created_at < cutoff)
+                    .map(|e| e.id),
+            );
+            if fetched < DEFAULT_EXPORT_CHUNK_SIZE {
+                break;
+            }
+        }
+        
+        for keys in expired.chunks(DEFAULT_EXPORT_CHUNK_SIZE) {
+            self.batch_delete(keys, &ctx).await?;
+        }
+        Ok(expired.len() as u64)
+    }
+    
+    /// Purge audit events older than `older_than_days`
+    pub async fn purge_audit_events(&self, older_than_days: u32) -> Result<u64, StorageError> {
+        let cutoff = Utc::now() - chrono::Duration::days(older_than_days as i64);
+        let purged = self.purge_older_than(AUDIT_EVENT_TYPE, cutoff).await?;
+        tracing::info!("Purged {} audit event(s) older than {} day(s)", purged, older_than_days);
+        Ok(purged)
+    }
+    
+    /// Run `purge_audit_events` on the policy's interval until the returned
+    /// task is aborted. Returns `None` when retention is disabled.
+    pub fn spawn_audit_retention(self: Arc<Self>, policy: AuditRetentionPolicy) -> Option<tokio::task::JoinHandle<()>> {
+        if !policy.enabled {
+            return None;
+        }
+        let period = std::time::Duration::from_secs(policy.purge_interval_secs.max(1));
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.purge_audit_events(policy.retention_days).await {
+                    tracing::warn!("Audit retention purge failed: {}", e);
+                }
+            }
+        }))
+    }
+    
+    /// Drop all but the newest `keep_versions` superseded versions of each
+    /// entity from the primary backend's history. Returns how many were
+    /// removed; 0 on backends without history.
+    pub async fn prune_version_history(&self, keep_versions: usize) -> Result<u64, StorageError> {
+        self.flush().await?;
+        let backend = self.primary_backend();
+        self.ensure_registered(&backend)?;
+        let ctx = StorageContext {
+            user_id: "system".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            skip_validation: true,
+        };
+        let pruned = self.adapters[&backend].prune_versions(keep_versions, &ctx).await?;
+        tracing::debug!(pruned, keep_versions, "version history pruned");
+        Ok(pruned)
+    }
+    
+    /// Run `prune_version_history` on the policy's interval until the
+    /// returned task is aborted. Returns `None` when pruning is disabled.
+    pub fn spawn_version_pruning(self: Arc<Self>, policy: VersionRetentionPolicy) -> Option<tokio::task::JoinHandle<()>> {
+        if !policy.enabled {
+            return None;
+        }
+        let period = std::time::Duration::from_secs(policy.prune_interval_secs.max(1));
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.prune_version_history(policy.keep_versions).await {
+                    tracing::warn!("Version history pruning failed: {}", e);
+                }
+            }
+        }))
+    }
+    
+    /// Apply a JSON Patch to an entity's `data` and store it (bumping the
+    /// version). The patch is all-or-nothing: if any operation fails, nothing
+    /// is written.
+    #[instrument(name = "storage_patch", level = "debug", skip_all, fields(key = %key, backend = %self.primary_backend(), ops = patch.len()))]
+    pub async fn patch(&self, key: &str, patch: Vec<PatchOp>, ctx: &StorageContext) -> Result<StoredEntity, StorageError> {
+        let _guard = self.write_locks.lock(key).await;
+        
+        let mut entity = self.get(key, ctx).await?
+            .ok_or_else(|| StorageError::NotFound { key: key.to_string() })?;
+        
+        json_patch::patch(&mut entity.data, &patch)
+            .map_err(|e| StorageError::ValidationFailed { error: format!("JSON patch failed: {}", e) })?;
+        
+        self.write(key, entity, ctx).await?;
+        self.get(key, ctx).await?
+            .ok_or_else(|| StorageError::NotFound { key: key.to_string() })
+    }
+    
+    /// Store a new entity under a key generated by the configured
+    /// `KeyStrategy`, returning the key (also set as the entity's `id`).
+    /// Taken keys are skipped; after `MAX_KEY_ATTEMPTS` candidates it fails
+    /// with `KeyExists`.
+    #[instrument(name = "storage_insert", level = "debug", skip_all, fields(backend = %self.primary_backend(), entity_type = %entity.entity_type))]
+    pub async fn insert(&self, mut entity: StoredEntity, ctx: &StorageContext) -> Result<String, StorageError> {
+        let mut last = String::new();
+        for attempt in 0..MAX_KEY_ATTEMPTS {
+            let key = format!("{}:{}", entity.entity_type, self.key_candidate(&entity, attempt, ctx).await?);
+            let _guard = self.write_locks.lock(&key).await;
+            if self.get(&key, ctx).await?.is_none() {
+                entity.id = key.clone();
+                self.write(&key, entity, ctx).await?;
+                return Ok(key);
+            }
+            last = key;
+        }
+        Err(StorageError::KeyExists { key: last })
+    }
+    
+    /// The `attempt`-th id to try for a new entity
+    async fn key_candidate(&self, entity: &StoredEntity, attempt: u32, ctx: &StorageContext) -> Result<String, StorageError> {
+        match &self.key_strategy {
+            KeyStrategy::Uuid => Ok(Uuid::new_v4().to_string()),
+            KeyStrategy::Slug { field } => {
+                let slug = entity.data.get(field).and_then(Value::as_str).map(slugify).unwrap_or_default();
+                if slug.is_empty() {
+                    return Err(StorageError::ValidationFailed {
+                        error: format!("cannot derive a key: `{}` is missing or has no letters or digits", field),
+                    });
+                }
+                Ok(if attempt == 0 { slug } else { format!("{}-{}", slug, attempt + 1) })
+            }
+            KeyStrategy::Sequential => {
+                let mut sequences = self.sequences.lock().await;
+                let next = match sequences.get(&entity.entity_type) {
+                    Some(next) => *next,
+                    None => {
+                        self.flush().await?;
+                        let backend = self.primary_backend();
+                        self.ensure_registered(&backend)?;
+                        self.adapters[&backend].get_by_type(&entity.entity_type, ctx).await?.len() as u64 + 1
+                    }
+                };
+                sequences.insert(entity.entity_type.clone(), next + 1);
+                Ok(next.to_string())
+            }
+        }
+    }
+    
+    /// The entity as it was at `timestamp`, or `None` if it didn't exist yet
+    /// (or that version has since been pruned, see `prune_version_history`),
+    /// read from the primary backend's version history. Fails with
+    /// `Unsupported` on backends that keep no history.
+    pub async fn get_as_of(&self, key: &str, timestamp: DateTime<Utc>, ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
+        self.flush().await?;
+        let adapter = self.require_capability(&self.primary_backend(), StorageCapability::History)?;
+        adapter.get_version_as_of(key, timestamp, ctx).await
+    }
+    
+    /// Deep-merge `partial` into an entity's `data` and store it (bumping the
+    /// version), creating the entity if it doesn't exist. Objects merge key
+    /// by key; anything else in `partial` replaces the existing value. A new
+    /// entity takes its type from the key prefix (`task:1` -> `task`).
+    #[instrument(name = "storage_merge", level = "debug", skip_all, fields(key = %key, backend = %self.primary_backend()))]
+    pub async fn merge(&self, key: &str, partial: Value, ctx: &StorageContext) -> Result<StoredEntity, StorageError> {
+        let _guard = self.write_locks.lock(key).await;
+        
+        let entity = match self.get(key, ctx).await? {
+            Some(mut entity) => {
+                deep_merge(&mut entity.data, partial);
+                entity
+            }
+            None => {
+                let now = Utc::now();
+                StoredEntity {
+                    id: key.to_string(),
+                    entity_type: key.split(':').next().unwrap_or(key).to_string(),
+                    data: partial,
+                    created_at: now,
+                    updated_at: now,
+                    created_by: ctx.user_id.clone(),
+                    updated_by: ctx.user_id.clone(),
+                    version: 0,
+                    deleted_at: None,
+                    sync_status: SyncStatus::Pending,
+                }
+            }
+        };
+        
+        self.write(key, entity, ctx).await?;
+        self.get(key, ctx).await?
+            .ok_or_else(|| StorageError::NotFound { key: key.to_string() })
+    }
+    
+    /// Delete an entity, cascading to its relationships as `cascade` says.
+    /// Returns the ids of every entity deleted, starting with `key`.
+    #[instrument(name = "storage_delete", level = "debug", skip_all, fields(key = %key, backend = %self.primary_backend(), cascade = ?cascade))]
+    pub async fn delete(&self, key: &str, cascade: &CascadeMode, ctx: &StorageContext) -> Result<Vec<String>, StorageError> {
+        self.metrics.operations_total.increment();
+        self.flush().await?;
         
         // Delete from primary backend
-        let adapter = self.adapters.get(&self.primary_backend)
+        let backend = self.primary_backend();
+        let adapter = self.adapters.get(&backend)
             .ok_or_else(|| StorageError::BackendError {
-                backend: self.primary_backend.clone(),
+                backend: backend.clone(),
                 error: "Adapter not found".to_string(),
             })?;
         
-        adapter.delete(key, ctx).await?;
+        // Collect the cascade before deleting anything
+        let mut affected = vec![key.to_string()];
+        if *cascade != CascadeMode::None {
+            let edges = self.relationships_of(key, ctx).await?;
+            if let CascadeMode::DeleteRelated { entity_types } = cascade {
+                for other in edges.iter().filter_map(|edge| other_end(edge, key)) {
+                    if affected.iter().any(|id| id == other) {
+                        continue;
+                    }
+                    let related = adapter.get(other, ctx).await?;
+                    if related.map_or(false, |e| e.deleted_at.is_none() && entity_types.contains(&e.entity_type)) {
+                        affected.push(other.to_string());
+                    }
+                }
+            }
+            affected.extend(edges.into_iter().map(|edge| edge.id));
+        }
         
-        // Remove from cache
-        self.evict_from_cache(key).await;
+        for id in &affected {
+            adapter.delete(id, ctx).await?;
+            // Remove from cache
+            self.invalidate_cache(id).await;
+        }
+        tracing::debug!(affected = affected.len(), "delete completed");
         
-        Ok(())
+        Ok(affected)
+    }
+    
+    /// Live relationships with `key` at either end
+    async fn relationships_of(&self, key: &str, ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
+        let query = StorageQuery::builder().entity_type(RELATIONSHIP_ENTITY_TYPE).build();
+        Ok(self.query(&query, ctx).await?
+            .into_iter()
+            .filter(|edge| edge.deleted_at.is_none() && edge.id != key && other_end(edge, key).is_some())
+            .collect())
     }
     
     /// Query entities
+    #[instrument(name = "storage_query", level = "debug", skip_all, fields(entity_type = ?query.entity_type, backend = %self.primary_backend()))]
     pub async fn query(&self, query: &StorageQuery, ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
-        self.metrics.operations_total.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.metrics.operations_total.increment();
+        self.flush().await?;
         
         // Query primary backend
-        let adapter = self.adapters.get(&self.primary_backend)
+        let backend = self.primary_backend();
+        let adapter = self.adapters.get(&backend)
             .ok_or_else(|| StorageError::BackendError {
-                backend: self.primary_backend.clone(),
+                backend: backend.clone(),
                 error: "Adapter not found".to_string(),
             })?;
         
         let results = adapter.query(query, ctx).await?;
+        tracing::debug!(results = results.len(), "query completed");
         
         Ok(results)
     }
     
+    /// One page of `query`'s matches on the primary backend, newest first
+    /// (see `StorageAdapter::query_newest`). Pass the last entity's
+    /// `(created_at, id)` as `before` to get the next page.
+    pub async fn query_newest(
+        &self,
+        query: &StorageQuery,
+        before: Option<(DateTime<Utc>, &str)>,
+        limit: usize,
+        ctx: &StorageContext,
+    ) -> Result<Vec<StoredEntity>, StorageError> {
+        self.metrics.operations_total.increment();
+        self.flush().await?;
+        let backend = self.primary_backend();
+        self.ensure_registered(&backend)?;
+        self.adapters[&backend].query_newest(query, before, limit, ctx).await
+    }
+    
     /// Get storage statistics
+    #[instrument(name = "storage_stats", level = "debug", skip_all, fields(backend = %self.primary_backend()))]
     pub async fn get_stats(&self) -> Result<StorageStats, StorageError> {
-        let adapter = self.adapters.get(&self.primary_backend)
-            .ok_or_else(|| StorageError::BackendError {
-                backend: self.primary_backend.clone(),
-                error: "Adapter not found".to_string(),
-            })?;
-        
+        self.flush().await?;
+        let adapter = self.require_capability(&self.primary_backend(), StorageCapability::Count)?;
         adapter.get_stats().await
     }
     
+    /// Entity count and stored size per type on the primary backend, largest
+    /// first
+    pub async fn size_by_type(&self) -> Result<Vec<EntityTypeUsage>, StorageError> {
+        let stats = self.get_stats().await?;
+        let mut usage: Vec<EntityTypeUsage> = stats
+            .entities_by_type
+            .iter()
+            .map(|(entity_type, count)| EntityTypeUsage {
+                entity_type: entity_type.clone(),
+                count: *count,
+                bytes: stats.bytes_by_type.get(entity_type).copied().unwrap_or(0),
+            })
+            .collect();
+        usage.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.entity_type.cmp(&b.entity_type)));
+        Ok(usage)
+    }
+    
+    /// Export all data from the primary backend
+    pub async fn export_data(&self, ctx: &StorageContext) -> Result<Vec<u8>, StorageError> {
+        self.flush().await?;
+        let adapter = self.require_capability(&self.primary_backend(), StorageCapability::Export)?;
+        adapter.export_data(ctx).await
+    }
+    
+    /// Import a backup (a JSON array of entities, or JSON Lines as written by
+    /// `export_stream`) into the primary backend. Each record is decoded and
+    /// validated on its own; in lenient mode bad records are reported by index
+    /// and the rest are imported, in strict mode any bad record aborts the
+    /// import before anything is written.
+    pub async fn import_data(&self, data: &[u8], mode: ImportMode, ctx: &StorageContext) -> Result<ImportReport, StorageError> {
+        // Earlier buffered writes must not land on top of the import
+        self.flush().await?;
+        let backend = self.primary_backend();
+        self.ensure_registered(&backend)?;
+        
+        let mut entities = Vec::new();
+        let mut errors = Vec::new();
+        for (index, record) in parse_import_records(data)?.into_iter().enumerate() {
+            let checked = match record {
+                Ok(entity) if !ctx.skip_validation => self
+                    .validate_entity(&entity, ctx)
+                    .await
+                    .map(|_| entity)
+                    .map_err(|e| e.to_string()),
+                other => other,
+            };
+            match checked {
+                Ok(entity) => entities.push((entity.id.clone(), entity)),
+                Err(error) => errors.push(ImportRecordError { index, error }),
+            }
+        }
+        
+        if mode == ImportMode::Strict && !errors.is_empty() {
+            let details: Vec<String> = errors.iter()
+                .map(|e| format!("record {}: {}", e.index, e.error))
+                .collect();
+            return Err(StorageError::ValidationFailed {
+                error: format!("{} invalid record(s), nothing imported: {}", errors.len(), details.join("; ")),
+            });
+        }
+        
+        let report = ImportReport {
+            imported: entities.len() as u64,
+            failed: errors.len() as u64,
+            errors,
+        };
+        if !entities.is_empty() {
+            let _guards = self.write_locks.lock_all(entities.iter().map(|(key, _)| key.as_str())).await;
+            self.adapters[&backend].batch_put(entities, ctx).await?;
+        }
+        
+        // Imported entities may replace cached ones
+        self.cache.write().await.clear();
+        
+        tracing::info!("Imported {} entities into {} ({} failed)", report.imported, backend, report.failed);
+        Ok(report)
+    }
+    
+    /// Copy every entity (including soft-deleted ones) from `source` into
+    /// `destination`, optionally making `destination` the primary on success.
+    /// The two must be different backends.
+    pub async fn migrate_backend(&self, source: &str, destination: &str, switch_primary: bool) -> Result<MigrationReport, StorageError> {
+        self.migrate_backend_with_progress(source, destination, DEFAULT_MIGRATION_CHUNK_SIZE, switch_primary, |_| {}).await
+    }
+    
+    /// Like `migrate_backend`, reading `chunk_size` entities at a time so large
+    /// stores are never loaded into memory at once. `on_progress` is called
+    /// after each chunk is written. Entities are written under their `id`.
+    pub async fn migrate_backend_with_progress<F>(
+        &self,
+        source: &str,
+        destination: &str,
+        chunk_size: usize,
+        switch_primary: bool,
+        on_progress: F,
+    ) -> Result<MigrationReport, StorageError>
+    where
+        F: Fn(&MigrationReport),
+    {
+        if source == destination {
+            return Err(StorageError::ValidationFailed {
+                error: format!("cannot migrate backend {} onto itself", source),
+            });
+        }
+        self.flush().await?;
+        self.ensure_registered(source)?;
+        self.ensure_registered(destination)?;
+        let source_adapter = &self.adapters[source];
+        let destination_adapter = &self.adapters[destination];
+        let ctx = StorageContext {
+            user_id: "system".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            skip_validation: true,
+        };
+        let chunk_size = chunk_size.max(1);
+        
+        let mut report = MigrationReport {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            entities_migrated: 0,
+            deleted_entities_migrated: 0,
+            chunks: 0,
+            switched_primary: false,
+        };
+        
+        loop {
+            let query = StorageQuery::builder()
+                .limit(chunk_size)
+                .offset(report.entities_migrated as usize)
+                .include_deleted(true)
+                .build();
+            let chunk = source_adapter.query(&query, &ctx).await?;
+            if chunk.is_empty() {
+                break;
+            }
+            
+            let fetched = chunk.len();
+            report.deleted_entities_migrated += chunk.iter().filter(|e| e.deleted_at.is_some()).count() as u64;
+            let entities = chunk.into_iter().map(|e| (e.id.clone(), e)).collect();
+            destination_adapter.batch_put(entities, &ctx).await?;
+            
+            report.entities_migrated += fetched as u64;
+            report.chunks += 1;
+            tracing::info!(
+                "Migrated {} entities from {} to {} ({} chunk(s))",
+                report.entities_migrated, source, destination, report.chunks
+            );
+            on_progress(&report);
+            
+            if fetched < chunk_size {
+                break;
+            }
+        }
+        
+        if switch_primary {
+            self.set_primary_backend(destination.to_string())?;
+            report.switched_primary = true;
+        }
+        
+        Ok(report)
+    }
+    
+    /// Export every entity (including soft-deleted ones) from the primary
+    /// backend without holding the whole store in memory. Entities are read a
+    /// page at a time and handed to `on_chunk` as JSON Lines (one entity per
+    /// line), so the chunks can be concatenated straight into a backup file.
+    pub async fn export_stream<F>(&self, chunk_size: usize, mut on_chunk: F) -> Result<ExportReport, StorageError>
+    where
+        F: FnMut(Vec<u8>) -> Result<(), StorageError>,
+    {
+        self.flush().await?;
+        let backend = self.primary_backend();
+        self.ensure_registered(&backend)?;
+        let adapter = &self.adapters[&backend];
+        let ctx = StorageContext {
+            user_id: "system".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            skip_validation: true,
+        };
+        let chunk_size = chunk_size.max(1);
+        
+        let mut report = ExportReport {
+            backend: backend.clone(),
+            entities_exported: 0,
+            chunks: 0,
+            bytes: 0,
+        };
+        
+        loop {
+            let query = StorageQuery::builder()
+                .limit(chunk_size)
+                .offset(report.entities_exported as usize)
+                .include_deleted(true)
+                .build();
+            let page = adapter.query(&query, &ctx).await?;
+            if page.is_empty() {
+                break;
+            }
+            
+            let fetched = page.len();
+            let mut chunk = Vec::new();
+            for entity in &page {
+                serde_json::to_writer(&mut chunk, entity)
+                    .map_err(|e| StorageError::SerializationError { error: e.to_string() })?;
+                chunk.push(b'\n');
+            }
+            
+            report.entities_exported += fetched as u64;
+            report.chunks += 1;
+            report.bytes += chunk.len() as u64;
+            on_chunk(chunk)?;
+            
+            if fetched < chunk_size {
+                break;
+            }
+        }
+        
+        tracing::info!(
+            "Exported {} entities from {} ({} chunk(s), {} bytes)",
+            report.entities_exported, backend, report.chunks, report.bytes
+        );
+        Ok(report)
+    }
+    
+    /// Every live entity of a type on the primary backend, as a stream of
+    /// batches of `DEFAULT_SCAN_BATCH_SIZE`. Pages are fetched by cursor as
+    /// the stream is polled, so memory stays bounded by one batch however
+    /// many entities there are.
+    pub fn iter_by_type<'a>(
+        &'a self,
+        entity_type: &'a str,
+        ctx: &'a StorageContext,
+    ) -> impl Stream<Item = Result<Vec<StoredEntity>, StorageError>> + Send + 'a {
+        self.iter_by_type_in_batches(entity_type, DEFAULT_SCAN_BATCH_SIZE, ctx)
+    }
+    
+    /// `iter_by_type` with batches of at most `batch_size` entities. Empty
+    /// batches are never yielded.
+    pub fn iter_by_type_in_batches<'a>(
+        &'a self,
+        entity_type: &'a str,
+        batch_size: usize,
+        ctx: &'a StorageContext,
+    ) -> impl Stream<Item = Result<Vec<StoredEntity>, StorageError>> + Send + 'a {
+        let batch_size = batch_size.max(1);
+        // State: `None` when done, else the cursor to resume after (`None` before the first page)
+        futures::stream::try_unfold(Some(None), move |state: Option<Option<String>>| async move {
+            let Some(mut after) = state else {
+                return Ok(None);
+            };
+            if after.is_none() {
+                self.flush().await?;
+            }
+            let backend = self.primary_backend();
+            self.ensure_registered(&backend)?;
+            let adapter = &self.adapters[&backend];
+            loop {
+                let page = adapter.scan_type(entity_type, after.as_deref(), batch_size, ctx).await?;
+                match page.next_cursor {
+                    None if page.entities.is_empty() => return Ok(None),
+                    None => return Ok(Some((page.entities, None))),
+                    Some(next) if page.entities.is_empty() => after = Some(next),
+                    Some(next) => return Ok(Some((page.entities, Some(Some(next))))),
+                }
+            }
+        })
+    }
+    
+    /// Declared capabilities of a registered backend
+    pub fn backend_capabilities(&self, backend: &str) -> Option<AdapterCapabilities> {
+        self.adapters.get(backend).map(|adapter| adapter.capabilities())
+    }
+    
     /// Health check all backends
     pub async fn health_check(&self) -> Result<HashMap<String, bool>, StorageError> {
         let mut results = HashMap::new();
@@ -490,8 +1985,74 @@ impl StorageManager {
         Ok(results)
     }
     
+    /// Health and capabilities of every registered backend
+    pub async fn health_report(&self) -> HashMap<String, BackendHealth> {
+        let mut results = HashMap::new();
+        
+        for (name, adapter) in &self.adapters {
+            results.insert(name.clone(), BackendHealth {
+                healthy: adapter.health_check().await.is_ok(),
+                capabilities: adapter.capabilities(),
+            });
+        }
+        
+        results
+    }
+    
     // Private helper methods
     
+    fn require_capability(&self, backend: &str, capability: StorageCapability) -> Result<&dyn StorageAdapter, StorageError> {
+        let adapter = self.adapters.get(backend)
+            .ok_or_else(|| StorageError::BackendError {
+                backend: backend.to_string(),
+                error: "Adapter not found".to_string(),
+            })?;
+        
+        if !adapter.capabilities().supports(capability) {
+            return Err(StorageError::Unsupported {
+                backend: backend.to_string(),
+                operation: capability.as_str().to_string(),
+            });
+        }
+        
+        Ok(adapter.as_ref())
+    }
+    
+    /// `get` under `PreferFresh`: every backend is read concurrently and the
+    /// newest copy wins
+    async fn get_freshest(&self, key: &str, ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
+        let primary = self.primary_backend();
+        let fallbacks = self.fallback_backends();
+        let (primary_result, fallback_results) = futures::join!(
+            self.get_from_backend(&primary, key, ctx),
+            futures::future::join_all(fallbacks.iter().map(|backend| self.get_from_backend(backend, key, ctx))),
+        );
+        
+        let mut freshest = match primary_result {
+            Ok(None) => return Ok(None),
+            Ok(Some(entity)) => Some(entity),
+            Err(e) => {
+                tracing::warn!(error = %e, "primary backend failed, serving freshest fallback");
+                if !fallback_results.iter().any(|r| matches!(r, Ok(Some(_)))) {
+                    self.metrics.errors_total.increment();
+                    return Err(e);
+                }
+                None
+            }
+        };
+        for (backend, entity) in fallbacks.iter().zip(fallback_results) {
+            let Ok(Some(entity)) = entity else { continue };
+            if freshest.as_ref().map_or(true, |current| entity.updated_at > current.updated_at) {
+                tracing::debug!(fallback = %backend, "fallback backend has the freshest copy");
+                freshest = Some(entity);
+            }
+        }
+        if let Some(entity) = &freshest {
+            self.cache_entity(key, entity).await;
+        }
+        Ok(freshest)
+    }
+    
     async fn get_from_backend(&self, backend: &str, key: &str, ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
         let adapter = self.adapters.get(backend)
             .ok_or_else(|| StorageError::BackendError {
@@ -539,9 +2100,142 @@ impl StorageManager {
         }
     }
     
-    async fn evict_from_cache(&self, key: &str) {
-        let mut cache = self.cache.write().await;
-        cache.remove(key);
+}
+
+/// `batch_put` for adapters that can only write one entity at a time: keeps
+/// up to `concurrency` puts in flight instead of awaiting each in turn. Stops
+/// at the first failure; entities already written stay written.
+pub async fn put_concurrently<A: StorageAdapter + ?Sized>(
+    adapter: &A,
+    entities: Vec<(String, StoredEntity)>,
+    concurrency: usize,
+    ctx: &StorageContext,
+) -> Result<(), StorageError> {
+    futures::stream::iter(entities)
+        .map(|(key, entity)| async move { adapter.put(&key, entity, ctx).await })
+        .buffer_unordered(concurrency.max(1))
+        .try_for_each(|()| futures::future::ok(()))
+        .await
+}
+
+/// Lowercase ASCII letters and digits, with every other run of characters
+/// collapsed to a single `-`
+fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Rebuild `value` with object keys in sorted order at every level. Explicit
+/// rather than relying on `serde_json::Map` ordering, which becomes insertion
+/// order if any crate in the build enables `preserve_order`.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().map(|(k, v)| (k, sort_keys(v))).collect())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
+
+/// Merge `patch` into `target`: objects recursively, everything else replaced
+fn deep_merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// The id on the far side of a relationship from `key`, if `key` is one of its ends
+fn other_end<'a>(edge: &'a StoredEntity, key: &str) -> Option<&'a str> {
+    let source = edge.data.get("source_id").and_then(Value::as_str);
+    let target = edge.data.get("target_id").and_then(Value::as_str);
+    match (source, target) {
+        (Some(source), Some(target)) if source == key => Some(target),
+        (Some(source), Some(target)) if target == key => Some(source),
+        _ => None,
+    }
+}
+
+/// Encode entities as a backup `StorageManager::import_data` restores: a
+/// JSON array of entities, each imported under its `id`. Adapters that
+/// export write this so every backend's backups read back the same way.
+pub fn encode_backup(entities: &[StoredEntity]) -> Result<Vec<u8>, StorageError> {
+    serde_json::to_vec(entities)
+        .map_err(|e| StorageError::SerializationError { error: format!("Failed to serialize backup: {}", e) })
+}
+
+/// Split a backup into individually decoded records. Only a backup that is
+/// not a JSON array or JSON Lines at all is an error; a record that fails
+/// to decode becomes an `Err` in its slot.
+fn parse_import_records(data: &[u8]) -> Result<Vec<Result<StoredEntity, String>>, StorageError> {
+    let is_array = data.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[');
+    if is_array {
+        let values: Vec<Value> = serde_json::from_slice(data)
+            .map_err(|e| StorageError::SerializationError { error: format!("Malformed backup: {}", e) })?;
+        return Ok(values.into_iter()
+            .map(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+            .collect());
+    }
+    
+    Ok(data
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.iter().all(|b| b.is_ascii_whitespace()))
+        .map(|line| serde_json::from_slice(line).map_err(|e| e.to_string()))
+        .collect())
+}
+
+/// On-disk encoding of stored entities for backends that persist bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SerializationFormat {
+    /// Human-readable, easy to inspect with any SQLite client
+    #[default]
+    Json,
+    /// Compact binary encoding; smaller rows and faster large-entity reads
+    MessagePack,
+}
+
+impl SerializationFormat {
+    pub fn encode(&self, entity: &StoredEntity) -> Result<Vec<u8>, StorageError> {
+        match self {
+            SerializationFormat::Json => serde_json::to_vec(entity)
+                .map_err(|e| StorageError::SerializationError { error: format!("serialize failed: {}", e) }),
+            SerializationFormat::MessagePack => rmp_serde::to_vec_named(entity)
+                .map_err(|e| StorageError::SerializationError { error: format!("serialize failed: {}", e) }),
+        }
+    }
+    
+    /// Decode bytes written in either format. An encoded entity is a map, so
+    /// JSON always starts with `{` and MessagePack never does; rows written
+    /// before a format switch stay readable.
+    pub fn decode(bytes: &[u8]) -> Result<StoredEntity, StorageError> {
+        let is_json = bytes.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{');
+        if is_json {
+            serde_json::from_slice(bytes)
+                .map_err(|e| StorageError::SerializationError { error: format!("deserialize failed: {}", e) })
+        } else {
+            rmp_serde::from_slice(bytes)
+                .map_err(|e| StorageError::SerializationError { error: format!("deserialize failed: {}", e) })
+        }
     }
 }
 
@@ -554,6 +2248,25 @@ pub struct StorageConfig {
     pub max_cache_size: usize,
     pub enable_compression: bool,
     pub enable_encryption: bool,
+    #[serde(default)]
+    pub serialization_format: SerializationFormat,
+    /// How `StorageManager::insert` generates keys
+    #[serde(default)]
+    pub key_strategy: KeyStrategy,
+    /// In-flight writes per `batch_put` on backends without transactional
+    /// batches (IndexedDB). SQLite writes a batch in one transaction instead.
+    #[serde(default = "default_batch_write_concurrency")]
+    pub batch_write_concurrency: usize,
+    /// Coalesce puts into batched writes; off when unset
+    #[serde(default)]
+    pub write_buffer: Option<WriteBufferConfig>,
+    /// Which backends `get` may serve from when the primary can't
+    #[serde(default)]
+    pub read_consistency: ReadConsistency,
+}
+
+fn default_batch_write_concurrency() -> usize {
+    DEFAULT_BATCH_WRITE_CONCURRENCY
 }
 
 impl Default for StorageConfig {
@@ -565,6 +2278,1013 @@ impl Default for StorageConfig {
             max_cache_size: 1000,
             enable_compression: false,
             enable_encryption: false, // Simplified for community
+            serialization_format: SerializationFormat::Json,
+            key_strategy: KeyStrategy::default(),
+            batch_write_concurrency: DEFAULT_BATCH_WRITE_CONCURRENCY,
+            write_buffer: None,
+            read_consistency: ReadConsistency::default(),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx() -> StorageContext {
+        StorageContext {
+            user_id: "test".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            skip_validation: false,
+        }
+    }
+
+    fn new_entity(entity_type: &str, data: Value) -> StoredEntity {
+        StoredEntity {
+            id: String::new(),
+            entity_type: entity_type.to_string(),
+            data,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            created_by: "test".to_string(),
+            updated_by: "test".to_string(),
+            version: 0,
+            deleted_at: None,
+            sync_status: SyncStatus::Local,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_metrics_snapshot_aggregates_concurrent_operations() {
+        let manager = Arc::new(StorageManager::new());
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        let tasks: Vec<_> = (0..8)
+            .map(|i| {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    for j in 0..25 {
+                        // Misses: nothing is stored under these keys
+                        manager.get(&format!("missing:{}:{}", i, j), &ctx()).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let snapshot = manager.metrics_snapshot();
+        assert_eq!(snapshot.operations_total, 200);
+        assert_eq!(snapshot.cache_misses, 200);
+        assert_eq!(snapshot.cache_hits, 0);
+        assert_eq!(snapshot.errors_total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_query_predicate_hides_soft_deleted() {
+        // Adapters without SQL (memory, IndexedDB) filter through
+        // `StorageQuery::predicate`, so this covers them all
+        let adapter = MemoryAdapter::new();
+        for id in ["note:1", "note:2"] {
+            let entity = StoredEntity { id: id.to_string(), ..new_entity("note", serde_json::json!({})) };
+            adapter.put(id, entity, &ctx()).await.unwrap();
+        }
+        adapter.delete("note:2", &ctx()).await.unwrap();
+
+        let ids = |entities: Vec<StoredEntity>| {
+            let mut ids: Vec<_> = entities.into_iter().map(|e| e.id).collect();
+            ids.sort();
+            ids
+        };
+        let live = adapter.query(&StorageQuery::builder().entity_type("note").build(), &ctx()).await.unwrap();
+        assert_eq!(ids(live), vec!["note:1"]);
+        let everything = StorageQuery::builder().entity_type("note").include_deleted(true).build();
+        assert_eq!(ids(adapter.query(&everything, &ctx()).await.unwrap()), vec!["note:1", "note:2"]);
+        // `get_by_type` is the raw listing and keeps soft-deleted entities
+        assert_eq!(ids(adapter.get_by_type("note", &ctx()).await.unwrap()), vec!["note:1", "note:2"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_consistency_modes() {
+        let stamped = |seconds: i64| StoredEntity {
+            id: "note:1".to_string(),
+            updated_at: DateTime::from_timestamp(seconds, 0).unwrap(),
+            ..new_entity("note", serde_json::json!({ "written": seconds }))
+        };
+        let manager_with = |read_consistency: ReadConsistency, primary: Option<i64>| async move {
+            let mut manager = StorageManager::new().with_read_consistency(read_consistency);
+            let (fresh, stale) = (MemoryAdapter::new(), MemoryAdapter::new());
+            fresh.put("note:1", stamped(200), &ctx()).await.unwrap();
+            stale.put("note:1", stamped(100), &ctx()).await.unwrap();
+            manager.register_adapter("fresh".to_string(), Box::new(fresh));
+            manager.register_adapter("stale".to_string(), Box::new(stale));
+            match primary {
+                // The default sqlite adapter is never initialized, so it errors
+                None => manager.set_backends("sqlite".to_string(), vec!["stale".to_string(), "fresh".to_string()]).unwrap(),
+                Some(written) => {
+                    manager.adapters["memory"].put("note:1", stamped(written), &ctx()).await.unwrap();
+                    manager.set_backends("memory".to_string(), vec!["stale".to_string(), "fresh".to_string()]).unwrap();
+                }
+            }
+            manager
+        };
+        let written = |entity: Option<StoredEntity>| entity.map(|e| e.data["written"].as_i64().unwrap());
+
+        let manager = manager_with(ReadConsistency::PrimaryOnly, None).await;
+        assert!(matches!(manager.get("note:1", &ctx()).await, Err(StorageError::DatabaseUnavailable { .. })));
+
+        // The first fallback answers, stale or not
+        let manager = manager_with(ReadConsistency::default(), None).await;
+        assert_eq!(written(manager.get("note:1", &ctx()).await.unwrap()), Some(100));
+
+        let manager = manager_with(ReadConsistency::PreferFresh, None).await;
+        assert_eq!(written(manager.get("note:1", &ctx()).await.unwrap()), Some(200));
+        let manager = manager_with(ReadConsistency::PreferFresh, Some(150)).await;
+        assert_eq!(written(manager.get("note:1", &ctx()).await.unwrap()), Some(200));
+        let manager = manager_with(ReadConsistency::PreferFresh, Some(300)).await;
+        assert_eq!(written(manager.get("note:1", &ctx()).await.unwrap()), Some(300));
+    }
+
+    #[test]
+    fn test_query_builder() {
+        let query = StorageQuery::builder()
+            .entity_type("task")
+            .filter("status", "open")
+            .filter("priority", 2)
+            .sort("priority", SortDirection::Desc)
+            .sort("title", SortDirection::Asc)
+            .limit(25)
+            .offset(50)
+            .include_deleted(true)
+            .build();
+
+        assert_eq!(query.entity_type.as_deref(), Some("task"));
+        assert_eq!(query.filters["status"], serde_json::json!("open"));
+        assert_eq!(query.filters["priority"], serde_json::json!(2));
+        let sort: Vec<&str> = query.sort.iter().flatten().map(|s| s.field.as_str()).collect();
+        assert_eq!(sort, vec!["priority", "title"]);
+        assert_eq!((query.limit, query.offset), (Some(25), Some(50)));
+        assert!(query.include_deleted);
+
+        let everything = StorageQuery::builder().build();
+        assert!(everything.entity_type.is_none() && everything.filters.is_empty() && everything.sort.is_none());
+        assert!(!everything.include_deleted);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_operation_checked_by_capabilities() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        let memory = manager.backend_capabilities("memory").unwrap();
+        assert!(memory.supports(StorageCapability::Count));
+        assert!(!memory.supports(StorageCapability::Export));
+
+        assert!(manager.get_stats().await.is_ok());
+        match manager.export_data(&ctx()).await {
+            Err(StorageError::Unsupported { backend, operation }) => {
+                assert_eq!(backend, "memory");
+                assert_eq!(operation, "export");
+            }
+            other => panic!("expected Unsupported, got {:?}", other),
+        }
+
+        let report = manager.health_report().await;
+        assert!(report["memory"].healthy);
+        assert_eq!(report["memory"].capabilities, memory);
+    }
+
+    #[tokio::test]
+    async fn test_switch_backends_keeps_cache() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        let entity = StoredEntity { id: "k1".to_string(), ..new_entity("note", serde_json::json!({ "text": "cached" })) };
+        manager.put("k1", entity, &ctx()).await.unwrap();
+
+        assert!(manager.set_backends("sqlite".to_string(), vec!["missing".to_string()]).is_err());
+        assert_eq!(manager.primary_backend(), "memory");
+
+        manager.set_backends("sqlite".to_string(), vec!["memory".to_string()]).unwrap();
+        assert_eq!(manager.primary_backend(), "sqlite");
+        assert_eq!(manager.fallback_backends(), vec!["memory".to_string()]);
+
+        // Served from cache even though the new primary has never seen it
+        let cached = manager.get("k1", &ctx()).await.unwrap().unwrap();
+        assert_eq!(cached.data["text"], "cached");
+    }
+
+    async fn graph_manager() -> StorageManager {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        let nodes = [("project:1", "project"), ("task:1", "task"), ("task:2", "task"), ("note:1", "note")];
+        let edges = [("edge:1", "project:1", "task:1"), ("edge:2", "task:2", "project:1"), ("edge:3", "project:1", "note:1"), ("edge:4", "task:1", "note:1")];
+        let nodes = nodes.iter().map(|(id, entity_type)| (*id, *entity_type, serde_json::json!({})));
+        let edges = edges.iter().map(|(id, source, target)| {
+            (*id, RELATIONSHIP_ENTITY_TYPE, serde_json::json!({ "source_id": source, "target_id": target }))
+        });
+        for (id, entity_type, data) in nodes.chain(edges) {
+            let entity = StoredEntity { id: id.to_string(), ..new_entity(entity_type, data) };
+            manager.put(id, entity, &ctx()).await.unwrap();
+        }
+        manager
+    }
+
+    async fn is_deleted(manager: &StorageManager, key: &str) -> bool {
+        manager.get(key, &ctx()).await.unwrap().unwrap().deleted_at.is_some()
+    }
+
+    #[tokio::test]
+    async fn test_clear_and_invalidate_cache() {
+        let manager = graph_manager().await;
+        let metrics = manager.metrics_snapshot();
+
+        // Every put cached its entity, so this read is a hit
+        manager.get("task:1", &ctx()).await.unwrap();
+        assert_eq!(manager.metrics_snapshot().cache_hits, metrics.cache_hits + 1);
+
+        assert!(manager.invalidate_cache("task:1").await);
+        assert!(!manager.invalidate_cache("task:1").await);
+        manager.get("task:1", &ctx()).await.unwrap();
+        assert_eq!(manager.metrics_snapshot().cache_misses, metrics.cache_misses + 1);
+
+        // 4 nodes + 4 edges were cached; task:1 was just re-cached by the miss
+        assert_eq!(manager.clear_cache().await, 8);
+        assert_eq!(manager.clear_cache().await, 0);
+        assert!(manager.get("project:1", &ctx()).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_version_saturates_instead_of_wrapping() {
+        let manager = graph_manager().await;
+        let mut entity = manager.get("task:1", &ctx()).await.unwrap().unwrap();
+        assert_eq!(entity.version, 1);
+
+        entity.version = u64::MAX - 1;
+        manager.put("task:1", entity, &ctx()).await.unwrap();
+        let entity = manager.get("task:1", &ctx()).await.unwrap().unwrap();
+        assert_eq!(entity.version, u64::MAX);
+
+        manager.put("task:1", entity, &ctx()).await.unwrap();
+        assert_eq!(manager.get("task:1", &ctx()).await.unwrap().unwrap().version, u64::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_delete_without_cascade_leaves_edges() {
+        let manager = graph_manager().await;
+
+        let affected = manager.delete("project:1", &CascadeMode::None, &ctx()).await.unwrap();
+        assert_eq!(affected, vec!["project:1".to_string()]);
+        assert!(is_deleted(&manager, "project:1").await);
+        for key in ["edge:1", "edge:2", "edge:3", "task:1"] {
+            assert!(!is_deleted(&manager, key).await, "{} should be untouched", key);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_cascades_to_edges() {
+        let manager = graph_manager().await;
+
+        let mut affected = manager.delete("project:1", &CascadeMode::DeleteEdges, &ctx()).await.unwrap();
+        assert_eq!(affected[0], "project:1");
+        affected.sort();
+        assert_eq!(affected, vec!["edge:1", "edge:2", "edge:3", "project:1"]);
+        // Edges in both directions go; unrelated edges and the far ends stay
+        assert!(!is_deleted(&manager, "edge:4").await);
+        assert!(!is_deleted(&manager, "task:1").await);
+        assert!(!is_deleted(&manager, "note:1").await);
+    }
+
+    #[tokio::test]
+    async fn test_delete_cascades_to_related_entities_of_given_types() {
+        let manager = graph_manager().await;
+        let cascade = CascadeMode::DeleteRelated { entity_types: vec!["task".to_string()] };
+
+        let mut affected = manager.delete("project:1", &cascade, &ctx()).await.unwrap();
+        affected.sort();
+        assert_eq!(affected, vec!["edge:1", "edge:2", "edge:3", "project:1", "task:1", "task:2"]);
+        assert!(is_deleted(&manager, "task:1").await);
+        assert!(is_deleted(&manager, "task:2").await);
+        // Other types and second-degree edges are left alone
+        assert!(!is_deleted(&manager, "note:1").await);
+        assert!(!is_deleted(&manager, "edge:4").await);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_backend_in_chunks() {
+        let mut manager = StorageManager::new();
+        manager.register_adapter("target".to_string(), Box::new(MemoryAdapter::new()));
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        for i in 0..5 {
+            let key = format!("note:{}", i);
+            let entity = StoredEntity { id: key.clone(), ..new_entity("note", serde_json::json!({ "n": i })) };
+            manager.put(&key, entity, &ctx()).await.unwrap();
         }
+        manager.delete("note:0", &CascadeMode::None, &ctx()).await.unwrap();
+
+        let progress = std::sync::Mutex::new(Vec::new());
+        let report = manager
+            .migrate_backend_with_progress("memory", "target", 2, true, |r| {
+                progress.lock().unwrap().push(r.entities_migrated)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(report.entities_migrated, 5);
+        assert_eq!(report.deleted_entities_migrated, 1);
+        assert_eq!(report.chunks, 3);
+        assert_eq!(*progress.lock().unwrap(), vec![2, 4, 5]);
+        assert!(report.switched_primary);
+        assert_eq!(manager.primary_backend(), "target");
+
+        let migrated = manager.adapters["target"].get("note:0", &ctx()).await.unwrap().unwrap();
+        assert!(migrated.deleted_at.is_some());
+
+        // A backend can't be migrated onto itself, even one that isn't registered
+        for backend in ["target", "missing"] {
+            assert!(matches!(
+                manager.migrate_backend(backend, backend, false).await,
+                Err(StorageError::ValidationFailed { .. })
+            ));
+        }
+        assert_eq!(manager.primary_backend(), "target");
+    }
+
+    #[tokio::test]
+    async fn test_insert_generates_keys_by_strategy() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        let key = manager.insert(new_entity("note", serde_json::json!({})), &ctx()).await.unwrap();
+        let id = key.strip_prefix("note:").unwrap();
+        assert!(Uuid::parse_str(id).is_ok());
+        assert_eq!(manager.get(&key, &ctx()).await.unwrap().unwrap().id, key);
+
+        let manager = StorageManager::new().with_key_strategy(KeyStrategy::Sequential);
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        // Taken numbers are skipped
+        manager.put("ticket:2", new_entity("ticket", serde_json::json!({})), &ctx()).await.unwrap();
+        let keys = [
+            manager.insert(new_entity("ticket", serde_json::json!({})), &ctx()).await.unwrap(),
+            manager.insert(new_entity("ticket", serde_json::json!({})), &ctx()).await.unwrap(),
+            manager.insert(new_entity("ticket", serde_json::json!({})), &ctx()).await.unwrap(),
+        ];
+        assert_eq!(keys, ["ticket:3", "ticket:4", "ticket:5"]);
+    }
+
+    #[tokio::test]
+    async fn test_insert_slug_keys_resolve_collisions() {
+        let manager = StorageManager::new().with_key_strategy(KeyStrategy::Slug { field: "name".to_string() });
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        let dashboard = || new_entity("dashboard", serde_json::json!({ "name": "Q3 Sales -- EMEA!" }));
+
+        assert_eq!(manager.insert(dashboard(), &ctx()).await.unwrap(), "dashboard:q3-sales-emea");
+        assert_eq!(manager.insert(dashboard(), &ctx()).await.unwrap(), "dashboard:q3-sales-emea-2");
+        assert_eq!(manager.insert(dashboard(), &ctx()).await.unwrap(), "dashboard:q3-sales-emea-3");
+
+        assert!(matches!(
+            manager.insert(new_entity("dashboard", serde_json::json!({ "name": "!!" })), &ctx()).await,
+            Err(StorageError::ValidationFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_canonical_json_is_independent_of_key_order() {
+        let fixed = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let entity = |data: Value| StoredEntity { created_at: fixed, updated_at: fixed, ..new_entity("note", data) };
+        let mut forward = serde_json::Map::new();
+        forward.insert("b".to_string(), serde_json::json!({ "y": 1, "x": [{ "q": 1, "p": 2 }] }));
+        forward.insert("a".to_string(), serde_json::json!(true));
+        let mut backward = serde_json::Map::new();
+        backward.insert("a".to_string(), serde_json::json!(true));
+        backward.insert("b".to_string(), serde_json::json!({ "x": [{ "p": 2, "q": 1 }], "y": 1 }));
+
+        let first = entity(Value::Object(forward));
+        let second = entity(Value::Object(backward));
+        let bytes = first.canonical_json().unwrap();
+        assert_eq!(bytes, second.canonical_json().unwrap());
+        assert!(String::from_utf8(bytes).unwrap().contains(r#""data":{"a":true,"b":{"x":[{"p":2,"q":1}],"y":1}}"#));
+
+        // Bookkeeping doesn't affect the content hash; data does
+        let mut bumped = second.clone();
+        bumped.version = 7;
+        bumped.updated_at = Utc::now();
+        assert_eq!(first.content_hash(), bumped.content_hash());
+        bumped.data["a"] = serde_json::json!(false);
+        assert_ne!(first.content_hash(), bumped.content_hash());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_query_conditions() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        for (i, status) in ["open", "closed", "open", "blocked"].iter().enumerate() {
+            manager
+                .merge(
+                    &format!("task:{}", i),
+                    serde_json::json!({ "status": status, "points": i * 2, "owner": { "name": format!("user{}", i) } }),
+                    &ctx(),
+                )
+                .await
+                .unwrap();
+        }
+        manager.merge("note:1", serde_json::json!({ "status": "open" }), &ctx()).await.unwrap();
+
+        let ids = |query: StorageQuery| {
+            let manager = &manager;
+            async move {
+                let mut ids: Vec<String> = manager.query(&query, &ctx()).await.unwrap().into_iter().map(|e| e.id).collect();
+                ids.sort();
+                ids
+            }
+        };
+
+        // The equality map still works, alone and alongside conditions
+        assert_eq!(ids(StorageQuery::builder().entity_type("task").filter("status", "open").build()).await, ["task:0", "task:2"]);
+        assert_eq!(
+            ids(StorageQuery::builder()
+                .entity_type("task")
+                .filter("status", "open")
+                .condition("points", ConditionOperator::GreaterThan, 1)
+                .build())
+            .await,
+            ["task:2"]
+        );
+        assert_eq!(
+            ids(StorageQuery::builder()
+                .condition("status", ConditionOperator::In, serde_json::json!(["closed", "blocked"]))
+                .build())
+            .await,
+            ["task:1", "task:3"]
+        );
+        assert_eq!(
+            ids(StorageQuery::builder().condition("owner.name", ConditionOperator::Contains, "3").build()).await,
+            ["task:3"]
+        );
+        assert_eq!(
+            ids(StorageQuery::builder().condition("points", ConditionOperator::NotExists, Value::Null).build()).await,
+            ["note:1"]
+        );
+        assert_eq!(
+            ids(StorageQuery::builder()
+                .entity_type("task")
+                .condition("created_at", ConditionOperator::LessThanOrEqual, rfc3339(&Utc::now()))
+                .limit(2)
+                .offset(1)
+                .build())
+            .await,
+            ["task:1", "task:2"]
+        );
+    }
+
+    #[test]
+    fn test_timestamp_conditions_compare_chronologically() {
+        let at = |timestamp: &str| StoredEntity {
+            created_at: DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc),
+            ..new_entity("note", Value::Null)
+        };
+        // Serialized, the whole second ("...00Z") sorts after its own fraction ("...00.1Z")
+        let whole = at("2024-01-01T00:00:00Z");
+        let fraction = at("2024-01-01T00:00:00.1Z");
+        let before = FilterCondition::new("created_at", ConditionOperator::LessThan, "2024-01-01T00:00:00.05Z");
+        assert!(before.matches(&whole));
+        assert!(!before.matches(&fraction));
+
+        // Any RFC 3339 spelling of the same instant is equal
+        let equal = FilterCondition::new("created_at", ConditionOperator::Equals, "2024-01-01T02:00:00.100+02:00");
+        assert!(equal.matches(&fraction));
+    }
+
+    #[tokio::test]
+    async fn test_query_multi_key_sort() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        // Each merge gets a later created_at than the one before
+        for (id, rank) in [("a", 2), ("b", 1), ("c", 2), ("d", 1), ("e", 3)] {
+            manager
+                .merge(&format!("card:{}", id), serde_json::json!({ "meta": { "rank": rank } }), &ctx())
+                .await
+                .unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        }
+        manager.merge("card:f", serde_json::json!({}), &ctx()).await.unwrap();
+
+        let sorted = |offset: usize, limit: usize| {
+            StorageQuery::builder()
+                .entity_type("card")
+                .sort("meta.rank", SortDirection::Asc)
+                .sort("created_at", SortDirection::Desc)
+                .offset(offset)
+                .limit(limit)
+                .build()
+        };
+        let ids: Vec<String> = manager.query(&sorted(0, 10), &ctx()).await.unwrap().into_iter().map(|e| e.id).collect();
+        // Missing rank first, then rank ascending, newest first within a rank
+        assert_eq!(ids, ["card:f", "card:d", "card:b", "card:c", "card:a", "card:e"]);
+
+        let page: Vec<String> = manager.query(&sorted(2, 2), &ctx()).await.unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(page, ["card:b", "card:c"]);
+
+        // Equal keys fall back to id order
+        let by_rank = StorageQuery::builder().entity_type("card").sort("meta.rank", SortDirection::Desc).build();
+        let ids: Vec<String> = manager.query(&by_rank, &ctx()).await.unwrap().into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, ["card:e", "card:a", "card:c", "card:b", "card:d", "card:f"]);
+    }
+
+    #[tokio::test]
+    async fn test_size_by_type() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        manager.merge("audit_event:1", serde_json::json!({ "detail": "x".repeat(500) }), &ctx()).await.unwrap();
+        manager.merge("audit_event:2", serde_json::json!({ "detail": "x".repeat(500) }), &ctx()).await.unwrap();
+        manager.merge("task:1", serde_json::json!({ "title": "t" }), &ctx()).await.unwrap();
+
+        let usage = manager.size_by_type().await.unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].entity_type, "audit_event");
+        assert_eq!(usage[0].count, 2);
+        assert!(usage[0].bytes > 1000);
+        assert_eq!(usage[1].entity_type, "task");
+        assert_eq!(usage[1].count, 1);
+        assert!(usage[1].bytes > 0 && usage[1].bytes < usage[0].bytes);
+
+        let stats = manager.get_stats().await.unwrap();
+        assert_eq!(stats.storage_size_bytes, usage[0].bytes + usage[1].bytes);
+    }
+
+    #[tokio::test]
+    async fn test_put_concurrently_writes_every_entity() {
+        let adapter = MemoryAdapter::new();
+        let entities: Vec<_> = (0..100)
+            .map(|i| {
+                let id = format!("task:{}", i);
+                let entity = StoredEntity { id: id.clone(), ..new_entity("task", serde_json::json!({ "n": i })) };
+                (id, entity)
+            })
+            .collect();
+
+        put_concurrently(&adapter, entities, 8, &ctx()).await.unwrap();
+        assert_eq!(adapter.get_by_type("task", &ctx()).await.unwrap().len(), 100);
+        assert_eq!(adapter.get("task:42", &ctx()).await.unwrap().unwrap().data["n"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_write_buffer_coalesces_puts() {
+        let manager = StorageManager::new().with_write_buffer(WriteBufferConfig {
+            max_entries: 3,
+            flush_interval_ms: 60_000,
+        });
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        let on_backend = |key: &'static str| {
+            let manager = &manager;
+            async move { manager.get_from_backend("memory", key, &ctx()).await.unwrap().is_some() }
+        };
+
+        manager.put("task:a", new_entity("task", serde_json::json!({ "n": 1 })), &ctx()).await.unwrap();
+        manager.put("task:b", new_entity("task", serde_json::json!({ "n": 1 })), &ctx()).await.unwrap();
+        manager.put("task:b", new_entity("task", serde_json::json!({ "n": 2 })), &ctx()).await.unwrap();
+        assert!(!on_backend("task:a").await);
+        // Reads see buffered writes even without the cache
+        manager.clear_cache().await;
+        assert_eq!(manager.get("task:b", &ctx()).await.unwrap().unwrap().data["n"], 2);
+
+        // The third distinct key fills the buffer
+        manager.put("task:c", new_entity("task", serde_json::json!({ "n": 1 })), &ctx()).await.unwrap();
+        assert!(on_backend("task:a").await && on_backend("task:c").await);
+        assert_eq!(manager.get_from_backend("memory", "task:b", &ctx()).await.unwrap().unwrap().data["n"], 2);
+
+        // Queries flush first
+        manager.put("task:d", new_entity("task", serde_json::json!({ "n": 1 })), &ctx()).await.unwrap();
+        let query = StorageQuery::builder().entity_type("task").build();
+        assert_eq!(manager.query(&query, &ctx()).await.unwrap().len(), 4);
+
+        manager.put("task:e", new_entity("task", serde_json::json!({ "n": 1 })), &ctx()).await.unwrap();
+        assert_eq!(manager.flush().await.unwrap(), 1);
+        assert!(on_backend("task:e").await);
+        assert_eq!(manager.flush().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_as_of() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        let before = Utc::now() - chrono::Duration::seconds(1);
+        manager.merge("dashboard:1", serde_json::json!({ "v": 1 }), &ctx()).await.unwrap();
+        let created = Utc::now();
+
+        assert!(manager.get_as_of("dashboard:1", before, &ctx()).await.unwrap().is_none());
+        assert!(manager.get_as_of("missing", created, &ctx()).await.unwrap().is_none());
+        let found = manager.get_as_of("dashboard:1", created, &ctx()).await.unwrap().unwrap();
+        assert_eq!(found.data["v"], 1);
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        manager.merge("dashboard:1", serde_json::json!({ "v": 2 }), &ctx()).await.unwrap();
+        let updated = Utc::now();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        manager.merge("dashboard:1", serde_json::json!({ "v": 3 }), &ctx()).await.unwrap();
+
+        // Superseded versions come from the history
+        assert_eq!(manager.get_as_of("dashboard:1", created, &ctx()).await.unwrap().unwrap().data["v"], 1);
+        assert_eq!(manager.get_as_of("dashboard:1", updated, &ctx()).await.unwrap().unwrap().data["v"], 2);
+        assert_eq!(manager.get_as_of("dashboard:1", Utc::now(), &ctx()).await.unwrap().unwrap().data["v"], 3);
+
+        // Purging takes the history with it
+        manager.batch_delete(&["dashboard:1".to_string()], &ctx()).await.unwrap();
+        assert!(manager.get_as_of("dashboard:1", updated, &ctx()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_prune_version_history_keeps_newest() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        let mut written = Vec::new();
+        for v in 1..=5 {
+            manager.merge("dashboard:1", serde_json::json!({ "v": v }), &ctx()).await.unwrap();
+            written.push(Utc::now());
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+        }
+
+        // Versions 1-4 are history; keeping two drops 1 and 2
+        assert_eq!(manager.prune_version_history(2).await.unwrap(), 2);
+        assert!(manager.get_as_of("dashboard:1", written[1], &ctx()).await.unwrap().is_none());
+        assert_eq!(manager.get_as_of("dashboard:1", written[2], &ctx()).await.unwrap().unwrap().data["v"], 3);
+        assert_eq!(manager.get_as_of("dashboard:1", Utc::now(), &ctx()).await.unwrap().unwrap().data["v"], 5);
+        assert_eq!(manager.prune_version_history(2).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_merge_creates_missing_entity() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        let created = manager
+            .merge("settings:ui", serde_json::json!({ "theme": "dark" }), &ctx())
+            .await
+            .unwrap();
+        assert_eq!(created.entity_type, "settings");
+        assert_eq!(created.data, serde_json::json!({ "theme": "dark" }));
+        assert_eq!(created.version, 1);
+        assert_eq!(created.created_by, "test");
+    }
+
+    #[tokio::test]
+    async fn test_merge_deep_merges_existing_data() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        manager
+            .merge(
+                "settings:ui",
+                serde_json::json!({ "theme": "dark", "grid": { "columns": 12, "gap": 8 }, "tags": ["a"] }),
+                &ctx(),
+            )
+            .await
+            .unwrap();
+
+        let merged = manager
+            .merge("settings:ui", serde_json::json!({ "grid": { "gap": 4 }, "tags": ["b"] }), &ctx())
+            .await
+            .unwrap();
+        assert_eq!(
+            merged.data,
+            serde_json::json!({ "theme": "dark", "grid": { "columns": 12, "gap": 4 }, "tags": ["b"] })
+        );
+        assert_eq!(merged.version, 2);
+        assert_eq!(manager.get("settings:ui", &ctx()).await.unwrap().unwrap().data, merged.data);
+    }
+
+    #[tokio::test]
+    async fn test_patch_entity_data() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        let entity = StoredEntity { id: "p1".to_string(), ..new_entity("note", serde_json::json!({ "title": "old", "tags": ["a"] })) };
+        manager.put("p1", entity, &ctx()).await.unwrap();
+
+        let ops: Vec<PatchOp> = serde_json::from_value(serde_json::json!([
+            { "op": "replace", "path": "/title", "value": "new" },
+            { "op": "add", "path": "/tags/-", "value": "b" }
+        ])).unwrap();
+        let patched = manager.patch("p1", ops, &ctx()).await.unwrap();
+        assert_eq!(patched.data, serde_json::json!({ "title": "new", "tags": ["a", "b"] }));
+        assert_eq!(patched.version, 2);
+
+        // A failing operation leaves the entity untouched
+        let failing: Vec<PatchOp> = serde_json::from_value(serde_json::json!([
+            { "op": "replace", "path": "/title", "value": "newer" },
+            { "op": "test", "path": "/title", "value": "mismatch" }
+        ])).unwrap();
+        assert!(manager.patch("p1", failing, &ctx()).await.is_err());
+        let current = manager.get("p1", &ctx()).await.unwrap().unwrap();
+        assert_eq!(current.data["title"], "new");
+        assert_eq!(current.version, 2);
+
+        assert!(matches!(
+            manager.patch("missing", Vec::new(), &ctx()).await,
+            Err(StorageError::NotFound { .. })
+        ));
+    }
+
+    /// Memory adapter whose writes of entities marked `slow` stall, holding
+    /// a `patch` between its read and its write, and whose batches fail
+    /// while they carry an entity marked `reject`
+    struct FlakyAdapter(MemoryAdapter);
+
+    #[async_trait]
+    impl StorageAdapter for FlakyAdapter {
+        async fn initialize(&mut self) -> Result<(), StorageError> { Ok(()) }
+        async fn health_check(&self) -> Result<(), StorageError> { Ok(()) }
+        async fn get(&self, key: &str, ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> { self.0.get(key, ctx).await }
+        async fn put(&self, key: &str, entity: StoredEntity, ctx: &StorageContext) -> Result<(), StorageError> {
+            if entity.data["slow"] == true {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+            self.0.put(key, entity, ctx).await
+        }
+        async fn delete(&self, key: &str, ctx: &StorageContext) -> Result<(), StorageError> { self.0.delete(key, ctx).await }
+        async fn purge(&self, key: &str, ctx: &StorageContext) -> Result<(), StorageError> { self.0.purge(key, ctx).await }
+        async fn query(&self, query: &StorageQuery, ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> { self.0.query(query, ctx).await }
+        async fn get_by_type(&self, entity_type: &str, ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> { self.0.get_by_type(entity_type, ctx).await }
+        async fn batch_put(&self, entities: Vec<(String, StoredEntity)>, ctx: &StorageContext) -> Result<(), StorageError> {
+            if entities.iter().any(|(_, entity)| entity.data["reject"] == true) {
+                return Err(StorageError::BackendError {
+                    backend: "flaky".to_string(),
+                    error: "batch rejected".to_string(),
+                });
+            }
+            self.0.batch_put(entities, ctx).await
+        }
+        async fn get_stats(&self) -> Result<StorageStats, StorageError> { self.0.get_stats().await }
+        async fn export_data(&self, ctx: &StorageContext) -> Result<Vec<u8>, StorageError> { self.0.export_data(ctx).await }
+    }
+
+    #[tokio::test]
+    async fn test_buffered_put_succeeds_when_flush_fails() {
+        let mut manager = StorageManager::new().with_write_buffer(WriteBufferConfig {
+            max_entries: 2,
+            flush_interval_ms: 60_000,
+        });
+        manager.register_adapter("flaky".to_string(), Box::new(FlakyAdapter(MemoryAdapter::new())));
+        manager.set_primary_backend("flaky".to_string()).unwrap();
+
+        manager.put("note:a", new_entity("note", serde_json::json!({ "n": 1 })), &ctx()).await.unwrap();
+        // The buffer is due and its flush fails, but the write is kept
+        manager.put("note:b", new_entity("note", serde_json::json!({ "reject": true })), &ctx()).await.unwrap();
+        assert!(manager.get_from_backend("flaky", "note:a", &ctx()).await.unwrap().is_none());
+        assert_eq!(manager.get("note:b", &ctx()).await.unwrap().unwrap().version, 1);
+        assert!(manager.flush().await.is_err());
+
+        // Replacing the rejected entity lets the buffer drain
+        manager.put("note:b", new_entity("note", serde_json::json!({ "n": 2 })), &ctx()).await.unwrap();
+        assert!(manager.get_from_backend("flaky", "note:a", &ctx()).await.unwrap().is_some());
+        assert_eq!(manager.get_from_backend("flaky", "note:b", &ctx()).await.unwrap().unwrap().data["n"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_put_waits_for_patch_on_same_key() {
+        let mut manager = StorageManager::new();
+        manager.register_adapter("slow".to_string(), Box::new(FlakyAdapter(MemoryAdapter::new())));
+        manager.set_primary_backend("slow".to_string()).unwrap();
+        let manager = Arc::new(manager);
+
+        let entity = |data: Value| StoredEntity { id: "note:1".to_string(), ..new_entity("note", data) };
+        manager.put("note:1", entity(serde_json::json!({ "title": "draft" })), &ctx()).await.unwrap();
+
+        let patching = {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                let ops: Vec<PatchOp> = serde_json::from_value(serde_json::json!([
+                    { "op": "add", "path": "/slow", "value": true }
+                ])).unwrap();
+                manager.patch("note:1", ops, &ctx()).await.unwrap()
+            })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        // The put lands after the patch instead of being overwritten by it
+        manager.put("note:1", entity(serde_json::json!({ "title": "final" })), &ctx()).await.unwrap();
+        let patched = patching.await.unwrap();
+        assert_eq!(patched.data, serde_json::json!({ "title": "draft", "slow": true }));
+        assert_eq!(manager.get("note:1", &ctx()).await.unwrap().unwrap().data, serde_json::json!({ "title": "final" }));
+
+        // Other keys don't wait on the stalled one
+        let other = manager.clone();
+        let slow = tokio::spawn(async move {
+            other.put("note:1", entity(serde_json::json!({ "slow": true })), &ctx()).await
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        let started = std::time::Instant::now();
+        manager.merge("task:1", serde_json::json!({ "done": false }), &ctx()).await.unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_millis(40));
+        slow.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_put_validates_registered_entity_types() {
+        let validation = Arc::new(super::super::ValidationManager::new());
+        let schema = serde_json::from_value(serde_json::json!({
+            "schema_name": "note",
+            "version": "1",
+            "description": "",
+            "rules": [{
+                "field_name": "title",
+                "required": true,
+                "data_type": { "String": { "min_length": null, "max_length": null } },
+                "constraints": [],
+                "custom_validators": []
+            }],
+            "cross_field_rules": [],
+            "business_rules": []
+        })).unwrap();
+        validation.register_schema(schema).await.unwrap();
+
+        let manager = StorageManager::new().with_validation(validation);
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        let entity = |entity_type: &str, data: Value| StoredEntity { id: "v1".to_string(), ..new_entity(entity_type, data) };
+
+        match manager.put("v1", entity("note", serde_json::json!({})), &ctx()).await {
+            Err(StorageError::ValidationFailed { error }) => assert!(error.contains("title")),
+            other => panic!("expected ValidationFailed, got {:?}", other),
+        }
+        assert!(manager.get("v1", &ctx()).await.unwrap().is_none());
+
+        manager.put("v1", entity("note", serde_json::json!({ "title": "ok" })), &ctx()).await.unwrap();
+
+        // Types without a schema and internal writes are not checked
+        manager.put("v2", entity("draft", serde_json::json!({})), &ctx()).await.unwrap();
+        let system = StorageContext { skip_validation: true, ..ctx() };
+        manager.put("v3", entity("note", serde_json::json!({})), &system).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_iter_by_type_pages_in_batches() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        for (i, entity_type) in ["note", "task", "note", "note", "task", "note", "note"].iter().enumerate() {
+            let key = format!("{}:{}", entity_type, i);
+            let entity = StoredEntity { id: key.clone(), ..new_entity(entity_type, serde_json::json!({ "n": i })) };
+            manager.put(&key, entity, &ctx()).await.unwrap();
+        }
+        manager.delete("note:3", &CascadeMode::None, &ctx()).await.unwrap();
+
+        let context = ctx();
+        let batches: Vec<Vec<StoredEntity>> = manager
+            .iter_by_type_in_batches("note", 2, &context)
+            .try_collect()
+            .await
+            .unwrap();
+        let ids: Vec<Vec<String>> = batches.iter().map(|b| b.iter().map(|e| e.id.clone()).collect()).collect();
+        assert_eq!(ids, vec![vec!["note:0", "note:2"], vec!["note:5", "note:6"]]);
+
+        let none: Vec<Vec<StoredEntity>> = manager.iter_by_type("widget", &context).try_collect().await.unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_stream_in_chunks() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        for i in 0..5 {
+            let key = format!("note:{}", i);
+            let entity = StoredEntity { id: key.clone(), ..new_entity("note", serde_json::json!({ "n": i })) };
+            manager.put(&key, entity, &ctx()).await.unwrap();
+        }
+
+        let mut chunks = Vec::new();
+        let report = manager
+            .export_stream(2, |chunk| {
+                chunks.push(chunk);
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(report.entities_exported, 5);
+        assert_eq!(report.chunks, 3);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(report.bytes, chunks.iter().map(|c| c.len() as u64).sum::<u64>());
+
+        let lines: Vec<StoredEntity> = chunks
+            .concat()
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[4].id, "note:4");
+
+        // A failing sink aborts the export
+        let failed = manager
+            .export_stream(2, |_| Err(StorageError::Io { error: "disk full".to_string() }))
+            .await;
+        assert!(matches!(failed, Err(StorageError::Io { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_import_reports_malformed_records() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        let entity = |id: &str| serde_json::json!({
+            "id": id,
+            "entity_type": "note",
+            "data": { "text": id },
+            "created_at": Utc::now(),
+            "updated_at": Utc::now(),
+            "created_by": "test",
+            "updated_by": "test",
+            "version": 1,
+            "deleted_at": null,
+            "sync_status": "Local"
+        });
+        let backup = format!(
+            "{}\n{{\"id\": \"broken\"}}\n{}\nnot json\n",
+            entity("a"),
+            entity("b")
+        );
+
+        let strict = manager.import_data(backup.as_bytes(), ImportMode::Strict, &ctx()).await;
+        assert!(matches!(strict, Err(StorageError::ValidationFailed { .. })));
+        assert!(manager.get("a", &ctx()).await.unwrap().is_none());
+
+        let report = manager.import_data(backup.as_bytes(), ImportMode::Lenient, &ctx()).await.unwrap();
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.failed, 2);
+        let failed: Vec<usize> = report.errors.iter().map(|e| e.index).collect();
+        assert_eq!(failed, vec![1, 3]);
+        assert_eq!(manager.get("b", &ctx()).await.unwrap().unwrap().data["text"], "b");
+
+        // JSON array backups are decoded per record too
+        let array = serde_json::to_vec(&serde_json::json!([entity("c"), { "id": 1 }])).unwrap();
+        let report = manager.import_data(&array, ImportMode::Lenient, &ctx()).await.unwrap();
+        assert_eq!((report.imported, report.failed), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_encoded_backup_round_trips_through_import() {
+        let mut live = new_entity("note", serde_json::json!({ "text": "kept" }));
+        live.id = "note:1".to_string();
+        let mut deleted = new_entity("note", serde_json::json!({ "text": "gone" }));
+        deleted.id = "note:2".to_string();
+        deleted.deleted_at = Some(Utc::now());
+        let backup = encode_backup(&[live, deleted]).unwrap();
+
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+        let report = manager.import_data(&backup, ImportMode::Strict, &ctx()).await.unwrap();
+        assert_eq!((report.imported, report.failed), (2, 0));
+
+        let restored = manager.get("note:1", &ctx()).await.unwrap().unwrap();
+        assert_eq!(restored.data["text"], "kept");
+        let restored = manager.adapters["memory"].get("note:2", &ctx()).await.unwrap().unwrap();
+        assert!(restored.deleted_at.is_some());
+    }
+
+    #[test]
+    fn test_serialization_formats_round_trip() {
+        let entity = StoredEntity {
+            id: "f1".to_string(),
+            version: 2,
+            sync_status: SyncStatus::Synced,
+            ..new_entity("note", serde_json::json!({ "n": 1, "tags": ["a"], "empty": null }))
+        };
+
+        let json = SerializationFormat::Json.encode(&entity).unwrap();
+        let msgpack = SerializationFormat::MessagePack.encode(&entity).unwrap();
+        assert!(msgpack.len() < json.len());
+
+        for bytes in [json, msgpack] {
+            let decoded = SerializationFormat::decode(&bytes).unwrap();
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&entity).unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_purge_old_audit_events() {
+        let manager = StorageManager::new();
+        manager.set_primary_backend("memory".to_string()).unwrap();
+
+        let event = |id: &str, entity_type: &str, age_days: i64| StoredEntity {
+            id: id.to_string(),
+            created_at: Utc::now() - chrono::Duration::days(age_days),
+            ..new_entity(entity_type, serde_json::json!({}))
+        };
+        let entities = vec![
+            event("old", AUDIT_EVENT_TYPE, 120),
+            event("recent", AUDIT_EVENT_TYPE, 10),
+            event("old_note", "note", 120),
+        ];
+        manager.adapters["memory"]
+            .batch_put(entities.into_iter().map(|e| (e.id.clone(), e)).collect(), &ctx())
+            .await
+            .unwrap();
+
+        assert_eq!(manager.purge_audit_events(DEFAULT_AUDIT_RETENTION_DAYS).await.unwrap(), 1);
+        assert!(manager.get("old", &ctx()).await.unwrap().is_none());
+        assert!(manager.get("recent", &ctx()).await.unwrap().is_some());
+        assert!(manager.get("old_note", &ctx()).await.unwrap().is_some());
+    }
+}
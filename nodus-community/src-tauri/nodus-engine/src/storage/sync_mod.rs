@@ -4,16 +4,18 @@
 
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{RwLock, Mutex};
+use tokio::sync::{broadcast, RwLock, Mutex};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use chrono::{DateTime, Utc};
+use async_trait::async_trait;
+use uuid::Uuid;
 
-use crate::storage::StorageManager;
+use crate::storage::{CascadeMode, StorageContext, StorageManager, StoredEntity};
 
 // Sub-modules (consolidated in this file or not present)
-// pub mod conflict_resolution;
-// pub mod sync_client;
+// pub mod conflict_resolution; (ConflictResolver lives below)
+// pub mod sync_client; (SyncClient lives below)
 // pub mod websocket_sync;
 // pub mod batch_processor;
 
@@ -77,16 +79,30 @@ pub struct SyncConfig {
     pub auth_token: Option<String>,
     /// Sync interval in seconds
     pub sync_interval_seconds: u64,
+    /// Sync interval in seconds while the machine is idle
+    #[serde(default = "default_idle_sync_interval")]
+    pub idle_sync_interval_seconds: u64,
     /// Batch size for sync operations
     pub batch_size: usize,
     /// Timeout for sync operations in seconds
     pub timeout_seconds: u64,
     /// Enable real-time sync via WebSocket
     pub enable_realtime: bool,
+    /// When false the manager uses `NullSyncClient` and never contacts a server
+    #[serde(default = "default_sync_enabled")]
+    pub sync_enabled: bool,
     /// Retry configuration
     pub retry_config: RetryConfig,
 }
 
+fn default_sync_enabled() -> bool {
+    true
+}
+
+fn default_idle_sync_interval() -> u64 {
+    600
+}
+
 /// Retry configuration for failed sync operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetryConfig {
@@ -141,16 +157,277 @@ pub enum SyncOperation {
     Restore,
 }
 
+/// Capacity of the change notification channel; slower subscribers lag and
+/// are told how many records they missed instead of blocking producers
+pub const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
+/// Where a recorded change originated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeSource {
+    /// Queued locally for upload
+    Local,
+    /// Applied from the sync server
+    Remote,
+}
+
+/// Notification sent to change subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    pub entity_id: String,
+    pub entity_type: String,
+    pub operation: SyncOperation,
+    pub source: ChangeSource,
+    pub version: u64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl ChangeRecord {
+    fn from_change(change: &SyncChange, source: ChangeSource) -> Self {
+        Self {
+            entity_id: change.entity_id.clone(),
+            entity_type: change.entity_type.clone(),
+            operation: change.operation.clone(),
+            source,
+            version: change.version,
+            recorded_at: Utc::now(),
+        }
+    }
+}
+
+/// Outcome of a dry-run sync: what applying the remote changes would do
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncPreview {
+    pub to_create: Vec<String>,
+    pub to_update: Vec<String>,
+    pub to_delete: Vec<String>,
+    /// Entities with unsynced local edits that the remote also changed
+    pub conflicts: Vec<String>,
+    /// Local edits that would be replaced or deleted by this sync
+    pub overwritten_local_edits: usize,
+}
+
+impl SyncPreview {
+    pub fn total_changes(&self) -> usize {
+        self.to_create.len() + self.to_update.len() + self.to_delete.len() + self.conflicts.len()
+    }
+}
+
+/// A local/remote conflict for a single entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictRecord {
+    pub entity_id: String,
+    pub entity_type: String,
+    pub local_data: Value,
+    pub remote_data: Value,
+    pub local_version: u64,
+    pub remote_version: u64,
+    pub local_updated_at: DateTime<Utc>,
+    pub remote_updated_at: DateTime<Utc>,
+    pub remote_user_id: String,
+}
+
+/// Built-in conflict resolution strategies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictStrategy {
+    /// Newest `updated_at` wins (remote wins ties)
+    LastWriteWins,
+    /// Keep the local version
+    LocalWins,
+    /// Take the remote version
+    RemoteWins,
+}
+
+/// Domain-specific merge logic (e.g. CRDT-style counters) registered per entity type
+#[async_trait]
+pub trait CustomConflictResolver: Send + Sync {
+    /// Produce the merged entity data for a conflict
+    async fn resolve(&self, conflict: &ConflictRecord) -> Result<Value, SyncError>;
+}
+
+/// Resolves conflicts using a registered custom resolver for the entity type,
+/// falling back to the built-in strategy for that type (or the default)
+pub struct ConflictResolver {
+    default_strategy: ConflictStrategy,
+    strategies: HashMap<String, ConflictStrategy>,
+    custom_resolvers: HashMap<String, Arc<dyn CustomConflictResolver>>,
+}
+
+impl std::fmt::Debug for ConflictResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConflictResolver")
+            .field("default_strategy", &self.default_strategy)
+            .field("strategies", &self.strategies)
+            .field("custom_resolvers", &self.custom_resolvers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ConflictResolver {
+    pub fn new(default_strategy: ConflictStrategy) -> Self {
+        Self {
+            default_strategy,
+            strategies: HashMap::new(),
+            custom_resolvers: HashMap::new(),
+        }
+    }
+
+    /// Use a built-in strategy for an entity type
+    pub fn set_strategy(&mut self, entity_type: &str, strategy: ConflictStrategy) {
+        self.strategies.insert(entity_type.to_string(), strategy);
+    }
+
+    /// Register a custom resolver for an entity type (replaces any previous one)
+    pub fn register_custom(&mut self, entity_type: &str, resolver: Arc<dyn CustomConflictResolver>) {
+        self.custom_resolvers.insert(entity_type.to_string(), resolver);
+    }
+
+    /// Remove a custom resolver, returning the entity type to built-in strategies
+    pub fn unregister_custom(&mut self, entity_type: &str) -> bool {
+        self.custom_resolvers.remove(entity_type).is_some()
+    }
+
+    pub fn has_custom(&self, entity_type: &str) -> bool {
+        self.custom_resolvers.contains_key(entity_type)
+    }
+
+    /// Resolve a conflict, preferring a custom resolver for the entity type
+    pub async fn resolve(&self, conflict: &ConflictRecord) -> Result<Value, SyncError> {
+        if let Some(resolver) = self.custom_resolvers.get(&conflict.entity_type) {
+            return resolver.resolve(conflict).await;
+        }
+
+        let strategy = self
+            .strategies
+            .get(&conflict.entity_type)
+            .copied()
+            .unwrap_or(self.default_strategy);
+
+        Ok(match strategy {
+            ConflictStrategy::LastWriteWins => {
+                if conflict.local_updated_at > conflict.remote_updated_at {
+                    conflict.local_data.clone()
+                } else {
+                    conflict.remote_data.clone()
+                }
+            }
+            ConflictStrategy::LocalWins => conflict.local_data.clone(),
+            ConflictStrategy::RemoteWins => conflict.remote_data.clone(),
+        })
+    }
+}
+
+impl Default for ConflictResolver {
+    fn default() -> Self {
+        Self::new(ConflictStrategy::LastWriteWins)
+    }
+}
+
+/// Transport used by the sync manager to talk to a remote server
+#[async_trait]
+pub trait SyncClient: Send + Sync {
+    /// Establish (or verify) the connection to the server
+    async fn connect(&self) -> Result<(), SyncError>;
+
+    /// Send local changes, returning how many the server accepted
+    async fn push(&self, changes: &[SyncChange]) -> Result<usize, SyncError>;
+
+    /// Fetch remote changes newer than `since`
+    async fn pull(&self, since: Option<DateTime<Utc>>) -> Result<Vec<SyncChange>, SyncError>;
+
+    /// Whether the client currently has a live connection
+    async fn is_connected(&self) -> bool;
+}
+
+/// Client for the configured sync server (simplified for community)
+#[derive(Debug)]
+pub struct RemoteSyncClient {
+    server_url: String,
+    connected: RwLock<bool>,
+}
+
+impl RemoteSyncClient {
+    pub fn new(server_url: &str) -> Self {
+        Self {
+            server_url: server_url.to_string(),
+            connected: RwLock::new(false),
+        }
+    }
+}
+
+#[async_trait]
+impl SyncClient for RemoteSyncClient {
+    async fn connect(&self) -> Result<(), SyncError> {
+        println!("[SyncManager] Testing connection to: {}", self.server_url);
+
+        // Simplified connection test (would use actual HTTP client in real implementation)
+        if self.server_url.starts_with("http") {
+            *self.connected.write().await = true;
+            println!("[SyncManager] Connection test passed");
+            Ok(())
+        } else {
+            Err(SyncError::ConnectionFailed {
+                reason: "Invalid server URL".to_string(),
+            })
+        }
+    }
+
+    async fn push(&self, changes: &[SyncChange]) -> Result<usize, SyncError> {
+        if !*self.connected.read().await {
+            return Err(SyncError::NotConnected);
+        }
+        // Simplified sync - in real implementation would send HTTP requests
+        Ok(changes.len())
+    }
+
+    async fn pull(&self, _since: Option<DateTime<Utc>>) -> Result<Vec<SyncChange>, SyncError> {
+        if !*self.connected.read().await {
+            return Err(SyncError::NotConnected);
+        }
+        Ok(Vec::new())
+    }
+
+    async fn is_connected(&self) -> bool {
+        *self.connected.read().await
+    }
+}
+
+/// Client for offline-only mode: connects instantly, never transfers anything
+/// and never reports a connection, so no server is contacted
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullSyncClient;
+
+#[async_trait]
+impl SyncClient for NullSyncClient {
+    async fn connect(&self) -> Result<(), SyncError> {
+        Ok(())
+    }
+
+    async fn push(&self, _changes: &[SyncChange]) -> Result<usize, SyncError> {
+        Ok(0)
+    }
+
+    async fn pull(&self, _since: Option<DateTime<Utc>>) -> Result<Vec<SyncChange>, SyncError> {
+        Ok(Vec::new())
+    }
+
+    async fn is_connected(&self) -> bool {
+        false
+    }
+}
+
 /// Main sync manager (simplified for community)
 pub struct SyncManager {
-    #[allow(dead_code)]
     storage: Arc<StorageManager>,
     config: SyncConfig,
+    client: Arc<dyn SyncClient>,
+    change_events: broadcast::Sender<ChangeRecord>,
+    conflict_resolver: Arc<RwLock<ConflictResolver>>,
     pending_changes: Arc<RwLock<VecDeque<SyncChange>>>,
     sync_status: Arc<RwLock<HashMap<String, SyncStatus>>>,
     stats: Arc<RwLock<SyncStats>>,
     is_connected: Arc<RwLock<bool>>,
     sync_task_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    idle: crate::idle::SharedIdleState,
 }
 
 impl std::fmt::Debug for SyncManager {
@@ -163,11 +440,24 @@ impl std::fmt::Debug for SyncManager {
 }
 
 impl SyncManager {
-    /// Create a new sync manager
+    /// Create a new sync manager; uses `NullSyncClient` when `sync_enabled` is false
     pub fn new(storage: Arc<StorageManager>, config: SyncConfig) -> Self {
+        let client: Arc<dyn SyncClient> = if config.sync_enabled {
+            Arc::new(RemoteSyncClient::new(&config.server_url))
+        } else {
+            Arc::new(NullSyncClient)
+        };
+        Self::with_client(storage, config, client)
+    }
+
+    /// Create a sync manager with an explicit transport
+    pub fn with_client(storage: Arc<StorageManager>, config: SyncConfig, client: Arc<dyn SyncClient>) -> Self {
         Self {
             storage,
             config,
+            client,
+            change_events: broadcast::channel(CHANGE_CHANNEL_CAPACITY).0,
+            conflict_resolver: Arc::new(RwLock::new(ConflictResolver::default())),
             pending_changes: Arc::new(RwLock::new(VecDeque::new())),
             sync_status: Arc::new(RwLock::new(HashMap::new())),
             stats: Arc::new(RwLock::new(SyncStats {
@@ -182,15 +472,28 @@ impl SyncManager {
             })),
             is_connected: Arc::new(RwLock::new(false)),
             sync_task_handle: Arc::new(Mutex::new(None)),
+            idle: Arc::new(crate::idle::IdleState::new()),
         }
     }
     
+    /// Share the app-wide idle flag; background sync slows down while idle
+    pub fn with_idle_state(mut self, idle: crate::idle::SharedIdleState) -> Self {
+        self.idle = idle;
+        self
+    }
+    
     /// Start sync manager
     pub async fn start(&self) -> Result<(), SyncError> {
         println!("[SyncManager] Starting sync manager");
         
         // Test connection
-        self.test_connection().await?;
+        self.client.connect().await?;
+        *self.is_connected.write().await = self.client.is_connected().await;
+        
+        if !self.config.sync_enabled {
+            tracing::debug!("Sync disabled, running in offline-only mode");
+            return Ok(());
+        }
         
         // Start background sync task
         self.start_sync_task().await;
@@ -233,11 +536,48 @@ impl SyncManager {
         let mut stats = self.stats.write().await;
         stats.pending_entities += 1;
         
+        self.notify(ChangeRecord::from_change(&change, ChangeSource::Local));
+        
         Ok(())
     }
     
+    /// Subscribe to recorded changes. Delivery is asynchronous with a bounded
+    /// buffer; a receiver that falls behind gets `RecvError::Lagged(n)`.
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeRecord> {
+        self.change_events.subscribe()
+    }
+    
+    /// Closure-based listener kept for compatibility. The callback runs on its
+    /// own task fed by `subscribe()`, so it never blocks change recording.
+    pub fn add_change_listener<F>(&self, listener: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(&ChangeRecord) + Send + Sync + 'static,
+    {
+        let mut receiver = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(record) => listener(&record),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!("Change listener lagged, skipped {} change(s)", missed);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+    
+    fn notify(&self, record: ChangeRecord) {
+        // No subscribers is not an error
+        let _ = self.change_events.send(record);
+    }
+    
     /// Force immediate sync
     pub async fn sync_now(&self) -> Result<SyncStats, SyncError> {
+        if !self.config.sync_enabled {
+            return Ok(self.get_stats().await);
+        }
+        
         println!("[SyncManager] Starting immediate sync");
         let start_time = std::time::Instant::now();
         
@@ -265,6 +605,161 @@ impl SyncManager {
         }
     }
     
+    /// Register a custom conflict resolver for an entity type
+    pub async fn register_conflict_resolver(&self, entity_type: &str, resolver: Arc<dyn CustomConflictResolver>) {
+        self.conflict_resolver.write().await.register_custom(entity_type, resolver);
+    }
+
+    /// Use a built-in conflict strategy for an entity type
+    pub async fn set_conflict_strategy(&self, entity_type: &str, strategy: ConflictStrategy) {
+        self.conflict_resolver.write().await.set_strategy(entity_type, strategy);
+    }
+
+    /// Apply remote changes to local storage. Entities with unsynced local
+    /// edits are conflicts and go through the `ConflictResolver`, which prefers
+    /// a registered custom resolver for the entity type.
+    pub async fn perform_sync(&self, remote_changes: Vec<SyncChange>) -> Result<SyncStats, SyncError> {
+        let start_time = std::time::Instant::now();
+        let ctx = StorageContext {
+            user_id: "sync".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            skip_validation: true,
+        };
+
+        for change in remote_changes {
+            let local = self.storage.get(&change.entity_id, &ctx).await
+                .map_err(|e| SyncError::StorageError { error: e.to_string() })?;
+            let has_local_edits = matches!(
+                self.sync_status.read().await.get(&change.entity_id),
+                Some(SyncStatus::Pending) | Some(SyncStatus::Conflict)
+            );
+
+            let final_status = match (local, &change.operation) {
+                (Some(local), SyncOperation::Create | SyncOperation::Update | SyncOperation::Restore) if has_local_edits => {
+                    let conflict = ConflictRecord {
+                        entity_id: change.entity_id.clone(),
+                        entity_type: change.entity_type.clone(),
+                        local_data: local.data.clone(),
+                        remote_data: change.data.clone().unwrap_or(Value::Null),
+                        local_version: local.version,
+                        remote_version: change.version,
+                        local_updated_at: local.updated_at,
+                        remote_updated_at: change.timestamp,
+                        remote_user_id: change.user_id.clone(),
+                    };
+
+                    let resolved = self.conflict_resolver.read().await.resolve(&conflict).await?;
+                    self.write_entity(&change, Some(local), resolved, &ctx).await?;
+                    self.stats.write().await.conflict_entities += 1;
+                    SyncStatus::Synced
+                }
+                (local, SyncOperation::Create | SyncOperation::Update | SyncOperation::Restore) => {
+                    let data = change.data.clone().unwrap_or(Value::Null);
+                    self.write_entity(&change, local, data, &ctx).await?;
+                    SyncStatus::Synced
+                }
+                (_, SyncOperation::Delete) => {
+                    self.storage.delete(&change.entity_id, &CascadeMode::None, &ctx).await
+                        .map_err(|e| SyncError::StorageError { error: e.to_string() })?;
+                    SyncStatus::Synced
+                }
+            };
+
+            self.sync_status.write().await.insert(change.entity_id.clone(), final_status);
+            self.stats.write().await.synced_entities += 1;
+            self.notify(ChangeRecord::from_change(&change, ChangeSource::Remote));
+        }
+
+        let mut stats = self.stats.write().await;
+        stats.sync_duration_ms = start_time.elapsed().as_millis() as u64;
+        stats.last_sync = Some(Utc::now());
+        Ok(stats.clone())
+    }
+
+    /// Pull remote changes and report what `perform_sync` would do with them,
+    /// without writing anything to storage
+    pub async fn sync_dry_run(&self) -> Result<SyncPreview, SyncError> {
+        let since = self.stats.read().await.last_sync;
+        let remote_changes = self.client.pull(since).await?;
+        self.preview_changes(&remote_changes).await
+    }
+
+    /// Classify remote changes against local state using the same rules as `perform_sync`
+    pub async fn preview_changes(&self, remote_changes: &[SyncChange]) -> Result<SyncPreview, SyncError> {
+        let ctx = StorageContext {
+            user_id: "sync".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            skip_validation: true,
+        };
+        let mut preview = SyncPreview::default();
+
+        for change in remote_changes {
+            let local = self.storage.get(&change.entity_id, &ctx).await
+                .map_err(|e| SyncError::StorageError { error: e.to_string() })?;
+            let has_local_edits = matches!(
+                self.sync_status.read().await.get(&change.entity_id),
+                Some(SyncStatus::Pending) | Some(SyncStatus::Conflict)
+            );
+            let entity_id = change.entity_id.clone();
+
+            match (local, &change.operation) {
+                (Some(_), SyncOperation::Create | SyncOperation::Update | SyncOperation::Restore) if has_local_edits => {
+                    preview.conflicts.push(entity_id);
+                    preview.overwritten_local_edits += 1;
+                }
+                (Some(_), SyncOperation::Create | SyncOperation::Update | SyncOperation::Restore) => {
+                    preview.to_update.push(entity_id);
+                }
+                (None, SyncOperation::Create | SyncOperation::Update | SyncOperation::Restore) => {
+                    preview.to_create.push(entity_id);
+                }
+                (local, SyncOperation::Delete) => {
+                    if local.is_some() && has_local_edits {
+                        preview.overwritten_local_edits += 1;
+                    }
+                    preview.to_delete.push(entity_id);
+                }
+            }
+        }
+
+        Ok(preview)
+    }
+
+    async fn write_entity(
+        &self,
+        change: &SyncChange,
+        local: Option<StoredEntity>,
+        data: Value,
+        ctx: &StorageContext,
+    ) -> Result<(), SyncError> {
+        let now = Utc::now();
+        let entity = match local {
+            Some(mut existing) => {
+                existing.data = data;
+                existing.version = existing.version.max(change.version);
+                existing.deleted_at = None;
+                existing
+            }
+            None => StoredEntity {
+                id: change.entity_id.clone(),
+                entity_type: change.entity_type.clone(),
+                data,
+                created_at: change.timestamp,
+                updated_at: now,
+                created_by: change.user_id.clone(),
+                updated_by: change.user_id.clone(),
+                version: change.version.saturating_sub(1),
+                deleted_at: None,
+                sync_status: crate::storage::SyncStatus::Synced,
+            },
+        };
+
+        self.storage.put(&change.entity_id, entity, ctx).await
+            .map_err(|e| SyncError::StorageError { error: e.to_string() })
+    }
+
     /// Get sync statistics
     pub async fn get_stats(&self) -> SyncStats {
         self.stats.read().await.clone()
@@ -283,23 +778,13 @@ impl SyncManager {
         *self.is_connected.read().await
     }
     
-    // Private helper methods
-    
-    async fn test_connection(&self) -> Result<(), SyncError> {
-        println!("[SyncManager] Testing connection to: {}", self.config.server_url);
-        
-        // Simplified connection test (would use actual HTTP client in real implementation)
-        if self.config.server_url.starts_with("http") {
-            *self.is_connected.write().await = true;
-            println!("[SyncManager] Connection test passed");
-            Ok(())
-        } else {
-            Err(SyncError::ConnectionFailed {
-                reason: "Invalid server URL".to_string(),
-            })
-        }
+    /// Whether this manager talks to a sync server at all
+    pub fn is_sync_enabled(&self) -> bool {
+        self.config.sync_enabled
     }
     
+    // Private helper methods
+    
     async fn start_sync_task(&self) {
         let sync_manager = SyncManagerRef {
             pending_changes: self.pending_changes.clone(),
@@ -307,6 +792,7 @@ impl SyncManager {
             stats: self.stats.clone(),
             is_connected: self.is_connected.clone(),
             config: self.config.clone(),
+            idle: self.idle.clone(),
         };
         
         let handle = tokio::spawn(async move {
@@ -337,7 +823,8 @@ impl SyncManager {
     async fn sync_batch(&self, changes: &[SyncChange]) -> Result<(), SyncError> {
         println!("[SyncManager] Syncing batch of {} changes", changes.len());
         
-        // Simplified sync - in real implementation would send HTTP requests
+        self.client.push(changes).await?;
+        
         for change in changes {
             // Update sync status
             let mut status_map = self.sync_status.write().await;
@@ -365,16 +852,22 @@ struct SyncManagerRef {
     stats: Arc<RwLock<SyncStats>>,
     is_connected: Arc<RwLock<bool>>,
     config: SyncConfig,
+    idle: crate::idle::SharedIdleState,
 }
 
 impl SyncManagerRef {
     async fn run_sync_loop(&self) {
-        let mut interval = tokio::time::interval(
-            std::time::Duration::from_secs(self.config.sync_interval_seconds)
-        );
-        
         loop {
-            interval.tick().await;
+            let idle = self.idle.is_idle();
+            let sleep = tokio::time::sleep(self.config.interval(idle));
+            if idle {
+                // Coming back from idle resumes the normal cadence right away
+                let resumed = self.idle.wait_until_active();
+                futures::pin_mut!(sleep, resumed);
+                futures::future::select(sleep, resumed).await;
+            } else {
+                sleep.await;
+            }
             
             if !*self.is_connected.read().await {
                 continue;
@@ -398,13 +891,25 @@ impl SyncConfig {
             server_url: server_url.to_string(),
             auth_token: None,
             sync_interval_seconds: 60,
+            idle_sync_interval_seconds: default_idle_sync_interval(),
             batch_size: 100,
             timeout_seconds: 30,
             enable_realtime: false,
+            sync_enabled: true,
             retry_config: RetryConfig::default(),
         }
     }
     
+    /// Configuration for local-only use; no server is ever contacted
+    pub fn offline() -> Self {
+        Self::new("").with_sync_enabled(false)
+    }
+    
+    pub fn with_sync_enabled(mut self, enabled: bool) -> Self {
+        self.sync_enabled = enabled;
+        self
+    }
+    
     pub fn with_auth_token(mut self, token: &str) -> Self {
         self.auth_token = Some(token.to_string());
         self
@@ -419,10 +924,168 @@ impl SyncConfig {
         self.batch_size = size;
         self
     }
+    
+    pub fn with_idle_sync_interval(mut self, seconds: u64) -> Self {
+        self.idle_sync_interval_seconds = seconds;
+        self
+    }
+    
+    /// Time between background syncs; never shorter than the active interval
+    pub fn interval(&self, idle: bool) -> std::time::Duration {
+        let seconds = if idle {
+            self.idle_sync_interval_seconds.max(self.sync_interval_seconds)
+        } else {
+            self.sync_interval_seconds
+        };
+        std::time::Duration::from_secs(seconds)
+    }
 }
 
 impl Default for SyncConfig {
     fn default() -> Self {
         Self::new("http://localhost:3000")
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sums numeric counters from both sides, like a grow-only CRDT counter
+    struct CounterMerge;
+
+    #[async_trait]
+    impl CustomConflictResolver for CounterMerge {
+        async fn resolve(&self, conflict: &ConflictRecord) -> Result<Value, SyncError> {
+            let local = conflict.local_data["count"].as_i64().unwrap_or(0);
+            let remote = conflict.remote_data["count"].as_i64().unwrap_or(0);
+            Ok(serde_json::json!({ "count": local + remote }))
+        }
+    }
+
+    fn conflict(entity_type: &str) -> ConflictRecord {
+        ConflictRecord {
+            entity_id: "c1".to_string(),
+            entity_type: entity_type.to_string(),
+            local_data: serde_json::json!({ "count": 2 }),
+            remote_data: serde_json::json!({ "count": 3 }),
+            local_version: 2,
+            remote_version: 2,
+            local_updated_at: Utc::now(),
+            remote_updated_at: Utc::now() - chrono::Duration::seconds(10),
+            remote_user_id: "remote".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_resolver_preferred_over_builtin() {
+        let mut resolver = ConflictResolver::default();
+        resolver.register_custom("counter", Arc::new(CounterMerge));
+
+        let merged = resolver.resolve(&conflict("counter")).await.unwrap();
+        assert_eq!(merged["count"], 5);
+
+        // Other types still use the built-in LastWriteWins (local is newer here)
+        let lww = resolver.resolve(&conflict("note")).await.unwrap();
+        assert_eq!(lww["count"], 2);
+    }
+
+    fn local_change(entity_id: &str) -> SyncChange {
+        SyncChange {
+            entity_id: entity_id.to_string(),
+            entity_type: "note".to_string(),
+            operation: SyncOperation::Update,
+            timestamp: Utc::now(),
+            data: Some(serde_json::json!({ "text": "hi" })),
+            version: 1,
+            user_id: "local".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_changes_and_report_lag() {
+        let manager = SyncManager::new(Arc::new(StorageManager::new()), SyncConfig::offline());
+        let mut receiver = manager.subscribe();
+
+        manager.queue_change(local_change("n1")).await.unwrap();
+        let record = receiver.recv().await.unwrap();
+        assert_eq!(record.entity_id, "n1");
+        assert_eq!(record.source, ChangeSource::Local);
+
+        for i in 0..CHANGE_CHANNEL_CAPACITY + 5 {
+            manager.queue_change(local_change(&format!("n{}", i))).await.unwrap();
+        }
+        assert!(matches!(
+            receiver.recv().await,
+            Err(broadcast::error::RecvError::Lagged(5))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_change_listener_adapter() {
+        let manager = SyncManager::new(Arc::new(StorageManager::new()), SyncConfig::offline());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let handle = manager.add_change_listener(move |record| {
+            let _ = tx.send(record.entity_id.clone());
+        });
+
+        manager.queue_change(local_change("n1")).await.unwrap();
+        assert_eq!(rx.recv().await.unwrap(), "n1");
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_preview_changes_does_not_write() {
+        let storage = Arc::new(StorageManager::new());
+        let manager = SyncManager::new(storage.clone(), SyncConfig::offline());
+        let ctx = StorageContext {
+            user_id: "local".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            skip_validation: false,
+        };
+
+        // "edited" has unsynced local edits, "clean" is already synced
+        manager.perform_sync(vec![local_change("edited"), local_change("clean")]).await.unwrap();
+        manager.queue_change(local_change("edited")).await.unwrap();
+
+        let mut delete = local_change("edited");
+        delete.operation = SyncOperation::Delete;
+        let remote = vec![local_change("edited"), local_change("clean"), local_change("new"), delete];
+
+        let preview = manager.preview_changes(&remote).await.unwrap();
+        assert_eq!(preview.conflicts, vec!["edited".to_string()]);
+        assert_eq!(preview.to_update, vec!["clean".to_string()]);
+        assert_eq!(preview.to_create, vec!["new".to_string()]);
+        assert_eq!(preview.to_delete, vec!["edited".to_string()]);
+        assert_eq!(preview.overwritten_local_edits, 2);
+        assert!(storage.get("new", &ctx).await.unwrap().is_none());
+
+        let dry_run = manager.sync_dry_run().await.unwrap();
+        assert_eq!(dry_run.total_changes(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_offline_mode_uses_null_client() {
+        let manager = SyncManager::new(Arc::new(StorageManager::new()), SyncConfig::offline());
+        assert!(!manager.is_sync_enabled());
+
+        manager.start().await.unwrap();
+        assert!(!manager.is_connected().await);
+
+        let stats = manager.sync_now().await.unwrap();
+        assert_eq!(stats.synced_entities, 0);
+        manager.stop().await.unwrap();
+    }
+
+    #[test]
+    fn test_idle_sync_interval() {
+        let config = SyncConfig::default().with_sync_interval(60).with_idle_sync_interval(900);
+        assert_eq!(config.interval(false).as_secs(), 60);
+        assert_eq!(config.interval(true).as_secs(), 900);
+
+        // Idle never syncs more often than active
+        let config = config.with_idle_sync_interval(10);
+        assert_eq!(config.interval(true).as_secs(), 60);
+    }
+}
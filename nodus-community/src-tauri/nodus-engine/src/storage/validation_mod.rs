@@ -4,11 +4,34 @@
 
 use std::collections::HashMap;
 use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use regex::Regex;
+use once_cell::sync::Lazy;
 use uuid::Uuid;
 
+/// Process-wide cache of compiled regexes keyed by pattern, so schema reloads
+/// and repeated validator construction reuse compiled patterns
+static REGEX_CACHE: Lazy<std::sync::RwLock<HashMap<String, Regex>>> =
+    Lazy::new(|| std::sync::RwLock::new(HashMap::new()));
+
+/// Get a compiled regex from the process-wide cache, compiling it on first use
+pub fn cached_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    if let Some(regex) = REGEX_CACHE.read().ok().and_then(|cache| cache.get(pattern).cloned()) {
+        return Ok(regex);
+    }
+
+    let compiled = Regex::new(pattern)?;
+    if let Ok(mut cache) = REGEX_CACHE.write() {
+        cache.insert(pattern.to_string(), compiled.clone());
+    }
+    Ok(compiled)
+}
+
+/// Default number of entities validated concurrently by `validate_batch`
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 8;
+
 /// Validation errors
 #[derive(Debug, thiserror::Error, Clone)]
 pub enum ValidationError {
@@ -70,6 +93,26 @@ pub struct ValidationResult {
     pub validation_time_ms: u64,
 }
 
+/// Serializable summary of a `ValidationResult` for callers outside the engine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub is_valid: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    pub validation_time_ms: u64,
+}
+
+impl From<ValidationResult> for ValidationReport {
+    fn from(result: ValidationResult) -> Self {
+        Self {
+            is_valid: result.is_valid,
+            errors: result.errors.iter().map(|e| e.to_string()).collect(),
+            warnings: result.warnings,
+            validation_time_ms: result.validation_time_ms,
+        }
+    }
+}
+
 /// Field validation rule
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationRule {
@@ -78,6 +121,92 @@ pub struct ValidationRule {
     pub data_type: DataType,
     pub constraints: Vec<Constraint>,
     pub custom_validators: Vec<String>,
+    /// Rule only applies when all conditions hold (e.g. "B required when A == X")
+    #[serde(default)]
+    pub conditions: Vec<ValidationCondition>,
+    /// Warning/Info rules report into `warnings` without failing validation
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+/// Condition on a sibling field that gates whether a rule applies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationCondition {
+    pub field: String,
+    pub operator: ConditionOperator,
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// Comparison operators for conditions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConditionOperator {
+    Equals,
+    NotEquals,
+    In,
+    NotIn,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+    Contains,
+    Exists,
+    NotExists,
+}
+
+impl ConditionOperator {
+    /// Evaluate `actual <op> expected`. A missing or null `actual` only
+    /// satisfies `NotExists`, `NotEquals` and `NotIn`.
+    pub fn evaluate(&self, actual: Option<&Value>, expected: &Value) -> bool {
+        let actual = actual.filter(|v| !v.is_null());
+        match self {
+            Self::Exists => actual.is_some(),
+            Self::NotExists => actual.is_none(),
+            Self::Equals => actual == Some(expected),
+            Self::NotEquals => actual != Some(expected),
+            Self::In => match (actual, expected.as_array()) {
+                (Some(a), Some(options)) => options.contains(a),
+                _ => false,
+            },
+            Self::NotIn => match (actual, expected.as_array()) {
+                (Some(a), Some(options)) => !options.contains(a),
+                _ => true,
+            },
+            Self::GreaterThan | Self::GreaterThanOrEqual | Self::LessThan | Self::LessThanOrEqual => {
+                let Some(ordering) = actual.and_then(|a| Self::compare(a, expected)) else {
+                    return false;
+                };
+                match self {
+                    Self::GreaterThan => ordering.is_gt(),
+                    Self::GreaterThanOrEqual => ordering.is_ge(),
+                    Self::LessThan => ordering.is_lt(),
+                    _ => ordering.is_le(),
+                }
+            }
+            Self::Contains => match actual {
+                Some(Value::String(s)) => expected.as_str().map_or(false, |e| s.contains(e)),
+                Some(Value::Array(items)) => items.contains(expected),
+                Some(Value::Object(map)) => expected.as_str().map_or(false, |k| map.contains_key(k)),
+                _ => false,
+            },
+        }
+    }
+
+    /// Order numbers numerically and strings lexically (ISO timestamps sort correctly)
+    fn compare(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
+        match (a, b) {
+            (Value::Number(x), Value::Number(y)) => x.as_f64()?.partial_cmp(&y.as_f64()?),
+            (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+            _ => None,
+        }
+    }
+}
+
+impl ValidationCondition {
+    /// Check the condition against the sibling field in `data`
+    pub fn is_met(&self, data: &Value) -> bool {
+        self.operator.evaluate(data.get(&self.field), &self.value)
+    }
 }
 
 /// Data types for validation
@@ -169,8 +298,9 @@ pub enum BusinessRuleType {
 }
 
 /// Validation severity levels
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum Severity {
+    #[default]
     Error,
     Warning,
     Info,
@@ -191,7 +321,6 @@ pub struct ValidationStats {
 pub struct ValidationManager {
     schemas: Arc<RwLock<HashMap<String, ValidationSchema>>>,
     custom_validators: Arc<RwLock<HashMap<String, Box<dyn CustomValidator>>>>,
-    compiled_regex: Arc<RwLock<HashMap<String, Regex>>>,
     stats: Arc<RwLock<ValidationStats>>,
 }
 
@@ -215,12 +344,12 @@ pub trait CustomValidator: Send + Sync {
 }
 
 impl ValidationManager {
-    /// Create a new validation manager
+    /// Create a new validation manager with the built-in validators
+    /// (`detect_pii`, `detect_pii_warn`) registered
     pub fn new() -> Self {
         Self {
             schemas: Arc::new(RwLock::new(HashMap::new())),
-            custom_validators: Arc::new(RwLock::new(HashMap::new())),
-            compiled_regex: Arc::new(RwLock::new(HashMap::new())),
+            custom_validators: Arc::new(RwLock::new(builtin_validators())),
             stats: Arc::new(RwLock::new(ValidationStats {
                 total_validations: 0,
                 successful_validations: 0,
@@ -242,6 +371,11 @@ impl ValidationManager {
         Ok(())
     }
     
+    /// Whether a schema is registered under `schema_name`
+    pub async fn has_schema(&self, schema_name: &str) -> bool {
+        self.schemas.read().await.contains_key(schema_name)
+    }
+    
     /// Register a custom validator
     pub async fn register_validator(&self, validator: Box<dyn CustomValidator>) -> Result<(), ValidationError> {
         println!("[ValidationManager] Registering validator: {}", validator.name());
@@ -306,12 +440,14 @@ impl ValidationManager {
         
         // Basic field validation
         for rule in &schema.rules {
-            if let Err(err) = self.validate_field(&sanitized_data, rule, context).await {
-                if matches!(context.validation_mode, ValidationMode::Strict) {
-                    errors.push(err);
-                } else {
-                    warnings.push(format!("Field validation warning: {}", err));
-                }
+            match self.validate_field(&sanitized_data, rule, context).await {
+                Ok(field_warnings) => warnings.extend(field_warnings),
+                Err(err) => match rule.severity {
+                    Severity::Warning => warnings.push(format!("Field validation warning: {}", err)),
+                    Severity::Info => warnings.push(format!("Field validation info: {}", err)),
+                    Severity::Error if matches!(context.validation_mode, ValidationMode::Strict) => errors.push(err),
+                    Severity::Error => warnings.push(format!("Field validation warning: {}", err)),
+                },
             }
         }
         
@@ -363,29 +499,72 @@ impl ValidationManager {
         })
     }
     
+    /// Validate many entities against one schema, e.g. an imported file.
+    /// Results are returned positionally; a lookup failure becomes an invalid
+    /// result for that item instead of aborting the batch.
+    pub async fn validate_batch(
+        &self,
+        schema_name: &str,
+        items: Vec<(Value, ValidationContext)>,
+    ) -> Vec<ValidationResult> {
+        self.validate_batch_with_concurrency(schema_name, items, DEFAULT_BATCH_CONCURRENCY).await
+    }
+
+    /// `validate_batch` with an explicit bound on concurrent validations
+    pub async fn validate_batch_with_concurrency(
+        &self,
+        schema_name: &str,
+        items: Vec<(Value, ValidationContext)>,
+        max_concurrency: usize,
+    ) -> Vec<ValidationResult> {
+        stream::iter(items)
+            .map(|(data, context)| async move {
+                match self.validate(&data, schema_name, &context).await {
+                    Ok(result) => result,
+                    Err(err) => ValidationResult {
+                        is_valid: false,
+                        errors: vec![err],
+                        warnings: vec![],
+                        sanitized_data: None,
+                        validation_time_ms: 0,
+                    },
+                }
+            })
+            // `buffered` keeps results in input order
+            .buffered(max_concurrency.max(1))
+            .collect()
+            .await
+    }
+
     /// Get validation statistics
     pub async fn get_stats(&self) -> ValidationStats {
         self.stats.read().await.clone()
     }
     
-    /// Clear all cached schemas and validators
+    /// Clear all cached schemas and registered validators; the built-in
+    /// validators stay available. The process-wide regex cache is shared
+    /// with other managers and is left untouched.
     pub async fn clear_cache(&self) -> Result<(), ValidationError> {
         println!("[ValidationManager] Clearing validation cache");
         
         let mut schemas = self.schemas.write().await;
         let mut validators = self.custom_validators.write().await;
-        let mut regex_cache = self.compiled_regex.write().await;
         
         schemas.clear();
-        validators.clear();
-        regex_cache.clear();
+        *validators = builtin_validators();
         
         Ok(())
     }
     
     // Private validation methods
     
-    async fn validate_field(&self, data: &Value, rule: &ValidationRule, _context: &ValidationContext) -> Result<(), ValidationError> {
+    /// Check one rule; on success, returns warnings raised by its custom validators
+    async fn validate_field(&self, data: &Value, rule: &ValidationRule, context: &ValidationContext) -> Result<Vec<String>, ValidationError> {
+        // Conditional rules only apply when every condition holds
+        if !rule.conditions.iter().all(|condition| condition.is_met(data)) {
+            return Ok(Vec::new());
+        }
+
         // Extract field value
         let field_value = if rule.field_name.contains('.') {
             // Handle nested field access (simplified)
@@ -404,7 +583,7 @@ impl ValidationManager {
         // If field is not present and not required, skip validation
         let value = match field_value {
             Some(val) if val != &Value::Null => val,
-            _ => return Ok(()),
+            _ => return Ok(Vec::new()),
         };
         
         // Validate data type
@@ -414,8 +593,26 @@ impl ValidationManager {
         for constraint in &rule.constraints {
             self.validate_constraint(value, constraint, &rule.field_name).await?;
         }
+
+        // Run registered custom validators named by the rule
+        let mut warnings = Vec::new();
+        if !rule.custom_validators.is_empty() {
+            let validators = self.custom_validators.read().await;
+            for name in &rule.custom_validators {
+                // Unknown names are skipped, as before validators ran from rules
+                let Some(validator) = validators.get(name) else {
+                    println!("[ValidationManager] Skipping unregistered validator: {}", name);
+                    continue;
+                };
+                let result = validator.validate(value, context).await?;
+                if let Some(err) = result.errors.into_iter().next() {
+                    return Err(err);
+                }
+                warnings.extend(result.warnings.into_iter().map(|w| format!("{}: {}", rule.field_name, w)));
+            }
+        }
         
-        Ok(())
+        Ok(warnings)
     }
     
     fn validate_data_type(&self, value: &Value, data_type: &DataType, field_name: &str) -> Result<(), ValidationError> {
@@ -472,32 +669,26 @@ impl ValidationManager {
                     });
                 }
             },
-            DataType::Boolean => {
-                if !value.is_boolean() {
-                    return Err(ValidationError::InvalidType {
-                        field: field_name.to_string(),
-                        expected: "boolean".to_string(),
-                        actual: format!("{:?}", value),
-                    });
-                }
+            DataType::Boolean if !value.is_boolean() => {
+                return Err(ValidationError::InvalidType {
+                    field: field_name.to_string(),
+                    expected: "boolean".to_string(),
+                    actual: format!("{:?}", value),
+                });
             },
-            DataType::Array { .. } => {
-                if !value.is_array() {
-                    return Err(ValidationError::InvalidType {
-                        field: field_name.to_string(),
-                        expected: "array".to_string(),
-                        actual: format!("{:?}", value),
-                    });
-                }
+            DataType::Array { .. } if !value.is_array() => {
+                return Err(ValidationError::InvalidType {
+                    field: field_name.to_string(),
+                    expected: "array".to_string(),
+                    actual: format!("{:?}", value),
+                });
             },
-            DataType::Object { .. } => {
-                if !value.is_object() {
-                    return Err(ValidationError::InvalidType {
-                        field: field_name.to_string(),
-                        expected: "object".to_string(),
-                        actual: format!("{:?}", value),
-                    });
-                }
+            DataType::Object { .. } if !value.is_object() => {
+                return Err(ValidationError::InvalidType {
+                    field: field_name.to_string(),
+                    expected: "object".to_string(),
+                    actual: format!("{:?}", value),
+                });
             },
             DataType::Email => {
                 if let Some(s) = value.as_str() {
@@ -543,22 +734,10 @@ impl ValidationManager {
         match constraint {
             Constraint::Regex { pattern, .. } => {
                 if let Some(s) = value.as_str() {
-                    // Get compiled regex or compile it
-                    let regex = {
-                        let mut regex_cache = self.compiled_regex.write().await;
-                        match regex_cache.get(pattern) {
-                            Some(r) => r.clone(),
-                            None => {
-                                let compiled = Regex::new(pattern)
-                                    .map_err(|e| ValidationError::InvalidFormat {
-                                        field: field_name.to_string(),
-                                        reason: format!("Invalid regex pattern: {}", e),
-                                    })?;
-                                regex_cache.insert(pattern.clone(), compiled.clone());
-                                compiled
-                            }
-                        }
-                    };
+                    let regex = cached_regex(pattern).map_err(|e| ValidationError::InvalidFormat {
+                        field: field_name.to_string(),
+                        reason: format!("Invalid regex pattern: {}", e),
+                    })?;
                     
                     if !regex.is_match(s) {
                         return Err(ValidationError::InvalidFormat {
@@ -603,4 +782,431 @@ impl Default for ValidationManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+/// Validators every manager starts with, keyed by name
+fn builtin_validators() -> HashMap<String, Box<dyn CustomValidator>> {
+    let validators: Vec<Box<dyn CustomValidator>> = vec![
+        Box::new(PiiDetectionValidator::default()),
+        Box::new(PiiDetectionValidator::default().with_warn_only(true)),
+    ];
+    validators.into_iter().map(|v| (v.name().to_string(), v)).collect()
+}
+
+/// Classes of personally identifiable information detected by `PiiDetectionValidator`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PiiClass {
+    Email,
+    CreditCard,
+    Phone,
+    IpAddress,
+}
+
+impl PiiClass {
+    pub fn all() -> Vec<PiiClass> {
+        vec![Self::Email, Self::CreditCard, Self::Phone, Self::IpAddress]
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Email => "email",
+            Self::CreditCard => "credit_card",
+            Self::Phone => "phone",
+            Self::IpAddress => "ip_address",
+        }
+    }
+
+    fn pattern(&self) -> &'static str {
+        match self {
+            Self::Email => r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+            Self::CreditCard => r"\b(?:\d[ -]?){12,18}\d\b",
+            Self::Phone => r"(?:\+\d{1,3}[ .-]?)?\(?\b\d{3}\)?[ .-]?\d{3}[ .-]?\d{4}\b",
+            Self::IpAddress => r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b",
+        }
+    }
+}
+
+/// Flags string values that contain PII (emails, card numbers, phones, IPs).
+/// Registered by default as `detect_pii`, which rejects, and as
+/// `detect_pii_warn`, which only reports detections as warnings so the
+/// validator can run for logging without blocking saves.
+pub struct PiiDetectionValidator {
+    classes: Vec<PiiClass>,
+    warn_only: bool,
+}
+
+impl PiiDetectionValidator {
+    pub fn new(classes: Vec<PiiClass>) -> Self {
+        Self { classes, warn_only: false }
+    }
+
+    /// Report detections as warnings instead of a `SecurityViolation`
+    pub fn with_warn_only(mut self, warn_only: bool) -> Self {
+        self.warn_only = warn_only;
+        self
+    }
+
+    /// Detect PII classes present in a single string
+    pub fn detect(&self, text: &str) -> Result<Vec<PiiClass>, ValidationError> {
+        let mut found = Vec::new();
+        for class in &self.classes {
+            let regex = cached_regex(class.pattern()).map_err(|e| ValidationError::CustomValidationFailed {
+                validator: "detect_pii".to_string(),
+                reason: format!("Invalid built-in pattern for {}: {}", class.label(), e),
+            })?;
+            let detected = match class {
+                // Only digit runs that pass the Luhn check count as card numbers
+                PiiClass::CreditCard => regex.find_iter(text).any(|m| luhn_valid(m.as_str())),
+                _ => regex.is_match(text),
+            };
+            if detected {
+                found.push(*class);
+            }
+        }
+        Ok(found)
+    }
+
+    fn scan(&self, value: &Value, path: &str, hits: &mut Vec<(String, Vec<PiiClass>)>) -> Result<(), ValidationError> {
+        match value {
+            Value::String(s) => {
+                let found = self.detect(s)?;
+                if !found.is_empty() {
+                    hits.push((path.to_string(), found));
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.scan(item, &format!("{}[{}]", path, i), hits)?;
+                }
+            }
+            Value::Object(map) => {
+                for (key, item) in map {
+                    let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    self.scan(item, &child, hits)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl Default for PiiDetectionValidator {
+    fn default() -> Self {
+        Self::new(PiiClass::all())
+    }
+}
+
+#[async_trait]
+impl CustomValidator for PiiDetectionValidator {
+    async fn validate(&self, value: &Value, _context: &ValidationContext) -> Result<ValidationResult, ValidationError> {
+        let start_time = std::time::Instant::now();
+        let mut hits = Vec::new();
+        self.scan(value, "", &mut hits)?;
+
+        let descriptions: Vec<String> = hits
+            .iter()
+            .map(|(path, classes)| {
+                let labels: Vec<&str> = classes.iter().map(|c| c.label()).collect();
+                let location = if path.is_empty() { "value" } else { path.as_str() };
+                format!("{}: {}", location, labels.join(", "))
+            })
+            .collect();
+
+        let mut result = ValidationResult {
+            is_valid: true,
+            errors: vec![],
+            warnings: vec![],
+            sanitized_data: Some(value.clone()),
+            validation_time_ms: 0,
+        };
+
+        if !descriptions.is_empty() {
+            if self.warn_only {
+                result.warnings = descriptions.iter().map(|d| format!("PII detected in {}", d)).collect();
+            } else {
+                result.is_valid = false;
+                result.sanitized_data = None;
+                result.errors.push(ValidationError::SecurityViolation {
+                    field: hits.iter().map(|(p, _)| p.clone()).collect::<Vec<_>>().join(", "),
+                    reason: format!("PII detected ({})", descriptions.join("; ")),
+                });
+            }
+        }
+
+        result.validation_time_ms = start_time.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        if self.warn_only {
+            "detect_pii_warn"
+        } else {
+            "detect_pii"
+        }
+    }
+}
+
+/// Luhn checksum for card-like digit runs (spaces and dashes ignored)
+fn luhn_valid(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                *d
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn context() -> ValidationContext {
+        ValidationContext {
+            user_id: "test".to_string(),
+            session_id: Uuid::new_v4(),
+            operation_id: Uuid::new_v4(),
+            entity_type: None,
+            validation_mode: ValidationMode::Strict,
+        }
+    }
+
+    fn conditional_schema(operator: ConditionOperator, value: Value) -> ValidationSchema {
+        ValidationSchema {
+            schema_name: "conditional".to_string(),
+            version: "1".to_string(),
+            description: String::new(),
+            rules: vec![ValidationRule {
+                field_name: "b".to_string(),
+                required: true,
+                data_type: DataType::String { min_length: None, max_length: None },
+                constraints: vec![],
+                custom_validators: vec![],
+                conditions: vec![ValidationCondition { field: "a".to_string(), operator, value }],
+                severity: Severity::Error,
+            }],
+            cross_field_rules: vec![],
+            business_rules: vec![],
+        }
+    }
+
+    /// Validates `data` (which never has `b`) and returns whether `b` was required
+    async fn b_required(operator: ConditionOperator, value: Value, data: Value) -> bool {
+        let manager = ValidationManager::new();
+        manager.register_schema(conditional_schema(operator, value)).await.unwrap();
+        let result = manager.validate(&data, "conditional", &context()).await.unwrap();
+        !result.is_valid
+    }
+
+    #[tokio::test]
+    async fn test_condition_equals() {
+        assert!(b_required(ConditionOperator::Equals, json!("x"), json!({ "a": "x" })).await);
+        assert!(!b_required(ConditionOperator::Equals, json!("x"), json!({ "a": "y" })).await);
+    }
+
+    #[tokio::test]
+    async fn test_condition_not_equals() {
+        assert!(b_required(ConditionOperator::NotEquals, json!("x"), json!({ "a": "y" })).await);
+        assert!(!b_required(ConditionOperator::NotEquals, json!("x"), json!({ "a": "x" })).await);
+    }
+
+    #[tokio::test]
+    async fn test_condition_in_and_not_in() {
+        assert!(b_required(ConditionOperator::In, json!(["x", "y"]), json!({ "a": "y" })).await);
+        assert!(!b_required(ConditionOperator::In, json!(["x", "y"]), json!({ "a": "z" })).await);
+        assert!(b_required(ConditionOperator::NotIn, json!(["x", "y"]), json!({ "a": "z" })).await);
+        assert!(!b_required(ConditionOperator::NotIn, json!(["x", "y"]), json!({ "a": "x" })).await);
+    }
+
+    #[tokio::test]
+    async fn test_condition_numeric_comparisons() {
+        assert!(b_required(ConditionOperator::GreaterThan, json!(10), json!({ "a": 11 })).await);
+        assert!(!b_required(ConditionOperator::GreaterThan, json!(10), json!({ "a": 10 })).await);
+        assert!(b_required(ConditionOperator::GreaterThanOrEqual, json!(10), json!({ "a": 10 })).await);
+        assert!(!b_required(ConditionOperator::GreaterThanOrEqual, json!(10), json!({ "a": 9.5 })).await);
+        assert!(b_required(ConditionOperator::LessThan, json!(10), json!({ "a": 9 })).await);
+        assert!(!b_required(ConditionOperator::LessThan, json!(10), json!({ "a": 10 })).await);
+        assert!(b_required(ConditionOperator::LessThanOrEqual, json!(10), json!({ "a": 10 })).await);
+        assert!(!b_required(ConditionOperator::LessThanOrEqual, json!(10), json!({ "a": 11 })).await);
+    }
+
+    #[tokio::test]
+    async fn test_condition_contains() {
+        assert!(b_required(ConditionOperator::Contains, json!("urgent"), json!({ "a": "very urgent" })).await);
+        assert!(b_required(ConditionOperator::Contains, json!("x"), json!({ "a": ["x", "y"] })).await);
+        assert!(!b_required(ConditionOperator::Contains, json!("x"), json!({ "a": "abc" })).await);
+    }
+
+    #[tokio::test]
+    async fn test_condition_exists_and_not_exists() {
+        assert!(b_required(ConditionOperator::Exists, Value::Null, json!({ "a": 1 })).await);
+        assert!(!b_required(ConditionOperator::Exists, Value::Null, json!({ "a": null })).await);
+        assert!(b_required(ConditionOperator::NotExists, Value::Null, json!({})).await);
+        assert!(!b_required(ConditionOperator::NotExists, Value::Null, json!({ "a": 1 })).await);
+    }
+
+    #[tokio::test]
+    async fn test_warning_severity_does_not_fail_validation() {
+        let manager = ValidationManager::new();
+        manager.register_schema(ValidationSchema {
+            schema_name: "soft".to_string(),
+            version: "1".to_string(),
+            description: String::new(),
+            rules: vec![ValidationRule {
+                field_name: "description".to_string(),
+                required: false,
+                data_type: DataType::String { min_length: Some(20), max_length: None },
+                constraints: vec![],
+                custom_validators: vec![],
+                conditions: vec![],
+                severity: Severity::Warning,
+            }],
+            cross_field_rules: vec![],
+            business_rules: vec![],
+        }).await.unwrap();
+
+        let result = manager.validate(&json!({ "description": "short" }), "soft", &context()).await.unwrap();
+        assert!(result.is_valid);
+        assert!(result.errors.is_empty());
+        assert_eq!(result.warnings.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pii_detection() {
+        let validator = PiiDetectionValidator::default();
+        let data = json!({
+            "note": "mail jane@example.com",
+            "card": "4111 1111 1111 1111",
+            "not_card": "4111 1111 1111 1112",
+            "host": "10.0.0.12",
+        });
+
+        let result = validator.validate(&data, &context()).await.unwrap();
+        assert!(!result.is_valid);
+        match &result.errors[0] {
+            ValidationError::SecurityViolation { field, .. } => {
+                assert!(field.contains("note"));
+                assert!(field.contains("card"));
+                assert!(field.contains("host"));
+                assert!(!field.contains("not_card"));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        let warn_only = PiiDetectionValidator::new(vec![PiiClass::Email]).with_warn_only(true);
+        let result = warn_only.validate(&data, &context()).await.unwrap();
+        assert!(result.is_valid);
+        assert_eq!(result.warnings.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pii_validators_run_from_schema() {
+        let manager = ValidationManager::new();
+        for (schema_name, validator) in [
+            ("strict_notes", "detect_pii"),
+            ("logged_notes", "detect_pii_warn"),
+            ("legacy_notes", "retired_validator"),
+        ] {
+            manager.register_schema(ValidationSchema {
+                schema_name: schema_name.to_string(),
+                version: "1".to_string(),
+                description: String::new(),
+                rules: vec![ValidationRule {
+                    field_name: "body".to_string(),
+                    required: false,
+                    data_type: DataType::String { min_length: None, max_length: None },
+                    constraints: vec![],
+                    custom_validators: vec![validator.to_string()],
+                    conditions: vec![],
+                    severity: Severity::Error,
+                }],
+                cross_field_rules: vec![],
+                business_rules: vec![],
+            }).await.unwrap();
+        }
+        let leaky = json!({ "body": "reach me at jane@example.com" });
+
+        let rejected = manager.validate(&leaky, "strict_notes", &context()).await.unwrap();
+        assert!(!rejected.is_valid);
+        assert!(matches!(rejected.errors[0], ValidationError::SecurityViolation { .. }));
+
+        let warned = manager.validate(&leaky, "logged_notes", &context()).await.unwrap();
+        assert!(warned.is_valid);
+        assert_eq!(warned.warnings.len(), 1);
+        assert!(warned.warnings[0].starts_with("body: PII detected"));
+
+        let clean = manager.validate(&json!({ "body": "nothing here" }), "logged_notes", &context()).await.unwrap();
+        assert!(clean.is_valid && clean.warnings.is_empty());
+
+        // Validator names nobody registered are ignored
+        let legacy = manager.validate(&leaky, "legacy_notes", &context()).await.unwrap();
+        assert!(legacy.is_valid && legacy.warnings.is_empty());
+
+        // Built-ins survive a cache clear
+        manager.clear_cache().await.unwrap();
+        assert_eq!(manager.custom_validators.read().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_validate_batch_reports_each_entity() {
+        let manager = ValidationManager::new();
+        manager.register_schema(ValidationSchema {
+            schema_name: "contact".to_string(),
+            version: "1".to_string(),
+            description: String::new(),
+            rules: vec![ValidationRule {
+                field_name: "name".to_string(),
+                required: true,
+                data_type: DataType::String { min_length: Some(2), max_length: None },
+                constraints: vec![],
+                custom_validators: vec![],
+                conditions: vec![],
+                severity: Severity::Error,
+            }],
+            cross_field_rules: vec![],
+            business_rules: vec![],
+        }).await.unwrap();
+        let items = || {
+            vec![json!({ "name": "Ada" }), json!({}), json!({ "name": 7 }), json!({ "name": "Grace" })]
+                .into_iter()
+                .map(|data| (data, context()))
+                .collect::<Vec<_>>()
+        };
+
+        // Results line up with the input, whatever the concurrency
+        for concurrency in [1, 3] {
+            let results = manager.validate_batch_with_concurrency("contact", items(), concurrency).await;
+            assert_eq!(results.iter().map(|r| r.is_valid).collect::<Vec<_>>(), vec![true, false, false, true]);
+            assert!(results[0].errors.is_empty() && results[3].errors.is_empty());
+            assert!(matches!(&results[1].errors[..], [ValidationError::RequiredFieldMissing { field }] if field == "name"));
+            assert!(matches!(&results[2].errors[..], [ValidationError::InvalidType { field, .. }] if field == "name"));
+        }
+
+        // A schema that can't be found fails every item instead of the batch
+        let results = manager.validate_batch("missing", items()).await;
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|r| !r.is_valid && r.errors.len() == 1));
+    }
+
+    #[test]
+    fn test_cached_regex() {
+        let first = cached_regex(r"^[a-z]+$").unwrap();
+        let second = cached_regex(r"^[a-z]+$").unwrap();
+        assert_eq!(first.as_str(), second.as_str());
+        assert!(second.is_match("abc"));
+        assert!(cached_regex("(unclosed").is_err());
+    }
+}
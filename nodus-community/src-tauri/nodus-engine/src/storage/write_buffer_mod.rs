@@ -0,0 +1,70 @@
+// src/storage/write_buffer_mod.rs
+// Optional write coalescing for `StorageManager::put`.
+//
+// Bulk imports issue thousands of puts, and on SQLite each one is its own
+// transaction. With a write buffer, puts are held for a short window and then
+// written together with one `batch_put`, which on SQLite is one transaction.
+// Later puts to the same key replace the buffered entity, so only the last
+// write reaches the backend.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::StoredEntity;
+
+/// Buffered writes that trigger a flush
+pub const DEFAULT_WRITE_BUFFER_MAX_ENTRIES: usize = 500;
+
+/// Longest a write waits in the buffer
+pub const DEFAULT_WRITE_BUFFER_FLUSH_INTERVAL_MS: u64 = 50;
+
+/// When buffered writes are flushed: once `max_entries` keys are pending, or
+/// once the oldest pending write is `flush_interval_ms` old
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WriteBufferConfig {
+    pub max_entries: usize,
+    pub flush_interval_ms: u64,
+}
+
+impl Default for WriteBufferConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_WRITE_BUFFER_MAX_ENTRIES,
+            flush_interval_ms: DEFAULT_WRITE_BUFFER_FLUSH_INTERVAL_MS,
+        }
+    }
+}
+
+impl WriteBufferConfig {
+    pub fn flush_interval(&self) -> Duration {
+        Duration::from_millis(self.flush_interval_ms.max(1))
+    }
+}
+
+/// Writes accepted by `put` but not yet on the backend
+#[derive(Debug, Default)]
+pub(crate) struct PendingWrites {
+    pub(crate) entries: HashMap<String, StoredEntity>,
+    /// When the oldest pending write arrived
+    oldest: Option<Instant>,
+}
+
+impl PendingWrites {
+    pub(crate) fn insert(&mut self, key: &str, entity: StoredEntity) {
+        self.oldest.get_or_insert_with(Instant::now);
+        self.entries.insert(key.to_string(), entity);
+    }
+
+    pub(crate) fn is_due(&self, config: &WriteBufferConfig) -> bool {
+        self.entries.len() >= config.max_entries.max(1)
+            || self.oldest.map_or(false, |oldest| oldest.elapsed() >= config.flush_interval())
+    }
+
+    /// Forget everything pending, after it reached the backend
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.oldest = None;
+    }
+}
@@ -29,9 +29,12 @@ pub struct UniversalPluginSystem {
     #[allow(dead_code)]
     plugin_relationships: Arc<RwLock<Vec<PluginRelationship>>>,
     
-    /// License-based restrictions (from your license system)
-    license_tier: LicenseTier,
-    plugin_access_mode: PluginAccessMode,
+    /// License-based restrictions (from your license system, refreshed on license reload)
+    license_tier: Arc<RwLock<LicenseTier>>,
+    plugin_access_mode: Arc<RwLock<PluginAccessMode>>,
+    
+    /// Id source for plugin execution metadata
+    ids: crate::id_gen::SharedIdGenerator,
 }
 
 /// JavaScript Plugin (hot reloadable)
@@ -55,11 +58,32 @@ pub struct JSPlugin {
     /// License requirements (integrates with your license system)
     pub license_requirements: LicenseRequirement,
     
+    /// Oldest engine version the plugin runs on (semver), if it declares one
+    #[serde(default)]
+    pub min_engine_version: Option<String>,
+    
+    /// Where the plugin was installed from
+    #[serde(default)]
+    pub source: PluginSource,
+    
     /// Plugin state
     pub enabled: bool,
     pub loaded_at: DateTime<Utc>,
 }
 
+/// Where a JavaScript plugin came from, so the same install can be repeated
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PluginSource {
+    /// Registered with its code directly (frontend registration, upload)
+    #[default]
+    Inline,
+    /// A bundle file on this machine
+    File { path: String },
+    /// The plugin marketplace; `url` is unset for the default marketplace
+    Marketplace { url: Option<String> },
+}
+
 /// Rust Plugin Trait (for compiled plugins)
 #[async_trait]
 pub trait RustPlugin: Send + Sync + std::fmt::Debug {
@@ -79,7 +103,12 @@ pub struct PluginMetadata {
     pub author: String,
     pub description: String,
     pub tags: Vec<String>,
+    /// Position among plugins handling the same action (lower numbers first)
     pub priority: i32,
+    /// Run and then hand the action on to the next plugin in priority order,
+    /// instead of answering it
+    #[serde(default)]
+    pub pass_through: bool,
     pub dependencies: Vec<String>,
     pub conflicts: Vec<String>,
     pub homepage: Option<String>,
@@ -127,8 +156,11 @@ pub struct PluginInfo {
     pub version: String,
     pub plugin_type: PluginType,
     pub enabled: bool,
+    /// When the plugin was registered; the plugin list is ordered by this
     pub loaded_at: DateTime<Utc>,
     pub license_tier_required: LicenseTier,
+    /// Action-type patterns this plugin registered to handle
+    pub handled_actions: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,6 +169,15 @@ pub enum PluginType {
     Rust,
 }
 
+/// A plugin eligible to handle an action, as `handler_chain` orders them
+#[derive(Debug, Clone)]
+struct HandlerEntry {
+    plugin_id: String,
+    plugin_type: PluginType,
+    priority: i32,
+    pass_through: bool,
+}
+
 /// Plugin errors
 #[derive(Debug, thiserror::Error)]
 pub enum PluginError {
@@ -164,6 +205,70 @@ pub enum PluginError {
     
     #[error("Plugin initialization error: {message}")]
     InitializationError { message: String },
+    
+    #[error("Plugin requires engine version {required} or newer, this engine is {actual}")]
+    IncompatibleVersion { required: String, actual: String },
+    
+    #[error("Plugin {plugin_id} failed {stage} check: {detail}")]
+    RegistrationFailed { stage: RegistrationStage, plugin_id: String, detail: String },
+}
+
+/// Registration step a plugin failed at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationStage {
+    /// The bundle could not be read or decoded
+    Parse,
+    /// The license doesn't grant what the plugin requires
+    Capability,
+    Signature,
+    Dependency,
+    Version,
+}
+
+impl RegistrationStage {
+    /// Checks `register_js_plugin` runs against a decoded plugin, in order
+    pub const CHECKS: [RegistrationStage; 4] = [
+        RegistrationStage::Capability,
+        RegistrationStage::Version,
+        RegistrationStage::Signature,
+        RegistrationStage::Dependency,
+    ];
+
+    /// What the user can do about a failure at this stage
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            RegistrationStage::Parse => "Check that the plugin file is a valid UTF-8 JavaScript bundle.",
+            RegistrationStage::Capability => "Upgrade the license to the tier the plugin requires.",
+            RegistrationStage::Signature => "Install a signed build of the plugin; this installation only accepts signed plugins.",
+            RegistrationStage::Dependency => "Install the plugins this one depends on first.",
+            RegistrationStage::Version => "Update the app, or install a plugin release built for this version.",
+        }
+    }
+}
+
+impl std::fmt::Display for RegistrationStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            RegistrationStage::Parse => "parse",
+            RegistrationStage::Capability => "capability",
+            RegistrationStage::Signature => "signature",
+            RegistrationStage::Dependency => "dependency",
+            RegistrationStage::Version => "version",
+        };
+        f.write_str(name)
+    }
+}
+
+impl PluginError {
+    /// Wrap an error raised while registering `plugin_id` with the stage it failed at
+    pub fn at_stage(stage: RegistrationStage, plugin_id: &str) -> impl FnOnce(PluginError) -> PluginError + '_ {
+        move |error| PluginError::RegistrationFailed {
+            stage,
+            plugin_id: plugin_id.to_string(),
+            detail: error.to_string(),
+        }
+    }
 }
 
 impl UniversalPluginSystem {
@@ -179,34 +284,36 @@ impl UniversalPluginSystem {
             rust_plugins: Arc::new(RwLock::new(HashMap::new())),
             execution_order: Arc::new(RwLock::new(Vec::new())),
             plugin_relationships: Arc::new(RwLock::new(Vec::new())),
-            license_tier,
-            plugin_access_mode,
+            license_tier: Arc::new(RwLock::new(license_tier)),
+            plugin_access_mode: Arc::new(RwLock::new(plugin_access_mode)),
+            ids: crate::id_gen::default_generator(),
         }
     }
+
+    /// Use a custom id generator (e.g. deterministic ids in tests)
+    pub fn with_id_generator(mut self, ids: crate::id_gen::SharedIdGenerator) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Re-apply license constraints after the license changed at runtime
+    pub async fn update_license_constraints(&self, license_tier: LicenseTier, plugin_access_mode: PluginAccessMode) {
+        tracing::info!(
+            "Updating plugin system license constraints: tier {:?}, access mode {:?}",
+            license_tier, plugin_access_mode
+        );
+        *self.license_tier.write().await = license_tier;
+        *self.plugin_access_mode.write().await = plugin_access_mode;
+    }
     
     /// Register JavaScript plugin (with license validation)
+    /// Failures come back as `RegistrationFailed`, tagged with the check that failed.
     pub async fn register_js_plugin(&self, mut js_plugin: JSPlugin) -> Result<(), PluginError> {
-        // Check license requirements FIRST (uses your license system)
-        self.check_license_requirements(&js_plugin.license_requirements, Some(&js_plugin.id)).await?;
-
-        // Check signature if required (enterprise feature)
-        if matches!(self.plugin_access_mode, PluginAccessMode::SignedOnly) {
-            if !js_plugin.license_requirements.requires_signed {
-                return Err(PluginError::InvalidSignature { 
-                    plugin_id: js_plugin.id.clone() 
-                });
-            }
-            // Perform a minimal signature verification pass (stub).
-            // In production this should verify a cryptographic signature.
-            if !Self::verify_plugin_signature(&js_plugin) {
-                return Err(PluginError::InvalidSignature { plugin_id: js_plugin.id.clone() });
-            }
-            tracing::info!("Signature validation (stub) passed for plugin: {}", js_plugin.id);
+        // Stops at the first failed check; license requirements come first
+        for stage in RegistrationStage::CHECKS {
+            self.check_registration_stage(stage, &js_plugin).await?;
         }
 
-        // Check dependencies
-        self.check_plugin_dependencies(&js_plugin.id, &js_plugin.metadata.dependencies).await?;
-
         // Store plugin
         let plugin_id = js_plugin.id.clone();
         js_plugin.loaded_at = Utc::now();
@@ -224,6 +331,50 @@ impl UniversalPluginSystem {
         Ok(())
     }
 
+    /// Run every registration check without registering, reporting each
+    /// stage's outcome instead of stopping at the first failure
+    pub async fn validate_js_plugin(&self, js_plugin: &JSPlugin) -> Vec<(RegistrationStage, Result<(), PluginError>)> {
+        let mut results = Vec::with_capacity(RegistrationStage::CHECKS.len());
+        for stage in RegistrationStage::CHECKS {
+            results.push((stage, self.check_registration_stage(stage, js_plugin).await));
+        }
+        results
+    }
+
+    /// One registration check; failures come back as `RegistrationFailed`
+    async fn check_registration_stage(&self, stage: RegistrationStage, js_plugin: &JSPlugin) -> Result<(), PluginError> {
+        let id = js_plugin.id.as_str();
+        let result = match stage {
+            RegistrationStage::Capability => {
+                self.check_license_requirements(&js_plugin.license_requirements, Some(id)).await
+            }
+            // Reject plugins built against a newer engine API
+            RegistrationStage::Version => match &js_plugin.min_engine_version {
+                Some(required) => Self::check_engine_version(id, required),
+                None => Ok(()),
+            },
+            // Check signature if required (enterprise feature)
+            RegistrationStage::Signature => {
+                if matches!(*self.plugin_access_mode.read().await, PluginAccessMode::SignedOnly) {
+                    // Perform a minimal signature verification pass (stub).
+                    // In production this should verify a cryptographic signature.
+                    if !js_plugin.license_requirements.requires_signed || !Self::verify_plugin_signature(js_plugin) {
+                        Err(PluginError::InvalidSignature { plugin_id: id.to_string() })
+                    } else {
+                        tracing::info!("Signature validation (stub) passed for plugin: {}", id);
+                        Ok(())
+                    }
+                } else {
+                    Ok(())
+                }
+            }
+            RegistrationStage::Dependency => self.check_plugin_dependencies(id, &js_plugin.metadata.dependencies).await,
+            // Bundles are decoded before they reach the plugin system
+            RegistrationStage::Parse => Ok(()),
+        };
+        result.map_err(PluginError::at_stage(stage, id))
+    }
+
     /// Remove JavaScript plugin
     pub async fn remove_js_plugin(&self, plugin_id: &str) -> Result<(), PluginError> {
         let mut js_plugins = self.js_plugins.write().await;
@@ -237,91 +388,159 @@ impl UniversalPluginSystem {
         }
     }
     
-    /// Try to execute action through plugin system
+    /// Set a JavaScript plugin's priority among handlers of the same action
+    /// (lower numbers first). Rust plugins keep the priority they were built with.
+    pub async fn set_plugin_priority(&self, plugin_id: &str, priority: i32) -> Result<(), PluginError> {
+        let mut js_plugins = self.js_plugins.write().await;
+        let js_plugin = js_plugins.get_mut(plugin_id).ok_or_else(|| PluginError::PluginNotFound {
+            plugin_id: plugin_id.to_string(),
+        })?;
+        js_plugin.metadata.priority = priority;
+        tracing::info!("Plugin {} priority set to {}", plugin_id, priority);
+        Ok(())
+    }
+
+    /// Try to execute action through plugin system.
+    ///
+    /// Plugins handling the action are consulted in `handler_chain` order:
+    /// lowest `priority` first, JavaScript before Rust on a tie, then by id.
+    /// The first plugin that succeeds answers the action, unless it is
+    /// marked `pass_through`: then its side effects are kept and the next
+    /// plugin is consulted, and its result only stands if no later plugin
+    /// answers. A plugin that fails is skipped.
     pub async fn try_execute_action(
         &self,
         action: &Action,
         context: &ActionContext,
         _app_state: &crate::state_mod::AppState,
     ) -> Result<Option<ActionResult>, PluginError> {
-        let action_type = &action.action_type;
+        let mut passed_through: Option<ActionResult> = None;
+        for entry in self.handler_chain(&action.action_type).await {
+            let Some(mut result) = self.execute_handler(&entry, action, context).await else {
+                continue; // Try next plugin
+            };
+            result.observability_metadata.handled_by_plugin = Some(entry.plugin_id.clone());
+            if let Some(earlier) = passed_through.take() {
+                let mut side_effects = earlier.side_effects;
+                side_effects.append(&mut result.side_effects);
+                result.side_effects = side_effects;
+                let mut executed = earlier.observability_metadata.middleware_executed;
+                executed.append(&mut result.observability_metadata.middleware_executed);
+                result.observability_metadata.middleware_executed = executed;
+            }
+            if !entry.pass_through {
+                return Ok(Some(result));
+            }
+            passed_through = Some(result);
+        }
         
-        // Check JavaScript plugins first (hot reloadable)
+        // No plugin answered; a pass-through result is the best there is
+        Ok(passed_through)
+    }
+
+    /// Enabled, licensed plugins handling `action_type`, in the order
+    /// `try_execute_action` consults them
+    async fn handler_chain(&self, action_type: &str) -> Vec<HandlerEntry> {
+        let mut chain = Vec::new();
         {
             let js_plugins = self.js_plugins.read().await;
             for (plugin_id, js_plugin) in js_plugins.iter() {
-                if js_plugin.enabled && js_plugin.handled_actions.contains(action_type) {
-                    // Check license requirements again at execution time
-                    if self.check_license_requirements(&js_plugin.license_requirements, Some(&js_plugin.id)).await.is_err() {
-                        tracing::warn!("Skipping plugin {} due to license requirements", plugin_id);
-                        continue;
-                    }
-                    
-                    let start_time = std::time::Instant::now();
-                    let result = self.execute_js_plugin(js_plugin, action, context).await;
-                    let duration = start_time.elapsed();
-                    
-                        let action_result = match result {
-                            Ok(data) => ActionResult {
-                                success: true,
-                                data: Some(data),
-                                error: None,
-                                execution_time_ms: duration.as_millis() as u64,
-                                side_effects: vec![format!("Plugin {} executed", plugin_id)],
-                                observability_metadata: crate::action_dispatcher::ObservabilityMetadata {
-                                    operation_id: Uuid::new_v4().to_string(),
-                                    instrumentation_applied: false,
-                                    audit_logged: false,
-                                    metrics_recorded: false,
-                                    performance_budget_status: "OK".to_string(),
-                                    middleware_executed: vec![plugin_id.clone()],
-                                },
-                            },
-                            Err(e) => {
-                                tracing::error!("Plugin {} execution failed: {}", plugin_id, e);
-                                continue; // Try next plugin
-                            }
-                        };
-                    
-                    return Ok(Some(action_result));
+                if !js_plugin.enabled || !js_plugin.handled_actions.iter().any(|a| a == action_type) {
+                    continue;
                 }
+                // Check license requirements again at execution time
+                if self.check_license_requirements(&js_plugin.license_requirements, Some(&js_plugin.id)).await.is_err() {
+                    tracing::warn!("Skipping plugin {} due to license requirements", plugin_id);
+                    continue;
+                }
+                chain.push(HandlerEntry {
+                    plugin_id: plugin_id.clone(),
+                    plugin_type: PluginType::JavaScript,
+                    priority: js_plugin.metadata.priority,
+                    pass_through: js_plugin.metadata.pass_through,
+                });
             }
         }
-        
-        // Check Rust plugins if no JS plugin handled it
         {
             let rust_plugins = self.rust_plugins.read().await;
             for (plugin_id, rust_plugin) in rust_plugins.iter() {
-                let handled_actions = rust_plugin.get_handled_actions();
-                if handled_actions.contains(&action_type.to_string()) {
-                    // Check license requirements
-                    let rust_plugin_id = rust_plugin.get_metadata().plugin_id.to_string();
-                    if self.check_license_requirements(rust_plugin.get_license_requirements(), Some(&rust_plugin_id)).await.is_err() {
-                        tracing::warn!("Skipping Rust plugin {} due to license requirements", plugin_id);
-                        continue;
+                if !rust_plugin.get_handled_actions().iter().any(|a| a == action_type) {
+                    continue;
+                }
+                let metadata = rust_plugin.get_metadata();
+                if self.check_license_requirements(rust_plugin.get_license_requirements(), Some(&metadata.plugin_id.to_string())).await.is_err() {
+                    tracing::warn!("Skipping Rust plugin {} due to license requirements", plugin_id);
+                    continue;
+                }
+                chain.push(HandlerEntry {
+                    plugin_id: plugin_id.clone(),
+                    plugin_type: PluginType::Rust,
+                    priority: metadata.priority,
+                    pass_through: metadata.pass_through,
+                });
+            }
+        }
+        let rank = |t: &PluginType| matches!(t, PluginType::Rust) as u8;
+        chain.sort_by(|a, b| {
+            a.priority
+                .cmp(&b.priority)
+                .then_with(|| rank(&a.plugin_type).cmp(&rank(&b.plugin_type)))
+                .then_with(|| a.plugin_id.cmp(&b.plugin_id))
+        });
+        chain
+    }
+
+    /// Run one plugin from the chain; `None` if it's gone or failed
+    async fn execute_handler(&self, entry: &HandlerEntry, action: &Action, context: &ActionContext) -> Option<ActionResult> {
+        let plugin_id = &entry.plugin_id;
+        let start_time = std::time::Instant::now();
+        match entry.plugin_type {
+            PluginType::JavaScript => {
+                let js_plugins = self.js_plugins.read().await;
+                let js_plugin = js_plugins.get(plugin_id)?;
+                match self.execute_js_plugin(js_plugin, action, context).await {
+                    Ok(data) => Some(ActionResult {
+                        success: true,
+                        data: Some(data),
+                        error: None,
+                        execution_time_ms: start_time.elapsed().as_millis() as u64,
+                        side_effects: vec![format!("Plugin {} executed", plugin_id)],
+                        observability_metadata: crate::action_dispatcher::ObservabilityMetadata {
+                            operation_id: self.ids.next_id().to_string(),
+                            instrumentation_applied: false,
+                            audit_logged: false,
+                            metrics_recorded: false,
+                            performance_budget_status: "OK".to_string(),
+                            middleware_executed: vec![plugin_id.clone()],
+                            handled_by_plugin: None,
+                        },
+                    }),
+                    Err(e) => {
+                        tracing::error!("Plugin {} execution failed: {}", plugin_id, e);
+                        None
                     }
-                    
-                    let start_time = std::time::Instant::now();
-                    match rust_plugin.execute_action(action, context).await {
-                        Ok(mut result) => {
-                            result.execution_time_ms = start_time.elapsed().as_millis() as u64;
-                            result.side_effects.push(format!("Rust plugin {} executed", plugin_id));
-                            return Ok(Some(result));
-                        }
-                        Err(e) => {
-                            tracing::error!("Rust plugin {} execution failed: {}", plugin_id, e);
-                            continue;
-                        }
+                }
+            }
+            PluginType::Rust => {
+                let rust_plugins = self.rust_plugins.read().await;
+                let rust_plugin = rust_plugins.get(plugin_id)?;
+                match rust_plugin.execute_action(action, context).await {
+                    Ok(mut result) => {
+                        result.execution_time_ms = start_time.elapsed().as_millis() as u64;
+                        result.side_effects.push(format!("Rust plugin {} executed", plugin_id));
+                        Some(result)
+                    }
+                    Err(e) => {
+                        tracing::error!("Rust plugin {} execution failed: {}", plugin_id, e);
+                        None
                     }
                 }
             }
         }
-        
-        // No plugin handled the action
-        Ok(None)
     }
     
-    /// Get all plugins
+    /// Get all plugins, oldest registration first (ties broken by id) so the
+    /// list is the same on every call
     pub async fn get_all_plugins(&self) -> Vec<PluginInfo> {
         let mut plugins = Vec::new();
         
@@ -337,12 +556,16 @@ impl UniversalPluginSystem {
                     enabled: plugin.enabled,
                     loaded_at: plugin.loaded_at,
                     license_tier_required: plugin.license_requirements.minimum_tier.clone(),
+                    handled_actions: plugin.handled_actions.clone(),
                 });
             }
         }
         
         // Rust plugins
         {
+            // Loaded at startup; no per-plugin time is kept, so they share one
+            // and sort after JS plugins by id
+            let loaded_at = Utc::now();
             let rust_plugins = self.rust_plugins.read().await;
             for (_, plugin) in rust_plugins.iter() {
                 let metadata = plugin.get_metadata();
@@ -353,15 +576,37 @@ impl UniversalPluginSystem {
                     version: metadata.version.clone(),
                     plugin_type: PluginType::Rust,
                     enabled: true, // Rust plugins are always enabled once loaded
-                    loaded_at: Utc::now(),
+                    loaded_at,
                     license_tier_required: license_req.minimum_tier.clone(),
+                    handled_actions: plugin.get_handled_actions(),
                 });
             }
         }
         
+        plugins.sort_by(|a, b| a.loaded_at.cmp(&b.loaded_at).then_with(|| a.id.cmp(&b.id)));
+        plugins
+    }
+    
+    /// Every JavaScript plugin, oldest registration first (ties broken by id)
+    pub async fn get_js_plugins(&self) -> Vec<JSPlugin> {
+        let mut plugins: Vec<JSPlugin> = self.js_plugins.read().await.values().cloned().collect();
+        plugins.sort_by(|a, b| a.loaded_at.cmp(&b.loaded_at).then_with(|| a.id.cmp(&b.id)));
         plugins
     }
     
+    /// Resolve which plugin would answer an action type, without executing it.
+    /// Mirrors the resolution rules of `try_execute_action`, assuming every
+    /// plugin succeeds: the first plugin in priority order that isn't
+    /// `pass_through`, else the last one that is.
+    pub async fn find_handler(&self, action_type: &str) -> Option<String> {
+        let chain = self.handler_chain(action_type).await;
+        chain
+            .iter()
+            .find(|entry| !entry.pass_through)
+            .or_else(|| chain.last())
+            .map(|entry| entry.plugin_id.clone())
+    }
+
     /// Execute JavaScript plugin (mock implementation)
     async fn execute_js_plugin(
         &self,
@@ -389,6 +634,24 @@ impl UniversalPluginSystem {
         }))
     }
     
+    /// Check that this engine (`CARGO_PKG_VERSION`) is at least `required`
+    pub fn check_engine_version(plugin_id: &str, required: &str) -> Result<(), PluginError> {
+        let actual = env!("CARGO_PKG_VERSION");
+        let required_version = semver::Version::parse(required.trim()).map_err(|e| {
+            PluginError::InitializationError {
+                message: format!("Plugin {} has invalid min_engine_version '{}': {}", plugin_id, required, e),
+            }
+        })?;
+        let engine_version = semver::Version::parse(actual).expect("CARGO_PKG_VERSION is valid semver");
+        if engine_version < required_version {
+            return Err(PluginError::IncompatibleVersion {
+                required: required_version.to_string(),
+                actual: actual.to_string(),
+            });
+        }
+        Ok(())
+    }
+
     /// Minimal plugin signature verification stub.
     /// Replace with real cryptographic verification in production.
     fn verify_plugin_signature(js_plugin: &JSPlugin) -> bool {
@@ -401,22 +664,23 @@ impl UniversalPluginSystem {
     /// `plugin_id` is optional and used to produce better error messages when present.
     async fn check_license_requirements(&self, requirements: &LicenseRequirement, plugin_id: Option<&str>) -> Result<(), PluginError> {
         let pid = plugin_id.unwrap_or("unknown").to_string();
+        let license_tier = self.license_tier.read().await.clone();
         // Check minimum tier
-        if self.license_tier < requirements.minimum_tier {
+        if license_tier < requirements.minimum_tier {
             return Err(PluginError::LicenseInsufficient {
                 plugin_id: pid.clone(),
                 required_tier: requirements.minimum_tier.clone(),
-                current_tier: self.license_tier.clone(),
+                current_tier: license_tier,
             });
         }
 
         // Check signature requirements
-        if requirements.requires_signed && matches!(self.plugin_access_mode, PluginAccessMode::UnsignedAllowed) {
+        if requirements.requires_signed && matches!(*self.plugin_access_mode.read().await, PluginAccessMode::UnsignedAllowed) {
             // This plugin requires signed access but we're in unsigned mode
             return Err(PluginError::LicenseInsufficient {
                 plugin_id: pid.clone(),
                 required_tier: LicenseTier::Enterprise, // Signed plugins need Enterprise
-                current_tier: self.license_tier.clone(),
+                current_tier: license_tier,
             });
         }
 
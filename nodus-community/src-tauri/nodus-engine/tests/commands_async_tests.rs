@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use serde_json::json;
+
+use nodus::error_code::ErrorCode;
+
+mod common;
+use common::build_test_state;
+
+#[tokio::test]
+async fn test_cancel_async_operation() {
+    let state = build_test_state().await;
+    let context = nodus::commands_async::AsyncOperationContext {
+        operation_id: uuid::Uuid::new_v4().to_string(),
+        operation_name: "import".to_string(),
+        user_id: "test".to_string(),
+        classification: "PUBLIC".to_string(),
+        timeout_ms: 1000,
+        metadata: json!({}),
+        depends_on: vec![],
+        priority: Default::default(),
+    };
+    let operation_id = context.operation_id.clone();
+    nodus::commands_async::start_async_operation(state.clone(), context).await.unwrap();
+
+    let result = nodus::commands_async::cancel_async_operation(state.clone(), operation_id.clone()).await.unwrap();
+    assert!(!result.success);
+    assert_eq!(result.error.as_deref(), Some("Cancelled"));
+    assert_eq!(nodus::commands_async::get_active_operations_count(state.clone()).await.unwrap(), 0);
+
+    assert!(nodus::commands_async::cancel_async_operation(state, operation_id).await.is_err());
+}
+
+fn async_context(operation_id: &str, depends_on: &[&str]) -> nodus::commands_async::AsyncOperationContext {
+    nodus::commands_async::AsyncOperationContext {
+        operation_id: operation_id.to_string(),
+        operation_name: format!("op-{}", operation_id),
+        user_id: "test".to_string(),
+        classification: "PUBLIC".to_string(),
+        timeout_ms: 1000,
+        metadata: json!({}),
+        depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+        priority: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_async_operation_dependencies() {
+    use nodus::commands_async::{complete_async_operation, list_active_operations, start_async_operation};
+    let state = build_test_state().await;
+
+    start_async_operation(state.clone(), async_context("sync", &[])).await.unwrap();
+    start_async_operation(state.clone(), async_context("export", &["sync"])).await.unwrap();
+
+    let listing = list_active_operations(state.clone()).await.unwrap();
+    let export = listing.iter().find(|op| op.operation_id == "export").unwrap();
+    assert_eq!(export.status, "waiting");
+    assert_eq!(export.pending_dependencies, vec!["sync".to_string()]);
+
+    // Export starts once sync succeeds
+    complete_async_operation(state.clone(), "sync".to_string(), true, None, None).await.unwrap();
+    let listing = list_active_operations(state.clone()).await.unwrap();
+    assert_eq!(listing.len(), 1);
+    assert_eq!(listing[0].status, "running");
+
+    // A failed dependency fails queued and later dependents
+    start_async_operation(state.clone(), async_context("report", &["export"])).await.unwrap();
+    complete_async_operation(state.clone(), "export".to_string(), false, None, Some("boom".to_string())).await.unwrap();
+    assert!(list_active_operations(state.clone()).await.unwrap().is_empty());
+    assert!(start_async_operation(state.clone(), async_context("email", &["report"])).await.is_err());
+}
+
+#[tokio::test]
+async fn test_async_operation_dependencies_are_checked() {
+    use nodus::commands_async::{complete_async_operation, start_async_operation};
+    let state = build_test_state().await;
+
+    let err = start_async_operation(state.clone(), async_context("loop", &["loop"])).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::ValidationFailed);
+    assert!(err.message.contains("cycle"), "{}", err);
+    let err = start_async_operation(state.clone(), async_context("orphan", &["never-started"])).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::NotFound);
+    assert!(err.message.contains("unknown operation never-started"), "{}", err);
+
+    // `fetch`'s outcome is kept while `merge` still waits on `parse`...
+    start_async_operation(state.clone(), async_context("fetch", &[])).await.unwrap();
+    start_async_operation(state.clone(), async_context("parse", &[])).await.unwrap();
+    start_async_operation(state.clone(), async_context("merge", &["fetch", "parse"])).await.unwrap();
+    complete_async_operation(state.clone(), "fetch".to_string(), true, None, None).await.unwrap();
+    // ...so a new `fetch` waiting on `merge` would close a cycle
+    let err = start_async_operation(state.clone(), async_context("fetch", &["merge"])).await.unwrap_err();
+    assert!(err.message.contains("cycle"), "{}", err);
+
+    // Once nothing waits on them, outcomes are forgotten
+    complete_async_operation(state.clone(), "parse".to_string(), true, None, None).await.unwrap();
+    let err = start_async_operation(state.clone(), async_context("publish", &["fetch"])).await.unwrap_err();
+    assert!(err.message.contains("unknown operation fetch"), "{}", err);
+    start_async_operation(state.clone(), async_context("publish", &["merge"])).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_cancel_stops_running_operation() {
+    use nodus::commands_async::{cancel_async_operation, list_active_operations, run_async_operation};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    let state = build_test_state().await;
+    let finished = Arc::new(AtomicBool::new(false));
+
+    let task_state = state.clone();
+    let task_finished = finished.clone();
+    let task = tokio::spawn(async move {
+        run_async_operation(task_state, async_context("reindex", &[]), |_token| async move {
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            task_finished.store(true, Ordering::SeqCst);
+            Ok("done".to_string())
+        })
+        .await
+    });
+
+    while list_active_operations(state.clone()).await.unwrap().is_empty() {
+        tokio::task::yield_now().await;
+    }
+    cancel_async_operation(state.clone(), "reindex".to_string()).await.unwrap();
+
+    let result = tokio::time::timeout(std::time::Duration::from_secs(1), task)
+        .await
+        .expect("operation kept running after cancel")
+        .unwrap()
+        .unwrap();
+    assert!(!result.success);
+    assert!(!finished.load(Ordering::SeqCst));
+    assert!(list_active_operations(state.clone()).await.unwrap().is_empty());
+
+    // Work that finishes normally is completed with its result
+    let done = run_async_operation(state.clone(), async_context("quick", &[]), |_token| async { Ok("ok".to_string()) })
+        .await
+        .unwrap();
+    assert!(done.success);
+    assert_eq!(done.result.as_deref(), Some("ok"));
+}
+
+#[tokio::test]
+async fn test_operation_results_are_persisted() {
+    use nodus::commands_async::{cancel_async_operation, complete_async_operation, get_operation_result, start_async_operation};
+    let state = build_test_state().await;
+
+    start_async_operation(state.clone(), async_context("backup", &[])).await.unwrap();
+    assert!(get_operation_result(state.clone(), "backup".to_string()).await.unwrap().is_none());
+    complete_async_operation(state.clone(), "backup".to_string(), true, Some("3 files".to_string()), None).await.unwrap();
+
+    let stored = get_operation_result(state.clone(), "backup".to_string()).await.unwrap().unwrap();
+    assert!(stored.success);
+    assert_eq!(stored.operation_name.as_deref(), Some("op-backup"));
+    assert_eq!(stored.result.as_deref(), Some("3 files"));
+    assert!(stored.started_at.is_some_and(|started| started <= stored.completed_at));
+
+    start_async_operation(state.clone(), async_context("import", &[])).await.unwrap();
+    cancel_async_operation(state.clone(), "import".to_string()).await.unwrap();
+    let cancelled = get_operation_result(state.clone(), "import".to_string()).await.unwrap().unwrap();
+    assert!(!cancelled.success);
+    assert!(cancelled.error.is_some());
+
+    assert!(get_operation_result(state.clone(), "unknown".to_string()).await.unwrap().is_none());
+
+    // Retention runs separately from persisting
+    let storage = state.read().await.storage.clone();
+    assert_eq!(nodus::commands_async::purge_operation_results(&storage, 0).await.unwrap(), 2);
+    assert!(get_operation_result(state.clone(), "backup".to_string()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_operation_activity_windows() {
+    use chrono::TimeZone;
+    use nodus::commands_async::{complete_async_operation, get_operation_activity, start_async_operation, OperationActivity};
+
+    let mut activity = OperationActivity::new(3, 2);
+    let at = |h: u32, m: u32| chrono::Utc.with_ymd_and_hms(2024, 5, 1, h, m, 0).unwrap();
+    for time in [at(9, 10), at(10, 5), at(11, 0), at(11, 59)] {
+        activity.record(time);
+    }
+    let report = activity.report(10, at(11, 30));
+    assert_eq!(report.lifetime_completed, 10);
+    assert_eq!(report.this_hour, 2);
+    assert_eq!(report.hourly_total, 4);
+    assert_eq!(report.daily_total, 4);
+
+    // Two hours later only the 11:00 bucket is still inside the 3-hour window
+    let report = activity.report(10, at(13, 0));
+    assert_eq!(report.this_hour, 0);
+    assert_eq!(report.hourly_total, 2);
+    assert_eq!(report.hourly.len(), 1);
+
+    // Buckets outside the window roll off as new ones are recorded
+    activity.record(at(23, 0) + chrono::Duration::days(1));
+    let report = activity.report(10, at(23, 0) + chrono::Duration::days(1));
+    assert_eq!(report.hourly_total, 1);
+    assert_eq!(report.daily_total, 5);
+
+    let state = build_test_state().await;
+    start_async_operation(state.clone(), async_context("sync", &[])).await.unwrap();
+    complete_async_operation(state.clone(), "sync".to_string(), true, None, None).await.unwrap();
+    let report = get_operation_activity(state.clone()).await.unwrap();
+    assert_eq!(report.lifetime_completed, 1);
+    assert_eq!(report.this_hour, 1);
+    assert_eq!(report.hourly_total, 1);
+}
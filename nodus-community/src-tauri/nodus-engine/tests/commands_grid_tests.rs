@@ -1,141 +1,29 @@
-use std::collections::HashMap;
-use std::sync::Arc;
-
-use tokio::sync::RwLock;
 use serde_json::json;
 
-use nodus::commands_grid::{self};
-use nodus::state_mod::{self, AppConfig};
-use nodus::storage::{StorageAdapter, StorageContext, StoredEntity, StorageError, StorageQuery, StorageStats};
-use nodus::license_mod::LicenseManager;
-use nodus::universal_plugin_system::UniversalPluginSystem;
-use nodus::action_dispatcher::ActionDispatcher;
-use nodus::async_orchestrator::AsyncOrchestrator;
-
-use tokio::sync::RwLock as TokioRwLock;
+use nodus::commands_grid::{self, GridBlock};
+use nodus::error_code::ErrorCode;
+use nodus::storage::StorageContext;
 
-// Simple in-memory storage adapter for tests
-struct InMemoryAdapter {
-    store: Arc<TokioRwLock<HashMap<String, StoredEntity>>>,
-}
+mod common;
+use common::build_test_state;
 
-impl InMemoryAdapter {
-    fn new() -> Self {
-        Self { store: Arc::new(TokioRwLock::new(HashMap::new())) }
+/// A plain HTML block at the given position and size
+fn grid_block(id: &str, x: u32, y: u32, w: u32, h: u32) -> GridBlock {
+    GridBlock {
+        id: id.to_string(),
+        block_type: "html".to_string(),
+        title: None,
+        x,
+        y,
+        w,
+        h,
+        config: json!({}),
+        static_grid: false,
+        entity_id: None,
+        updated_at: None,
     }
 }
 
-#[async_trait::async_trait]
-impl StorageAdapter for InMemoryAdapter {
-    async fn initialize(&mut self) -> Result<(), StorageError> { Ok(()) }
-    async fn health_check(&self) -> Result<(), StorageError> { Ok(()) }
-
-    async fn get(&self, key: &str, _ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
-        let store = self.store.read().await;
-        Ok(store.get(key).cloned())
-    }
-
-    async fn put(&self, key: &str, entity: StoredEntity, _ctx: &StorageContext) -> Result<(), StorageError> {
-        let mut store = self.store.write().await;
-        store.insert(key.to_string(), entity);
-        Ok(())
-    }
-
-    async fn delete(&self, key: &str, _ctx: &StorageContext) -> Result<(), StorageError> {
-        let mut store = self.store.write().await;
-        store.remove(key);
-        Ok(())
-    }
-
-    async fn purge(&self, key: &str, _ctx: &StorageContext) -> Result<(), StorageError> { self.delete(key, _ctx).await }
-
-    async fn query(&self, query: &StorageQuery, _ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
-        let store = self.store.read().await;
-        let mut out = Vec::new();
-        for (_k, v) in store.iter() {
-            if let Some(ref et) = query.entity_type {
-                if &v.entity_type == et { out.push(v.clone()); }
-            } else {
-                out.push(v.clone());
-            }
-        }
-        Ok(out)
-    }
-
-    async fn get_by_type(&self, entity_type: &str, _ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
-        let store = self.store.read().await;
-        Ok(store.values().filter(|e| e.entity_type == entity_type).cloned().collect())
-    }
-
-    async fn batch_put(&self, entities: Vec<(String, StoredEntity)>, _ctx: &StorageContext) -> Result<(), StorageError> {
-        let mut store = self.store.write().await;
-        for (k, v) in entities { store.insert(k, v); }
-        Ok(())
-    }
-
-    async fn get_stats(&self) -> Result<StorageStats, StorageError> {
-        let store = self.store.read().await;
-        let total = store.len() as u64;
-        let mut by_type: std::collections::HashMap<String, u64> = std::collections::HashMap::new();
-        let mut size: u64 = 0;
-        for (_k, v) in store.iter() {
-            *by_type.entry(v.entity_type.clone()).or_insert(0) += 1;
-            if let Ok(bytes) = serde_json::to_vec(&v.data) { size += bytes.len() as u64; }
-        }
-        Ok(StorageStats { total_entities: total, entities_by_type: by_type, storage_size_bytes: size, last_sync: None, pending_changes: 0 })
-    }
-
-    async fn export_data(&self, _ctx: &StorageContext) -> Result<Vec<u8>, StorageError> {
-        let store = self.store.read().await;
-        let vec: Vec<_> = store.values().cloned().collect();
-        serde_json::to_vec(&vec).map_err(|e| StorageError::SerializationError { error: e.to_string() })
-    }
-
-    async fn import_data(&mut self, data: &[u8], _ctx: &StorageContext) -> Result<(), StorageError> {
-        let entities: Vec<StoredEntity> = serde_json::from_slice(data).map_err(|e| StorageError::SerializationError { error: e.to_string() })?;
-        let mut store = self.store.write().await;
-        for ent in entities { store.insert(ent.id.clone(), ent); }
-        Ok(())
-    }
-}
-
-async fn build_test_state() -> Arc<RwLock<state_mod::AppState>> {
-    // License manager and plugin system
-    let license_manager = LicenseManager::new().await.unwrap();
-    let license_tier = license_manager.get_tier().await;
-    let plugin_access_mode = license_manager.get_plugin_access_mode().await;
-    let plugin_system = UniversalPluginSystem::new(license_tier, plugin_access_mode).await;
-
-    // Storage manager with in-memory adapter
-    let mut storage = nodus::storage::StorageManager::new();
-    let adapter = InMemoryAdapter::new();
-    storage.register_adapter("memory".to_string(), Box::new(adapter));
-    let _ = storage.set_primary_backend("memory".to_string());
-
-    // Action dispatcher and orchestrator
-    let action_dispatcher = ActionDispatcher::new().await.unwrap();
-    let async_orchestrator = AsyncOrchestrator::new().await.unwrap();
-
-    // App config
-    let config = AppConfig { app_name: "nodus-test".to_string(), version: "0.1".to_string(), license_tier: "Community".to_string(), plugin_access_mode: "UnsignedAllowed".to_string() };
-
-    let app_state = state_mod::AppState {
-        license_manager: Arc::new(license_manager),
-        initialized: false,
-        config,
-        sessions: Arc::new(RwLock::new(HashMap::new())),
-        plugin_system: Arc::new(plugin_system),
-        storage: Arc::new(storage),
-        action_dispatcher: Arc::new(action_dispatcher),
-        async_orchestrator: Arc::new(async_orchestrator),
-        active_async_operations: Arc::new(RwLock::new(HashMap::new())),
-        active_async_operation_starts: Arc::new(RwLock::new(HashMap::new())),
-        completed_operations_count: Arc::new(RwLock::new(0)),
-    };
-
-    Arc::new(RwLock::new(app_state))
-}
-
 #[tokio::test]
 async fn test_add_block_persists_and_returns_id() {
     let state = build_test_state().await;
@@ -174,3 +62,325 @@ async fn test_get_grid_config_returns_default_when_missing() {
     assert_eq!(cfg.config_id, "nonexistent_grid".to_string());
     assert_eq!(cfg.blocks.len(), 0);
 }
+
+#[tokio::test]
+async fn test_grid_updates_are_debounced_until_flush() {
+    let state = build_test_state().await;
+    let ctx = StorageContext { user_id: "test".to_string(), session_id: uuid::Uuid::new_v4(), operation_id: uuid::Uuid::new_v4(), skip_validation: false };
+
+    for i in 0..3 {
+        let update = commands_grid::GridStateUpdate {
+            config_id: "drag_grid".to_string(),
+            block_id: format!("b{}", i),
+            update_type: "add".to_string(),
+            data: json!({ "block_type": "html", "x": i }),
+        };
+        commands_grid::update_grid_state(state.clone(), update).await.unwrap();
+    }
+
+    // Reads see the coalesced state, but nothing has hit storage yet
+    let config = commands_grid::get_grid_config(state.clone(), "drag_grid".to_string()).await.unwrap();
+    assert_eq!(config.blocks.len(), 3);
+    let storage = state.read().await.storage.clone();
+    assert!(storage.get("grid_config:drag_grid", &ctx).await.unwrap().is_none());
+
+    let flushed = commands_grid::flush_grid_state(state.clone(), None).await.unwrap();
+    assert_eq!(flushed, 1);
+    let stored = storage.get("grid_config:drag_grid", &ctx).await.unwrap().unwrap();
+    assert_eq!(stored.data["blocks"].as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_shutdown_flushes_pending_debounced_saves() {
+    let state = build_test_state().await;
+    let ctx = StorageContext { user_id: "test".to_string(), session_id: uuid::Uuid::new_v4(), operation_id: uuid::Uuid::new_v4(), skip_validation: false };
+    let update = commands_grid::GridStateUpdate {
+        config_id: "quit_grid".to_string(),
+        block_id: "b1".to_string(),
+        update_type: "add".to_string(),
+        data: json!({ "block_type": "html" }),
+    };
+    commands_grid::update_grid_state(state.clone(), update).await.unwrap();
+
+    let storage = state.read().await.storage.clone();
+    assert!(storage.get("grid_config:quit_grid", &ctx).await.unwrap().is_none());
+    assert_eq!(state.read().await.grid_autosave.pending_count().await, 1);
+
+    // Quit before the debounce interval elapses
+    state.read().await.shutdown().await.unwrap();
+
+    let stored = storage.get("grid_config:quit_grid", &ctx).await.unwrap().unwrap();
+    assert_eq!(stored.data["blocks"].as_array().unwrap().len(), 1);
+    assert_eq!(state.read().await.grid_autosave.pending_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_layout_staging_commit_and_cancel() {
+    let state = build_test_state().await;
+    let block = grid_block("b1", 3, 4, 1, 1);
+
+    // Cancel discards the provisional layout
+    commands_grid::begin_layout_edit(state.clone(), "edit_grid".to_string()).await.unwrap();
+    commands_grid::stage_layout(state.clone(), "edit_grid".to_string(), vec![block.clone()]).await.unwrap();
+    let staged = commands_grid::get_grid_config(state.clone(), "edit_grid".to_string()).await.unwrap();
+    assert_eq!(staged.blocks.len(), 1);
+    assert!(commands_grid::cancel_layout(state.clone(), "edit_grid".to_string()).await.unwrap());
+    let reverted = commands_grid::get_grid_config(state.clone(), "edit_grid".to_string()).await.unwrap();
+    assert!(reverted.blocks.is_empty());
+
+    // Staging without an edit in progress is rejected
+    assert!(commands_grid::stage_layout(state.clone(), "edit_grid".to_string(), vec![block.clone()]).await.is_err());
+
+    // Commit persists it
+    commands_grid::begin_layout_edit(state.clone(), "edit_grid".to_string()).await.unwrap();
+    commands_grid::stage_layout(state.clone(), "edit_grid".to_string(), vec![block]).await.unwrap();
+    commands_grid::commit_layout(state.clone(), "edit_grid".to_string()).await.unwrap();
+    assert!(state.read().await.staged_layouts.read().await.is_empty());
+    let committed = commands_grid::get_grid_config(state.clone(), "edit_grid".to_string()).await.unwrap();
+    assert_eq!(committed.blocks[0].x, 3);
+}
+
+#[tokio::test]
+async fn test_compact_all_configs_saves_only_changed_layouts() {
+    let state = build_test_state().await;
+    let block = |id: &str, y: u32| grid_block(id, 0, y, 2, 1);
+    let config = |config_id: &str, blocks| commands_grid::GridConfig {
+        blocks,
+        columns: Some(12),
+        config_id: config_id.to_string(),
+        metadata: None,
+    };
+
+    commands_grid::save_grid_config(state.clone(), "sparse".to_string(), config("sparse", vec![block("a", 3), block("b", 7)])).await.unwrap();
+    commands_grid::save_grid_config(state.clone(), "tidy".to_string(), config("tidy", vec![block("a", 0), block("b", 1)])).await.unwrap();
+    let storage = state.read().await.storage.clone();
+    let ctx = StorageContext { user_id: "test".to_string(), session_id: uuid::Uuid::new_v4(), operation_id: uuid::Uuid::new_v4(), skip_validation: true };
+    let mut broken = storage.get("grid_config:tidy", &ctx).await.unwrap().unwrap();
+    broken.id = "grid_config:broken".to_string();
+    broken.data = json!({ "blocks": "not a list" });
+    storage.put("grid_config:broken", broken, &ctx).await.unwrap();
+    // A layout being edited is left for its commit
+    commands_grid::save_grid_config(state.clone(), "editing".to_string(), config("editing", vec![block("a", 4)])).await.unwrap();
+    commands_grid::begin_layout_edit(state.clone(), "editing".to_string()).await.unwrap();
+    // A debounced update still waiting is compacted rather than left to overwrite the result
+    let autosave = state.read().await.grid_autosave.clone();
+    autosave.schedule(config("tidy", vec![block("a", 0), block("b", 1), block("c", 6)])).await.unwrap();
+
+    let mut results = commands_grid::compact_all_configs(state.clone()).await.unwrap();
+    results.sort_by(|a, b| a.config_id.cmp(&b.config_id));
+    let summary: Vec<(&str, bool, bool, bool)> = results.iter().map(|r| (r.config_id.as_str(), r.changed, r.skipped, r.error.is_some())).collect();
+    assert_eq!(summary, vec![
+        ("broken", false, false, true),
+        ("editing", false, true, false),
+        ("sparse", true, false, false),
+        ("tidy", true, false, false),
+    ]);
+    assert_eq!(autosave.pending_count().await, 0);
+
+    for (config_id, expected_rows) in [("sparse", vec![0, 1]), ("tidy", vec![0, 1, 2]), ("editing", vec![4])] {
+        let stored = storage.get(&format!("grid_config:{}", config_id), &ctx).await.unwrap().unwrap();
+        let stored: commands_grid::GridConfig = serde_json::from_value(stored.data).unwrap();
+        assert_eq!(stored.blocks.iter().map(|b| b.y).collect::<Vec<u32>>(), expected_rows, "{}", config_id);
+    }
+}
+
+#[tokio::test]
+async fn test_save_grid_config_stamps_changed_blocks() {
+    let state = build_test_state().await;
+    let block = |id: &str, x: u32| grid_block(id, x, 0, 2, 1);
+    let save = |blocks| {
+        let config = commands_grid::GridConfig { blocks, columns: Some(12), config_id: "home".to_string(), metadata: None };
+        commands_grid::save_grid_config(state.clone(), "home".to_string(), config)
+    };
+    let stamps = |config: commands_grid::GridConfig| {
+        config.blocks.into_iter().map(|b| b.updated_at.unwrap()).collect::<Vec<_>>()
+    };
+
+    save(vec![block("a", 0), block("b", 4)]).await.unwrap();
+    let first = stamps(commands_grid::get_grid_config(state.clone(), "home".to_string()).await.unwrap());
+
+    // The frontend sends whole configs without stamps; only "b" moved
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    save(vec![block("a", 0), block("b", 6)]).await.unwrap();
+    let second = stamps(commands_grid::get_grid_config(state, "home".to_string()).await.unwrap());
+    assert_eq!(second[0], first[0]);
+    assert!(second[1] > first[1]);
+}
+
+#[tokio::test]
+async fn test_oversized_payloads_are_rejected_per_kind() {
+    use nodus::payload_limits::PayloadKind;
+    let state = build_test_state().await;
+    let limits = nodus::payload_limits::PayloadLimits {
+        grid_config_bytes: 512,
+        plugin_bundle_bytes: 64,
+        action_bytes: 128,
+    };
+    state.write().await.config.payload_limits = limits.clone();
+
+    let config = commands_grid::GridConfig {
+        blocks: Vec::new(),
+        columns: Some(12),
+        config_id: "big".to_string(),
+        metadata: Some(json!({ "notes": "x".repeat(600) })),
+    };
+    // IPC bodies are held to their limit before they are decoded
+    let raw = serde_json::to_string(&config).unwrap();
+    let err = limits.decode::<commands_grid::GridConfig>(PayloadKind::GridConfig, raw.as_bytes()).unwrap_err();
+    assert!(err.message.contains("Grid config payload"), "{}", err);
+    assert!(commands_grid::import_grid_config(raw, state.clone()).await.is_err());
+
+    // Actions have their own, smaller limit, unless they save a config
+    let action = serde_json::to_vec(&json!({ "note": "x".repeat(200) })).unwrap();
+    let err = limits.decode::<serde_json::Value>(commands_grid::dispatch_payload_kind("system.ping"), &action).unwrap_err();
+    assert_eq!(err.code, ErrorCode::ValidationFailed);
+    assert!(err.message.contains("Action payload"), "{}", err);
+    assert!(limits.decode::<serde_json::Value>(commands_grid::dispatch_payload_kind("grid.config.save"), &action).is_ok());
+
+    let err = nodus::commands_plugin::upload_plugin_file(state.clone(), vec![b' '; 65], "big.js".to_string()).await.unwrap_err();
+    assert!(err.message.contains("Plugin bundle payload is 65 bytes"), "{}", err);
+    assert_eq!(err.details, Some(json!({ "kind": "plugin_bundle", "size": 65, "limit": 64 })));
+
+    // Small payloads still go through
+    let small = commands_grid::GridConfig { metadata: None, ..config };
+    commands_grid::save_grid_config(state, "big".to_string(), small).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_apply_template_replaces_or_keeps_blocks() {
+    use commands_grid::{apply_template, list_templates, register_template, GridTemplate};
+    let state = build_test_state().await;
+    let names: Vec<String> = list_templates(state.clone()).await.unwrap().into_iter().map(|t| t.name).collect();
+    assert_eq!(names, ["blank", "dashboard-3x3", "two-column"]);
+
+    let existing = GridBlock { static_grid: true, ..grid_block("notes", 20, 3, 30, 2) };
+    let config = commands_grid::GridConfig {
+        blocks: vec![existing],
+        columns: Some(48),
+        config_id: "home".to_string(),
+        metadata: None,
+    };
+    commands_grid::save_grid_config(state.clone(), "home".to_string(), config).await.unwrap();
+
+    // Kept blocks are narrowed to the template's columns and go below its blocks
+    let applied = apply_template(state.clone(), "home".to_string(), "two-column".to_string(), true).await.unwrap();
+    assert_eq!(applied.columns, Some(24));
+    assert_eq!(applied.blocks.len(), 3);
+    assert!(applied.blocks.iter().all(|b| b.id != "left" && b.id != "right"));
+    let notes = applied.blocks.iter().find(|b| b.id == "notes").unwrap();
+    assert_eq!((notes.x, notes.y, notes.w), (0, 8, 24));
+    let stored = commands_grid::get_grid_config(state.clone(), "home".to_string()).await.unwrap();
+    assert_eq!(stored.blocks.len(), 3);
+
+    let applied = apply_template(state.clone(), "home".to_string(), "dashboard-3x3".to_string(), false).await.unwrap();
+    assert_eq!(applied.blocks.len(), 9);
+    assert!(applied.blocks.iter().all(|b| b.id != "notes"));
+
+    // Templates registered at runtime are listed and applied like built-ins
+    let sidebar = GridTemplate {
+        name: "sidebar".to_string(),
+        description: None,
+        columns: Some(12),
+        blocks: vec![GridBlock { x: 0, y: 0, w: 3, h: 10, static_grid: false, ..applied.blocks[0].clone() }],
+    };
+    register_template(state.clone(), sidebar).await.unwrap();
+    assert!(list_templates(state.clone()).await.unwrap().iter().any(|t| t.name == "sidebar"));
+    let applied = apply_template(state.clone(), "home".to_string(), "sidebar".to_string(), false).await.unwrap();
+    assert_eq!((applied.columns, applied.blocks.len()), (Some(12), 1));
+
+    assert!(apply_template(state, "home".to_string(), "nope".to_string(), false).await.is_err());
+}
+
+#[tokio::test]
+async fn test_reflow_to_columns_scales_and_repacks() {
+    use commands_grid::{reflow_to_columns, GridConfig};
+    let state = build_test_state().await;
+    let block = |id: &str, x: u32, y: u32, w: u32| grid_block(id, x, y, w, 2);
+    let config = GridConfig {
+        blocks: vec![block("left", 0, 0, 6), block("right", 6, 0, 6), block("odd", 0, 2, 3), block("next", 3, 2, 3)],
+        columns: Some(12),
+        config_id: "home".to_string(),
+        metadata: None,
+    };
+    commands_grid::save_grid_config(state.clone(), "home".to_string(), config).await.unwrap();
+
+    let result = reflow_to_columns(state.clone(), "home".to_string(), 6).await.unwrap();
+    assert_eq!(result.config.columns, Some(6));
+    let position = |id: &str| {
+        let b = result.config.blocks.iter().find(|b| b.id == id).unwrap();
+        (b.x, b.y, b.w)
+    };
+    assert_eq!(position("left"), (0, 0, 3));
+    assert_eq!(position("right"), (3, 0, 3));
+    // 3 columns of 12 is 1.5 of 6: widths round up, so the second block overlaps and moves down
+    assert_eq!(position("odd"), (0, 2, 2));
+    assert_eq!(position("next"), (1, 4, 2));
+    let mut inexact = result.inexact_blocks.clone();
+    inexact.sort();
+    assert_eq!(inexact, ["next", "odd"]);
+    assert!(result.config.blocks.iter().all(|b| b.x + b.w <= 6));
+
+    let stored = commands_grid::get_grid_config(state.clone(), "home".to_string()).await.unwrap();
+    assert_eq!(stored.columns, Some(6));
+
+    // Widths never fall below one column or exceed the grid
+    let narrow = reflow_to_columns(state.clone(), "home".to_string(), 1).await.unwrap();
+    assert!(narrow.config.blocks.iter().all(|b| (b.x, b.w) == (0, 1)));
+    assert!(reflow_to_columns(state, "home".to_string(), 0).await.is_err());
+}
+
+#[tokio::test]
+async fn test_ping_reports_version_and_clock() {
+    let state = build_test_state().await;
+    let before = chrono::Utc::now();
+    let reply = commands_grid::ping(state.clone()).await.unwrap();
+    assert!(reply.pong);
+    assert_eq!(reply.engine_version, env!("CARGO_PKG_VERSION"));
+    assert!(reply.server_time >= before && reply.server_time <= chrono::Utc::now());
+
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    assert!(commands_grid::ping(state).await.unwrap().uptime_ms > reply.uptime_ms);
+}
+
+#[tokio::test]
+async fn test_load_grid_config_repairs_legacy_layouts() {
+    use commands_grid::{load_grid_config, GridConfig, GridLoadOptions};
+    let state = build_test_state().await;
+    let block = |id: &str, x: u32, y: u32, w: u32| grid_block(id, x, y, w, 2);
+    // Saved by an old client: overlapping blocks, one hanging off a 12-column grid
+    let legacy = GridConfig {
+        blocks: vec![block("a", 0, 0, 6), block("b", 3, 1, 6), block("wide", 10, 6, 20)],
+        columns: Some(12),
+        config_id: "legacy".to_string(),
+        metadata: None,
+    };
+    commands_grid::save_grid_config(state.clone(), "legacy".to_string(), legacy).await.unwrap();
+    let load = |repair: bool, persist: bool| load_grid_config(state.clone(), "legacy".to_string(), GridLoadOptions { repair, persist });
+
+    let untouched = load(false, false).await.unwrap();
+    assert!(!untouched.repaired && !untouched.persisted);
+    assert_eq!((untouched.config.blocks[1].x, untouched.config.blocks[1].y), (3, 1));
+
+    let repaired = load(true, false).await.unwrap();
+    assert!(repaired.repaired && !repaired.persisted);
+    let position = |config: &GridConfig, id: &str| {
+        let b = config.blocks.iter().find(|b| b.id == id).unwrap();
+        (b.x, b.y, b.w)
+    };
+    assert_eq!(position(&repaired.config, "a"), (0, 0, 6));
+    assert_eq!(position(&repaired.config, "b"), (3, 2, 6));
+    assert_eq!(position(&repaired.config, "wide"), (0, 4, 12));
+
+    // Repair alone leaves storage as it was
+    let stored = commands_grid::get_grid_config(state.clone(), "legacy".to_string()).await.unwrap();
+    assert_eq!(position(&stored, "b"), (3, 1, 6));
+
+    let persisted = load(true, true).await.unwrap();
+    assert!(persisted.repaired && persisted.persisted);
+    let stored = commands_grid::get_grid_config(state.clone(), "legacy".to_string()).await.unwrap();
+    assert_eq!(position(&stored, "b"), (3, 2, 6));
+
+    // A healthy layout needs no repair and isn't saved again
+    let again = load(true, true).await.unwrap();
+    assert!(!again.repaired && !again.persisted);
+}
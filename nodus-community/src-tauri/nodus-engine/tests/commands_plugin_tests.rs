@@ -0,0 +1,343 @@
+use serde_json::json;
+
+use nodus::error_code::ErrorCode;
+use nodus::storage::{StorageContext, StoredEntity};
+
+mod common;
+use common::build_test_state;
+
+fn plugin_request(id: &str, min_engine_version: Option<&str>) -> nodus::commands_plugin::JSPluginRequest {
+    serde_json::from_value(json!({
+        "id": id,
+        "name": id,
+        "version": "1.0.0",
+        "author": "test",
+        "description": "",
+        "code": "",
+        "handled_actions": ["*"],
+        "metadata": {
+            "plugin_id": uuid::Uuid::new_v4(),
+            "name": id,
+            "version": "1.0.0",
+            "author": "test",
+            "description": "",
+            "tags": [],
+            "priority": 100,
+            "dependencies": [],
+            "conflicts": [],
+            "homepage": null,
+            "documentation": null
+        },
+        "license_requirements": null,
+        "min_engine_version": min_engine_version
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_install_plugin_from_file_checks_sidecar_checksum() {
+    use nodus::commands_plugin::install_plugin_from_file;
+    use nodus::marketplace::sha256_hex;
+    let state = build_test_state().await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("word-count.js");
+    let code = b"export default { name: 'word-count' };";
+    std::fs::write(&path, code).unwrap();
+    let path = path.to_string_lossy().to_string();
+
+    std::fs::write(format!("{}.sha256", path), "0000  word-count.js\n").unwrap();
+    let err = install_plugin_from_file(state.clone(), path.clone()).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::PluginRejected);
+    assert!(err.message.contains("Checksum mismatch"), "{}", err);
+
+    std::fs::write(format!("{}.sha256", path), format!("{}  word-count.js\n", sha256_hex(code))).unwrap();
+    let response = install_plugin_from_file(state.clone(), path).await.unwrap();
+    assert!(response.success);
+    assert_eq!(response.plugin_id, "word-count");
+
+    let txt = dir.path().join("notes.txt").to_string_lossy().to_string();
+    assert!(install_plugin_from_file(state, txt).await.is_err());
+}
+
+#[tokio::test]
+async fn test_register_js_plugin_checks_min_engine_version() {
+    use nodus::commands_plugin::register_js_plugin;
+    let state = build_test_state().await;
+
+    let rejected = register_js_plugin(state.clone(), plugin_request("from-the-future", Some("99.0.0"))).await.unwrap();
+    assert!(!rejected.success);
+    let failure = rejected.failure.unwrap();
+    assert_eq!(failure.stage, nodus::universal_plugin_system::RegistrationStage::Version);
+    assert!(failure.detail.contains("requires engine version 99.0.0"), "{}", failure.detail);
+    assert!(!failure.remediation.is_empty());
+
+    // A missing dependency is reported as its own stage
+    let mut dependent = plugin_request("dependent", Some(env!("CARGO_PKG_VERSION")));
+    dependent.metadata.dependencies = vec!["not-installed".to_string()];
+    let rejected = register_js_plugin(state.clone(), dependent).await.unwrap();
+    assert_eq!(rejected.failure.unwrap().stage, nodus::universal_plugin_system::RegistrationStage::Dependency);
+
+    // Bundles that can't be decoded fail at the parse stage
+    let rejected = nodus::commands_plugin::upload_plugin_file(state.clone(), vec![0xff, 0xfe], "broken.js".to_string()).await.unwrap();
+    assert_eq!(rejected.failure.unwrap().stage, nodus::universal_plugin_system::RegistrationStage::Parse);
+
+    let response = register_js_plugin(state, plugin_request("current", Some(env!("CARGO_PKG_VERSION")))).await.unwrap();
+    assert!(response.success);
+}
+
+#[tokio::test]
+async fn test_validate_plugin_reports_every_stage_without_registering() {
+    use nodus::commands_plugin::{get_loaded_plugins, validate_plugin};
+    use nodus::universal_plugin_system::RegistrationStage;
+    let state = build_test_state().await;
+
+    // Two failures at once: both are reported, not just the first
+    let mut request = plugin_request("dry-run", Some("99.0.0"));
+    request.metadata.dependencies = vec!["not-installed".to_string()];
+    let report = validate_plugin(state.clone(), request).await.unwrap();
+    assert!(!report.valid);
+    let stages: Vec<_> = report.stages.iter().map(|s| (s.stage, s.passed)).collect();
+    assert_eq!(
+        stages,
+        [
+            (RegistrationStage::Parse, true),
+            (RegistrationStage::Capability, true),
+            (RegistrationStage::Version, false),
+            (RegistrationStage::Signature, true),
+            (RegistrationStage::Dependency, false),
+        ]
+    );
+    let dependency = &report.stages[4];
+    assert!(dependency.detail.as_deref().unwrap().contains("not-installed"));
+    assert!(dependency.remediation.is_some());
+
+    let report = validate_plugin(state.clone(), plugin_request("dry-run", None)).await.unwrap();
+    assert!(report.valid);
+    assert!(get_loaded_plugins(state).await.unwrap().iter().all(|p| p.id != "dry-run"));
+}
+
+#[tokio::test]
+async fn test_overlapping_plugins_run_in_priority_order() {
+    use nodus::commands_plugin::{execute_action_with_plugins, register_js_plugin, set_plugin_priority, which_plugin_handles, ActionHandledBy};
+    let state = build_test_state().await;
+    let handler = |id: &str, priority: i32, pass_through: bool| {
+        let mut request = plugin_request(id, None);
+        request.handled_actions = vec!["notes.export".to_string()];
+        request.metadata.priority = priority;
+        request.metadata.pass_through = pass_through;
+        request
+    };
+    let run = || execute_action_with_plugins(state.clone(), "notes.export".to_string(), json!({}), None);
+
+    // Registered in the opposite order of their priorities
+    assert!(register_js_plugin(state.clone(), handler("late", 20, false)).await.unwrap().success);
+    assert!(register_js_plugin(state.clone(), handler("early", 10, false)).await.unwrap().success);
+    let execution = run().await.unwrap();
+    assert_eq!(execution.handled_by, ActionHandledBy::Plugin);
+    assert_eq!(execution.plugin_id.as_deref(), Some("early"));
+    assert_eq!(execution.result["data"]["plugin_id"], json!("early"));
+    assert_eq!(execution.result["side_effects"], json!(["Plugin early executed"]));
+
+    set_plugin_priority(state.clone(), "late".to_string(), 5).await.unwrap();
+    assert_eq!(run().await.unwrap().result["data"]["plugin_id"], json!("late"));
+    assert!(set_plugin_priority(state.clone(), "missing".to_string(), 1).await.is_err());
+
+    // A pass-through plugin runs first and hands on to the next one, which is credited
+    assert!(register_js_plugin(state.clone(), handler("logger", 1, true)).await.unwrap().success);
+    let execution = run().await.unwrap();
+    assert_eq!(execution.plugin_id.as_deref(), Some("late"));
+    assert_eq!(execution.result["data"]["plugin_id"], json!("late"));
+    assert_eq!(execution.result["side_effects"], json!(["Plugin logger executed", "Plugin late executed"]));
+    assert_eq!(which_plugin_handles(state.clone(), "notes.export".to_string()).await.unwrap().as_deref(), Some("late"));
+}
+
+#[tokio::test]
+async fn test_action_timeout_override_is_license_capped() {
+    use nodus::commands_plugin::{execute_action_with_plugins, ActionHandledBy};
+
+    struct SlowHandler;
+
+    #[async_trait::async_trait]
+    impl nodus::action_dispatcher::ActionHandler for SlowHandler {
+        async fn execute(
+            &self,
+            _action: &nodus::action_dispatcher::Action,
+            _context: &nodus::action_dispatcher::ActionContext,
+            _app_state: nodus::state_mod::AppStateType,
+        ) -> Result<serde_json::Value, nodus::action_dispatcher::ActionError> {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            Ok(json!({ "done": true }))
+        }
+
+        fn action_type(&self) -> &str {
+            "report.rebuild"
+        }
+    }
+
+    let state = build_test_state().await;
+    state.read().await.action_dispatcher.register_handler(SlowHandler).await;
+    let run = |payload: serde_json::Value| execute_action_with_plugins(state.clone(), "report.rebuild".to_string(), payload, None);
+
+    let err = run(json!({ "timeout_ms": 20 })).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::Timeout);
+    assert!(err.message.contains("timed out after 20ms"), "{}", err);
+
+    // Handled by the core dispatcher, and timed end to end
+    let execution = run(json!({ "timeout_ms": 5000 })).await.unwrap();
+    assert_eq!(execution.result["success"], json!(true));
+    assert_eq!(execution.result["plugin_executed"], json!(false));
+    assert_eq!(execution.handled_by, ActionHandledBy::Core);
+    assert_eq!(execution.plugin_id, None);
+    assert!(execution.duration_ms >= 200, "{}", execution.duration_ms);
+
+    // Community tier caps overrides at two minutes
+    let err = run(json!({ "timeout_ms": 10 * 60 * 1000 })).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::AccessDenied);
+    assert!(err.message.contains("exceeds this license's maximum"), "{}", err);
+    let err = run(json!({ "timeout_ms": "soon" })).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::ValidationFailed);
+}
+
+#[tokio::test]
+async fn test_loaded_plugins_are_listed_in_registration_order() {
+    use nodus::commands_plugin::{get_loaded_plugins, register_js_plugin};
+    let state = build_test_state().await;
+
+    for id in ["zeta", "alpha", "mid"] {
+        assert!(register_js_plugin(state.clone(), plugin_request(id, None)).await.unwrap().success);
+    }
+
+    let ids = |plugins: Vec<nodus::universal_plugin_system::PluginInfo>| -> Vec<String> {
+        plugins.into_iter().map(|p| p.id).collect()
+    };
+    let first = get_loaded_plugins(state.clone()).await.unwrap();
+    assert!(first.windows(2).all(|w| (w[0].loaded_at, &w[0].id) <= (w[1].loaded_at, &w[1].id)));
+    let first = ids(first);
+    for _ in 0..5 {
+        assert_eq!(ids(get_loaded_plugins(state.clone()).await.unwrap()), first);
+    }
+}
+
+#[tokio::test]
+async fn test_plugin_manifest_round_trips_to_another_engine() {
+    use nodus::commands_plugin::{
+        export_plugin_manifest, get_loaded_plugins, import_plugin_manifest, install_plugin_from_file, register_js_plugin,
+    };
+    use nodus::universal_plugin_system::PluginSource;
+    let source = build_test_state().await;
+
+    let mut inline = plugin_request("word-count", None);
+    inline.code = "export default {};".to_string();
+    inline.handled_actions = vec!["text.*".to_string()];
+    inline.metadata.priority = 7;
+    assert!(register_js_plugin(source.clone(), inline).await.unwrap().success);
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("local-tool.js");
+    std::fs::write(&path, "export default {};").unwrap();
+    let path = path.to_string_lossy().to_string();
+    assert!(install_plugin_from_file(source.clone(), path.clone()).await.unwrap().success);
+
+    let manifest = export_plugin_manifest(source).await.unwrap();
+    assert_eq!(manifest.plugins.len(), 2);
+    let entry = |id: &str| manifest.plugins.iter().find(|p| p.id == id).unwrap().clone();
+    assert_eq!(entry("word-count").code.as_deref(), Some("export default {};"));
+    assert_eq!(entry("local-tool").source, PluginSource::File { path });
+    assert!(entry("local-tool").code.is_none());
+
+    // The manifest survives JSON, and a missing file fails only its own entry
+    let mut manifest: nodus::commands_plugin::PluginManifest =
+        serde_json::from_str(&serde_json::to_string(&manifest).unwrap()).unwrap();
+    let mut missing = entry("local-tool");
+    missing.id = "gone".to_string();
+    missing.source = PluginSource::File { path: dir.path().join("gone.js").to_string_lossy().to_string() };
+    manifest.plugins.push(missing);
+
+    let target = build_test_state().await;
+    let results = import_plugin_manifest(target.clone(), manifest.clone()).await.unwrap();
+    let mut outcomes: Vec<_> = results.iter().map(|r| (r.plugin_id.as_str(), r.success, r.error_code)).collect();
+    outcomes.sort_by_key(|(id, _, _)| *id);
+    assert_eq!(
+        outcomes,
+        vec![("gone", false, Some(ErrorCode::NotFound)), ("local-tool", true, None), ("word-count", true, None)]
+    );
+    let loaded = get_loaded_plugins(target.clone()).await.unwrap();
+    let word_count = loaded.iter().find(|p| p.id == "word-count").unwrap();
+    assert_eq!(word_count.handled_actions, vec!["text.*"]);
+    let plugins = target.read().await.plugin_system.get_js_plugins().await;
+    assert_eq!(plugins.iter().find(|p| p.id == "word-count").unwrap().metadata.priority, 7);
+
+    // Importing again leaves installed plugins alone
+    let again = import_plugin_manifest(target, manifest).await.unwrap();
+    let skipped = again.iter().filter(|r| r.success && r.message == "Plugin already installed").count();
+    assert_eq!(skipped, 2);
+}
+
+#[tokio::test]
+async fn test_plugin_audit_log_filters_and_pages_newest_first() {
+    use nodus::commands_plugin::{get_plugin_audit_log, PluginAuditQuery};
+
+    let state = build_test_state().await;
+    let ctx = StorageContext {
+        user_id: "test".to_string(),
+        session_id: uuid::Uuid::new_v4(),
+        operation_id: uuid::Uuid::new_v4(),
+        skip_validation: true,
+    };
+    let base = chrono::Utc::now() - chrono::Duration::hours(1);
+    let events = [
+        ("e1", "alpha", "save", true, 0),
+        ("e2", "beta", "save", true, 1),
+        ("e3", "alpha", "delete", false, 2),
+        ("e4", "alpha", "save", true, 3),
+        // Same instant as e4: ordered by id, descending
+        ("e5", "alpha", "save", true, 3),
+        ("e6", "alpha", "save", true, 4),
+    ];
+    {
+        let app_state = state.read().await;
+        for (id, plugin_id, action_type, success, minutes) in events {
+            let at = base + chrono::Duration::minutes(minutes);
+            let entity = StoredEntity {
+                id: id.to_string(),
+                entity_type: nodus::storage::AUDIT_EVENT_TYPE.to_string(),
+                data: json!({ "plugin_id": plugin_id, "action_type": action_type, "success": success, "details": { "n": minutes } }),
+                created_at: at,
+                updated_at: at,
+                created_by: "test".to_string(),
+                updated_by: "test".to_string(),
+                version: 0,
+                deleted_at: None,
+                sync_status: nodus::storage::SyncStatus::Synced,
+            };
+            app_state.storage.put(&format!("audit_event:{}", id), entity, &ctx).await.unwrap();
+        }
+    }
+
+    let ids = |page: &nodus::commands_plugin::PluginAuditPage| page.entries.iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+    let query = PluginAuditQuery {
+        plugin_id: Some("alpha".to_string()),
+        success_only: true,
+        limit: Some(2),
+        ..PluginAuditQuery::default()
+    };
+    let first = get_plugin_audit_log(state.clone(), query.clone()).await.unwrap();
+    assert_eq!(ids(&first), ["e6", "e5"]);
+    let second = get_plugin_audit_log(state.clone(), PluginAuditQuery { cursor: first.next_cursor.clone(), ..query.clone() }).await.unwrap();
+    assert_eq!(ids(&second), ["e4", "e1"]);
+    assert!(second.next_cursor.is_none());
+    assert_eq!(second.entries[1].details, json!({ "n": 0 }));
+
+    let window = PluginAuditQuery {
+        action_type: Some("save".to_string()),
+        since: Some(base + chrono::Duration::minutes(1)),
+        until: Some(base + chrono::Duration::minutes(3)),
+        ..PluginAuditQuery::default()
+    };
+    assert_eq!(ids(&get_plugin_audit_log(state.clone(), window).await.unwrap()), ["e5", "e4", "e2"]);
+
+    let too_large = PluginAuditQuery { limit: Some(nodus::commands_plugin::MAX_AUDIT_PAGE_SIZE + 1), ..PluginAuditQuery::default() };
+    assert!(get_plugin_audit_log(state.clone(), too_large).await.is_err());
+    let bad_cursor = PluginAuditQuery { cursor: Some("not-a-cursor".to_string()), ..PluginAuditQuery::default() };
+    assert!(get_plugin_audit_log(state, bad_cursor).await.is_err());
+}
@@ -0,0 +1,159 @@
+use serde_json::json;
+
+use nodus::commands_grid;
+use nodus::error_code::ErrorCode;
+use nodus::state_mod;
+
+mod common;
+use common::build_test_state;
+
+#[tokio::test]
+async fn test_system_status_is_typed() {
+    let state = build_test_state().await;
+    let status = nodus::commands::get_system_status(state).await.unwrap();
+
+    assert_eq!(status.app_version, "0.1");
+    assert_eq!(status.storage_backend, "memory");
+    assert_eq!(status.storage_health.get("memory"), Some(&true));
+    assert_eq!(status.active_async_operations, 0);
+    assert!(!status.sync.enabled);
+}
+
+#[tokio::test]
+async fn test_register_schema_and_validate_entity() {
+    use nodus::commands::{register_validation_schema, validate_entity};
+    let state = build_test_state().await;
+
+    let schema = serde_json::from_value(json!({
+        "schema_name": "note",
+        "version": "1",
+        "description": "Plugin note entity",
+        "rules": [{
+            "field_name": "title",
+            "required": true,
+            "data_type": { "String": { "min_length": 1, "max_length": null } },
+            "constraints": [],
+            "custom_validators": []
+        }],
+        "cross_field_rules": [],
+        "business_rules": []
+    })).unwrap();
+    register_validation_schema(state.clone(), schema).await.unwrap();
+
+    let report = validate_entity(state.clone(), "note".to_string(), json!({ "title": "Hello" })).await.unwrap();
+    assert!(report.is_valid);
+
+    let report = validate_entity(state.clone(), "note".to_string(), json!({})).await.unwrap();
+    assert!(!report.is_valid);
+    assert_eq!(report.errors.len(), 1);
+
+    assert!(validate_entity(state, "unknown".to_string(), json!({})).await.is_err());
+}
+
+#[tokio::test]
+async fn test_can_execute_reports_license_and_handler_gates() {
+    use nodus::commands::can_execute;
+    let state = build_test_state().await;
+
+    // Community license: AI actions need Pro
+    let permission = can_execute(state.clone(), "ai.search".to_string()).await.unwrap();
+    assert!(!permission.allowed);
+    assert_eq!(permission.required_tier.as_deref(), Some("Professional"));
+    assert!(permission.reason.unwrap().contains("ai_search"));
+
+    // Nothing registered to handle this one
+    let permission = can_execute(state.clone(), "notes.archive".to_string()).await.unwrap();
+    assert!(!permission.allowed);
+    assert_eq!(permission.required_tier, None);
+
+    state.read().await.action_dispatcher.register_handler(nodus::action_dispatcher::GridActionHandler).await;
+    let permission = can_execute(state, "grid.block.add".to_string()).await.unwrap();
+    assert!(permission.allowed, "{:?}", permission.reason);
+}
+
+#[tokio::test]
+async fn test_dispatched_actions_are_recorded_as_spans() {
+    use nodus::commands::get_recent_spans;
+    let state = build_test_state().await;
+    state.read().await.action_dispatcher.register_handler(nodus::action_dispatcher::GridActionHandler).await;
+
+    let _ = state_mod::execute_action(state.clone(), "grid.block.add".to_string(), json!({})).await;
+    let _ = state_mod::execute_action(state, "spans.unhandled".to_string(), json!({})).await;
+
+    let spans = get_recent_spans(nodus::spans::DEFAULT_SPAN_CAPACITY).await.unwrap();
+    let unhandled = spans.iter().find(|s| s.name == "action.spans.unhandled").unwrap();
+    assert!(!unhandled.success);
+    assert!(spans.iter().any(|s| s.name == "action.grid.block.add"));
+    assert!(get_recent_spans(1).await.unwrap().len() <= 1);
+}
+
+#[tokio::test]
+async fn test_limit_warning_thresholds_are_validated() {
+    use nodus::commands::set_limit_warning_thresholds;
+    let state = build_test_state().await;
+
+    let err = set_limit_warning_thresholds(state.clone(), vec![50, 120]).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::ValidationFailed);
+    assert!(err.message.contains("got 120"), "{}", err);
+    assert_eq!(set_limit_warning_thresholds(state.clone(), vec![90, 75]).await.unwrap(), vec![75, 90]);
+    assert_eq!(state.read().await.license_manager.limit_warning_thresholds(), vec![75, 90]);
+}
+
+#[tokio::test]
+async fn test_session_activity_requires_session_and_feature() {
+    use nodus::commands::get_session_activity;
+    let state = build_test_state().await;
+
+    let unknown = uuid::Uuid::new_v4();
+    let err = state_mod::execute_session_action(state.clone(), unknown, "system.ping".to_string(), json!({}))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, state_mod::AppStateError::SessionNotFound { .. }));
+
+    let session_id = state.read().await.create_session("alice").await.unwrap();
+    let before = state.read().await.sessions.read().await[&session_id].last_access;
+    let _ = state_mod::execute_session_action(state.clone(), session_id, "system.ping".to_string(), json!({})).await;
+    assert!(state.read().await.sessions.read().await[&session_id].last_access >= before);
+
+    // Community licenses don't include the Team activity feed
+    let err = get_session_activity(state, session_id).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::AccessDenied);
+    assert!(err.message.contains("activity_feeds"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_dump_state_writes_redacted_snapshot() {
+    let state = build_test_state().await;
+    let session_id = state.read().await.create_session("alice").await.unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json").to_string_lossy().to_string();
+    nodus::commands::dump_state(state, path.clone()).await.unwrap();
+
+    let dump = std::fs::read_to_string(&path).unwrap();
+    let snapshot: serde_json::Value = serde_json::from_str(&dump).unwrap();
+    assert_eq!(snapshot["sessions"][0]["session_id"], json!(session_id));
+    assert_eq!(snapshot["license_tier"], json!("Community"));
+    if !snapshot["license"].is_null() {
+        assert_eq!(snapshot["license"]["signature"], json!(state_mod::REDACTED));
+        assert_eq!(snapshot["license"]["verification_key"], json!(state_mod::REDACTED));
+    }
+    assert!(!dump.contains("community-default"));
+}
+
+#[tokio::test]
+async fn test_get_storage_usage_reports_each_type() {
+    let state = build_test_state().await;
+    let config = commands_grid::GridConfig {
+        blocks: Vec::new(),
+        columns: Some(12),
+        config_id: "usage_grid".to_string(),
+        metadata: None,
+    };
+    commands_grid::save_grid_config(state.clone(), "usage_grid".to_string(), config).await.unwrap();
+
+    let usage = nodus::commands::get_storage_usage(state.clone()).await.unwrap();
+    let grid = usage.iter().find(|u| u.entity_type == "grid_config").expect("grid_config usage");
+    assert_eq!(grid.count, 1);
+    assert!(grid.bytes > 0);
+}
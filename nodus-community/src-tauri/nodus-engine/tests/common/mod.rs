@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use nodus::commands_grid;
+use nodus::state_mod::{self, AppConfig};
+use nodus::storage::{AdapterCapabilities, StorageAdapter, StorageContext, StoredEntity, StorageError, StorageQuery, StorageStats};
+use nodus::license_mod::LicenseManager;
+use nodus::universal_plugin_system::UniversalPluginSystem;
+use nodus::action_dispatcher::ActionDispatcher;
+use nodus::async_orchestrator::AsyncOrchestrator;
+
+use tokio::sync::RwLock as TokioRwLock;
+
+// Simple in-memory storage adapter for tests
+struct InMemoryAdapter {
+    store: Arc<TokioRwLock<HashMap<String, StoredEntity>>>,
+}
+
+impl InMemoryAdapter {
+    fn new() -> Self {
+        Self { store: Arc::new(TokioRwLock::new(HashMap::new())) }
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageAdapter for InMemoryAdapter {
+    async fn initialize(&mut self) -> Result<(), StorageError> { Ok(()) }
+    async fn health_check(&self) -> Result<(), StorageError> { Ok(()) }
+    fn capabilities(&self) -> AdapterCapabilities { AdapterCapabilities { count: true, ..AdapterCapabilities::default() } }
+
+    async fn get(&self, key: &str, _ctx: &StorageContext) -> Result<Option<StoredEntity>, StorageError> {
+        let store = self.store.read().await;
+        Ok(store.get(key).cloned())
+    }
+
+    async fn put(&self, key: &str, entity: StoredEntity, _ctx: &StorageContext) -> Result<(), StorageError> {
+        let mut store = self.store.write().await;
+        store.insert(key.to_string(), entity);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str, _ctx: &StorageContext) -> Result<(), StorageError> {
+        let mut store = self.store.write().await;
+        store.remove(key);
+        Ok(())
+    }
+
+    async fn purge(&self, key: &str, _ctx: &StorageContext) -> Result<(), StorageError> { self.delete(key, _ctx).await }
+
+    async fn query(&self, query: &StorageQuery, _ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
+        let store = self.store.read().await;
+        let matches = query.predicate();
+        let mut out: Vec<StoredEntity> = store.values().filter(|v| matches(v)).cloned().collect();
+        out.sort_by(|a, b| query.compare(a, b));
+        Ok(out.into_iter().skip(query.offset.unwrap_or(0)).take(query.limit.unwrap_or(usize::MAX)).collect())
+    }
+
+    async fn get_by_type(&self, entity_type: &str, _ctx: &StorageContext) -> Result<Vec<StoredEntity>, StorageError> {
+        let store = self.store.read().await;
+        Ok(store.values().filter(|e| e.entity_type == entity_type).cloned().collect())
+    }
+
+    async fn batch_put(&self, entities: Vec<(String, StoredEntity)>, _ctx: &StorageContext) -> Result<(), StorageError> {
+        let mut store = self.store.write().await;
+        for (k, v) in entities { store.insert(k, v); }
+        Ok(())
+    }
+
+    async fn get_stats(&self) -> Result<StorageStats, StorageError> {
+        let store = self.store.read().await;
+        let total = store.len() as u64;
+        let mut by_type: std::collections::HashMap<String, u64> = std::collections::HashMap::new();
+        let mut bytes_by_type: std::collections::HashMap<String, u64> = std::collections::HashMap::new();
+        let mut size: u64 = 0;
+        for (_k, v) in store.iter() {
+            *by_type.entry(v.entity_type.clone()).or_insert(0) += 1;
+            if let Ok(bytes) = serde_json::to_vec(&v.data) {
+                size += bytes.len() as u64;
+                *bytes_by_type.entry(v.entity_type.clone()).or_insert(0) += bytes.len() as u64;
+            }
+        }
+        Ok(StorageStats { total_entities: total, entities_by_type: by_type, bytes_by_type, storage_size_bytes: size, last_sync: None, pending_changes: 0 })
+    }
+
+    async fn export_data(&self, _ctx: &StorageContext) -> Result<Vec<u8>, StorageError> {
+        let store = self.store.read().await;
+        let vec: Vec<_> = store.values().cloned().collect();
+        serde_json::to_vec(&vec).map_err(|e| StorageError::SerializationError { error: e.to_string() })
+    }
+}
+
+pub async fn build_test_state() -> Arc<RwLock<state_mod::AppState>> {
+    // License manager and plugin system
+    let license_manager = LicenseManager::new().await.unwrap();
+    let license_tier = license_manager.get_tier().await;
+    let plugin_access_mode = license_manager.get_plugin_access_mode().await;
+    let plugin_system = UniversalPluginSystem::new(license_tier, plugin_access_mode).await;
+
+    // Storage manager with in-memory adapter
+    let mut storage = nodus::storage::StorageManager::new();
+    let adapter = InMemoryAdapter::new();
+    storage.register_adapter("memory".to_string(), Box::new(adapter));
+    let _ = storage.set_primary_backend("memory".to_string());
+    let storage = Arc::new(storage);
+
+    // Action dispatcher and orchestrator
+    let action_dispatcher = ActionDispatcher::new().await.unwrap();
+    let async_orchestrator = AsyncOrchestrator::new().await.unwrap();
+
+    // App config
+    let config = AppConfig { app_name: "nodus-test".to_string(), version: "0.1".to_string(), license_tier: "Community".to_string(), plugin_access_mode: "UnsignedAllowed".to_string(), audit_retention: Default::default(), version_retention: Default::default(), http_access: Default::default(), payload_limits: Default::default(), operation_results: Default::default() };
+
+    let app_state = state_mod::AppState {
+        license_manager: Arc::new(license_manager),
+        initialized: false,
+        config,
+        sessions: Arc::new(RwLock::new(HashMap::new())),
+        session_activity: Arc::new(RwLock::new(HashMap::new())),
+        plugin_system: Arc::new(plugin_system),
+        storage: storage.clone(),
+        action_dispatcher: Arc::new(action_dispatcher),
+        async_orchestrator: Arc::new(async_orchestrator),
+        validation: Arc::new(nodus::storage::ValidationManager::new()),
+        active_async_operations: Arc::new(RwLock::new(HashMap::new())),
+        active_async_operation_starts: Arc::new(RwLock::new(HashMap::new())),
+        completed_operations_count: Arc::new(RwLock::new(0)),
+        operation_activity: Arc::new(RwLock::new(Default::default())),
+        async_operation_dependencies: Arc::new(RwLock::new(Default::default())),
+        grid_autosave: Arc::new(commands_grid::GridAutoSave::new(storage)),
+        operation_metrics: Arc::new(RwLock::new(commands_grid::OperationMetrics::default())),
+        staged_layouts: Arc::new(RwLock::new(HashMap::new())),
+        sync_manager: None,
+        started_at: std::time::Instant::now(),
+        ids: nodus::id_gen::default_generator(),
+        audit_retention_task: None,
+        version_pruning_task: None,
+        usage_persistence_task: None,
+        operation_result_retention_task: None,
+        idle: Arc::new(nodus::idle::IdleState::new()),
+    };
+
+    Arc::new(RwLock::new(app_state))
+}
@@ -35,10 +35,10 @@ async fn test_sqlite_adapter_migrations_and_batch_put() {
         entities.push((format!("object:{}", ent.id), ent));
     }
 
-    adapter.batch_put(entities.clone(), &StorageContext { user_id: "test".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4() }).await.expect("batch_put failed");
+    adapter.batch_put(entities.clone(), &StorageContext { user_id: "test".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4(), skip_validation: false }).await.expect("batch_put failed");
 
     // Query back
-    let results = adapter.query(&nodus::storage::StorageQuery { entity_type: Some("object".to_string()), filters: std::collections::HashMap::new(), sort: None, limit: None, offset: None, include_deleted: false }, &StorageContext { user_id: "test".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4() }).await.expect("query failed");
+    let results = adapter.query(&nodus::storage::StorageQuery::builder().entity_type("object").build(), &StorageContext { user_id: "test".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4(), skip_validation: false }).await.expect("query failed");
 
     // Expect at least the ones we inserted (depending on migration tables presence)
     assert!(results.len() >= 5, "expected >=5 objects, got {}", results.len());
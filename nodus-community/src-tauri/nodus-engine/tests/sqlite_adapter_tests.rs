@@ -1,27 +1,40 @@
 use uuid::Uuid;
 use chrono::Utc;
 
-use nodus::storage::{SqliteAdapter, StorageContext, StoredEntity, SyncStatus, StorageAdapter};
+use nodus::storage::{ConditionOperator, SerializationFormat, SqliteAdapter, StorageAdapter, StorageContext, StorageQuery, SortDirection, StoredEntity, SyncStatus};
 
-#[tokio::test]
-async fn test_sqlite_adapter_put_get_purge() {
-    // This test requires the environment to allow creating/opening a sqlite file.
-    // To avoid spurious failures on systems where sqlite or permissions are missing,
-    // only run the test when the caller sets `NODUS_SQLITE_TEST=1`.
+/// Remove a test database along with the WAL files SQLite keeps beside it
+fn remove_db(path: &str) {
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+    }
+}
+
+/// Open a fresh JSON adapter on a throwaway database file, returning it with
+/// the path to pass to `remove_db`. These tests need to create and open a
+/// sqlite file, so to avoid spurious failures where sqlite or permissions are
+/// missing they only run when the caller sets `NODUS_SQLITE_TEST=1`.
+async fn sqlite_test_adapter() -> Option<(SqliteAdapter, String)> {
     if std::env::var("NODUS_SQLITE_TEST").is_err() {
         println!("Skipping sqlite adapter test; set NODUS_SQLITE_TEST=1 to run it");
-        return;
+        return None;
     }
 
     // Use a relative test DB filename (avoids platform-specific absolute path parsing)
     let path = format!("nodus_test_{}.sqlite", Uuid::new_v4());
-    // Ensure no pre-existing file
-    let _ = std::fs::remove_file(&path);
-
+    remove_db(&path);
+    // sqlx only opens existing files with a plain `sqlite://` URL
+    std::fs::File::create(&path).expect("create db file failed");
     let mut adapter = SqliteAdapter::new(path.clone());
     adapter.initialize().await.expect("initialize failed");
+    Some((adapter, path))
+}
 
-    let ctx = StorageContext { user_id: "test-user".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4() };
+#[tokio::test]
+async fn test_sqlite_adapter_put_get_purge() {
+    let Some((mut adapter, path)) = sqlite_test_adapter().await else { return };
+
+    let ctx = StorageContext { user_id: "test-user".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4(), skip_validation: false };
 
     let ent = StoredEntity {
         id: "k1".to_string(),
@@ -46,6 +59,330 @@ async fn test_sqlite_adapter_put_get_purge() {
     let got2 = adapter.get("test:k1", &ctx).await.expect("get after purge failed");
     assert!(got2.is_none());
 
-    // Cleanup temp file
-    let _ = std::fs::remove_file(&path);
+    adapter.close().await;
+    remove_db(&path);
+}
+
+#[tokio::test]
+async fn test_sqlite_adapter_round_trips_both_formats() {
+    let Some((mut json_adapter, path)) = sqlite_test_adapter().await else { return };
+
+    let ctx = StorageContext { user_id: "test-user".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4(), skip_validation: false };
+    let ent = StoredEntity {
+        id: "k1".to_string(),
+        entity_type: "test_entity".to_string(),
+        data: serde_json::json!({"value": 42, "nested": {"tags": ["a", "b"], "ratio": 0.5, "none": null}}),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        created_by: "tester".to_string(),
+        updated_by: "tester".to_string(),
+        version: 3,
+        deleted_at: Some(Utc::now()),
+        sync_status: SyncStatus::Pending,
+    };
+
+    json_adapter.put("test:json", ent.clone(), &ctx).await.expect("json put failed");
+    json_adapter.close().await;
+
+    // Same database, switched to MessagePack: old JSON rows stay readable
+    let mut msgpack_adapter = SqliteAdapter::new(path.clone()).with_serialization_format(SerializationFormat::MessagePack);
+    msgpack_adapter.initialize().await.expect("initialize failed");
+    msgpack_adapter.put("test:msgpack", ent.clone(), &ctx).await.expect("msgpack put failed");
+
+    for key in ["test:json", "test:msgpack"] {
+        let got = msgpack_adapter.get(key, &ctx).await.expect("get failed").expect("not found");
+        assert_eq!(serde_json::to_value(&got).unwrap(), serde_json::to_value(&ent).unwrap());
+    }
+    msgpack_adapter.close().await;
+
+    remove_db(&path);
+}
+
+#[tokio::test]
+async fn test_sqlite_batch_put_writes_in_one_transaction() {
+    let Some((mut adapter, path)) = sqlite_test_adapter().await else { return };
+
+    let ctx = StorageContext { user_id: "test-user".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4(), skip_validation: false };
+    let entities: Vec<(String, StoredEntity)> = (0..250)
+        .map(|i| {
+            let ent = StoredEntity {
+                id: format!("k{}", i),
+                entity_type: "test_entity".to_string(),
+                data: serde_json::json!({"value": i}),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                created_by: "tester".to_string(),
+                updated_by: "tester".to_string(),
+                version: 1,
+                deleted_at: None,
+                sync_status: SyncStatus::Local,
+            };
+            (format!("test:k{}", i), ent)
+        })
+        .collect();
+
+    adapter.batch_put(entities, &ctx).await.expect("batch put failed");
+    assert_eq!(adapter.get_stats().await.unwrap().total_entities, 250);
+    let got = adapter.get("test:k249", &ctx).await.expect("get failed").expect("not found");
+    assert_eq!(got.data["value"], 249);
+
+    let stats = adapter.get_stats().await.unwrap();
+    assert_eq!(stats.entities_by_type.get("test_entity"), Some(&250));
+    assert!(stats.bytes_by_type["test_entity"] > 0);
+    assert_eq!(stats.storage_size_bytes, stats.bytes_by_type["test_entity"]);
+
+    adapter.close().await;
+    remove_db(&path);
+}
+
+#[tokio::test]
+async fn test_sqlite_query_conditions() {
+    let Some((mut adapter, path)) = sqlite_test_adapter().await else { return };
+
+    let ctx = StorageContext { user_id: "test-user".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4(), skip_validation: false };
+    let entity = |id: &str, entity_type: &str, data: serde_json::Value| StoredEntity {
+        id: id.to_string(),
+        entity_type: entity_type.to_string(),
+        data,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        created_by: "tester".to_string(),
+        updated_by: "tester".to_string(),
+        version: 1,
+        deleted_at: None,
+        sync_status: SyncStatus::Local,
+    };
+    adapter.put("task:1", entity("task:1", "task", serde_json::json!({"status": "open", "points": 1, "done": false})), &ctx).await.unwrap();
+    adapter.put("task:2", entity("task:2", "task", serde_json::json!({"status": "closed", "points": 5, "done": true})), &ctx).await.unwrap();
+    adapter.put("task:3", entity("task:3", "task", serde_json::json!({"status": "open", "points": "many"})), &ctx).await.unwrap();
+    adapter.put("note:1", entity("note:1", "note", serde_json::json!({"status": "open"})), &ctx).await.unwrap();
+    adapter.close().await;
+
+    // A row written in the other format is filtered after decoding
+    let mut msgpack_adapter = SqliteAdapter::new(path.clone()).with_serialization_format(SerializationFormat::MessagePack);
+    msgpack_adapter.initialize().await.expect("initialize failed");
+    msgpack_adapter.put("task:4", entity("task:4", "task", serde_json::json!({"status": "open", "points": 8})), &ctx).await.unwrap();
+    msgpack_adapter.close().await;
+    adapter.initialize().await.expect("reopen failed");
+
+    let ids = |query: StorageQuery| {
+        let adapter = &adapter;
+        let ctx = &ctx;
+        async move { adapter.query(&query, ctx).await.unwrap().into_iter().map(|e| e.id).collect::<Vec<_>>() }
+    };
+
+    assert_eq!(ids(StorageQuery::builder().entity_type("task").filter("status", "open").build()).await, ["task:1", "task:3", "task:4"]);
+    // "many" is text: SQLite would rank it above any number, the filter doesn't
+    assert_eq!(
+        ids(StorageQuery::builder().entity_type("task").condition("points", ConditionOperator::GreaterThan, 2).build()).await,
+        ["task:2", "task:4"]
+    );
+    assert_eq!(ids(StorageQuery::builder().condition("done", ConditionOperator::Equals, true).build()).await, ["task:2"]);
+    assert_eq!(
+        ids(StorageQuery::builder().condition("status", ConditionOperator::NotIn, serde_json::json!(["open"])).build()).await,
+        ["task:2"]
+    );
+    assert_eq!(
+        ids(StorageQuery::builder().entity_type("task").condition("id", ConditionOperator::Contains, "task:").offset(1).limit(2).build()).await,
+        ["task:2", "task:3"]
+    );
+
+    adapter.close().await;
+    remove_db(&path);
+}
+
+#[tokio::test]
+async fn test_sqlite_query_multi_key_sort() {
+    let Some((mut adapter, path)) = sqlite_test_adapter().await else { return };
+
+    let ctx = StorageContext { user_id: "test-user".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4(), skip_validation: false };
+    let base = Utc::now();
+    let entity = |id: &str, rank: Option<i64>, age_minutes: i64| StoredEntity {
+        id: id.to_string(),
+        entity_type: "card".to_string(),
+        data: rank.map_or(serde_json::json!({}), |rank| serde_json::json!({ "meta": { "rank": rank } })),
+        created_at: base - chrono::Duration::minutes(age_minutes),
+        updated_at: base,
+        created_by: "tester".to_string(),
+        updated_by: "tester".to_string(),
+        version: 1,
+        deleted_at: None,
+        sync_status: SyncStatus::Local,
+    };
+    let rows = vec![
+        ("card:a".to_string(), entity("card:a", Some(2), 5)),
+        ("card:b".to_string(), entity("card:b", Some(1), 4)),
+        ("card:c".to_string(), entity("card:c", Some(2), 3)),
+        ("card:d".to_string(), entity("card:d", None, 2)),
+    ];
+    adapter.batch_put(rows, &ctx).await.unwrap();
+
+    let query = StorageQuery::builder()
+        .entity_type("card")
+        .sort("meta.rank", SortDirection::Asc)
+        .sort("created_at", SortDirection::Desc)
+        .build();
+    let ids = |entities: Vec<StoredEntity>| entities.into_iter().map(|e| e.id).collect::<Vec<_>>();
+
+    // All JSON: ordered and paged in SQL
+    assert_eq!(ids(adapter.query(&query, &ctx).await.unwrap()), ["card:d", "card:b", "card:c", "card:a"]);
+
+    // A sort with no type or filters is still a sort, not key order
+    let sort_only = StorageQuery::builder().sort("created_at", SortDirection::Asc).limit(3).build();
+    assert_eq!(ids(adapter.query(&sort_only, &ctx).await.unwrap()), ["card:a", "card:b", "card:c"]);
+    let sort_only = StorageQuery::builder().sort("meta.rank", SortDirection::Desc).build();
+    assert_eq!(ids(adapter.query(&sort_only, &ctx).await.unwrap()), ["card:a", "card:c", "card:b", "card:d"]);
+    adapter.close().await;
+
+    // A MessagePack row moves the sort into Rust; the order is the same
+    let mut msgpack_adapter = SqliteAdapter::new(path.clone()).with_serialization_format(SerializationFormat::MessagePack);
+    msgpack_adapter.initialize().await.expect("initialize failed");
+    msgpack_adapter.put("card:e", entity("card:e", Some(1), 1), &ctx).await.unwrap();
+    msgpack_adapter.close().await;
+    adapter.initialize().await.expect("reopen failed");
+    assert_eq!(ids(adapter.query(&query, &ctx).await.unwrap()), ["card:d", "card:e", "card:b", "card:c", "card:a"]);
+    let sort_only = StorageQuery::builder().sort("created_at", SortDirection::Desc).limit(2).build();
+    assert_eq!(ids(adapter.query(&sort_only, &ctx).await.unwrap()), ["card:e", "card:d"]);
+    adapter.close().await;
+
+    remove_db(&path);
+}
+
+#[tokio::test]
+async fn test_sqlite_scan_type_pages_by_key() {
+    let Some((mut adapter, path)) = sqlite_test_adapter().await else { return };
+
+    let ctx = StorageContext { user_id: "test-user".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4(), skip_validation: false };
+    let entity = |id: &str, entity_type: &str| StoredEntity {
+        id: id.to_string(),
+        entity_type: entity_type.to_string(),
+        data: serde_json::json!({}),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+        created_by: "tester".to_string(),
+        updated_by: "tester".to_string(),
+        version: 1,
+        deleted_at: None,
+        sync_status: SyncStatus::Local,
+    };
+    for key in ["task:1", "task:2", "task:3", "task:5"] {
+        adapter.put(key, entity(key, "task"), &ctx).await.unwrap();
+    }
+    adapter.put("note:1", entity("note:1", "note"), &ctx).await.unwrap();
+    adapter.close().await;
+
+    // SQL can't see the type of a MessagePack row, so it is checked after decoding
+    let mut msgpack_adapter = SqliteAdapter::new(path.clone()).with_serialization_format(SerializationFormat::MessagePack);
+    msgpack_adapter.initialize().await.expect("initialize failed");
+    msgpack_adapter.put("task:4", entity("task:4", "note"), &ctx).await.unwrap();
+    msgpack_adapter.close().await;
+    adapter.initialize().await.expect("reopen failed");
+
+    let mut pages = Vec::new();
+    let mut after: Option<String> = None;
+    loop {
+        let page = adapter.scan_type("task", after.as_deref(), 2, &ctx).await.unwrap();
+        pages.push(page.entities.into_iter().map(|e| e.id).collect::<Vec<_>>());
+        match page.next_cursor {
+            Some(next) => after = Some(next),
+            None => break,
+        }
+    }
+    assert_eq!(pages, vec![vec!["task:1", "task:2"], vec!["task:3"], vec!["task:5"]]);
+
+    adapter.close().await;
+    remove_db(&path);
+}
+
+#[tokio::test]
+async fn test_sqlite_version_history_as_of() {
+    let Some((mut adapter, path)) = sqlite_test_adapter().await else { return };
+
+    let ctx = StorageContext { user_id: "test-user".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4(), skip_validation: false };
+    let created = Utc::now();
+    let version = |v: i64, updated_at| StoredEntity {
+        id: "dashboard:1".to_string(),
+        entity_type: "dashboard".to_string(),
+        data: serde_json::json!({ "v": v }),
+        created_at: created,
+        updated_at,
+        created_by: "tester".to_string(),
+        updated_by: "tester".to_string(),
+        version: v as u64,
+        deleted_at: None,
+        sync_status: SyncStatus::Local,
+    };
+    let second = created + chrono::Duration::minutes(1);
+    let third = created + chrono::Duration::minutes(2);
+    adapter.put("dashboard:1", version(1, created), &ctx).await.unwrap();
+    adapter.put("dashboard:1", version(2, second), &ctx).await.unwrap();
+    adapter.batch_put(vec![("dashboard:1".to_string(), version(3, third))], &ctx).await.unwrap();
+
+    let as_of = |offset_secs: i64| adapter.get_version_as_of("dashboard:1", created + chrono::Duration::seconds(offset_secs), &ctx);
+    assert!(as_of(-1).await.unwrap().is_none());
+    assert_eq!(as_of(0).await.unwrap().unwrap().data["v"], 1);
+    assert_eq!(as_of(90).await.unwrap().unwrap().data["v"], 2);
+    assert_eq!(as_of(600).await.unwrap().unwrap().data["v"], 3);
+
+    // Keeping one superseded version drops the first
+    assert_eq!(adapter.prune_versions(1, &ctx).await.unwrap(), 1);
+    assert!(as_of(0).await.unwrap().is_none());
+    assert_eq!(as_of(90).await.unwrap().unwrap().data["v"], 2);
+
+    adapter.purge("dashboard:1", &ctx).await.unwrap();
+    assert!(as_of(90).await.unwrap().is_none());
+
+    adapter.close().await;
+    remove_db(&path);
+}
+
+#[tokio::test]
+async fn test_sqlite_query_newest_walks_creation_time_index() {
+    let Some((mut adapter, path)) = sqlite_test_adapter().await else { return };
+
+    let ctx = StorageContext { user_id: "test-user".to_string(), session_id: Uuid::new_v4(), operation_id: Uuid::new_v4(), skip_validation: false };
+    // A whole second stores as "...:00Z" and half a second later as
+    // "...:00.5Z", which sort the wrong way round as stored text
+    let base = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    let event = |id: &str, kind: &str, millis: i64| StoredEntity {
+        id: id.to_string(),
+        entity_type: "audit_event".to_string(),
+        data: serde_json::json!({ "kind": kind }),
+        created_at: base + chrono::Duration::milliseconds(millis),
+        updated_at: Utc::now(),
+        created_by: "tester".to_string(),
+        updated_by: "tester".to_string(),
+        version: 1,
+        deleted_at: None,
+        sync_status: SyncStatus::Local,
+    };
+    for (id, kind, millis) in [("e1", "save", 0), ("e2", "save", 500), ("e3", "load", 700), ("e4", "load", 800), ("e5", "save", 1000), ("e6", "save", 1000)] {
+        adapter.put(&format!("audit_event:{}", id), event(id, kind, millis), &ctx).await.unwrap();
+    }
+    adapter.put("note:1", StoredEntity { entity_type: "note".to_string(), ..event("n1", "save", 2000) }, &ctx).await.unwrap();
+
+    let saves = StorageQuery::builder().entity_type("audit_event").filter("kind", "save").build();
+    let ids = |entities: Vec<StoredEntity>| entities.into_iter().map(|e| e.id).collect::<Vec<_>>();
+    let first = adapter.query_newest(&saves, None, 2, &ctx).await.unwrap();
+    assert_eq!(ids(first.clone()), ["e6", "e5"]);
+    let last = first.last().unwrap();
+    // The walk steps over the non-matching e3 and e4
+    let second = adapter.query_newest(&saves, Some((last.created_at, last.id.as_str())), 2, &ctx).await.unwrap();
+    assert_eq!(ids(second), ["e2", "e1"]);
+
+    let window = StorageQuery::builder()
+        .entity_type("audit_event")
+        .condition("created_at", ConditionOperator::LessThan, nodus::storage::rfc3339(&(base + chrono::Duration::milliseconds(800))))
+        .build();
+    assert_eq!(ids(adapter.query_newest(&window, None, 10, &ctx).await.unwrap()), ["e3", "e2", "e1"]);
+
+    // Rows from before the index columns existed are filled in on open
+    let pool = adapter.pool.clone().unwrap();
+    sqlx::query("UPDATE kv_store SET entity_type = NULL, entity_id = NULL, created_at = NULL").execute(&pool).await.unwrap();
+    adapter.close().await;
+    adapter.initialize().await.expect("reopen failed");
+    assert_eq!(ids(adapter.query_newest(&saves, None, 10, &ctx).await.unwrap()), ["e6", "e5", "e2", "e1"]);
+
+    adapter.close().await;
+    remove_db(&path);
 }
@@ -4,57 +4,103 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use std::sync::Arc;
+use tauri::ipc::{InvokeBody, Request};
 use tauri::State;
 use tokio::sync::RwLock;
 
 // Use types and commands from the local engine crate with integrated license system
+use nodus::error_code::{CommandError, ErrorCode};
+use nodus::payload_limits::PayloadKind;
 use nodus::state_mod::AppState;
 
 type AppStateType = Arc<RwLock<AppState>>;
 
+// Commands carrying large payloads (grid configs, plugin bundles, actions) are
+// invoked with their JSON-encoded arguments as a raw byte body, so its size is
+// checked against the payload limits before serde parses any of it.
+fn raw_body<'a>(request: &'a Request<'_>) -> Result<&'a [u8], CommandError> {
+    match request.body() {
+        InvokeBody::Raw(body) => Ok(body),
+        InvokeBody::Json(_) => Err(CommandError::new(ErrorCode::ValidationFailed, "Arguments must be sent as a raw JSON body")),
+    }
+}
+
+async fn decode_args<T: serde::de::DeserializeOwned>(
+    state: &AppStateType,
+    request: &Request<'_>,
+    kind: PayloadKind,
+) -> Result<T, CommandError> {
+    let body = raw_body(request)?;
+    state.read().await.config.payload_limits.decode(kind, body)
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginRequestArgs {
+    plugin_request: nodus::commands_plugin::JSPluginRequest,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveGridConfigArgs {
+    config_id: String,
+    config: nodus::commands_grid::GridConfig,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateGridStateArgs {
+    state_update: nodus::commands_grid::GridStateUpdate,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StageLayoutArgs {
+    config_id: String,
+    widgets: Vec<nodus::commands_grid::GridBlock>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DispatchActionArgs {
+    action_type: String,
+    payload: serde_json::Value,
+}
+
+#[derive(serde::Deserialize)]
+struct RegisterTemplateArgs {
+    template: nodus::commands_grid::GridTemplate,
+}
+
 // Backwards-compatible frontend-facing wrappers (names expected by the JS safeInvoke)
 // These must be declared before the generate_handler! invocation so the macro
 // expansion can find the generated command symbols.
 #[tauri::command]
 async fn register_js_plugin(
     state: State<'_, AppStateType>,
-    plugin_request: nodus::commands_plugin::JSPluginRequest,
-) -> Result<nodus::commands_plugin::PluginRegistrationResponse, String> {
+    request: Request<'_>,
+) -> Result<nodus::commands_plugin::PluginRegistrationResponse, CommandError> {
     let arc = state.inner().clone();
-    nodus::commands_plugin::register_js_plugin(arc, plugin_request).await
+    let args: PluginRequestArgs = decode_args(&arc, &request, PayloadKind::PluginBundle).await?;
+    nodus::commands_plugin::register_js_plugin(arc, args.plugin_request).await
 }
 
 #[tauri::command]
 async fn execute_action_with_plugins(
     state: State<'_, AppStateType>,
-    args: serde_json::Value,
-) -> Result<serde_json::Value, String> {
-    // Support two shapes coming from the frontend:
-    // 1) Direct top-level: { actionType: "...", payload: { ... } }
-    // 2) Wrapped by the Tauri low-level invoke shape: { args: { actionType: "...", payload: { ... } } }
-    // Normalize to a single `effective_args` value so both callers work.
-    let effective_args = if let Some(inner) = args.get("args") {
-        inner.clone()
-    } else {
-        args.clone()
-    };
-
-    // Accept either camelCase (actionType) or snake_case (action_type)
-    let action_type = effective_args
-        .get("actionType")
-        .or_else(|| effective_args.get("action_type"))
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| "Missing actionType".to_string())?
-        .to_string();
-
-    let payload = effective_args.get("payload").cloned().unwrap_or_else(|| serde_json::json!({}));
+    request: Request<'_>,
+) -> Result<nodus::commands_plugin::ActionExecution, CommandError> {
+    let args: serde_json::Value = decode_args(state.inner(), &request, PayloadKind::Action).await?;
+    // `{ actionType, payload, sessionId? }`, optionally wrapped in `{ args: ... }`
+    let (action_type, payload) = nodus::commands_plugin::normalize_action_request(&args)?;
+    let session_id = nodus::commands_plugin::action_request_session(&args)?;
 
     let arc = state.inner().clone();
-    nodus::commands_plugin::execute_action_with_plugins(arc, action_type, payload).await
+    nodus::commands_plugin::execute_action_with_plugins(arc, action_type, payload, session_id).await
 }
 
 #[tauri::command]
-async fn get_loaded_plugins(state: State<'_, AppStateType>) -> Result<serde_json::Value, String> {
+async fn get_loaded_plugins(state: State<'_, AppStateType>) -> Result<serde_json::Value, CommandError> {
     let arc = state.inner().clone();
     match nodus::commands_plugin::get_loaded_plugins(arc).await {
         Ok(list) => Ok(serde_json::to_value(list).unwrap_or_else(|_| serde_json::json!([]))),
@@ -63,25 +109,30 @@ async fn get_loaded_plugins(state: State<'_, AppStateType>) -> Result<serde_json
 }
 
 #[tauri::command]
-async fn remove_js_plugin(state: State<'_, AppStateType>, plugin_id: String) -> Result<(), String> {
+async fn remove_js_plugin(state: State<'_, AppStateType>, plugin_id: String) -> Result<(), CommandError> {
     let arc = state.inner().clone();
     nodus::commands_plugin::remove_js_plugin(arc, plugin_id).await
 }
 
 #[tauri::command]
-async fn get_plugin_marketplace(_state: State<'_, AppStateType>) -> Result<serde_json::Value, String> {
-    // Marketplace listing is not implemented yet; return empty list for now
-    Ok(serde_json::json!([]))
+async fn get_plugin_marketplace(_state: State<'_, AppStateType>) -> Result<Vec<nodus::marketplace::MarketplaceEntry>, CommandError> {
+    nodus::commands_plugin::get_plugin_marketplace(None).await
 }
 
 #[tauri::command]
-async fn install_marketplace_plugin(state: State<'_, AppStateType>, plugin_id: String) -> Result<nodus::commands_plugin::PluginRegistrationResponse, String> {
+async fn install_marketplace_plugin(state: State<'_, AppStateType>, plugin_id: String) -> Result<nodus::commands_plugin::PluginRegistrationResponse, CommandError> {
     let arc = state.inner().clone();
     nodus::commands_plugin::install_marketplace_plugin(arc, plugin_id, None).await
 }
 
 #[tauri::command]
-async fn get_system_plugin_status(state: State<'_, AppStateType>) -> Result<serde_json::Value, String> {
+async fn install_plugin_from_file(state: State<'_, AppStateType>, path: String) -> Result<nodus::commands_plugin::PluginRegistrationResponse, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_plugin::install_plugin_from_file(arc, path).await
+}
+
+#[tauri::command]
+async fn get_system_plugin_status(state: State<'_, AppStateType>) -> Result<serde_json::Value, CommandError> {
     let arc = state.inner().clone();
     match nodus::commands_plugin::get_system_plugin_status(arc).await {
         Ok(status) => Ok(serde_json::to_value(status).unwrap_or_else(|_| serde_json::json!({}))),
@@ -91,7 +142,7 @@ async fn get_system_plugin_status(state: State<'_, AppStateType>) -> Result<serd
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt::init();
+    nodus::logging::init();
     println!("🦀 Starting Nodus Community");
 
     // Use the integrated license system (defaults to Community tier)
@@ -111,6 +162,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .invoke_handler(tauri::generate_handler![
             // System commands (wrappers)
             wrapper_get_system_status,
+            wrapper_reload_license,
+            wrapper_license_diagnostics,
+            wrapper_get_machine_id,
+            wrapper_get_recent_spans,
+            wrapper_get_license_usage,
+            wrapper_set_limit_warning_thresholds,
+            wrapper_subscribe_limit_warnings,
+            wrapper_get_storage_health,
+            wrapper_set_storage_backend,
+            wrapper_migrate_storage_backend,
+            wrapper_patch_entity,
+            wrapper_export_storage_to_file,
+            wrapper_import_storage_from_file,
+            wrapper_purge_old_audit_events,
+            wrapper_can_execute,
+            wrapper_get_session_activity,
+            wrapper_set_idle_state,
+            wrapper_dump_state,
+            wrapper_validate_license_file,
+            wrapper_get_storage_usage,
+            wrapper_clear_storage_cache,
+            wrapper_invalidate_storage_cache,
+            wrapper_register_validation_schema,
+            wrapper_validate_entity,
             // Backwards-compatible wrapper names expected by the frontend
             register_js_plugin,
             execute_action_with_plugins,
@@ -118,6 +193,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             remove_js_plugin,
             get_plugin_marketplace,
             install_marketplace_plugin,
+            install_plugin_from_file,
             get_system_plugin_status,
             // Plugin commands (wrappers) - now with license integration
             wrapper_list_plugins,
@@ -125,27 +201,63 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             wrapper_unload_plugin,
             wrapper_register_js_plugin,
             wrapper_get_plugin_capabilities,
+            wrapper_which_plugin_handles,
+            wrapper_set_plugin_priority,
+            wrapper_validate_plugin,
+            wrapper_get_plugin_audit_log,
+            wrapper_export_plugin_manifest,
+            wrapper_import_plugin_manifest,
             // Grid commands (wrappers)
             wrapper_execute_action,
             wrapper_get_grid_config,
+            wrapper_load_grid_config,
             wrapper_save_grid_config,
             wrapper_update_grid_state,
+            wrapper_flush_grid_state,
+            wrapper_begin_layout_edit,
+            wrapper_stage_layout,
+            wrapper_commit_layout,
+            wrapper_cancel_layout,
+            wrapper_compact_all_configs,
             // NEW: direct bridge wrappers for converted JS components
             wrapper_dispatch_action,
             wrapper_operation_completed,
             // Backwards-compatible command name expected by some frontend bundles
             operation_completed,
+            wrapper_get_operation_metrics,
             wrapper_get_grid_stats,
             wrapper_export_grid_config,
             wrapper_import_grid_config,
+            wrapper_list_templates,
+            wrapper_register_template,
+            wrapper_apply_template,
+            wrapper_reflow_to_columns,
+            wrapper_find_free_regions,
             wrapper_ping,
             // Async orchestrator commands (wrappers)
             wrapper_start_async_operation,
             wrapper_complete_async_operation,
+            wrapper_cancel_async_operation,
+            wrapper_get_operation_result,
+            wrapper_list_active_operations,
             wrapper_get_active_operations_count,
+            wrapper_get_operation_activity,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(move |_app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Final flush so debounced grid edits are not lost
+                let state = app_state_arc.clone();
+                tokio::task::block_in_place(|| {
+                    tokio::runtime::Handle::current().block_on(async move {
+                        if let Err(e) = state.read().await.shutdown().await {
+                            eprintln!("Shutdown flush failed: {}", e);
+                        }
+                    })
+                });
+            }
+        });
 
     Ok(())
 }
@@ -155,13 +267,225 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 #[tauri::command]
 async fn wrapper_get_system_status(
     state: State<'_, AppStateType>,
-) -> Result<serde_json::Value, String> {
+) -> Result<nodus::state_mod::SystemStatus, CommandError> {
     let arc = state.inner().clone();
     nodus::commands::get_system_status(arc).await
 }
 
 #[tauri::command]
-async fn wrapper_list_plugins(state: State<'_, AppStateType>) -> Result<Vec<String>, String> {
+async fn wrapper_reload_license(
+    state: State<'_, AppStateType>,
+) -> Result<nodus::license_mod::LicenseChangeEvent, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::reload_license(arc).await
+}
+
+#[tauri::command]
+async fn wrapper_license_diagnostics(
+    state: State<'_, AppStateType>,
+) -> Result<nodus::license_mod::LicenseDiagnostics, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::license_diagnostics(arc).await
+}
+
+#[tauri::command]
+async fn wrapper_get_machine_id() -> Result<String, CommandError> {
+    nodus::commands::get_machine_id().await
+}
+
+#[tauri::command]
+async fn wrapper_get_recent_spans(limit: usize) -> Result<Vec<nodus::spans::SpanRecord>, CommandError> {
+    nodus::commands::get_recent_spans(limit).await
+}
+
+#[tauri::command]
+async fn wrapper_get_license_usage(
+    state: State<'_, AppStateType>,
+) -> Result<Vec<nodus::license_mod::LimitUsage>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::get_license_usage(arc).await
+}
+
+#[tauri::command]
+async fn wrapper_set_limit_warning_thresholds(
+    state: State<'_, AppStateType>,
+    thresholds: Vec<u8>,
+) -> Result<Vec<u8>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::set_limit_warning_thresholds(arc, thresholds).await
+}
+
+/// Forward limit warnings to the frontend until its channel goes away
+#[tauri::command]
+async fn wrapper_subscribe_limit_warnings(
+    state: State<'_, AppStateType>,
+    on_warning: tauri::ipc::Channel<nodus::license_mod::LimitWarningEvent>,
+) -> Result<(), CommandError> {
+    let arc = state.inner().clone();
+    let mut warnings = nodus::commands::subscribe_limit_warnings(arc).await?;
+    tokio::spawn(async move {
+        loop {
+            match warnings.recv().await {
+                Ok(warning) => {
+                    if on_warning.send(warning).is_err() {
+                        break;
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+async fn wrapper_get_storage_health(
+    state: State<'_, AppStateType>,
+) -> Result<std::collections::HashMap<String, nodus::storage::BackendHealth>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::get_storage_health(arc).await
+}
+
+#[tauri::command]
+async fn wrapper_set_storage_backend(
+    state: State<'_, AppStateType>,
+    primary: String,
+    fallbacks: Vec<String>,
+) -> Result<(), CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::set_storage_backend(arc, primary, fallbacks).await
+}
+
+#[tauri::command]
+async fn wrapper_migrate_storage_backend(
+    state: State<'_, AppStateType>,
+    source: String,
+    destination: String,
+    switch_primary: bool,
+) -> Result<nodus::storage::MigrationReport, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::migrate_storage_backend(arc, source, destination, switch_primary).await
+}
+
+#[tauri::command]
+async fn wrapper_patch_entity(
+    state: State<'_, AppStateType>,
+    key: String,
+    patch: Vec<nodus::storage::PatchOp>,
+) -> Result<nodus::storage::StoredEntity, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::patch_entity(arc, key, patch).await
+}
+
+#[tauri::command]
+async fn wrapper_export_storage_to_file(
+    state: State<'_, AppStateType>,
+    path: String,
+) -> Result<nodus::storage::ExportReport, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::export_storage_to_file(arc, path).await
+}
+
+#[tauri::command]
+async fn wrapper_import_storage_from_file(
+    state: State<'_, AppStateType>,
+    path: String,
+    mode: Option<nodus::storage::ImportMode>,
+) -> Result<nodus::storage::ImportReport, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::import_storage_from_file(arc, path, mode.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn wrapper_purge_old_audit_events(
+    state: State<'_, AppStateType>,
+    older_than_days: u32,
+) -> Result<u64, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::purge_old_audit_events(arc, older_than_days).await
+}
+
+#[tauri::command]
+async fn wrapper_get_session_activity(
+    state: State<'_, AppStateType>,
+    session_id: uuid::Uuid,
+) -> Result<Vec<nodus::state_mod::SessionActivity>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::get_session_activity(arc, session_id).await
+}
+
+#[tauri::command]
+async fn wrapper_set_idle_state(state: State<'_, AppStateType>, idle: bool) -> Result<bool, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::set_idle_state(arc, idle).await
+}
+
+#[tauri::command]
+async fn wrapper_dump_state(state: State<'_, AppStateType>, path: String) -> Result<(), CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::dump_state(arc, path).await
+}
+
+#[tauri::command]
+async fn wrapper_validate_license_file(
+    state: State<'_, AppStateType>,
+    path: String,
+) -> Result<nodus::license_mod::LicenseDiagnostics, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::validate_license_file(arc, path).await
+}
+
+#[tauri::command]
+async fn wrapper_get_storage_usage(
+    state: State<'_, AppStateType>,
+) -> Result<Vec<nodus::storage::EntityTypeUsage>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::get_storage_usage(arc).await
+}
+
+#[tauri::command]
+async fn wrapper_clear_storage_cache(state: State<'_, AppStateType>) -> Result<usize, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::clear_storage_cache(arc).await
+}
+
+#[tauri::command]
+async fn wrapper_invalidate_storage_cache(state: State<'_, AppStateType>, key: String) -> Result<bool, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::invalidate_storage_cache(arc, key).await
+}
+
+#[tauri::command]
+async fn wrapper_can_execute(
+    state: State<'_, AppStateType>,
+    action_type: String,
+) -> Result<nodus::state_mod::ActionPermission, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::can_execute(arc, action_type).await
+}
+
+#[tauri::command]
+async fn wrapper_register_validation_schema(
+    state: State<'_, AppStateType>,
+    schema: nodus::storage::ValidationSchema,
+) -> Result<(), CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::register_validation_schema(arc, schema).await
+}
+
+#[tauri::command]
+async fn wrapper_validate_entity(
+    state: State<'_, AppStateType>,
+    entity_type: String,
+    data: serde_json::Value,
+) -> Result<nodus::storage::ValidationReport, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands::validate_entity(arc, entity_type, data).await
+}
+
+#[tauri::command]
+async fn wrapper_list_plugins(state: State<'_, AppStateType>) -> Result<Vec<String>, CommandError> {
     let arc = state.inner().clone();
     nodus::commands::list_plugins(arc).await
 }
@@ -170,7 +494,7 @@ async fn wrapper_list_plugins(state: State<'_, AppStateType>) -> Result<Vec<Stri
 async fn wrapper_load_plugin(
     state: State<'_, AppStateType>,
     plugin_path: String,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     let arc = state.inner().clone();
     nodus::commands::load_plugin(arc, plugin_path).await
 }
@@ -179,7 +503,7 @@ async fn wrapper_load_plugin(
 async fn wrapper_unload_plugin(
     state: State<'_, AppStateType>,
     plugin_id: String,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let arc = state.inner().clone();
     nodus::commands::unload_plugin(arc, plugin_id).await
 }
@@ -188,26 +512,82 @@ async fn wrapper_unload_plugin(
 #[tauri::command]
 async fn wrapper_register_js_plugin(
     state: State<'_, AppStateType>,
-    plugin_request: nodus::commands_plugin::JSPluginRequest,
-) -> Result<nodus::commands_plugin::PluginRegistrationResponse, String> {
+    request: Request<'_>,
+) -> Result<nodus::commands_plugin::PluginRegistrationResponse, CommandError> {
     let arc = state.inner().clone();
-    nodus::commands_plugin::register_js_plugin(arc, plugin_request).await
+    let args: PluginRequestArgs = decode_args(&arc, &request, PayloadKind::PluginBundle).await?;
+    nodus::commands_plugin::register_js_plugin(arc, args.plugin_request).await
 }
 
 #[tauri::command]
 async fn wrapper_get_plugin_capabilities(
     state: State<'_, AppStateType>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, CommandError> {
     let app_state = state.inner().read().await;
     Ok(nodus::commands_plugin::get_plugin_capabilities(&app_state.license_manager).await)
 }
 
+#[tauri::command]
+async fn wrapper_which_plugin_handles(
+    state: State<'_, AppStateType>,
+    action_type: String,
+) -> Result<Option<String>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_plugin::which_plugin_handles(arc, action_type).await
+}
+
+#[tauri::command]
+async fn wrapper_set_plugin_priority(
+    state: State<'_, AppStateType>,
+    plugin_id: String,
+    priority: i32,
+) -> Result<(), CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_plugin::set_plugin_priority(arc, plugin_id, priority).await
+}
+
+#[tauri::command]
+async fn wrapper_validate_plugin(
+    state: State<'_, AppStateType>,
+    request: Request<'_>,
+) -> Result<nodus::commands_plugin::PluginValidationReport, CommandError> {
+    let arc = state.inner().clone();
+    let args: PluginRequestArgs = decode_args(&arc, &request, PayloadKind::PluginBundle).await?;
+    nodus::commands_plugin::validate_plugin(arc, args.plugin_request).await
+}
+
+#[tauri::command]
+async fn wrapper_get_plugin_audit_log(
+    state: State<'_, AppStateType>,
+    query: nodus::commands_plugin::PluginAuditQuery,
+) -> Result<nodus::commands_plugin::PluginAuditPage, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_plugin::get_plugin_audit_log(arc, query).await
+}
+
+#[tauri::command]
+async fn wrapper_export_plugin_manifest(
+    state: State<'_, AppStateType>,
+) -> Result<nodus::commands_plugin::PluginManifest, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_plugin::export_plugin_manifest(arc).await
+}
+
+#[tauri::command]
+async fn wrapper_import_plugin_manifest(
+    state: State<'_, AppStateType>,
+    manifest: nodus::commands_plugin::PluginManifest,
+) -> Result<Vec<nodus::commands_plugin::PluginImportResult>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_plugin::import_plugin_manifest(arc, manifest).await
+}
+
 // Grid command wrappers
 #[tauri::command]
 async fn wrapper_execute_action(
     state: State<'_, AppStateType>,
     action: nodus::action_dispatcher::Action,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, CommandError> {
     let arc = state.inner().clone();
     nodus::commands_grid::execute_action(arc, action).await
 }
@@ -216,32 +596,97 @@ async fn wrapper_execute_action(
 async fn wrapper_get_grid_config(
     state: State<'_, AppStateType>,
     config_id: String,
-) -> Result<nodus::commands_grid::GridConfig, String> {
+) -> Result<nodus::commands_grid::GridConfig, CommandError> {
     let arc = state.inner().clone();
     nodus::commands_grid::get_grid_config(arc, config_id).await
 }
 
 #[tauri::command]
-async fn wrapper_save_grid_config(
+async fn wrapper_load_grid_config(
     state: State<'_, AppStateType>,
     config_id: String,
-    config: nodus::commands_grid::GridConfig,
-) -> Result<(), String> {
+    options: Option<nodus::commands_grid::GridLoadOptions>,
+) -> Result<nodus::commands_grid::LoadedGridConfig, CommandError> {
     let arc = state.inner().clone();
-    nodus::commands_grid::save_grid_config(arc, config_id, config).await
+    nodus::commands_grid::load_grid_config(arc, config_id, options.unwrap_or_default()).await
+}
+
+#[tauri::command]
+async fn wrapper_save_grid_config(
+    state: State<'_, AppStateType>,
+    request: Request<'_>,
+) -> Result<(), CommandError> {
+    let arc = state.inner().clone();
+    let args: SaveGridConfigArgs = decode_args(&arc, &request, PayloadKind::GridConfig).await?;
+    nodus::commands_grid::save_grid_config(arc, args.config_id, args.config).await
 }
 
 #[tauri::command]
 async fn wrapper_update_grid_state(
     state: State<'_, AppStateType>,
-    state_update: nodus::commands_grid::GridStateUpdate,
-) -> Result<(), String> {
+    request: Request<'_>,
+) -> Result<(), CommandError> {
+    let arc = state.inner().clone();
+    let args: UpdateGridStateArgs = decode_args(&arc, &request, PayloadKind::GridConfig).await?;
+    nodus::commands_grid::update_grid_state(arc, args.state_update).await
+}
+
+#[tauri::command]
+async fn wrapper_flush_grid_state(
+    state: State<'_, AppStateType>,
+    config_id: Option<String>,
+) -> Result<usize, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_grid::flush_grid_state(arc, config_id).await
+}
+
+#[tauri::command]
+async fn wrapper_begin_layout_edit(
+    state: State<'_, AppStateType>,
+    config_id: String,
+) -> Result<nodus::commands_grid::GridConfig, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_grid::begin_layout_edit(arc, config_id).await
+}
+
+#[tauri::command]
+async fn wrapper_stage_layout(
+    state: State<'_, AppStateType>,
+    request: Request<'_>,
+) -> Result<(), CommandError> {
+    let arc = state.inner().clone();
+    let args: StageLayoutArgs = decode_args(&arc, &request, PayloadKind::GridConfig).await?;
+    nodus::commands_grid::stage_layout(arc, args.config_id, args.widgets).await
+}
+
+#[tauri::command]
+async fn wrapper_commit_layout(
+    state: State<'_, AppStateType>,
+    config_id: String,
+) -> Result<(), CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_grid::commit_layout(arc, config_id).await
+}
+
+#[tauri::command]
+async fn wrapper_cancel_layout(
+    state: State<'_, AppStateType>,
+    config_id: String,
+) -> Result<bool, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_grid::cancel_layout(arc, config_id).await
+}
+
+#[tauri::command]
+async fn wrapper_compact_all_configs(
+    state: State<'_, AppStateType>,
+) -> Result<Vec<nodus::commands_grid::ConfigCompactionResult>, CommandError> {
     let arc = state.inner().clone();
-    nodus::commands_grid::update_grid_state(arc, state_update).await
+    nodus::commands_grid::compact_all_configs(arc).await
 }
 
 #[tauri::command]
-async fn wrapper_ping(state: State<'_, AppStateType>) -> Result<String, String> {
+async fn wrapper_ping(state: State<'_, AppStateType>) -> Result<nodus::commands_grid::PingResponse, CommandError> {
     let arc = state.inner().clone();
     nodus::commands_grid::ping(arc).await
 }
@@ -250,11 +695,17 @@ async fn wrapper_ping(state: State<'_, AppStateType>) -> Result<String, String>
 #[tauri::command]
 async fn wrapper_dispatch_action(
     state: State<'_, AppStateType>,
-    action_type: String,
-    payload: serde_json::Value,
-) -> Result<serde_json::Value, String> {
+    request: Request<'_>,
+) -> Result<serde_json::Value, CommandError> {
     let arc = state.inner().clone();
-    nodus::commands_grid::dispatch_action(action_type, payload, arc).await
+    let limits = arc.read().await.config.payload_limits.clone();
+    let body = raw_body(&request)?;
+    // Which limit applies depends on the action type inside the body: parse
+    // under the larger of the two, then hold the body to its own
+    let larger = if limits.grid_config_bytes >= limits.action_bytes { PayloadKind::GridConfig } else { PayloadKind::Action };
+    let args: DispatchActionArgs = limits.decode(larger, body)?;
+    limits.check_len(nodus::commands_grid::dispatch_payload_kind(&args.action_type), body.len())?;
+    nodus::commands_grid::dispatch_action(args.action_type, args.payload, arc).await
 }
 
 #[tauri::command]
@@ -265,7 +716,7 @@ async fn wrapper_operation_completed(
     duration: Option<f64>,
     error: Option<String>,
     metadata: Option<serde_json::Value>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let arc = state.inner().clone();
     nodus::commands_grid::operation_completed(operation_type, success, duration, error, metadata, arc).await
 }
@@ -280,16 +731,24 @@ async fn operation_completed(
     duration: Option<f64>,
     error: Option<String>,
     metadata: Option<serde_json::Value>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let arc = state.inner().clone();
     nodus::commands_grid::operation_completed(operation_type, success, duration, error, metadata, arc).await
 }
 
+#[tauri::command]
+async fn wrapper_get_operation_metrics(
+    state: State<'_, AppStateType>,
+) -> Result<std::collections::HashMap<String, nodus::commands_grid::OperationStats>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_grid::get_operation_metrics(arc).await
+}
+
 #[tauri::command]
 async fn wrapper_get_grid_stats(
     state: State<'_, AppStateType>,
     config_id: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, CommandError> {
     let arc = state.inner().clone();
     nodus::commands_grid::get_grid_stats(config_id, arc).await
 }
@@ -298,7 +757,7 @@ async fn wrapper_get_grid_stats(
 async fn wrapper_export_grid_config(
     state: State<'_, AppStateType>,
     config_id: String,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     let arc = state.inner().clone();
     nodus::commands_grid::export_grid_config(config_id, arc).await
 }
@@ -307,17 +766,67 @@ async fn wrapper_export_grid_config(
 async fn wrapper_import_grid_config(
     state: State<'_, AppStateType>,
     config_json: String,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, CommandError> {
     let arc = state.inner().clone();
     nodus::commands_grid::import_grid_config(config_json, arc).await
 }
 
+#[tauri::command]
+async fn wrapper_list_templates(
+    state: State<'_, AppStateType>,
+) -> Result<Vec<nodus::commands_grid::GridTemplate>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_grid::list_templates(arc).await
+}
+
+#[tauri::command]
+async fn wrapper_register_template(
+    state: State<'_, AppStateType>,
+    request: Request<'_>,
+) -> Result<(), CommandError> {
+    let arc = state.inner().clone();
+    let args: RegisterTemplateArgs = decode_args(&arc, &request, PayloadKind::GridConfig).await?;
+    nodus::commands_grid::register_template(arc, args.template).await
+}
+
+#[tauri::command]
+async fn wrapper_apply_template(
+    state: State<'_, AppStateType>,
+    config_id: String,
+    template_name: String,
+    preserve_blocks: Option<bool>,
+) -> Result<nodus::commands_grid::GridConfig, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_grid::apply_template(arc, config_id, template_name, preserve_blocks.unwrap_or(false)).await
+}
+
+#[tauri::command]
+async fn wrapper_reflow_to_columns(
+    state: State<'_, AppStateType>,
+    config_id: String,
+    new_columns: u32,
+) -> Result<nodus::commands_grid::ReflowResult, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_grid::reflow_to_columns(arc, config_id, new_columns).await
+}
+
+#[tauri::command]
+async fn wrapper_find_free_regions(
+    state: State<'_, AppStateType>,
+    config_id: String,
+    min_w: u32,
+    min_h: u32,
+) -> Result<Vec<nodus::commands_grid::FreeRegion>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_grid::find_free_regions(arc, config_id, min_w, min_h).await
+}
+
 // Async orchestrator command wrappers
 #[tauri::command]
 async fn wrapper_start_async_operation(
     state: State<'_, AppStateType>,
     context: nodus::commands_async::AsyncOperationContext,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     let arc = state.inner().clone();
     nodus::commands_async::start_async_operation(arc, context).await
 }
@@ -329,15 +838,49 @@ async fn wrapper_complete_async_operation(
     success: bool,
     result: Option<String>,
     error: Option<String>,
-) -> Result<nodus::commands_async::OperationResult, String> {
+) -> Result<nodus::commands_async::OperationResult, CommandError> {
     let arc = state.inner().clone();
     nodus::commands_async::complete_async_operation(arc, operation_id, success, result, error).await
 }
 
+#[tauri::command]
+async fn wrapper_cancel_async_operation(
+    state: State<'_, AppStateType>,
+    operation_id: String,
+) -> Result<nodus::commands_async::OperationResult, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_async::cancel_async_operation(arc, operation_id).await
+}
+
+#[tauri::command]
+async fn wrapper_get_operation_result(
+    state: State<'_, AppStateType>,
+    operation_id: String,
+) -> Result<Option<nodus::commands_async::StoredOperationResult>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_async::get_operation_result(arc, operation_id).await
+}
+
+#[tauri::command]
+async fn wrapper_list_active_operations(
+    state: State<'_, AppStateType>,
+) -> Result<Vec<nodus::commands_async::ActiveOperationInfo>, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_async::list_active_operations(arc).await
+}
+
+#[tauri::command]
+async fn wrapper_get_operation_activity(
+    state: State<'_, AppStateType>,
+) -> Result<nodus::commands_async::OperationActivityReport, CommandError> {
+    let arc = state.inner().clone();
+    nodus::commands_async::get_operation_activity(arc).await
+}
+
 #[tauri::command]
 async fn wrapper_get_active_operations_count(
     state: State<'_, AppStateType>,
-) -> Result<usize, String> {
+) -> Result<usize, CommandError> {
     let arc = state.inner().clone();
     nodus::commands_async::get_active_operations_count(arc).await
 }
\ No newline at end of file
@@ -255,6 +255,12 @@ async function safeInvoke(cmd, args) {
 	return null;
 }
 
+// Commands with size-limited payloads take their arguments as a raw JSON
+// body, which the engine measures before parsing any of it
+function jsonBody(args) {
+	return new TextEncoder().encode(JSON.stringify(args));
+}
+
 /**
  * @class ActionDispatcher
  * @classdesc Enhanced proxy with Universal Plugin System support
@@ -346,9 +352,10 @@ class ActionDispatcher {
 				},
 			};
 
-			const result = await safeInvoke("register_js_plugin", {
-				pluginRequest: pluginRequest,
-			});
+			const result = await safeInvoke(
+				"register_js_plugin",
+				jsonBody({ pluginRequest: pluginRequest })
+			);
 
 			if (result?.success) {
 				console.log(`[ActionDispatcher] JS Plugin registered: ${id}`);
@@ -409,7 +416,7 @@ class ActionDispatcher {
 					try {
 						result = await globalThis.__TAURI__.core.invoke(
 							"execute_action_with_plugins",
-							{ args: { actionType: actionType, payload } }
+							jsonBody({ args: { actionType: actionType, payload } })
 						);
 					} catch (err) {
 						// If the direct invoke throws because of shape mismatch, fall back to safeInvoke
@@ -419,17 +426,20 @@ class ActionDispatcher {
 						);
 						result = await safeInvoke(
 							"execute_action_with_plugins",
-							{
+							jsonBody({
 								actionType: actionType,
 								payload,
-							}
+							})
 						);
 					}
 				} else {
-					result = await safeInvoke("execute_action_with_plugins", {
-						actionType: actionType,
-						payload,
-					});
+					result = await safeInvoke(
+						"execute_action_with_plugins",
+						jsonBody({
+							actionType: actionType,
+							payload,
+						})
+					);
 				}
 			} catch (err) {
 				console.warn(
@@ -439,12 +449,14 @@ class ActionDispatcher {
 			}
 
 			if (result) {
+				// The command reports routing alongside the action's own result
 				console.log(`[ActionDispatcher] Action completed:`, {
-					success: result.success,
-					executionTime: result.execution_time_ms,
-					pluginExecuted: result.plugin_executed,
+					success: result.result?.success,
+					executionTime: result.duration_ms,
+					handledBy: result.handled_by,
+					pluginId: result.plugin_id,
 				});
-				return result;
+				return result.result;
 			}
 
 			// If we get here, the native invoke returned null/undefined. Provide a